@@ -23,8 +23,24 @@ pub struct Glyph {
     pub scalable_width: Option<Coord>,
 
     /// Device width.
+    ///
+    /// Set to zero if the glyph doesn't contain a `DWIDTH` statement, which is optional for fonts
+    /// which only contain vertical metrics.
     pub device_width: Coord,
 
+    /// Scalable width for vertical writing (`SWIDTH1`).
+    pub scalable_width_vertical: Option<Coord>,
+
+    /// Device width for vertical writing (`DWIDTH1`).
+    pub device_width_vertical: Option<Coord>,
+
+    /// Offset from the horizontal origin to the vertical origin (`VVECTOR`).
+    ///
+    /// If this is `None` the global value in [`Metadata::vertical_vector`] is used.
+    ///
+    /// [`Metadata::vertical_vector`]: crate::Metadata::vertical_vector
+    pub vertical_vector: Option<Coord>,
+
     /// Bounding box.
     pub bounding_box: BoundingBox,
 
@@ -33,11 +49,14 @@ pub struct Glyph {
 }
 
 impl Glyph {
-    fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+    pub(crate) fn parse(input: &[u8]) -> IResult<&[u8], Self> {
         let (input, name) = statement("STARTCHAR", parse_string)(input)?;
         let (input, encoding) = statement("ENCODING", parse_encoding)(input)?;
         let (input, scalable_width) = opt(statement("SWIDTH", Coord::parse))(input)?;
-        let (input, device_width) = statement("DWIDTH", Coord::parse)(input)?;
+        let (input, device_width) = opt(statement("DWIDTH", Coord::parse))(input)?;
+        let (input, scalable_width_vertical) = opt(statement("SWIDTH1", Coord::parse))(input)?;
+        let (input, device_width_vertical) = opt(statement("DWIDTH1", Coord::parse))(input)?;
+        let (input, vertical_vector) = opt(statement("VVECTOR", Coord::parse))(input)?;
         let (input, bounding_box) = statement("BBX", BoundingBox::parse)(input)?;
         let (input, bitmap) = parse_bitmap(input)?;
 
@@ -47,7 +66,10 @@ impl Glyph {
                 name,
                 encoding,
                 scalable_width,
-                device_width,
+                device_width: device_width.unwrap_or_else(|| Coord::new(0, 0)),
+                scalable_width_vertical,
+                device_width_vertical,
+                vertical_vector,
                 bounding_box,
                 bitmap,
             },
@@ -66,7 +88,7 @@ impl Glyph {
     pub fn pixel(&self, x: usize, y: usize) -> bool {
         let width = usize::try_from(self.bounding_box.size.x).unwrap();
 
-        let bytes_per_row = (width + 7) / 8;
+        let bytes_per_row = width.div_ceil(8);
         let byte_offset = x / 8;
         let bit_mask = 0x80 >> (x % 8);
 
@@ -206,6 +228,9 @@ mod tests {
                 },
                 scalable_width: Some(Coord::new(500, 0)),
                 device_width: Coord::new(8, 0),
+                scalable_width_vertical: None,
+                device_width_vertical: None,
+                vertical_vector: None,
             },
         )
     }
@@ -290,6 +315,42 @@ mod tests {
                 name: "000".to_string(),
                 scalable_width: Some(Coord::new(432, 0)),
                 device_width: Coord::new(6, 0),
+                scalable_width_vertical: None,
+                device_width_vertical: None,
+                vertical_vector: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_glyph_with_vertical_metrics() {
+        let chardata = indoc! {br#"
+            STARTCHAR U+4E00
+            ENCODING 19968
+            SWIDTH1 0 -1000
+            DWIDTH1 0 -16
+            VVECTOR 8 14
+            BBX 2 1 0 0
+            BITMAP
+            C0
+            ENDCHAR
+        "#};
+
+        assert_parser_ok!(
+            Glyph::parse(chardata),
+            Glyph {
+                bitmap: vec![0xC0],
+                bounding_box: BoundingBox {
+                    size: Coord::new(2, 1),
+                    offset: Coord::new(0, 0),
+                },
+                encoding: Some('\u{4E00}'),
+                name: "U+4E00".to_string(),
+                scalable_width: None,
+                device_width: Coord::new(0, 0),
+                scalable_width_vertical: Some(Coord::new(0, -1000)),
+                device_width_vertical: Some(Coord::new(0, -16)),
+                vertical_vector: Some(Coord::new(8, 14)),
             }
         );
     }
@@ -318,6 +379,9 @@ mod tests {
                 name: "000".to_string(),
                 scalable_width: Some(Coord::new(432, 0)),
                 device_width: Coord::new(6, 0),
+                scalable_width_vertical: None,
+                device_width_vertical: None,
+                vertical_vector: None,
             }
         );
     }
@@ -0,0 +1,570 @@
+//! Normalization of non-conformant BDF files.
+//!
+//! The lenient parser rewrites the input into a conformant BDF file, which is then parsed by the
+//! regular parser. Every fixed problem is reported as a [`Warning`].
+
+use bstr::ByteSlice;
+
+/// Warning about a problem which was fixed by the lenient parser.
+///
+/// Line numbers start at 1 and refer to the original input.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Warning {
+    /// An unknown or misplaced statement was ignored.
+    #[error("ignored unexpected statement in line {line}")]
+    UnexpectedStatement {
+        /// Line number.
+        line: usize,
+    },
+
+    /// A statement was moved to the position required by the BDF specification.
+    #[error("statement out of order in line {line}")]
+    StatementOutOfOrder {
+        /// Line number.
+        line: usize,
+    },
+
+    /// The `ENDPROPERTIES` statement is missing.
+    #[error("missing ENDPROPERTIES for properties starting in line {line}")]
+    MissingEndProperties {
+        /// Line number of the `STARTPROPERTIES` statement.
+        line: usize,
+    },
+
+    /// The `ENDCHAR` statement of a glyph is missing.
+    #[error("missing ENDCHAR for glyph starting in line {line}")]
+    MissingEndChar {
+        /// Line number of the `STARTCHAR` statement.
+        line: usize,
+    },
+
+    /// The `ENDFONT` statement is missing.
+    #[error("missing ENDFONT at the end of the file")]
+    MissingEndFont {
+        /// Line number after the last line.
+        line: usize,
+    },
+
+    /// A bitmap row was padded with zeros or truncated to the width of the bounding box.
+    #[error("bitmap row with wrong length in line {line}")]
+    BitmapRowLength {
+        /// Line number.
+        line: usize,
+    },
+
+    /// A bitmap row contained invalid characters and was replaced by an empty row.
+    #[error("invalid bitmap row in line {line}")]
+    InvalidBitmapRow {
+        /// Line number.
+        line: usize,
+    },
+
+    /// Empty rows were added or extra rows were removed to match the height of the bounding box.
+    #[error("wrong number of bitmap rows in glyph starting in line {line}")]
+    BitmapRowCount {
+        /// Line number of the `STARTCHAR` statement.
+        line: usize,
+    },
+}
+
+impl Warning {
+    /// Returns the line number the warning refers to.
+    pub fn line(&self) -> usize {
+        match self {
+            Self::UnexpectedStatement { line }
+            | Self::StatementOutOfOrder { line }
+            | Self::MissingEndProperties { line }
+            | Self::MissingEndChar { line }
+            | Self::MissingEndFont { line }
+            | Self::BitmapRowLength { line }
+            | Self::InvalidBitmapRow { line }
+            | Self::BitmapRowCount { line } => *line,
+        }
+    }
+}
+
+/// Statements of the font header in the required order.
+const HEADER: &[&str] = &[
+    "STARTFONT",
+    "FONT",
+    "SIZE",
+    "FONTBOUNDINGBOX",
+    "METRICSSET",
+    "VVECTOR",
+];
+
+/// Statements of a glyph before the bitmap in the required order.
+const GLYPH: &[&str] = &[
+    "STARTCHAR",
+    "ENCODING",
+    "SWIDTH",
+    "DWIDTH",
+    "SWIDTH1",
+    "DWIDTH1",
+    "VVECTOR",
+    "BBX",
+];
+
+/// Line of the normalized file.
+#[derive(Debug, Clone)]
+struct Line {
+    /// Line number in the original input.
+    number: usize,
+    text: Vec<u8>,
+}
+
+impl Line {
+    fn new(number: usize, text: &[u8]) -> Self {
+        Self {
+            number,
+            text: text.to_vec(),
+        }
+    }
+
+    fn keyword(&self) -> &[u8] {
+        self.text.split_str(" ").next().unwrap_or_default()
+    }
+
+    fn is(&self, keyword: &str) -> bool {
+        self.keyword() == keyword.as_bytes()
+    }
+
+    /// Returns the integer parameters of the statement.
+    fn parameters(&self) -> Vec<i32> {
+        self.text
+            .fields()
+            .skip(1)
+            .filter_map(|field| field.to_str().ok()?.parse().ok())
+            .collect()
+    }
+}
+
+/// Normalized BDF file.
+#[derive(Debug)]
+pub(crate) struct Normalized {
+    /// Conformant BDF file.
+    pub data: Vec<u8>,
+    /// Original line numbers of the lines in `data`.
+    lines: Vec<usize>,
+    /// Warnings, sorted by line number.
+    pub warnings: Vec<Warning>,
+}
+
+impl Normalized {
+    /// Returns the original line number for a line number in the normalized data.
+    pub fn original_line(&self, line: usize) -> usize {
+        line.checked_sub(1)
+            .and_then(|index| self.lines.get(index))
+            .or_else(|| self.lines.last())
+            .copied()
+            .unwrap_or(1)
+    }
+}
+
+/// State of the normalizer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Header,
+    Properties,
+    Glyphs,
+    Glyph,
+    End,
+}
+
+#[derive(Debug, Default)]
+struct Normalizer {
+    header: Vec<Line>,
+    properties: Vec<Line>,
+    chars: Option<Line>,
+    glyphs: Vec<Line>,
+    glyph: Vec<Line>,
+    warnings: Vec<Warning>,
+}
+
+/// Normalizes a BDF file.
+pub(crate) fn normalize(input: &[u8]) -> Normalized {
+    let input = input.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(input);
+
+    let mut normalizer = Normalizer::default();
+    let mut state = State::Header;
+    // State after the properties.
+    let mut outer_state = State::Header;
+    let mut line_count = 0;
+
+    for (index, text) in input.lines().enumerate() {
+        line_count = index + 1;
+
+        // Collapse the whitespace between the keyword and the parameters.
+        let text = text.trim();
+        let line = match text.find_byteset(b" \t") {
+            Some(end) => {
+                let mut normalized = text[..end].to_vec();
+                normalized.push(b' ');
+                normalized.extend_from_slice(text[end..].trim_start());
+                Line::new(line_count, &normalized)
+            }
+            None => Line::new(line_count, text),
+        };
+
+        // Comments are dropped, because they aren't allowed everywhere by the regular parser.
+        if line.text.is_empty() || line.is("COMMENT") {
+            continue;
+        }
+
+        // Lines which end the current block implicitly are processed again in the next state.
+        loop {
+            let (next, consumed) = normalizer.line(state, &line, &mut outer_state);
+            state = next;
+            if consumed {
+                break;
+            }
+        }
+    }
+
+    match state {
+        State::Properties => normalizer.end_properties(),
+        State::Glyph => normalizer.end_glyph(),
+        _ => {}
+    }
+
+    if state != State::End {
+        normalizer.warnings.push(Warning::MissingEndFont {
+            line: line_count + 1,
+        });
+    }
+
+    normalizer.finish(line_count + 1)
+}
+
+impl Normalizer {
+    /// Processes a line and returns the next state.
+    ///
+    /// The returned flag is `false` if the line ended the current block without being consumed.
+    fn line(&mut self, state: State, line: &Line, outer_state: &mut State) -> (State, bool) {
+        let next = match state {
+            State::Properties => {
+                if line.is("ENDPROPERTIES") {
+                    self.properties.push(line.clone());
+                    *outer_state
+                } else if line.is("STARTCHAR") || line.is("CHARS") || line.is("ENDFONT") {
+                    self.end_properties();
+                    return (*outer_state, false);
+                } else {
+                    self.properties.push(line.clone());
+                    State::Properties
+                }
+            }
+            State::Glyph => {
+                if line.is("ENDCHAR") {
+                    self.glyph.push(line.clone());
+                    self.end_glyph();
+                    State::Glyphs
+                } else if line.is("STARTCHAR") || line.is("ENDFONT") {
+                    self.end_glyph();
+                    return (State::Glyphs, false);
+                } else {
+                    self.glyph.push(line.clone());
+                    State::Glyph
+                }
+            }
+            State::Header | State::Glyphs => {
+                if line.is("STARTPROPERTIES") {
+                    if state == State::Glyphs || !self.properties.is_empty() {
+                        self.warnings
+                            .push(Warning::StatementOutOfOrder { line: line.number });
+                    }
+
+                    // Multiple property blocks are merged into one.
+                    if self
+                        .properties
+                        .last()
+                        .is_some_and(|line| line.is("ENDPROPERTIES"))
+                    {
+                        self.properties.pop();
+                    } else {
+                        self.properties.push(line.clone());
+                    }
+                    *outer_state = state;
+                    State::Properties
+                } else if line.is("CHARS") && self.chars.is_none() && self.glyphs.is_empty() {
+                    self.chars = Some(line.clone());
+                    State::Glyphs
+                } else if line.is("STARTCHAR") {
+                    self.glyph.push(line.clone());
+                    State::Glyph
+                } else if line.is("ENDFONT") {
+                    State::End
+                } else if state == State::Header
+                    && HEADER.iter().any(|keyword| line.is(keyword))
+                    && !self
+                        .header
+                        .iter()
+                        .any(|other| other.keyword() == line.keyword())
+                {
+                    self.header.push(line.clone());
+                    State::Header
+                } else {
+                    self.warnings
+                        .push(Warning::UnexpectedStatement { line: line.number });
+                    state
+                }
+            }
+            State::End => {
+                self.warnings
+                    .push(Warning::UnexpectedStatement { line: line.number });
+                State::End
+            }
+        };
+
+        (next, true)
+    }
+
+    fn end_properties(&mut self) {
+        let start = self.properties.first().map_or(1, |line| line.number);
+        self.warnings
+            .push(Warning::MissingEndProperties { line: start });
+
+        let number = self.properties.last().map_or(1, |line| line.number);
+        self.properties.push(Line::new(number, b"ENDPROPERTIES"));
+    }
+
+    /// Normalizes the current glyph and appends it to the glyphs.
+    fn end_glyph(&mut self) {
+        let mut lines = core::mem::take(&mut self.glyph);
+        let start = lines[0].number;
+
+        if !lines.last().is_some_and(|line| line.is("ENDCHAR")) {
+            self.warnings.push(Warning::MissingEndChar { line: start });
+            let number = lines.last().map_or(start, |line| line.number);
+            lines.push(Line::new(number, b"ENDCHAR"));
+        }
+        let end_char = lines.pop().unwrap();
+
+        let bitmap_start = lines
+            .iter()
+            .position(|line| line.is("BITMAP"))
+            .unwrap_or(lines.len());
+        let rows = lines.split_off(bitmap_start);
+
+        // Sort the statements before the bitmap.
+        lines.retain(|line| {
+            let known = GLYPH.iter().any(|keyword| line.is(keyword));
+            if !known {
+                self.warnings
+                    .push(Warning::UnexpectedStatement { line: line.number });
+            }
+            known
+        });
+        self.sort(&mut lines, GLYPH);
+
+        let size = lines
+            .iter()
+            .find(|line| line.is("BBX"))
+            .map(Line::parameters)
+            .filter(|parameters| parameters.len() == 4 && parameters[0] >= 0 && parameters[1] >= 0)
+            .map(|parameters| (parameters[0] as usize, parameters[1] as usize));
+
+        self.glyphs.extend(lines);
+
+        // Bitmaps can only be fixed if the size is known, otherwise the glyph is left unchanged
+        // and the regular parser reports the error.
+        match (rows.split_first(), size) {
+            (Some((bitmap, rows)), Some((width, height))) => {
+                self.glyphs.push(bitmap.clone());
+                self.bitmap_rows(rows, width, height, start, end_char.number);
+            }
+            _ => self.glyphs.extend(rows),
+        }
+
+        self.glyphs.push(end_char);
+    }
+
+    /// Appends the bitmap rows of a glyph, fixed to the given size.
+    fn bitmap_rows(
+        &mut self,
+        rows: &[Line],
+        width: usize,
+        height: usize,
+        start: usize,
+        end: usize,
+    ) {
+        let digits = width.div_ceil(8) * 2;
+
+        for row in rows.iter().take(height) {
+            let mut text = row.text.clone();
+            if !text.iter().all(u8::is_ascii_hexdigit) {
+                self.warnings
+                    .push(Warning::InvalidBitmapRow { line: row.number });
+                text.clear();
+            } else if text.len() != digits {
+                self.warnings
+                    .push(Warning::BitmapRowLength { line: row.number });
+            }
+            text.resize(digits, b'0');
+
+            self.glyphs.push(Line::new(row.number, &text));
+        }
+
+        if rows.len() != height {
+            self.warnings.push(Warning::BitmapRowCount { line: start });
+        }
+        for _ in rows.len()..height {
+            self.glyphs.push(Line::new(end, &vec![b'0'; digits]));
+        }
+    }
+
+    /// Sorts lines by the order of their keywords and reports moved lines.
+    fn sort(&mut self, lines: &mut [Line], order: &[&str]) {
+        let rank = |line: &Line| order.iter().position(|keyword| line.is(keyword));
+
+        if let Some(misplaced) = lines
+            .windows(2)
+            .find(|pair| rank(&pair[0]) > rank(&pair[1]))
+        {
+            self.warnings.push(Warning::StatementOutOfOrder {
+                line: misplaced[1].number,
+            });
+        }
+        lines.sort_by_key(rank);
+    }
+
+    fn finish(mut self, end: usize) -> Normalized {
+        let mut header = core::mem::take(&mut self.header);
+        self.sort(&mut header, HEADER);
+
+        let mut data = Vec::new();
+        let mut lines = Vec::new();
+
+        let chars = self.chars.take();
+        let end_font = Line::new(end, b"ENDFONT");
+        for line in header
+            .iter()
+            .chain(&self.properties)
+            .chain(&chars)
+            .chain(&self.glyphs)
+            .chain(core::iter::once(&end_font))
+        {
+            data.extend_from_slice(&line.text);
+            data.push(b'\n');
+            lines.push(line.number);
+        }
+
+        let mut warnings = self.warnings;
+        warnings.sort_by_key(Warning::line);
+
+        Normalized {
+            data,
+            lines,
+            warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BdfFont;
+    use indoc::indoc;
+
+    const FONT: &str = indoc! {r#"
+        STARTFONT 2.1
+        FONT "test font"
+        SIZE 16 75 75
+        FONTBOUNDINGBOX 16 24 0 0
+        STARTPROPERTIES 2
+        FONT_ASCENT 1
+        FONT_DESCENT 2
+        ENDPROPERTIES
+        CHARS 2
+        STARTCHAR Char 0
+        ENCODING 64
+        DWIDTH 8 0
+        BBX 12 2 0 0
+        BITMAP
+        1f00
+        0100
+        ENDCHAR
+        STARTCHAR Char 1
+        ENCODING 65
+        DWIDTH 8 0
+        BBX 8 3 0 0
+        BITMAP
+        2f
+        02
+        00
+        ENDCHAR
+        ENDFONT
+    "#};
+
+    #[test]
+    fn conformant_font() {
+        let (font, warnings) = BdfFont::parse_lenient(FONT.as_bytes()).unwrap();
+
+        assert_eq!(font, BdfFont::parse(FONT.as_bytes()).unwrap());
+        assert_eq!(warnings, []);
+    }
+
+    #[test]
+    fn fix_problems() {
+        let input = indoc! {"
+            \u{FEFF}  STARTFONT 2.1
+            SIZE 16 75 75
+            FONT \"test font\"
+            CONTENTVERSION 1
+            FONTBOUNDINGBOX 16 24 0 0
+            CHARS 2
+            STARTCHAR Char 0
+            DWIDTH 8 0
+            ENCODING 64
+            BBX 12 2 0 0
+            BITMAP
+            1f
+            01x0
+            STARTCHAR Char 1
+            ENCODING 65
+            DWIDTH\t8 0
+            BBX 8 3 0 0
+            ATTRIBUTES 0000
+            BITMAP
+            2f
+            02
+            ENDCHAR
+            STARTPROPERTIES 2
+            FONT_ASCENT\t1
+            FONT_DESCENT 2
+        "};
+        let (font, warnings) = BdfFont::parse_lenient(input.as_bytes()).unwrap();
+
+        let expected = FONT.replace("0100", "0000");
+        assert_eq!(font, BdfFont::parse(expected.as_bytes()).unwrap());
+
+        assert_eq!(
+            warnings,
+            [
+                Warning::StatementOutOfOrder { line: 3 },
+                Warning::UnexpectedStatement { line: 4 },
+                Warning::MissingEndChar { line: 7 },
+                Warning::StatementOutOfOrder { line: 9 },
+                Warning::BitmapRowLength { line: 12 },
+                Warning::InvalidBitmapRow { line: 13 },
+                Warning::BitmapRowCount { line: 14 },
+                Warning::UnexpectedStatement { line: 18 },
+                Warning::StatementOutOfOrder { line: 23 },
+                Warning::MissingEndProperties { line: 23 },
+                Warning::MissingEndFont { line: 26 },
+            ]
+        );
+    }
+
+    #[test]
+    fn error_line_numbers() {
+        let input = FONT
+            .replacen("ENDPROPERTIES\n", "", 1)
+            .replace("BBX 8 3 0 0", "BBX 8");
+
+        assert_eq!(
+            BdfFont::parse_lenient(input.as_bytes()),
+            Err(crate::ParserError::Glyphs { line: 20 })
+        );
+    }
+}
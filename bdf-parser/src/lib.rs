@@ -16,11 +16,13 @@ use nom::{
 mod helpers;
 
 mod glyph;
+mod lenient;
 mod metadata;
 mod properties;
 
 pub use glyph::{Glyph, Glyphs};
 use helpers::*;
+pub use lenient::Warning;
 pub use metadata::Metadata;
 pub use properties::{Properties, Property, PropertyError};
 
@@ -42,16 +44,39 @@ impl BdfFont {
     ///
     /// BDF files are expected to be ASCII encoded according to the BDF specification. Any non
     /// ASCII characters in strings will be replaced by the `U+FFFD` replacement character.
+    ///
+    /// Errors contain the line number at which parsing failed.
     pub fn parse(input: &[u8]) -> Result<Self, ParserError> {
-        let (input, metadata) = Metadata::parse(input).map_err(|_| ParserError::Metadata)?;
+        let file = input;
+        let line = |error| error_line(file, error);
+
+        let (input, metadata) =
+            Metadata::parse(input).map_err(|e| ParserError::Metadata { line: line(e) })?;
         let input = skip_whitespace(input);
-        let (input, properties) = Properties::parse(input).map_err(|_| ParserError::Properties)?;
+        let (input, properties) =
+            Properties::parse(input).map_err(|e| ParserError::Properties { line: line(e) })?;
         let input = skip_whitespace(input);
-        let (input, glyphs) = Glyphs::parse(input).map_err(|_| ParserError::Glyphs)?;
+        if input.starts_with(b"STARTPROPERTIES") {
+            return Err(ParserError::Properties {
+                line: line_number(file, input),
+            });
+        }
+        let (input, glyphs) =
+            Glyphs::parse(input).map_err(|e| ParserError::Glyphs { line: line(e) })?;
         let input = skip_whitespace(input);
+
+        // Invalid glyphs end the list of glyphs. Parse the first invalid glyph again to report
+        // the line of the statement which caused the error.
+        if input.starts_with(b"STARTCHAR") {
+            let error = Glyph::parse(input).err();
+            return Err(ParserError::Glyphs {
+                line: error.map_or_else(|| line_number(file, input), line),
+            });
+        }
+
         let (input, _) = end_font(input).unwrap();
         let input = skip_whitespace(input);
-        end_of_file(input).map_err(|_| ParserError::EndOfFile)?;
+        end_of_file(input).map_err(|e| ParserError::EndOfFile { line: line(e) })?;
 
         Ok(Self {
             properties,
@@ -59,6 +84,25 @@ impl BdfFont {
             glyphs,
         })
     }
+
+    /// Parses a BDF file which doesn't fully conform to the BDF specification.
+    ///
+    /// Common problems, like a missing `ENDFONT` or `ENDCHAR` statement, statements in the wrong
+    /// order, unknown statements or bitmap rows which don't match the size of the bounding box,
+    /// are fixed and reported as warnings. Windows line endings and additional whitespace are
+    /// accepted by both parsers.
+    ///
+    /// The line numbers in errors and warnings refer to the original input.
+    pub fn parse_lenient(input: &[u8]) -> Result<(Self, Vec<Warning>), ParserError> {
+        let normalized = lenient::normalize(input);
+
+        let font = Self::parse(&normalized.data).map_err(|error| {
+            let line = normalized.original_line(error.line());
+            error.with_line(line)
+        })?;
+
+        Ok((font, normalized.warnings))
+    }
 }
 
 fn skip_whitespace(input: &[u8]) -> &[u8] {
@@ -73,6 +117,23 @@ fn end_of_file(input: &[u8]) -> IResult<&[u8], &[u8]> {
     eof(input)
 }
 
+/// Returns the line number of the start of `remaining`, which must be a subslice of `file`.
+fn line_number(file: &[u8], remaining: &[u8]) -> usize {
+    let offset = (remaining.as_ptr() as usize)
+        .saturating_sub(file.as_ptr() as usize)
+        .min(file.len());
+
+    file[..offset].iter().filter(|&&b| b == b'\n').count() + 1
+}
+
+/// Returns the line number at which a parser failed.
+fn error_line(file: &[u8], error: nom::Err<nom::error::Error<&[u8]>>) -> usize {
+    match error {
+        nom::Err::Error(e) | nom::Err::Failure(e) => line_number(file, e.input),
+        nom::Err::Incomplete(_) => line_number(file, &file[file.len()..]),
+    }
+}
+
 /// Bounding box.
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct BoundingBox {
@@ -119,23 +180,59 @@ impl BoundingBox {
 }
 
 /// Parser error.
+///
+/// Line numbers start at 1.
 #[derive(Debug, PartialEq, thiserror::Error)]
 pub enum ParserError {
     /// Metadata.
-    #[error("couldn't parse metadata")]
-    Metadata,
+    #[error("couldn't parse metadata in line {line}")]
+    Metadata {
+        /// Line number.
+        line: usize,
+    },
 
     /// Properties.
-    #[error("couldn't parse properties")]
-    Properties,
+    #[error("couldn't parse properties in line {line}")]
+    Properties {
+        /// Line number.
+        line: usize,
+    },
 
     /// Glyphs.
-    #[error("couldn't parse glyphs")]
-    Glyphs,
+    #[error("couldn't parse glyph in line {line}")]
+    Glyphs {
+        /// Line number.
+        line: usize,
+    },
 
     /// Unexpected input at the end of the file.
-    #[error("unexpected input at the end of the file")]
-    EndOfFile,
+    #[error("unexpected input at the end of the file in line {line}")]
+    EndOfFile {
+        /// Line number.
+        line: usize,
+    },
+}
+
+impl ParserError {
+    /// Returns the line number at which parsing failed.
+    pub fn line(&self) -> usize {
+        match self {
+            Self::Metadata { line }
+            | Self::Properties { line }
+            | Self::Glyphs { line }
+            | Self::EndOfFile { line } => *line,
+        }
+    }
+
+    /// Returns the same error with a different line number.
+    fn with_line(self, line: usize) -> Self {
+        match self {
+            Self::Metadata { .. } => Self::Metadata { line },
+            Self::Properties { .. } => Self::Properties { line },
+            Self::Glyphs { .. } => Self::Glyphs { line },
+            Self::EndOfFile { .. } => Self::EndOfFile { line },
+        }
+    }
 }
 
 #[cfg(test)]
@@ -184,6 +281,8 @@ mod tests {
                     size: Coord::new(16, 24),
                     offset: Coord::new(0, 0),
                 },
+                metrics_set: 0,
+                vertical_vector: None,
             }
         );
 
@@ -200,6 +299,9 @@ mod tests {
                     name: "Char 0".to_string(),
                     device_width: Coord::new(8, 0),
                     scalable_width: None,
+                    scalable_width_vertical: None,
+                    device_width_vertical: None,
+                    vertical_vector: None,
                 },
                 Glyph {
                     bitmap: vec![0x2f, 0x02],
@@ -211,6 +313,9 @@ mod tests {
                     name: "Char 1".to_string(),
                     device_width: Coord::new(8, 0),
                     scalable_width: None,
+                    scalable_width_vertical: None,
+                    device_width_vertical: None,
+                    vertical_vector: None,
                 },
             ],
         );
@@ -254,7 +359,36 @@ mod tests {
 
         assert_eq!(
             BdfFont::parse(input.as_bytes()),
-            Err(ParserError::EndOfFile)
+            Err(ParserError::EndOfFile { line: 27 })
+        );
+    }
+
+    #[test]
+    fn error_line_numbers() {
+        let replace = |from: &str, to: &str| {
+            let input = FONT.replacen(from, to, 1);
+            BdfFont::parse(input.as_bytes()).unwrap_err()
+        };
+
+        assert_eq!(
+            replace("SIZE 16 75 75", "SIZE 16 75"),
+            ParserError::Metadata { line: 3 }
+        );
+        assert_eq!(
+            replace("ENDPROPERTIES", "END"),
+            ParserError::Properties { line: 5 },
+            "missing ENDPROPERTIES"
+        );
+
+        let error = replace("BBX 8 8 0 0", "BBX 8 8 0");
+        assert_eq!(error, ParserError::Glyphs { line: 13 });
+        assert_eq!(error.line(), 13);
+        assert_eq!(error.to_string(), "couldn't parse glyph in line 13");
+
+        assert_eq!(
+            replace("ENCODING 65", "ENCODING A"),
+            ParserError::Glyphs { line: 19 },
+            "invalid encoding in second glyph"
         );
     }
 }
@@ -1,6 +1,6 @@
 use nom::{
     character::complete::{multispace0, space1},
-    combinator::map_opt,
+    combinator::{map_opt, opt},
     sequence::separated_pair,
     IResult, ParseTo,
 };
@@ -24,6 +24,14 @@ pub struct Metadata {
 
     /// Font bounding box.
     pub bounding_box: BoundingBox,
+
+    /// Metrics set (`METRICSSET`).
+    ///
+    /// `0` for fonts with horizontal metrics, `1` for vertical metrics and `2` for both.
+    pub metrics_set: u32,
+
+    /// Default offset from the horizontal origin to the vertical origin (`VVECTOR`).
+    pub vertical_vector: Option<Coord>,
 }
 
 impl Metadata {
@@ -32,6 +40,8 @@ impl Metadata {
         let (input, name) = skip_comments(metadata_name)(input)?;
         let (input, (point_size, resolution)) = skip_comments(metadata_size)(input)?;
         let (input, bounding_box) = skip_comments(metadata_bounding_box)(input)?;
+        let (input, metrics_set) = opt(skip_comments(metadata_metrics_set))(input)?;
+        let (input, vertical_vector) = opt(skip_comments(metadata_vertical_vector))(input)?;
         let (input, _) = multispace0(input)?;
 
         Ok((
@@ -42,6 +52,8 @@ impl Metadata {
                 point_size,
                 resolution,
                 bounding_box,
+                metrics_set: metrics_set.unwrap_or_default(),
+                vertical_vector,
             },
         ))
     }
@@ -65,6 +77,14 @@ fn metadata_bounding_box(input: &[u8]) -> IResult<&[u8], BoundingBox> {
     statement("FONTBOUNDINGBOX", BoundingBox::parse)(input)
 }
 
+fn metadata_metrics_set(input: &[u8]) -> IResult<&[u8], u32> {
+    statement("METRICSSET", parse_to_u32)(input)
+}
+
+fn metadata_vertical_vector(input: &[u8]) -> IResult<&[u8], Coord> {
+    statement("VVECTOR", Coord::parse)(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,8 +119,25 @@ FONTBOUNDINGBOX 16 24 1 2"#;
                 bounding_box: BoundingBox {
                     size: Coord::new(16, 24),
                     offset: Coord::new(1, 2),
-                }
+                },
+                metrics_set: 0,
+                vertical_vector: None,
             }
         );
     }
+
+    #[test]
+    fn parse_vertical_metadata() {
+        let input = br#"STARTFONT 2.2
+FONT vertical
+SIZE 16 75 75
+FONTBOUNDINGBOX 16 16 0 -2
+METRICSSET 2
+VVECTOR 8 14
+"#;
+
+        let (_, metadata) = Metadata::parse(input).unwrap();
+        assert_eq!(metadata.metrics_set, 2);
+        assert_eq!(metadata.vertical_vector, Some(Coord::new(8, 14)));
+    }
 }
@@ -218,10 +218,7 @@ impl PropertyValue {
         map(
             many1(delimited(tag("\""), take_until("\""), tag("\""))),
             |parts| {
-                let parts: Vec<_> = parts
-                    .iter()
-                    .map(|part| ascii_to_string_lossy(*part))
-                    .collect();
+                let parts: Vec<_> = parts.iter().copied().map(ascii_to_string_lossy).collect();
                 PropertyValue::Text(parts.join("\""))
             },
         )(input)
@@ -0,0 +1,195 @@
+//! Run length encoding of glyph bitmaps.
+//!
+//! The encoded bitmaps can be drawn using the `RunLength` codec of eg-bdf. See the documentation
+//! of the codec for a description of the format.
+
+use crate::{font::Font, source::bits_to_bytes};
+
+/// Maximum number of bits used to store a run length.
+const MAX_RUN_BITS: u8 = 8;
+
+/// Run length encodes the glyph bitmaps of a font.
+///
+/// Returns the start index of each glyph and the encoded data. The number of bits per run is
+/// chosen to minimize the size of the data.
+pub fn run_length_encode(font: &Font) -> (Vec<usize>, Vec<u8>) {
+    let runs: Vec<_> = font
+        .glyphs
+        .iter()
+        .map(|glyph| runs(&glyph.bitmap))
+        .collect();
+
+    let (start_indices, bits) = (1..=MAX_RUN_BITS)
+        .flat_map(|background_bits| {
+            (1..=MAX_RUN_BITS).map(move |foreground_bits| (background_bits, foreground_bits))
+        })
+        .map(|(background_bits, foreground_bits)| {
+            let mut bits = Vec::new();
+            push(&mut bits, background_bits << 4 | foreground_bits, 8);
+
+            let start_indices: Vec<_> = runs
+                .iter()
+                .map(|runs| {
+                    let start_index = bits.len();
+                    encode(&mut bits, runs, background_bits, foreground_bits);
+                    start_index
+                })
+                .collect();
+
+            (start_indices, bits)
+        })
+        .min_by_key(|(_, bits)| bits.len())
+        .unwrap();
+
+    (start_indices, bits_to_bytes(&bits))
+}
+
+/// Returns the lengths of the alternating runs of background and foreground pixels.
+fn runs(bitmap: &[bool]) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+
+    let mut index = 0;
+    while index < bitmap.len() {
+        let background = bitmap[index..].iter().take_while(|pixel| !**pixel).count();
+        index += background;
+        let foreground = bitmap[index..].iter().take_while(|pixel| **pixel).count();
+        index += foreground;
+
+        runs.push((background, foreground));
+    }
+
+    runs
+}
+
+/// Encodes the runs of a single glyph.
+fn encode(bits: &mut Vec<bool>, runs: &[(usize, usize)], background_bits: u8, foreground_bits: u8) {
+    let max_background = (1 << background_bits) - 1;
+    let max_foreground = (1 << foreground_bits) - 1;
+
+    // Runs which are too long for the number of bits are split into multiple pairs.
+    let mut pairs = Vec::new();
+    for &(mut background, mut foreground) in runs {
+        while background > max_background {
+            pairs.push((max_background, 0));
+            background -= max_background;
+        }
+        while foreground > max_foreground {
+            pairs.push((background, max_foreground));
+            background = 0;
+            foreground -= max_foreground;
+        }
+        pairs.push((background, foreground));
+    }
+
+    let mut pairs = pairs.iter().peekable();
+    while let Some(&(background, foreground)) = pairs.next() {
+        push(bits, background, background_bits);
+        push(bits, foreground, foreground_bits);
+
+        while pairs.next_if_eq(&&(background, foreground)).is_some() {
+            bits.push(true);
+        }
+
+        // The decoder stops after the last pair without reading the repeat bit.
+        if pairs.peek().is_some() {
+            bits.push(false);
+        }
+    }
+}
+
+/// Appends a number with the given number of bits, MSB first.
+fn push<T: Into<usize>>(bits: &mut Vec<bool>, value: T, len: u8) {
+    let value = value.into();
+    bits.extend((0..len).rev().map(|i| value >> i & 1 != 0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::Glyph;
+    use eg_bdf::{
+        codec::{GlyphCodec, RunLength, RunLengthFont},
+        BdfFont, BitmapLayout, FontExtents,
+    };
+
+    /// Decodes a glyph using the codec of eg-bdf.
+    fn decode(data: &[u8], glyph: &Glyph, start_index: usize) -> Vec<bool> {
+        let font = BdfFont {
+            replacement_character: 0,
+            glyphs: &[],
+            data: &[],
+            pixel_size: 0,
+            font_ascent: 0,
+            font_descent: 0,
+            vertical_metrics: &[],
+            scalable_widths: &[],
+            bitmap_layout: BitmapLayout::default(),
+            extents: FontExtents::default(),
+        };
+        let font = RunLengthFont { font, data };
+        let glyph = eg_bdf::__private::glyph(
+            glyph.character,
+            0,
+            0,
+            glyph.size.0,
+            glyph.size.1,
+            0,
+            start_index,
+        );
+
+        let mut codec = RunLength::<128>::new(&font);
+        let bitmap = codec.decode(&glyph);
+        (0..glyph.bounding_box.size.width * glyph.bounding_box.size.height)
+            .map(|i| bitmap.data[i as usize / 8] & 0x80 >> (i % 8) != 0)
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let glyph = |character, size: (u32, u32), pixel: fn(u32) -> bool| Glyph {
+            character,
+            top_left: (0, 0),
+            size,
+            device_width: size.0 as i32,
+            scalable_width: None,
+            vertical: None,
+            bitmap: (0..size.0 * size.1).map(pixel).collect(),
+        };
+        let font = Font {
+            glyphs: vec![
+                glyph('a', (3, 3), |i| i % 2 == 0),
+                glyph('b', (0, 0), |_| true),
+                glyph('c', (30, 30), |_| true),
+                glyph('d', (30, 30), |i| i > 800),
+                glyph('e', (5, 7), |i| i % 7 < 3),
+                glyph('f', (8, 8), |_| false),
+            ],
+            pixel_size: 30,
+            ascent: 30,
+            descent: 0,
+        };
+
+        let (start_indices, data) = run_length_encode(&font);
+        assert_eq!(start_indices.len(), font.glyphs.len());
+        assert!(data.len() < font.glyphs.iter().map(|g| g.bitmap.len()).sum::<usize>() / 8);
+
+        for (glyph, start_index) in font.glyphs.iter().zip(start_indices) {
+            assert_eq!(
+                decode(&data, glyph, start_index),
+                glyph.bitmap,
+                "{}",
+                glyph.character
+            );
+        }
+    }
+
+    #[test]
+    fn repeated_pairs() {
+        let mut bits = Vec::new();
+        encode(&mut bits, &runs(&[true, false, true, false, true]), 1, 1);
+
+        // (0, 1), (1, 1) and a single repeat, without a final repeat bit.
+        let expected = [false, true, false, true, true, true];
+        assert_eq!(bits, expected);
+    }
+}
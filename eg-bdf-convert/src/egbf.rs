@@ -0,0 +1,111 @@
+//! EGBF output.
+//!
+//! EGBF is the binary font format of eg-bdf, which can be loaded at runtime, e.g. from flash or
+//! an SD card, instead of being compiled into the firmware.
+
+use eg_bdf::{owned::OwnedBdfFont, BdfGlyph, FontExtents, VerticalMetrics};
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+
+use crate::{font::Font, source::bits_to_bytes};
+
+/// Converts a font into the EGBF format.
+///
+/// The glyphs must be sorted, which is done by [`Font::prepare`].
+pub fn write_egbf(font: &Font, replacement_character: usize) -> Result<Vec<u8>, String> {
+    let mut bits = Vec::new();
+    let mut glyphs = Vec::new();
+    for glyph in &font.glyphs {
+        glyphs.push(BdfGlyph {
+            character: glyph.character,
+            bounding_box: Rectangle::new(
+                Point::new(glyph.top_left.0, glyph.top_left.1),
+                Size::new(glyph.size.0, glyph.size.1),
+            ),
+            device_width: glyph.device_width,
+            start_index: bits.len(),
+        });
+        bits.extend_from_slice(&glyph.bitmap);
+    }
+
+    // Scalable widths and vertical metrics are only included if all glyphs provide them.
+    let scalable_widths: Option<Vec<_>> = font
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.scalable_width)
+        .collect();
+    let vertical_metrics: Option<Vec<_>> = font
+        .glyphs
+        .iter()
+        .map(|glyph| {
+            glyph.vertical.map(|metrics| VerticalMetrics {
+                origin: Point::new(metrics.origin.0, metrics.origin.1),
+                advance: metrics.advance,
+            })
+        })
+        .collect();
+
+    let owned = OwnedBdfFont {
+        replacement_character,
+        extents: FontExtents::from_glyphs(&glyphs),
+        glyphs,
+        data: bits_to_bytes(&bits),
+        pixel_size: font.pixel_size,
+        font_ascent: font.ascent,
+        font_descent: font.descent,
+        vertical_metrics: vertical_metrics.unwrap_or_default(),
+        scalable_widths: scalable_widths.unwrap_or_default(),
+        bitmap_layout: Default::default(),
+    };
+    owned.as_font().validate().map_err(|e| e.to_string())?;
+
+    let mut output = Vec::new();
+    owned.to_egbf(&mut output).map_err(|e| e.to_string())?;
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::Glyph;
+
+    #[test]
+    fn round_trip() {
+        let glyph = |character, bitmap: Vec<bool>| Glyph {
+            character,
+            top_left: (0, -1),
+            size: (3, 3),
+            device_width: 4,
+            scalable_width: Some(3500),
+            vertical: None,
+            bitmap,
+        };
+        let font = Font {
+            glyphs: vec![
+                glyph(
+                    'a',
+                    vec![true, false, true, false, true, false, true, false, true],
+                ),
+                glyph('b', vec![false; 9]),
+            ],
+            pixel_size: 4,
+            ascent: 3,
+            descent: 1,
+        };
+
+        let data = write_egbf(&font, 1).unwrap();
+        let loaded = OwnedBdfFont::from_egbf(&data).unwrap();
+        let loaded = loaded.as_font();
+
+        assert_eq!(loaded.glyphs.len(), 2);
+        assert_eq!(loaded.replacement_character, 1);
+        assert_eq!(loaded.scalable_widths, &[3500, 3500]);
+        assert!(loaded.vertical_metrics.is_empty());
+        assert_eq!(loaded.data, &[0xAA, 0x80, 0x00]);
+        assert_eq!(
+            loaded.get_glyph('a').bounding_box,
+            Rectangle::new(Point::new(0, -1), Size::new(3, 3))
+        );
+        assert_eq!(loaded.extents.fixed_advance, Some(4));
+    }
+}
@@ -0,0 +1,458 @@
+use bdf_parser::{Coord, Property, Warning};
+use std::{convert::TryFrom, ops::RangeInclusive};
+
+/// Glyph with an unpacked bitmap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    pub character: char,
+    /// Top left corner of the bounding box relative to the origin on the baseline.
+    pub top_left: (i32, i32),
+    pub size: (u32, u32),
+    pub device_width: i32,
+    /// Scalable advance width in thousandths of a pixel, if the font provides it.
+    pub scalable_width: Option<i32>,
+    /// Vertical metrics, if the font provides them.
+    pub vertical: Option<VerticalMetrics>,
+    /// Pixels in row-major order.
+    pub bitmap: Vec<bool>,
+}
+
+/// Vertical metrics of a glyph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerticalMetrics {
+    /// Offset from the horizontal origin to the vertical origin, with the Y axis pointing down.
+    pub origin: (i32, i32),
+    pub advance: i32,
+}
+
+/// Font which can be converted into Rust source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    pub glyphs: Vec<Glyph>,
+    pub pixel_size: u32,
+    pub ascent: u32,
+    pub descent: u32,
+}
+
+impl Font {
+    /// Loads a BDF font.
+    ///
+    /// The conversion uses the same rules as the `include_bdf` macro. Glyphs with bitmaps that
+    /// don't match their bounding box are rejected, because the bitmap data of these glyphs can't
+    /// be interpreted correctly.
+    pub fn from_bdf(data: &[u8]) -> Result<Self, String> {
+        let font = bdf_parser::BdfFont::parse(data).map_err(|e| e.to_string())?;
+        check_bitmaps(data)?;
+
+        let pixel_size = font
+            .properties
+            .try_get::<i32>(Property::PixelSize)
+            .unwrap_or_default() as u32;
+        let ascent = font
+            .properties
+            .try_get::<i32>(Property::FontAscent)
+            .unwrap_or(pixel_size as i32) as u32;
+        let descent = font
+            .properties
+            .try_get::<i32>(Property::FontDescent)
+            .unwrap_or(pixel_size as i32) as u32;
+
+        let mut glyphs = Vec::new();
+        for glyph in font.glyphs.iter() {
+            let character = match glyph.encoding {
+                Some(c) => c,
+                None => continue,
+            };
+
+            let bounding_box = &glyph.bounding_box;
+            let width = usize::try_from(bounding_box.size.x).unwrap_or_default();
+            let height = usize::try_from(bounding_box.size.y).unwrap_or_default();
+
+            let mut bitmap = Vec::with_capacity(width * height);
+            for y in 0..height {
+                for x in 0..width {
+                    bitmap.push(glyph.pixel(x, y));
+                }
+            }
+
+            let vertical = if font.metadata.metrics_set != 0 {
+                let vector = glyph
+                    .vertical_vector
+                    .or(font.metadata.vertical_vector)
+                    .unwrap_or_else(|| Coord::new(glyph.device_width.x / 2, ascent as i32));
+
+                Some(VerticalMetrics {
+                    origin: (vector.x, 1 - vector.y),
+                    advance: glyph
+                        .device_width_vertical
+                        .map_or((ascent + descent) as i32, |width| -width.y),
+                })
+            } else {
+                None
+            };
+
+            glyphs.push(Glyph {
+                character,
+                top_left: (
+                    bounding_box.offset.x,
+                    -bounding_box.offset.y - (bounding_box.size.y - 1),
+                ),
+                size: (width as u32, height as u32),
+                device_width: glyph.device_width.x,
+                // Convert the scalable width from 1/1000 of the point size into 1/1000 pixels.
+                scalable_width: glyph.scalable_width.map(|width| {
+                    (i64::from(width.x)
+                        * i64::from(font.metadata.point_size)
+                        * i64::from(font.metadata.resolution.x)
+                        / 72) as i32
+                }),
+                vertical,
+                bitmap,
+            });
+        }
+
+        Ok(Self {
+            glyphs,
+            pixel_size,
+            ascent,
+            descent,
+        })
+    }
+
+    /// Loads a font in the GNU Unifont hex format.
+    ///
+    /// Each line contains a code point and the bitmap, separated by a colon. Glyphs are 16 pixels
+    /// high and the width is determined by the length of the bitmap.
+    pub fn from_hex(text: &str) -> Result<Self, String> {
+        const HEIGHT: u32 = 16;
+        const ASCENT: u32 = 14;
+
+        let mut glyphs = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = || format!("invalid glyph in line {}", number + 1);
+
+            let (code, bitmap) = line.split_once(':').ok_or_else(error)?;
+            let character = u32::from_str_radix(code, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(error)?;
+
+            let bits = bitmap.len() as u32 * 4;
+            if bits == 0 || !bits.is_multiple_of(HEIGHT) {
+                return Err(error());
+            }
+
+            let mut pixels = Vec::with_capacity(bits as usize);
+            for digit in bitmap.chars() {
+                let value = digit.to_digit(16).ok_or_else(error)?;
+                pixels.extend((0..4).rev().map(|bit| value & (1 << bit) != 0));
+            }
+
+            let width = bits / HEIGHT;
+            glyphs.push(Glyph {
+                character,
+                top_left: (0, -(ASCENT as i32 - 1)),
+                size: (width, HEIGHT),
+                device_width: width as i32,
+                scalable_width: None,
+                vertical: None,
+                bitmap: pixels,
+            });
+        }
+
+        Ok(Self {
+            glyphs,
+            pixel_size: HEIGHT,
+            ascent: ASCENT,
+            descent: HEIGHT - ASCENT,
+        })
+    }
+
+    /// Checks that all glyphs fit within the font ascent and descent.
+    ///
+    /// Returns an error which lists the offending glyphs. Glyphs that extend beyond the font
+    /// extents overlap the previous or next line of text and are clipped by some renderers.
+    pub fn check_extents(&self) -> Result<(), String> {
+        let ascent = self.ascent as i32;
+        let descent = self.descent as i32;
+
+        let offending: Vec<_> = self
+            .glyphs
+            .iter()
+            .filter(|glyph| glyph.size.0 > 0 && glyph.size.1 > 0)
+            .filter_map(|glyph| {
+                // Rows above the baseline are negative and the baseline row counts as ascent.
+                let top = glyph.top_left.1;
+                let bottom = top + glyph.size.1 as i32 - 1;
+                if top < 1 - ascent || bottom > descent {
+                    Some(format!(
+                        "U+{:04X} extends from row {} to {} relative to the baseline",
+                        glyph.character as u32, top, bottom
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        if offending.is_empty() {
+            Ok(())
+        } else {
+            Err(glyph_error(
+                &format!(
+                    "glyphs don't fit within the font ascent of {} and descent of {}",
+                    ascent, descent
+                ),
+                &offending,
+            ))
+        }
+    }
+
+    /// Removes all glyphs which aren't included in `ranges`.
+    pub fn subset(&mut self, ranges: &[RangeInclusive<char>]) {
+        self.glyphs
+            .retain(|glyph| ranges.iter().any(|range| range.contains(&glyph.character)));
+    }
+
+    /// Sorts the glyphs and returns the index of the replacement character.
+    ///
+    /// `U+FFFD` is used as the replacement character if it is included in the font, otherwise
+    /// the space character or the first glyph is used.
+    pub fn prepare(&mut self) -> usize {
+        self.glyphs.sort_by_key(|glyph| glyph.character);
+        self.glyphs.dedup_by_key(|glyph| glyph.character);
+
+        let find = |c| self.glyphs.iter().position(|glyph| glyph.character == c);
+        find(char::REPLACEMENT_CHARACTER)
+            .or_else(|| find(' '))
+            .unwrap_or_default()
+    }
+}
+
+/// Checks that the bitmap rows of all glyphs in a BDF file match their bounding boxes.
+///
+/// The regular parser ignores the line breaks in bitmaps, which means that rows with the wrong
+/// length shift the pixels of all following rows. The problems are detected by the lenient
+/// parser, which is only used to check the font.
+fn check_bitmaps(data: &[u8]) -> Result<(), String> {
+    let (_, warnings) = bdf_parser::BdfFont::parse_lenient(data).map_err(|e| e.to_string())?;
+
+    let lines: Vec<_> = data.split(|byte| *byte == b'\n').collect();
+    let offending: Vec<_> = warnings
+        .iter()
+        .filter(|warning| {
+            matches!(
+                warning,
+                Warning::BitmapRowLength { .. }
+                    | Warning::InvalidBitmapRow { .. }
+                    | Warning::BitmapRowCount { .. }
+            )
+        })
+        .map(|warning| {
+            // The glyph name is taken from the last `STARTCHAR` statement before the problem.
+            let name = lines[..warning.line().min(lines.len())]
+                .iter()
+                .rev()
+                .map(|line| String::from_utf8_lossy(line))
+                .find_map(|line| {
+                    line.trim()
+                        .strip_prefix("STARTCHAR")
+                        .map(|name| name.trim().to_string())
+                })
+                .unwrap_or_default();
+
+            format!("glyph \"{}\": {}", name, warning)
+        })
+        .collect();
+
+    if offending.is_empty() {
+        Ok(())
+    } else {
+        Err(glyph_error(
+            "glyph bitmaps don't match their bounding boxes",
+            &offending,
+        ))
+    }
+}
+
+/// Maximum number of offending glyphs which are listed in an error message.
+const MAX_LISTED_GLYPHS: usize = 10;
+
+/// Creates an error message with a list of offending glyphs.
+fn glyph_error(message: &str, glyphs: &[String]) -> String {
+    let mut error = format!("{}:", message);
+    for glyph in glyphs.iter().take(MAX_LISTED_GLYPHS) {
+        error += &format!("\n    {}", glyph);
+    }
+    if glyphs.len() > MAX_LISTED_GLYPHS {
+        error += &format!("\n    and {} more", glyphs.len() - MAX_LISTED_GLYPHS);
+    }
+
+    error
+}
+
+/// Parses a list of character ranges.
+///
+/// Ranges are separated by commas and contain a single hexadecimal code point or two code points
+/// separated by a hyphen, e.g. `20-7E,B0,FFFD`. Code points can optionally be prefixed by `U+`.
+pub fn parse_ranges(input: &str) -> Result<Vec<RangeInclusive<char>>, String> {
+    let code_point = |value: &str| {
+        let value = value.trim();
+        let digits = value
+            .strip_prefix("U+")
+            .or_else(|| value.strip_prefix("u+"))
+            .unwrap_or(value);
+
+        u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("invalid code point \"{}\"", value))
+    };
+
+    input
+        .split(',')
+        .map(|range| match range.split_once('-') {
+            Some((start, end)) => Ok(code_point(start)?..=code_point(end)?),
+            None => code_point(range).map(|c| c..=c),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BDF: &str = "STARTFONT 2.1
+FONT test
+SIZE 4 75 75
+FONTBOUNDINGBOX 2 3 0 -1
+STARTPROPERTIES 3
+PIXEL_SIZE 4
+FONT_ASCENT 3
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 2
+STARTCHAR b
+ENCODING 98
+SWIDTH 500 0
+DWIDTH 3 0
+BBX 2 3 0 -1
+BITMAP
+80
+C0
+40
+ENDCHAR
+STARTCHAR a
+ENCODING 97
+SWIDTH 500 0
+DWIDTH 3 0
+BBX 1 1 1 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+    #[test]
+    fn load_bdf() {
+        let mut font = Font::from_bdf(BDF.as_bytes()).unwrap();
+        assert_eq!((font.pixel_size, font.ascent, font.descent), (4, 3, 1));
+        assert_eq!(font.prepare(), 0);
+
+        assert_eq!(
+            font.glyphs,
+            vec![
+                Glyph {
+                    character: 'a',
+                    top_left: (1, 0),
+                    size: (1, 1),
+                    device_width: 3,
+                    scalable_width: Some(2083),
+                    vertical: None,
+                    bitmap: vec![true],
+                },
+                Glyph {
+                    character: 'b',
+                    top_left: (0, -1),
+                    size: (2, 3),
+                    device_width: 3,
+                    scalable_width: Some(2083),
+                    vertical: None,
+                    bitmap: vec![true, false, true, true, false, true],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bitmap_errors() {
+        // The first row of `b` is too long, which would shift the remaining rows, and `a` has an
+        // extra row.
+        let bdf = BDF.replacen("BITMAP\n80\nC0", "BITMAP\n8000\nC0", 1);
+        let bdf = bdf.replacen("BITMAP\n80\nENDCHAR", "BITMAP\n80\n00\nENDCHAR", 1);
+
+        assert_eq!(
+            Font::from_bdf(bdf.as_bytes()),
+            Err("glyph bitmaps don't match their bounding boxes:\n    \
+                glyph \"b\": bitmap row with wrong length in line 17\n    \
+                glyph \"a\": wrong number of bitmap rows in glyph starting in line 21"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn extents() {
+        let mut font = Font::from_bdf(BDF.as_bytes()).unwrap();
+        assert_eq!(font.check_extents(), Ok(()));
+
+        font.ascent = 1;
+        font.descent = 0;
+        assert_eq!(
+            font.check_extents(),
+            Err(
+                "glyphs don't fit within the font ascent of 1 and descent of 0:\n    \
+                U+0062 extends from row -1 to 1 relative to the baseline"
+                    .to_string()
+            )
+        );
+
+        font.glyphs = vec![font.glyphs[0].clone(); 12];
+        let error = font.check_extents().unwrap_err();
+        assert_eq!(error.lines().count(), 12);
+        assert!(error.ends_with("\n    and 2 more"));
+    }
+
+    #[test]
+    fn load_hex() {
+        let font = Font::from_hex("0041:0000000018242442427E424242420000\n").unwrap();
+
+        let glyph = &font.glyphs[0];
+        assert_eq!(glyph.character, 'A');
+        assert_eq!((glyph.size, glyph.top_left), ((8, 16), (0, -13)));
+        assert_eq!(
+            &glyph.bitmap[32..40],
+            &[false, false, false, true, true, false, false, false]
+        );
+
+        assert!(Font::from_hex("0041:123\n").is_err());
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(
+            parse_ranges("20-7E,U+FFFD").unwrap(),
+            vec![' '..='~', '\u{FFFD}'..='\u{FFFD}']
+        );
+        assert!(parse_ranges("20-").is_err());
+
+        let mut font = Font::from_bdf(BDF.as_bytes()).unwrap();
+        font.subset(&parse_ranges("62").unwrap());
+        assert_eq!(font.glyphs.len(), 1);
+        assert_eq!(font.glyphs[0].character, 'b');
+    }
+}
@@ -0,0 +1,217 @@
+//! Adafruit GFX font format.
+//!
+//! GFX fonts are C headers which contain a bitmap array, a glyph table and a `GFXfont` struct.
+//! Each entry in the glyph table contains the offset into the bitmap array, the glyph size, the
+//! advance and the offset from the origin to the top left corner of the glyph. Glyph bitmaps are
+//! stored row by row without padding, with the most significant bit first.
+
+use std::convert::TryFrom;
+
+use crate::font::{Font, Glyph};
+
+/// Removes all comments from a C source file.
+fn strip_comments(source: &str) -> Result<String, String> {
+    let mut output = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(start) = rest.find('/') {
+        output.push_str(&rest[..start]);
+        let comment = &rest[start..];
+
+        rest = if comment.starts_with("//") {
+            comment.find('\n').map_or("", |end| &comment[end..])
+        } else if let Some(block) = comment.strip_prefix("/*") {
+            let end = block.find("*/").ok_or("unterminated comment")?;
+            &block[end + 2..]
+        } else {
+            output.push('/');
+            &comment[1..]
+        };
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+/// Returns all integer literals in an initializer.
+///
+/// Identifiers, like type casts and references to other arrays, are skipped.
+fn parse_integers(initializer: &str) -> Result<Vec<i64>, String> {
+    let tokens = initializer
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .filter(|token| !token.is_empty());
+
+    let mut values = Vec::new();
+    for token in tokens {
+        let (negative, digits) = match token.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, token),
+        };
+        if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+            continue;
+        }
+
+        let value = match digits
+            .strip_prefix("0x")
+            .or_else(|| digits.strip_prefix("0X"))
+        {
+            Some(hex) => i64::from_str_radix(hex, 16),
+            None => digits.parse(),
+        }
+        .map_err(|_| format!("invalid number \"{}\"", token))?;
+
+        values.push(if negative { -value } else { value });
+    }
+
+    Ok(values)
+}
+
+impl Font {
+    /// Loads an Adafruit GFX font header.
+    ///
+    /// The font ascent and descent are derived from the glyph bounding boxes and the pixel size
+    /// is set to the line height (`yAdvance`).
+    pub fn from_gfx(source: &str) -> Result<Self, String> {
+        let source = strip_comments(source)?;
+
+        let (mut bitmap, mut table, mut font) = (None, None, None);
+        for statement in source.split(';') {
+            let (declaration, initializer) = match statement.split_once('=') {
+                Some(statement) => statement,
+                None => continue,
+            };
+
+            if declaration.contains("GFXglyph") {
+                table = Some(parse_integers(initializer)?);
+            } else if declaration.contains("GFXfont") {
+                font = Some(parse_integers(initializer)?);
+            } else if declaration.contains("uint8_t") {
+                bitmap = Some(parse_integers(initializer)?);
+            }
+        }
+
+        let bitmap = bitmap
+            .ok_or("missing bitmap array")?
+            .into_iter()
+            .map(|value| u8::try_from(value).map_err(|_| "invalid bitmap data".to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let table = table.ok_or("missing glyph table")?;
+        let (first, last, y_advance) = match font.ok_or("missing GFXfont struct")?[..] {
+            [first, last, y_advance] => (first, last, y_advance),
+            _ => return Err("invalid GFXfont struct".to_string()),
+        };
+
+        if !table.len().is_multiple_of(6) || (table.len() / 6) as i64 != last - first + 1 {
+            return Err("glyph table doesn't match the character range".to_string());
+        }
+
+        let mut glyphs = Vec::new();
+        for (code, entry) in (first..=last).zip(table.chunks(6)) {
+            let error = || format!("invalid glyph {:#04X}", code);
+
+            let character = u32::try_from(code)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(error)?;
+            let offset = usize::try_from(entry[0]).map_err(|_| error())?;
+            let width = u32::try_from(entry[1]).map_err(|_| error())?;
+            let height = u32::try_from(entry[2]).map_err(|_| error())?;
+
+            let mut pixels = Vec::with_capacity((width * height) as usize);
+            for index in 0..(width * height) as usize {
+                let byte = bitmap.get(offset + index / 8).ok_or_else(error)?;
+                pixels.push(byte & (0x80 >> (index % 8)) != 0);
+            }
+
+            glyphs.push(Glyph {
+                character,
+                top_left: (entry[4] as i32, entry[5] as i32),
+                size: (width, height),
+                device_width: entry[3] as i32,
+                scalable_width: None,
+                vertical: None,
+                bitmap: pixels,
+            });
+        }
+
+        // The rows from the top of the glyph up to and including the baseline count as ascent.
+        let visible_glyphs = || glyphs.iter().filter(|glyph| glyph.size.1 > 0);
+        let ascent = visible_glyphs()
+            .map(|glyph| 1 - glyph.top_left.1)
+            .max()
+            .unwrap_or_default()
+            .max(0) as u32;
+        let descent = visible_glyphs()
+            .map(|glyph| glyph.top_left.1 + glyph.size.1 as i32 - 1)
+            .max()
+            .unwrap_or_default()
+            .max(0) as u32;
+
+        Ok(Self {
+            glyphs,
+            pixel_size: u32::try_from(y_advance).map_err(|_| "invalid line height")?,
+            ascent,
+            descent,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "\
+// Test font with two glyphs
+const uint8_t TestBitmaps[] PROGMEM = {
+  0xAD, 0x40 /* 'a' */ };
+
+const GFXglyph TestGlyphs[] PROGMEM = {
+  {     0,   3,   3,   4,    0,   -2 },   // 0x61 'a'
+  {     0,   0,   0,   2,    0,    1 } }; // 0x62 'b'
+
+const GFXfont Test PROGMEM = {
+  (uint8_t  *)TestBitmaps,
+  (GFXglyph *)TestGlyphs,
+  0x61, 0x62, 5 };
+
+// Approx. 24 bytes
+";
+
+    #[test]
+    fn load_gfx() {
+        let font = Font::from_gfx(HEADER).unwrap();
+        assert_eq!((font.pixel_size, font.ascent, font.descent), (5, 3, 0));
+
+        assert_eq!(
+            font.glyphs,
+            vec![
+                Glyph {
+                    character: 'a',
+                    top_left: (0, -2),
+                    size: (3, 3),
+                    device_width: 4,
+                    scalable_width: None,
+                    vertical: None,
+                    bitmap: vec![true, false, true, false, true, true, false, true, false],
+                },
+                Glyph {
+                    character: 'b',
+                    top_left: (0, 1),
+                    size: (0, 0),
+                    device_width: 2,
+                    scalable_width: None,
+                    vertical: None,
+                    bitmap: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_headers() {
+        assert!(Font::from_gfx("const uint8_t Bitmaps[] = { 0x00 };").is_err());
+        assert!(Font::from_gfx(&HEADER.replace("0x62, 5", "0x63, 5")).is_err());
+        assert!(Font::from_gfx(&HEADER.replace("0xAD, 0x40", "0xAD")).is_err());
+        assert!(Font::from_gfx("/* unterminated").is_err());
+    }
+}
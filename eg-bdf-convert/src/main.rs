@@ -0,0 +1,250 @@
+//! Converts bitmap fonts into Rust source code or EGBF files for eg-bdf.
+//!
+//! The generated code contains a `BdfFont` constant, which is equivalent to the output of the
+//! `include_bdf` macro. This makes it possible to convert fonts offline instead of parsing them
+//! during every build. Alternatively the font can be written in the binary EGBF format, which
+//! can be loaded at runtime.
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::Path,
+    process,
+};
+
+mod compress;
+mod egbf;
+mod font;
+mod gfx;
+mod pcf;
+mod source;
+mod u8g2;
+
+use font::{parse_ranges, Font};
+
+const USAGE: &str = "\
+Usage: eg-bdf-convert [OPTIONS] <INPUT> [OUTPUT]
+
+Converts a BDF (.bdf), PCF (.pcf), GNU Unifont hex (.hex), u8g2 C source (.c)
+or Adafruit GFX header (.h) font into Rust source code or an EGBF file.
+The output is written to stdout if no output file is given.
+
+Options:
+    --format <FORMAT>   Output format, rust or egbf [default: rust]
+    --name <NAME>       Name of the generated constant [default: FONT_<INPUT>]
+    --chars <RANGES>    Only include the given characters, e.g. 20-7E,B0,FFFD
+    --allow-overflow    Allow glyphs which extend beyond the font ascent or descent
+    --compress          Generate a run length encoded RunLengthFont, only for rust output
+    -h, --help          Print help";
+
+/// Output format.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Rust source code with a `BdfFont` constant.
+    #[default]
+    Rust,
+    /// Binary EGBF font.
+    Egbf,
+}
+
+/// Command line arguments.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Args {
+    input: String,
+    output: Option<String>,
+    format: Format,
+    name: Option<String>,
+    chars: Option<String>,
+    allow_overflow: bool,
+    compress: bool,
+}
+
+impl Args {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut files = Vec::new();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--format" => {
+                    parsed.format = match value()?.as_str() {
+                        "rust" => Format::Rust,
+                        "egbf" => Format::Egbf,
+                        format => return Err(format!("unknown output format {}", format)),
+                    }
+                }
+                "--name" => parsed.name = Some(value()?),
+                "--chars" => parsed.chars = Some(value()?),
+                "--allow-overflow" => parsed.allow_overflow = true,
+                "--compress" => parsed.compress = true,
+                _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+                _ => files.push(arg),
+            }
+        }
+
+        let mut files = files.into_iter();
+        parsed.input = files.next().ok_or("missing input file")?;
+        parsed.output = files.next();
+        if files.next().is_some() {
+            return Err("too many arguments".to_string());
+        }
+
+        // EGBF fonts are validated when they are loaded, which requires uncompressed bitmaps.
+        if parsed.compress && parsed.format == Format::Egbf {
+            return Err("--compress isn't supported for egbf output".to_string());
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Returns the default constant name for an input file.
+fn default_name(input: &str) -> String {
+    let stem = Path::new(input)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+
+    let stem: String = stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    format!("FONT_{}", stem)
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let data = fs::read(&args.input).map_err(|e| format!("couldn't read {}: {}", args.input, e))?;
+
+    let extension = Path::new(&args.input)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+    let mut font = match extension.as_deref() {
+        Some("bdf") => Font::from_bdf(&data)?,
+        Some("pcf") => Font::from_pcf(&data)?,
+        Some("hex") => Font::from_hex(&String::from_utf8_lossy(&data))?,
+        Some("h") => Font::from_gfx(&String::from_utf8_lossy(&data))?,
+        Some("c") => Font::from_u8g2(&u8g2::parse_c_source(&String::from_utf8_lossy(&data))?)?,
+        _ => return Err(format!("unsupported input format: {}", args.input)),
+    };
+
+    if let Some(chars) = &args.chars {
+        font.subset(&parse_ranges(chars)?);
+    }
+    if font.glyphs.is_empty() {
+        return Err("the font doesn't contain any glyphs".to_string());
+    }
+    if !args.allow_overflow {
+        font.check_extents()?;
+    }
+
+    let replacement_character = font.prepare();
+    let output = match args.format {
+        Format::Rust => {
+            let name = args
+                .name
+                .clone()
+                .unwrap_or_else(|| default_name(&args.input));
+
+            let mut output = format!(
+                "// Generated by eg-bdf-convert from {}.\n\n",
+                Path::new(&args.input)
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default()
+            );
+            source::write_rust_source(
+                &mut output,
+                &font,
+                replacement_character,
+                &name,
+                args.compress,
+            )
+            .map_err(|e| e.to_string())?;
+
+            output.into_bytes()
+        }
+        Format::Egbf => egbf::write_egbf(&font, replacement_character)?,
+    };
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, output).map_err(|e| format!("couldn't write {}: {}", path, e))
+        }
+        None => io::stdout()
+            .write_all(&output)
+            .map_err(|e| format!("couldn't write output: {}", e)),
+    }
+}
+
+fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    if let Err(e) = Args::parse(args.into_iter()).and_then(run) {
+        eprintln!("error: {}\n\n{}", e, USAGE);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_args() {
+        assert_eq!(
+            args(&["--chars", "20-7E", "in.bdf", "out.rs"]).unwrap(),
+            Args {
+                input: "in.bdf".to_string(),
+                output: Some("out.rs".to_string()),
+                format: Format::Rust,
+                name: None,
+                chars: Some("20-7E".to_string()),
+                allow_overflow: false,
+                compress: false,
+            }
+        );
+        assert!(
+            args(&["--allow-overflow", "in.bdf"])
+                .unwrap()
+                .allow_overflow
+        );
+
+        assert_eq!(
+            args(&["--format", "egbf", "in.bdf"]).unwrap().format,
+            Format::Egbf
+        );
+
+        assert!(args(&[]).is_err());
+        assert!(args(&["--format", "bin", "in.bdf"]).is_err());
+        assert!(args(&["--name"]).is_err());
+        assert!(args(&["--compress", "in.bdf"]).unwrap().compress);
+        assert!(args(&["--compress", "--format", "egbf", "in.bdf"]).is_err());
+        assert!(args(&["a.bdf", "b.rs", "c.rs"]).is_err());
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(default_name("fonts/6x10.bdf"), "FONT_6X10");
+        assert_eq!(default_name("unifont-15.hex"), "FONT_UNIFONT_15");
+    }
+}
@@ -0,0 +1,519 @@
+//! X11 portable compiled font (PCF) format.
+//!
+//! PCF files start with a table of contents, which contains the type, format and location of
+//! the property, metrics, bitmap and encoding tables. The format field, which is repeated at the
+//! start of each table, defines the byte order of the table and, for bitmaps, the bit order, the
+//! row padding and the scan unit. Compressed `.pcf.gz` files need to be decompressed first.
+
+use std::{collections::HashMap, convert::TryFrom};
+
+use crate::font::{Font, Glyph};
+
+/// Magic number at the start of a PCF file.
+const MAGIC: &[u8] = b"\x01fcp";
+
+const PROPERTIES: u32 = 1 << 0;
+const ACCELERATORS: u32 = 1 << 1;
+const METRICS: u32 = 1 << 2;
+const BITMAPS: u32 = 1 << 3;
+const BDF_ENCODINGS: u32 = 1 << 5;
+const SWIDTHS: u32 = 1 << 6;
+const BDF_ACCELERATORS: u32 = 1 << 8;
+
+/// Format flag for tables with big endian byte order.
+const BYTE_ORDER_MSB: u32 = 1 << 2;
+/// Format flag for bitmaps with the leftmost pixel in the most significant bit.
+const BIT_ORDER_MSB: u32 = 1 << 3;
+/// Format flag for metrics which are stored in 5 bytes instead of 12.
+const COMPRESSED_METRICS: u32 = 1 << 8;
+
+/// Glyph index which marks unused entries in the encoding table.
+const NO_GLYPH: u16 = 0xFFFF;
+
+/// Reader for a PCF table.
+struct Table<'a> {
+    data: &'a [u8],
+    position: usize,
+    format: u32,
+}
+
+impl<'a> Table<'a> {
+    /// Returns the table with the given type or `None` if the font doesn't contain it.
+    fn find(data: &'a [u8], table_type: u32) -> Result<Option<Self>, String> {
+        let mut toc = Self {
+            data,
+            position: MAGIC.len(),
+            format: 0,
+        };
+
+        for _ in 0..toc.u32()? {
+            let (entry_type, _format, size, offset) =
+                (toc.u32()?, toc.u32()?, toc.u32()?, toc.u32()?);
+            if entry_type != table_type {
+                continue;
+            }
+
+            let start = offset as usize;
+            let data = start
+                .checked_add(size as usize)
+                .and_then(|end| data.get(start..end))
+                .ok_or_else(truncated)?;
+            let mut table = Self {
+                data,
+                position: 0,
+                format: 0,
+            };
+            // The format at the start of the table is always little endian.
+            table.format = table.u32()?;
+
+            return Ok(Some(table));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the table with the given type or an error if the font doesn't contain it.
+    fn get(data: &'a [u8], table_type: u32, name: &str) -> Result<Self, String> {
+        Self::find(data, table_type)?.ok_or_else(|| format!("missing PCF {} table", name))
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .position
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or_else(truncated)?;
+        self.position += len;
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = <[u8; 2]>::try_from(self.bytes(2)?).unwrap();
+
+        Ok(if self.format & BYTE_ORDER_MSB != 0 {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn i16(&mut self) -> Result<i32, String> {
+        Ok(i32::from(self.u16()? as i16))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = <[u8; 4]>::try_from(self.bytes(4)?).unwrap();
+
+        Ok(if self.format & BYTE_ORDER_MSB != 0 {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(self.u32()? as i32)
+    }
+
+    /// Reads a count, which is stored as a signed 32 bit value.
+    fn count(&mut self) -> Result<usize, String> {
+        usize::try_from(self.i32()?).map_err(|_| "invalid PCF table length".to_string())
+    }
+}
+
+fn truncated() -> String {
+    "unexpected end of PCF data".to_string()
+}
+
+/// Metrics of a glyph.
+#[derive(Debug, Clone, Copy)]
+struct Metrics {
+    left_bearing: i32,
+    right_bearing: i32,
+    width: i32,
+    ascent: i32,
+    descent: i32,
+}
+
+impl Metrics {
+    fn read(table: &mut Table<'_>, compressed: bool) -> Result<Self, String> {
+        if compressed {
+            let mut value = || Ok::<_, String>(i32::from(table.u8()?) - 0x80);
+
+            return Ok(Self {
+                left_bearing: value()?,
+                right_bearing: value()?,
+                width: value()?,
+                ascent: value()?,
+                descent: value()?,
+            });
+        }
+
+        let metrics = Self {
+            left_bearing: table.i16()?,
+            right_bearing: table.i16()?,
+            width: table.i16()?,
+            ascent: table.i16()?,
+            descent: table.i16()?,
+        };
+        // Skip the attributes.
+        table.u16()?;
+
+        Ok(metrics)
+    }
+}
+
+/// Reads the integer properties of a font.
+fn integer_properties(data: &[u8]) -> Result<HashMap<String, i32>, String> {
+    let mut table = match Table::find(data, PROPERTIES)? {
+        Some(table) => table,
+        None => return Ok(HashMap::new()),
+    };
+
+    let count = table.count()?;
+    let mut properties = Vec::new();
+    for _ in 0..count {
+        let name = table.count()?;
+        let is_string = table.u8()? != 0;
+        let value = table.i32()?;
+        properties.push((name, is_string, value));
+    }
+
+    // The property list is padded to a multiple of 4 bytes.
+    table.bytes((4 - count % 4) % 4)?;
+    let strings_len = table.count()?;
+    let strings = table.bytes(strings_len)?;
+
+    let mut integers = HashMap::new();
+    for (name, is_string, value) in properties {
+        let name = strings
+            .get(name..)
+            .and_then(|name| name.split(|byte| *byte == 0).next())
+            .ok_or("invalid PCF property name")?;
+        if !is_string {
+            integers.insert(String::from_utf8_lossy(name).into_owned(), value);
+        }
+    }
+
+    Ok(integers)
+}
+
+/// Reads the font ascent and descent from the accelerator table.
+fn accelerator_extents(data: &[u8]) -> Result<Option<(i32, i32)>, String> {
+    let table = match Table::find(data, BDF_ACCELERATORS)? {
+        Some(table) => Some(table),
+        None => Table::find(data, ACCELERATORS)?,
+    };
+
+    match table {
+        Some(mut table) => {
+            // Skip the flags, which are stored in 8 bytes.
+            table.bytes(8)?;
+            Ok(Some((table.i32()?, table.i32()?)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Bitmap data of a font.
+struct Bitmaps<'a> {
+    data: &'a [u8],
+    offsets: Vec<usize>,
+    /// Row padding in bytes.
+    padding: usize,
+    /// Size of the units in which the bytes are swapped if the byte and bit order differ.
+    scan_unit: usize,
+    msb_first: bool,
+    swap_bytes: bool,
+}
+
+impl<'a> Bitmaps<'a> {
+    fn read(data: &'a [u8]) -> Result<Self, String> {
+        let mut table = Table::get(data, BITMAPS, "bitmap")?;
+        let format = table.format;
+
+        let count = table.count()?;
+        let mut offsets = Vec::with_capacity(count);
+        for _ in 0..count {
+            offsets.push(table.count()?);
+        }
+
+        // The table contains the data size for all 4 possible paddings.
+        let padding_index = (format & 3) as usize;
+        let mut sizes = [0; 4];
+        for size in sizes.iter_mut() {
+            *size = table.count()?;
+        }
+        let data = table.bytes(sizes[padding_index])?;
+
+        let msb_first = format & BIT_ORDER_MSB != 0;
+        Ok(Self {
+            data,
+            offsets,
+            padding: 1 << padding_index,
+            scan_unit: 1 << ((format >> 4) & 3),
+            msb_first,
+            swap_bytes: (format & BYTE_ORDER_MSB != 0) != msb_first,
+        })
+    }
+
+    /// Returns the bitmap of a glyph in row-major order.
+    fn glyph(&self, index: usize, width: usize, height: usize) -> Result<Vec<bool>, String> {
+        let start = *self.offsets.get(index).ok_or("invalid PCF glyph index")?;
+        let row_len = width.div_ceil(8).div_ceil(self.padding) * self.padding;
+
+        let mut bitmap = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut byte = start + y * row_len + x / 8;
+                if self.swap_bytes {
+                    let unit_start = byte - byte % self.scan_unit;
+                    byte = unit_start + self.scan_unit - 1 - byte % self.scan_unit;
+                }
+
+                let mask = if self.msb_first {
+                    0x80 >> (x % 8)
+                } else {
+                    1 << (x % 8)
+                };
+                let value = self.data.get(byte).ok_or_else(truncated)?;
+                bitmap.push(value & mask != 0);
+            }
+        }
+
+        Ok(bitmap)
+    }
+}
+
+impl Font {
+    /// Loads a font in the PCF format.
+    ///
+    /// The font ascent and descent are read from the properties, like for BDF fonts, or from the
+    /// accelerator table if the properties are missing.
+    pub fn from_pcf(data: &[u8]) -> Result<Self, String> {
+        if !data.starts_with(MAGIC) {
+            return Err("invalid PCF magic number".to_string());
+        }
+
+        let properties = integer_properties(data)?;
+        let (accelerator_ascent, accelerator_descent) = accelerator_extents(data)?
+            .unwrap_or((properties.get("PIXEL_SIZE").copied().unwrap_or_default(), 0));
+        let ascent = properties
+            .get("FONT_ASCENT")
+            .copied()
+            .unwrap_or(accelerator_ascent);
+        let descent = properties
+            .get("FONT_DESCENT")
+            .copied()
+            .unwrap_or(accelerator_descent);
+        let pixel_size = properties
+            .get("PIXEL_SIZE")
+            .copied()
+            .unwrap_or(ascent + descent);
+
+        let mut table = Table::get(data, METRICS, "metrics")?;
+        let compressed = table.format & COMPRESSED_METRICS != 0;
+        let count = if compressed {
+            usize::from(table.u16()?)
+        } else {
+            table.count()?
+        };
+        let mut metrics = Vec::with_capacity(count);
+        for _ in 0..count {
+            metrics.push(Metrics::read(&mut table, compressed)?);
+        }
+
+        // Scalable widths are stored in 1/1000 of the point size, which is stored in decipoints.
+        let mut scalable_widths = Vec::new();
+        if let Some(mut table) = Table::find(data, SWIDTHS)? {
+            let scale = i64::from(properties.get("POINT_SIZE").copied().unwrap_or_default())
+                * i64::from(properties.get("RESOLUTION_X").copied().unwrap_or_default());
+            for _ in 0..table.count()? {
+                scalable_widths.push((i64::from(table.i32()?) * scale / 720) as i32);
+            }
+        }
+
+        let bitmaps = Bitmaps::read(data)?;
+
+        let mut table = Table::get(data, BDF_ENCODINGS, "encoding")?;
+        let (min_byte2, max_byte2) = (table.i16()?, table.i16()?);
+        let (min_byte1, max_byte1) = (table.i16()?, table.i16()?);
+        // Skip the default character.
+        table.u16()?;
+
+        let mut glyphs = Vec::new();
+        for byte1 in min_byte1..=max_byte1 {
+            for byte2 in min_byte2..=max_byte2 {
+                let index = table.u16()?;
+                if index == NO_GLYPH {
+                    continue;
+                }
+
+                let index = usize::from(index);
+                let character = u32::try_from(byte1 << 8 | byte2)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or("invalid PCF encoding")?;
+                let metrics = metrics.get(index).ok_or("invalid PCF glyph index")?;
+
+                let width = (metrics.right_bearing - metrics.left_bearing).max(0) as u32;
+                let height = (metrics.ascent + metrics.descent).max(0) as u32;
+                glyphs.push(Glyph {
+                    character,
+                    top_left: (metrics.left_bearing, 1 - metrics.ascent),
+                    size: (width, height),
+                    device_width: metrics.width,
+                    scalable_width: scalable_widths.get(index).copied(),
+                    vertical: None,
+                    bitmap: bitmaps.glyph(index, width as usize, height as usize)?,
+                });
+            }
+        }
+
+        let unsigned = |value: i32| u32::try_from(value).map_err(|_| "invalid PCF font metrics");
+        Ok(Self {
+            glyphs,
+            pixel_size: unsigned(pixel_size)?,
+            ascent: unsigned(ascent)?,
+            descent: unsigned(descent)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a PCF file from tables, which are given as type, format and data.
+    fn pcf(tables: &[(u32, u32, Vec<u8>)]) -> Vec<u8> {
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&(tables.len() as u32).to_le_bytes());
+
+        let mut offset = 8 + 16 * tables.len();
+        for (table_type, format, table) in tables {
+            let size = 4 + table.len();
+            for value in [*table_type, *format, size as u32, offset as u32] {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            offset += size;
+        }
+        for (_, format, table) in tables {
+            data.extend_from_slice(&format.to_le_bytes());
+            data.extend_from_slice(table);
+        }
+
+        data
+    }
+
+    /// Concatenates little endian 32 bit values.
+    fn le32(values: &[i32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    /// Builds a font with the glyphs `a` and `b`, which use the given bitmap format and data.
+    fn font(bitmap_format: u32, bitmap_data: &[u8]) -> Vec<u8> {
+        // Integer properties, which are padded to a multiple of 4 bytes, and the string table.
+        let mut properties = le32(&[2]);
+        for (name, value) in [(0, 3), (12, 1)] {
+            properties.extend_from_slice(&le32(&[name]));
+            properties.push(0);
+            properties.extend_from_slice(&le32(&[value]));
+        }
+        properties.extend_from_slice(&[0, 0]);
+        properties.extend_from_slice(&le32(&[25]));
+        properties.extend_from_slice(b"FONT_ASCENT\0FONT_DESCENT\0");
+
+        // Compressed metrics of a 2x3 glyph with one row below the baseline and an empty glyph.
+        let mut metrics = 2u16.to_be_bytes().to_vec();
+        metrics.extend_from_slice(&[0x80, 0x82, 0x83, 0x82, 0x81]);
+        metrics.extend_from_slice(&[0x80, 0x80, 0x82, 0x80, 0x80]);
+
+        // The values in the bitmap table use the byte order of the bitmap format.
+        let len = bitmap_data.len() as i32;
+        let mut values = [2, 0, len, 0, 0, 0, 0];
+        values[3 + (bitmap_format & 3) as usize] = len;
+        let mut bitmaps: Vec<_> = values
+            .iter()
+            .flat_map(|value| match bitmap_format & BYTE_ORDER_MSB {
+                0 => value.to_le_bytes(),
+                _ => value.to_be_bytes(),
+            })
+            .collect();
+        bitmaps.extend_from_slice(bitmap_data);
+
+        // Encoding table for 'a' and 'b'.
+        let mut encodings = Vec::new();
+        for value in [0x61u16, 0x62, 0, 0, 0] {
+            encodings.extend_from_slice(&value.to_le_bytes());
+        }
+        encodings.extend_from_slice(&[0, 0, 1, 0]);
+
+        pcf(&[
+            (PROPERTIES, 0, properties),
+            (METRICS, COMPRESSED_METRICS | BYTE_ORDER_MSB, metrics),
+            (BITMAPS, bitmap_format, bitmaps),
+            (BDF_ENCODINGS, 0, encodings),
+        ])
+    }
+
+    #[test]
+    fn load_pcf() {
+        let data = font(BYTE_ORDER_MSB | BIT_ORDER_MSB, &[0x80, 0xC0, 0x40]);
+        let font = Font::from_pcf(&data).unwrap();
+        assert_eq!((font.pixel_size, font.ascent, font.descent), (4, 3, 1));
+
+        assert_eq!(
+            font.glyphs,
+            vec![
+                Glyph {
+                    character: 'a',
+                    top_left: (0, -1),
+                    size: (2, 3),
+                    device_width: 3,
+                    scalable_width: None,
+                    vertical: None,
+                    bitmap: vec![true, false, true, true, false, true],
+                },
+                Glyph {
+                    character: 'b',
+                    top_left: (0, 1),
+                    size: (0, 0),
+                    device_width: 2,
+                    scalable_width: None,
+                    vertical: None,
+                    bitmap: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bit_and_byte_order() {
+        let expected = Font::from_pcf(&font(BYTE_ORDER_MSB | BIT_ORDER_MSB, &[0x80, 0xC0, 0x40]));
+
+        // Least significant bit first.
+        let lsb_first = font(0, &[0x01, 0x03, 0x02]);
+        assert_eq!(Font::from_pcf(&lsb_first), expected);
+
+        // Most significant bit first, with the bytes swapped in 2 byte scan units and 2 byte
+        // padding.
+        let swapped = font(BIT_ORDER_MSB | 1 | 1 << 4, &[0, 0x80, 0, 0xC0, 0, 0x40]);
+        assert_eq!(Font::from_pcf(&swapped), expected);
+    }
+
+    #[test]
+    fn invalid_pcf() {
+        assert!(Font::from_pcf(b"STARTFONT 2.1").is_err());
+
+        let data = font(BYTE_ORDER_MSB | BIT_ORDER_MSB, &[0x80, 0xC0, 0x40]);
+        assert!(Font::from_pcf(&data[..data.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use std::fmt::{self, Write};
+
+use crate::{compress::run_length_encode, font::Font};
+
+/// Number of data bytes per line.
+const BYTES_PER_LINE: usize = 16;
+
+/// Maximum number of entries in a chunk of a table.
+///
+/// Must match the chunk length used by the `include_bdf` macro.
+const CHUNK_LEN: usize = 4096;
+
+/// Generates Rust source code for a font.
+///
+/// The generated code defines a public `BdfFont` constant with the given name, which is equivalent
+/// to the output of the `include_bdf` macro. If `compress` is `true` the bitmaps are run length
+/// encoded and the constant is a `RunLengthFont` instead, which can only be drawn using the
+/// `RunLength` codec.
+pub fn write_rust_source<W: Write>(
+    output: &mut W,
+    font: &Font,
+    replacement_character: usize,
+    name: &str,
+    compress: bool,
+) -> fmt::Result {
+    let (start_indices, bytes) = if compress {
+        // The decode buffer must be large enough for the largest glyph.
+        let buffer_len = font
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.bitmap.len().div_ceil(8))
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        writeln!(
+            output,
+            "/// The glyph bitmaps are run length encoded and must be drawn using\n\
+            /// `draw_string_with_codec` and a `::eg_bdf::codec::RunLength::<{}>` codec.",
+            buffer_len
+        )?;
+
+        run_length_encode(font)
+    } else {
+        let mut bits = Vec::new();
+        let start_indices = font
+            .glyphs
+            .iter()
+            .map(|glyph| {
+                let start_index = bits.len();
+                bits.extend_from_slice(&glyph.bitmap);
+                start_index
+            })
+            .collect();
+
+        (start_indices, bits_to_bytes(&bits))
+    };
+
+    let mut fields = String::new();
+
+    let mut glyphs = Vec::new();
+    for (glyph, start_index) in font.glyphs.iter().zip(start_indices) {
+        glyphs.push(format!(
+            "::eg_bdf::__private::glyph({:?}, {}, {}, {}, {}, {}, {})",
+            glyph.character,
+            glyph.top_left.0,
+            glyph.top_left.1,
+            glyph.size.0,
+            glyph.size.1,
+            glyph.device_width,
+            start_index
+        ));
+    }
+    write_table(&mut fields, "glyphs", "::eg_bdf::BdfGlyph", &glyphs)?;
+    if compress {
+        // The encoded bitmaps are stored outside of the `BdfFont`.
+        writeln!(fields, "    data: &[],")?;
+    } else {
+        write_bytes(&mut fields, "data", &bytes)?;
+    }
+    writeln!(fields, "    pixel_size: {},", font.pixel_size)?;
+    writeln!(fields, "    font_ascent: {},", font.ascent)?;
+    writeln!(fields, "    font_descent: {},", font.descent)?;
+    writeln!(
+        fields,
+        "    replacement_character: {},",
+        replacement_character
+    )?;
+
+    // Scalable widths and vertical metrics are only included if all glyphs provide them.
+    let scalable_widths: Option<Vec<_>> = font
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.scalable_width)
+        .collect();
+    let scalable_widths: Vec<_> = scalable_widths
+        .unwrap_or_default()
+        .iter()
+        .map(|width| width.to_string())
+        .collect();
+    write_table(&mut fields, "scalable_widths", "i32", &scalable_widths)?;
+
+    let vertical: Option<Vec<_>> = font.glyphs.iter().map(|glyph| glyph.vertical).collect();
+    let vertical: Vec<_> = vertical
+        .unwrap_or_default()
+        .iter()
+        .map(|metrics| {
+            format!(
+                "::eg_bdf::VerticalMetrics {{ \
+                origin: ::eg_bdf::__private::Point::new({}, {}), advance: {} }}",
+                metrics.origin.0, metrics.origin.1, metrics.advance
+            )
+        })
+        .collect();
+    write_table(
+        &mut fields,
+        "vertical_metrics",
+        "::eg_bdf::VerticalMetrics",
+        &vertical,
+    )?;
+
+    writeln!(
+        fields,
+        "    bitmap_layout: ::eg_bdf::BitmapLayout::new(\
+        ::eg_bdf::BitOrder::MsbFirst, ::eg_bdf::RowLayout::Packed),"
+    )?;
+
+    // Glyphs with empty bounding boxes don't contribute to the union.
+    let corners = font
+        .glyphs
+        .iter()
+        .filter(|glyph| glyph.size.0 > 0 && glyph.size.1 > 0)
+        .map(|glyph| {
+            let (x, y) = glyph.top_left;
+            ((x, y), (x + glyph.size.0 as i32, y + glyph.size.1 as i32))
+        })
+        .reduce(|(min, max), (top_left, bottom_right)| {
+            (
+                (min.0.min(top_left.0), min.1.min(top_left.1)),
+                (max.0.max(bottom_right.0), max.1.max(bottom_right.1)),
+            )
+        })
+        .unwrap_or_default();
+    let max_advance = font
+        .glyphs
+        .iter()
+        .map(|glyph| glyph.device_width)
+        .max()
+        .unwrap_or_default();
+    let count = font.glyphs.len().max(1) as i64;
+    let advance_sum: i64 = font
+        .glyphs
+        .iter()
+        .map(|glyph| i64::from(glyph.device_width))
+        .sum();
+    let average_advance = (advance_sum * 2 + count).div_euclid(count * 2);
+    let fixed_advance = match font.glyphs.first() {
+        Some(first)
+            if first.device_width >= 0
+                && font
+                    .glyphs
+                    .iter()
+                    .all(|glyph| glyph.device_width == first.device_width) =>
+        {
+            format!("Some({})", first.device_width)
+        }
+        _ => "None".to_string(),
+    };
+
+    let ((x, y), (end_x, end_y)) = corners;
+    writeln!(
+        fields,
+        "    extents: ::eg_bdf::FontExtents {{ \
+        max_bounding_box: ::eg_bdf::__private::Rectangle::new(\
+        ::eg_bdf::__private::Point::new({}, {}), \
+        ::eg_bdf::__private::Size::new({}, {})), \
+        max_advance: {}, average_advance: {}, fixed_advance: {} }},",
+        x,
+        y,
+        end_x - x,
+        end_y - y,
+        max_advance,
+        average_advance,
+        fixed_advance
+    )?;
+
+    if !compress {
+        writeln!(
+            output,
+            "pub const {}: ::eg_bdf::BdfFont = ::eg_bdf::BdfFont {{",
+            name
+        )?;
+        write!(output, "{}", fields)?;
+        return writeln!(output, "}};");
+    }
+
+    writeln!(
+        output,
+        "pub const {}: ::eg_bdf::codec::RunLengthFont = ::eg_bdf::codec::RunLengthFont {{",
+        name
+    )?;
+    writeln!(output, "    font: ::eg_bdf::BdfFont {{")?;
+    for line in fields.lines() {
+        writeln!(output, "    {}", line)?;
+    }
+    writeln!(output, "    }},")?;
+    write_bytes(output, "data", &bytes)?;
+    writeln!(output, "}};")
+}
+
+/// Writes a byte slice field.
+fn write_bytes<W: Write>(output: &mut W, field: &str, bytes: &[u8]) -> fmt::Result {
+    writeln!(output, "    {}: &[", field)?;
+    for line in bytes.chunks(BYTES_PER_LINE) {
+        let line = line
+            .iter()
+            .map(|byte| format!("0x{:02X}", byte))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(output, "        {},", line)?;
+    }
+    writeln!(output, "    ],")
+}
+
+/// Writes a table field with one entry per line.
+///
+/// Tables with more than [`CHUNK_LEN`] entries are split into chunks, which are defined as
+/// separate constants and concatenated at compile time, because very long array expressions are
+/// slow to compile.
+fn write_table<W: Write>(output: &mut W, field: &str, ty: &str, entries: &[String]) -> fmt::Result {
+    if entries.is_empty() {
+        return writeln!(output, "    {}: &[],", field);
+    }
+
+    if entries.len() <= CHUNK_LEN {
+        writeln!(output, "    {}: &[", field)?;
+        for entry in entries {
+            writeln!(output, "        {},", entry)?;
+        }
+        return writeln!(output, "    ],");
+    }
+
+    writeln!(output, "    {}: {{", field)?;
+    let chunks = entries.chunks(CHUNK_LEN);
+    let count = chunks.len();
+    for (i, chunk) in chunks.enumerate() {
+        writeln!(output, "        const CHUNK_{}: &[{}] = &[", i, ty)?;
+        for entry in chunk {
+            writeln!(output, "            {},", entry)?;
+        }
+        writeln!(output, "        ];")?;
+    }
+    let names: Vec<_> = (0..count).map(|i| format!("CHUNK_{}", i)).collect();
+    writeln!(
+        output,
+        "        const TABLE: [{}; {}] = ::eg_bdf::__private::concat(&[{}]);",
+        ty,
+        entries.len(),
+        names.join(", ")
+    )?;
+    writeln!(output, "        &TABLE")?;
+    writeln!(output, "    }},")
+}
+
+/// Packs pixels into bytes, with the first pixel in the most significant bit.
+pub fn bits_to_bytes(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|bits| {
+            bits.iter()
+                .enumerate()
+                .filter(|(_, b)| **b)
+                .map(|(i, _)| 0x80 >> i)
+                .sum()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::Glyph;
+
+    #[test]
+    fn generate_source() {
+        let font = Font {
+            glyphs: vec![Glyph {
+                character: 'a',
+                top_left: (0, -1),
+                size: (3, 3),
+                device_width: 4,
+                scalable_width: None,
+                vertical: None,
+                bitmap: vec![true, false, true, false, true, false, true, false, true],
+            }],
+            pixel_size: 4,
+            ascent: 3,
+            descent: 1,
+        };
+
+        let mut source = String::new();
+        write_rust_source(&mut source, &font, 0, "FONT", false).unwrap();
+        assert!(source.starts_with("pub const FONT: ::eg_bdf::BdfFont"));
+        assert!(source.contains("        ::eg_bdf::__private::glyph('a', 0, -1, 3, 3, 4, 0),\n"));
+        assert!(source.contains("        0xAA, 0x80,\n"));
+        assert!(source.contains("    vertical_metrics: &[],\n"));
+        assert!(source.contains(
+            "::eg_bdf::__private::Size::new(3, 3)), max_advance: 4, average_advance: 4, fixed_advance: Some(4) },"
+        ));
+
+        let mut source = String::new();
+        write_rust_source(&mut source, &font, 0, "FONT", true).unwrap();
+        assert!(source.starts_with("/// The glyph bitmaps are run length encoded"));
+        assert!(source.contains("`::eg_bdf::codec::RunLength::<2>` codec"));
+        assert!(source.contains(
+            "pub const FONT: ::eg_bdf::codec::RunLengthFont = ::eg_bdf::codec::RunLengthFont {\n    \
+            font: ::eg_bdf::BdfFont {\n"
+        ));
+        assert!(
+            source.contains("            ::eg_bdf::__private::glyph('a', 0, -1, 3, 3, 4, 8),\n")
+        );
+        assert!(source.contains("        data: &[],\n"));
+        assert!(source.ends_with("    },\n    data: &[\n        0x11, 0x5F,\n    ],\n};\n"));
+    }
+
+    #[test]
+    fn chunked_tables() {
+        let glyph = |character| Glyph {
+            character,
+            top_left: (0, 0),
+            size: (1, 1),
+            device_width: 1,
+            scalable_width: Some(1000),
+            vertical: None,
+            bitmap: vec![true],
+        };
+        let font = Font {
+            glyphs: ('\u{4E00}'..'\u{6000}').map(glyph).collect(),
+            pixel_size: 1,
+            ascent: 1,
+            descent: 0,
+        };
+
+        let mut source = String::new();
+        write_rust_source(&mut source, &font, 0, "FONT", false).unwrap();
+        assert!(
+            source.contains("    glyphs: {\n        const CHUNK_0: &[::eg_bdf::BdfGlyph] = &[\n")
+        );
+        assert!(source.contains(
+            "        const TABLE: [::eg_bdf::BdfGlyph; 4608] = ::eg_bdf::__private::concat(&[CHUNK_0, CHUNK_1]);\n"
+        ));
+        assert!(source.contains("const TABLE: [i32; 4608]"));
+        assert!(source.contains("    vertical_metrics: &[],\n"));
+    }
+}
@@ -0,0 +1,435 @@
+//! u8g2 font format.
+//!
+//! u8g2 fonts are distributed as C source files, which contain the font data as a string literal.
+//! The data starts with a 23 byte header, which is followed by a list of glyphs with 8 bit
+//! encodings and a list of glyphs with 16 bit encodings. Glyph bitmaps are run length encoded
+//! with a variable number of bits per value.
+
+use std::convert::TryFrom;
+
+use crate::font::{Font, Glyph};
+
+/// Size of the font header in bytes.
+const HEADER_SIZE: usize = 23;
+
+/// Font header.
+struct Header {
+    bits_per_0: u8,
+    bits_per_1: u8,
+    bits_per_char_width: u8,
+    bits_per_char_height: u8,
+    bits_per_char_x: u8,
+    bits_per_char_y: u8,
+    bits_per_delta_x: u8,
+    max_char_height: u8,
+    y_offset: i8,
+    start_pos_unicode: usize,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        let header = data
+            .get(..HEADER_SIZE)
+            .ok_or("incomplete u8g2 font header")?;
+
+        Ok(Self {
+            bits_per_0: header[2],
+            bits_per_1: header[3],
+            bits_per_char_width: header[4],
+            bits_per_char_height: header[5],
+            bits_per_char_x: header[6],
+            bits_per_char_y: header[7],
+            bits_per_delta_x: header[8],
+            max_char_height: header[10],
+            y_offset: header[12] as i8,
+            start_pos_unicode: usize::from(u16::from_be_bytes([header[21], header[22]])),
+        })
+    }
+}
+
+/// Reader for the glyph bit stream, which stores the least significant bit first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn unsigned(&mut self, bits: u8) -> Result<u32, String> {
+        let mut value = 0;
+        for bit in 0..bits {
+            let byte = self
+                .data
+                .get(self.position / 8)
+                .ok_or("unexpected end of glyph data")?;
+            if byte >> (self.position % 8) & 1 != 0 {
+                value |= 1 << bit;
+            }
+            self.position += 1;
+        }
+
+        Ok(value)
+    }
+
+    /// Reads a signed value, which is stored with an offset of half the value range.
+    fn signed(&mut self, bits: u8) -> Result<i32, String> {
+        Ok(self.unsigned(bits)? as i32 - (1 << bits) / 2)
+    }
+}
+
+/// Decodes a glyph.
+fn decode_glyph(header: &Header, character: char, data: &[u8]) -> Result<Glyph, String> {
+    let mut reader = BitReader::new(data);
+
+    let width = reader.unsigned(header.bits_per_char_width)?;
+    let height = reader.unsigned(header.bits_per_char_height)?;
+    let x = reader.signed(header.bits_per_char_x)?;
+    let y = reader.signed(header.bits_per_char_y)?;
+    let device_width = reader.signed(header.bits_per_delta_x)?;
+
+    // The bitmap consists of runs of background and foreground pixels. Each pair of run lengths
+    // is followed by a bit, which is set if the pair is repeated.
+    let size = (width * height) as usize;
+    let mut bitmap = Vec::with_capacity(size);
+    while bitmap.len() < size {
+        let zeros = reader.unsigned(header.bits_per_0)? as usize;
+        let ones = reader.unsigned(header.bits_per_1)? as usize;
+
+        loop {
+            bitmap.resize(bitmap.len() + zeros, false);
+            bitmap.resize(bitmap.len() + ones, true);
+
+            if reader.unsigned(1)? == 0 {
+                break;
+            }
+        }
+    }
+    bitmap.truncate(size);
+
+    Ok(Glyph {
+        character,
+        top_left: (x, -y - (height as i32 - 1)),
+        size: (width, height),
+        device_width,
+        scalable_width: None,
+        vertical: None,
+        bitmap,
+    })
+}
+
+impl Font {
+    /// Loads a font in the u8g2 format.
+    ///
+    /// The font ascent and descent are derived from the font bounding box.
+    pub fn from_u8g2(data: &[u8]) -> Result<Self, String> {
+        let header = Header::parse(data)?;
+        let data = &data[HEADER_SIZE..];
+        let truncated = || "unexpected end of u8g2 font data".to_string();
+
+        let mut glyphs = Vec::new();
+
+        // Glyphs with 8 bit encodings, which start with the encoding and the offset to the next
+        // glyph. The list is terminated by an offset of zero.
+        let mut offset = 0;
+        loop {
+            let (encoding, next) = match data.get(offset..offset + 2) {
+                Some(&[encoding, next]) => (encoding, usize::from(next)),
+                _ => return Err(truncated()),
+            };
+            if next == 0 {
+                break;
+            }
+
+            let glyph_data = data.get(offset + 2..offset + next).ok_or_else(truncated)?;
+            glyphs.push(decode_glyph(&header, char::from(encoding), glyph_data)?);
+            offset += next;
+        }
+
+        // Glyphs with 16 bit encodings are preceded by a lookup table, whose first entry contains
+        // the offset from the table to the first glyph. Each glyph starts with the encoding and
+        // the offset to the next glyph. The list is terminated by an encoding of zero.
+        let table = header.start_pos_unicode;
+        let mut offset = match data.get(table..table + 2) {
+            Some(&[high, low]) => table + usize::from(u16::from_be_bytes([high, low])),
+            _ => return Err(truncated()),
+        };
+        loop {
+            let (encoding, next) = match data.get(offset..offset + 2) {
+                Some(&[0, 0]) => break,
+                Some(&[high, low]) => match data.get(offset + 2) {
+                    Some(&next) => (u16::from_be_bytes([high, low]), usize::from(next)),
+                    None => return Err(truncated()),
+                },
+                _ => return Err(truncated()),
+            };
+
+            let character = char::from_u32(u32::from(encoding))
+                .ok_or_else(|| format!("invalid encoding {:#06X}", encoding))?;
+            if next < 3 {
+                return Err(format!("invalid glyph offset for {:#06X}", encoding));
+            }
+
+            let glyph_data = data.get(offset + 3..offset + next).ok_or_else(truncated)?;
+            glyphs.push(decode_glyph(&header, character, glyph_data)?);
+            offset += next;
+        }
+
+        let pixel_size = u32::from(header.max_char_height);
+        let descent = (-i32::from(header.y_offset)).max(0) as u32;
+        let ascent = (i32::from(header.max_char_height) + i32::from(header.y_offset)).max(0) as u32;
+
+        Ok(Self {
+            glyphs,
+            pixel_size,
+            ascent,
+            descent,
+        })
+    }
+}
+
+/// Extracts the font data from a u8g2 C source file.
+///
+/// The font data is the concatenation of the string literals in the initializer of the first
+/// array, including the terminating NUL character, which is part of the font data.
+pub fn parse_c_source(source: &str) -> Result<Vec<u8>, String> {
+    let source = source.as_bytes();
+    let mut data = Vec::new();
+    let mut initializer = false;
+    let mut literals = 0;
+
+    let mut index = 0;
+    while index < source.len() {
+        match (source[index], source.get(index + 1)) {
+            (b'/', Some(b'*')) => {
+                let end = source[index + 2..]
+                    .windows(2)
+                    .position(|window| window == b"*/")
+                    .ok_or("unterminated comment")?;
+                index += end + 4;
+                continue;
+            }
+            (b'/', Some(b'/')) => {
+                while index < source.len() && source[index] != b'\n' {
+                    index += 1;
+                }
+                continue;
+            }
+            (b'=', _) => initializer = true,
+            (b';', _) if initializer => break,
+            (b'"', _) => {
+                // String literals before the initializer, e.g. section names, are skipped.
+                let mut skipped = Vec::new();
+                let output = if initializer { &mut data } else { &mut skipped };
+                index = parse_string_literal(source, index + 1, output)?;
+                literals += usize::from(initializer);
+                continue;
+            }
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    if literals == 0 {
+        return Err("no font data found".to_string());
+    }
+    data.push(0);
+
+    Ok(data)
+}
+
+/// Parses a C string literal starting after the opening quote.
+///
+/// Returns the index after the closing quote.
+fn parse_string_literal(
+    source: &[u8],
+    mut index: usize,
+    output: &mut Vec<u8>,
+) -> Result<usize, String> {
+    let unterminated = || "unterminated string literal".to_string();
+
+    loop {
+        let byte = *source.get(index).ok_or_else(unterminated)?;
+        index += 1;
+
+        let value = match byte {
+            b'"' => return Ok(index),
+            b'\n' => return Err(unterminated()),
+            b'\\' => {
+                let escape = *source.get(index).ok_or_else(unterminated)?;
+                index += 1;
+
+                match escape {
+                    b'0'..=b'7' => {
+                        // Octal escapes contain up to three digits.
+                        let mut value = u32::from(escape - b'0');
+                        for _ in 0..2 {
+                            match source.get(index) {
+                                Some(digit @ b'0'..=b'7') => {
+                                    value = value * 8 + u32::from(digit - b'0');
+                                    index += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        value
+                    }
+                    b'x' => {
+                        let digits = source[index..]
+                            .iter()
+                            .take_while(|digit| digit.is_ascii_hexdigit())
+                            .count();
+                        let hex = std::str::from_utf8(&source[index..index + digits]).unwrap();
+                        index += digits;
+                        u32::from_str_radix(hex, 16).map_err(|_| "invalid hex escape")?
+                    }
+                    b'n' => 0x0A,
+                    b't' => 0x09,
+                    b'r' => 0x0D,
+                    b'a' => 0x07,
+                    b'b' => 0x08,
+                    b'f' => 0x0C,
+                    b'v' => 0x0B,
+                    b'\\' | b'"' | b'\'' | b'?' => u32::from(escape),
+                    _ => return Err(format!("invalid escape sequence \\{}", escape as char)),
+                }
+            }
+            byte => u32::from(byte),
+        };
+
+        output.push(u8::try_from(value).map_err(|_| "escape sequence out of range")?);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writer for the glyph bit stream.
+    #[derive(Default)]
+    struct BitWriter {
+        data: Vec<u8>,
+        position: usize,
+    }
+
+    impl BitWriter {
+        fn push(mut self, value: i32, bits: u8) -> Self {
+            for bit in 0..bits {
+                if self.position.is_multiple_of(8) {
+                    self.data.push(0);
+                }
+                if value >> bit & 1 != 0 {
+                    *self.data.last_mut().unwrap() |= 1 << (self.position % 8);
+                }
+                self.position += 1;
+            }
+
+            self
+        }
+
+        /// Writes the glyph metrics with the bit widths used by `font`.
+        fn metrics(self, size: (i32, i32), offset: (i32, i32), advance: i32) -> Self {
+            self.push(size.0, 3)
+                .push(size.1, 3)
+                .push(offset.0 + 2, 2)
+                .push(offset.1 + 2, 2)
+                .push(advance + 4, 3)
+        }
+
+        /// Writes a pair of run lengths, which is repeated `repeat` times.
+        fn run(self, zeros: i32, ones: i32, repeat: usize) -> Self {
+            (0..repeat)
+                .fold(self.push(zeros, 2).push(ones, 2), |writer, _| {
+                    writer.push(1, 1)
+                })
+                .push(0, 1)
+        }
+    }
+
+    fn font_data() -> Vec<u8> {
+        // 2x3 glyph for 'a', which repeats the pattern of one background and one foreground pixel.
+        let a = BitWriter::default()
+            .metrics((2, 3), (0, -1), 3)
+            .run(1, 1, 1)
+            .run(0, 2, 0)
+            .data;
+        // 1x1 glyph for U+20AC.
+        let euro = BitWriter::default()
+            .metrics((1, 1), (1, 0), 3)
+            .run(0, 1, 0)
+            .data;
+
+        let mut data = vec![2, 0, 2, 2, 3, 3, 2, 2, 3, 2, 3, 0, 0xFF, 2, 0xFF, 2, 0xFF];
+        data.extend_from_slice(&[0, 0, 0, 0]);
+        let unicode = 2 + a.len() + 2;
+        data.extend_from_slice(&(unicode as u16).to_be_bytes());
+
+        data.extend_from_slice(&[b'a', 2 + a.len() as u8]);
+        data.extend_from_slice(&a);
+        data.extend_from_slice(&[0, 0]);
+
+        data.extend_from_slice(&[0, 4, 0xFF, 0xFF]);
+        data.extend_from_slice(&[0x20, 0xAC, 3 + euro.len() as u8]);
+        data.extend_from_slice(&euro);
+        data.extend_from_slice(&[0, 0]);
+
+        data
+    }
+
+    #[test]
+    fn load_u8g2() {
+        let font = Font::from_u8g2(&font_data()).unwrap();
+        assert_eq!((font.pixel_size, font.ascent, font.descent), (3, 2, 1));
+
+        assert_eq!(
+            font.glyphs,
+            vec![
+                Glyph {
+                    character: 'a',
+                    top_left: (0, -1),
+                    size: (2, 3),
+                    device_width: 3,
+                    scalable_width: None,
+                    vertical: None,
+                    bitmap: vec![false, true, false, true, true, true],
+                },
+                Glyph {
+                    character: '\u{20AC}',
+                    top_left: (1, 0),
+                    size: (1, 1),
+                    device_width: 3,
+                    scalable_width: None,
+                    vertical: None,
+                    bitmap: vec![true],
+                },
+            ]
+        );
+
+        let data = font_data();
+        assert!(Font::from_u8g2(&data[..10]).is_err());
+        assert!(Font::from_u8g2(&data[..data.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn c_source() {
+        let source = r#"/*
+  Fontname: test
+  Copyright: a = b
+*/
+const uint8_t u8g2_font_test[6] U8G2_FONT_SECTION("u8g2_font_test") =
+  "\2\0\377"
+  "a\x41\\";
+"#;
+        assert_eq!(
+            parse_c_source(source).unwrap(),
+            vec![2, 0, 255, b'a', b'A', b'\\', 0]
+        );
+
+        assert!(parse_c_source("const uint8_t font[1];").is_err());
+        assert!(parse_c_source("x = \"\\400\";").is_err());
+        assert!(parse_c_source("x = \"abc").is_err());
+    }
+}
@@ -1,19 +1,21 @@
-use bdf_parser::{BdfFont, BoundingBox, Glyph, Property};
+use bdf_parser::{BdfFont, BoundingBox, Coord, Glyph, Property, PropertyError};
 use embedded_graphics::{prelude::*, primitives::Rectangle};
 use proc_macro::TokenStream;
-use proc_macro2::Span;
-use quote::quote;
+use proc_macro2::{Literal, Span};
+use quote::{quote, ToTokens};
 use std::{convert::TryFrom, fs, path::PathBuf};
 use syn::{
     parse::{Parse, ParseStream},
     parse_macro_input,
     punctuated::Punctuated,
-    LitChar, LitStr, Result, Token,
+    Error, Ident, LitBool, LitChar, LitInt, LitStr, Result, Token,
 };
 
 struct IncludeBdf {
     filename: LitStr,
     character_ranges: Option<CharacterRanges>,
+    rotation: Rotation,
+    lenient: bool,
 }
 
 impl IncludeBdf {
@@ -28,16 +30,45 @@ impl IncludeBdf {
 impl Parse for IncludeBdf {
     fn parse(input: ParseStream) -> Result<Self> {
         let filename = input.parse()?;
-
-        let character_ranges = if input.lookahead1().peek(Token![,]) {
-            Some(input.parse()?)
-        } else {
-            None
-        };
+        let mut character_ranges = None;
+        let mut rotation = None;
+        let mut lenient = None;
+
+        while input.peek(Token![,]) {
+            // Options start with an identifier, character ranges with a character literal.
+            if input.peek2(Ident) {
+                input.parse::<Token![,]>()?;
+                let name = input.parse::<Ident>()?;
+                let is_duplicate = if name == "rotate" {
+                    rotation.replace(Rotation::parse_option(input)?).is_some()
+                } else if name == "lenient" {
+                    input.parse::<Token![=]>()?;
+                    lenient.replace(input.parse::<LitBool>()?.value).is_some()
+                } else {
+                    return Err(Error::new(
+                        name.span(),
+                        "unknown option, expected `rotate` or `lenient`",
+                    ));
+                };
+
+                if is_duplicate {
+                    return Err(Error::new(
+                        name.span(),
+                        format!("duplicate option `{}`", name),
+                    ));
+                }
+            } else if character_ranges.is_none() {
+                character_ranges = Some(input.parse()?);
+            } else {
+                return Err(input.error("expected an option"));
+            }
+        }
 
         Ok(Self {
             filename,
             character_ranges,
+            rotation: rotation.unwrap_or(Rotation::None),
+            lenient: lenient.unwrap_or(false),
         })
     }
 }
@@ -91,22 +122,134 @@ impl Parse for CharacterRange {
             None
         };
 
-        Ok(Self { from, to })
+        let range = Self { from, to };
+        if let Some((_, to)) = &range.to {
+            if range.from.value() > to.value() {
+                return Err(Error::new_spanned(
+                    &range,
+                    format!(
+                        "empty character range, {:?} is greater than {:?}",
+                        range.from.value(),
+                        to.value()
+                    ),
+                ));
+            }
+        }
+
+        Ok(range)
+    }
+}
+
+impl ToTokens for CharacterRange {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.from.to_tokens(tokens);
+        if let Some((dots, to)) = &self.to {
+            dots.to_tokens(tokens);
+            to.to_tokens(tokens);
+        }
+    }
+}
+
+/// Rotation of the generated glyph data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rotation {
+    None,
+    Clockwise,
+    CounterClockwise,
+}
+
+impl Rotation {
+    /// Parses the value of a `rotate = <degrees>` option, starting at the `=` token.
+    fn parse_option(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![=]>()?;
+
+        let degrees = input.parse::<LitInt>()?;
+        match degrees.base10_parse::<u32>()? {
+            0 => Ok(Self::None),
+            90 => Ok(Self::Clockwise),
+            270 => Ok(Self::CounterClockwise),
+            _ => Err(Error::new(
+                degrees.span(),
+                "unsupported rotation, expected 0, 90 or 270",
+            )),
+        }
+    }
+
+    /// Rotates a glyph bounding box around the glyph origin.
+    fn bounding_box(self, rectangle: &Rectangle) -> Rectangle {
+        let Rectangle {
+            top_left: Point { x, y },
+            size: Size { width, height },
+        } = *rectangle;
+        let rotated_size = Size::new(height, width);
+
+        match self {
+            Self::None => *rectangle,
+            Self::Clockwise => {
+                Rectangle::new(Point::new(-(y + height as i32 - 1), x), rotated_size)
+            }
+            Self::CounterClockwise => {
+                Rectangle::new(Point::new(y, -(x + width as i32 - 1)), rotated_size)
+            }
+        }
+    }
+
+    /// Returns the position in the unrotated bitmap for a position in the rotated bitmap.
+    ///
+    /// `size` is the size of the unrotated bitmap.
+    fn source_pixel(self, x: usize, y: usize, size: (usize, usize)) -> (usize, usize) {
+        match self {
+            Self::None => (x, y),
+            Self::Clockwise => (y, size.1 - 1 - x),
+            Self::CounterClockwise => (size.0 - 1 - y, x),
+        }
     }
 }
 
 /// Converts a BDF bounding box into an embedded-graphics rectangle.
+///
+/// Glyphs with negative bounding box sizes are rejected before this conversion.
 fn bounding_box_to_rectangle(bounding_box: &BoundingBox) -> Rectangle {
     Rectangle::new(
         Point::new(
             bounding_box.offset.x,
-            -bounding_box.offset.y - (bounding_box.size.y as i32 - 1),
+            -bounding_box.offset.y - (bounding_box.size.y - 1),
         ),
-        // TODO: check for negative values
         Size::new(bounding_box.size.x as u32, bounding_box.size.y as u32),
     )
 }
 
+/// Maximum number of entries in a chunk of a generated table.
+const CHUNK_LEN: usize = 4096;
+
+/// Returns a slice expression for a table.
+///
+/// Tables with more than [`CHUNK_LEN`] entries are split into chunks, which are defined as
+/// separate constants and concatenated at compile time. Very long array expressions make the
+/// compiler slow and use a lot of memory, which becomes a problem for fonts with tens of
+/// thousands of glyphs.
+fn table<T: ToTokens>(ty: proc_macro2::TokenStream, entries: &[T]) -> proc_macro2::TokenStream {
+    if entries.len() <= CHUNK_LEN {
+        return quote! { &[ #( #entries ),* ] };
+    }
+
+    let len = entries.len();
+    let names: Vec<_> = (0..len.div_ceil(CHUNK_LEN))
+        .map(|i| Ident::new(&format!("CHUNK_{}", i), Span::call_site()))
+        .collect();
+    let chunks = entries
+        .chunks(CHUNK_LEN)
+        .map(|chunk| quote! { #( #chunk ),* });
+
+    quote! {
+        {
+            #( const #names: &[#ty] = &[ #chunks ]; )*
+            const TABLE: [#ty; #len] = ::eg_bdf::__private::concat(&[ #( #names ),* ]);
+            &TABLE
+        }
+    }
+}
+
 fn rectangle_constructor(rectangle: &Rectangle) -> proc_macro2::TokenStream {
     let Rectangle {
         top_left: Point { x, y },
@@ -114,27 +257,41 @@ fn rectangle_constructor(rectangle: &Rectangle) -> proc_macro2::TokenStream {
     } = rectangle;
 
     quote! {
-        ::embedded_graphics::primitives::Rectangle::new(
-            ::embedded_graphics::geometry::Point::new(#x, #y),
-            ::embedded_graphics::geometry::Size::new(#width, #height),
+        ::eg_bdf::__private::Rectangle::new(
+            ::eg_bdf::__private::Point::new(#x, #y),
+            ::eg_bdf::__private::Size::new(#width, #height),
         )
     }
 }
 
-fn glyph_literal(glyph: &Glyph, start_index: usize) -> (Vec<bool>, proc_macro2::TokenStream) {
+/// Returns the bitmap data and the literal of a glyph.
+///
+/// Rotated glyphs are rotated around their origin and use `device_width` instead of the device
+/// width of the BDF glyph.
+fn glyph_literal(
+    glyph: &Glyph,
+    start_index: usize,
+    rotation: Rotation,
+    device_width: i32,
+) -> (Vec<bool>, proc_macro2::TokenStream) {
     let character = LitChar::new(glyph.encoding.unwrap(), Span::call_site());
 
-    let rectangle = bounding_box_to_rectangle(&glyph.bounding_box);
-    let bounding_box = rectangle_constructor(&rectangle);
+    let rectangle = rotation.bounding_box(&bounding_box_to_rectangle(&glyph.bounding_box));
+    let Rectangle {
+        top_left: Point { x, y },
+        size: Size { width, height },
+    } = rectangle;
 
-    // TODO: handle height != 0
-    // TODO: check for negative values
-    let device_width = glyph.device_width.x as u32;
+    let size = (
+        usize::try_from(glyph.bounding_box.size.x).unwrap(),
+        usize::try_from(glyph.bounding_box.size.y).unwrap(),
+    );
 
     let mut data = Vec::new();
 
-    for y in 0..usize::try_from(glyph.bounding_box.size.y).unwrap() {
-        for x in 0..usize::try_from(glyph.bounding_box.size.x).unwrap() {
+    for y in 0..rectangle.size.height as usize {
+        for x in 0..rectangle.size.width as usize {
+            let (x, y) = rotation.source_pixel(x, y, size);
             data.push(glyph.pixel(x, y))
         }
     }
@@ -142,72 +299,374 @@ fn glyph_literal(glyph: &Glyph, start_index: usize) -> (Vec<bool>, proc_macro2::
     (
         data,
         quote! {
-            ::eg_bdf::BdfGlyph {
-                character: #character,
-                bounding_box: #bounding_box,
-                device_width: #device_width,
-                start_index: #start_index,
-            }
+            ::eg_bdf::__private::glyph(
+                #character, #x, #y, #width, #height, #device_width, #start_index,
+            )
         },
     )
 }
 
+/// Returns the vertical metrics of a glyph.
+///
+/// Glyphs without a `DWIDTH1` statement advance by the height of the font and glyphs without a
+/// `VVECTOR` statement use the global vector or, if none is given, the horizontal center of the
+/// top row of the font.
+fn vertical_metrics_literal(
+    glyph: &Glyph,
+    font: &BdfFont,
+    font_ascent: u32,
+    font_descent: u32,
+) -> proc_macro2::TokenStream {
+    let advance = glyph
+        .device_width_vertical
+        .map_or((font_ascent + font_descent) as i32, |width| -width.y);
+
+    let vector = glyph
+        .vertical_vector
+        .or(font.metadata.vertical_vector)
+        .unwrap_or_else(|| Coord::new(glyph.device_width.x / 2, font_ascent as i32));
+    // Convert the BDF vector, which points to the top edge of the first row, into a pixel
+    // coordinate with the Y axis pointing downwards.
+    let (x, y) = (vector.x, 1 - vector.y);
+
+    quote! {
+        ::eg_bdf::VerticalMetrics {
+            origin: ::eg_bdf::__private::Point::new(#x, #y),
+            advance: #advance,
+        }
+    }
+}
+
+/// Extents of the included glyphs.
+#[derive(Debug, Default)]
+struct Extents {
+    corners: Option<(Point, Point)>,
+    max_advance: i32,
+    min_advance: i32,
+    advance_sum: i64,
+    count: i64,
+}
+
+impl Extents {
+    fn add(&mut self, bounding_box: &Rectangle, device_width: i32) {
+        // Glyphs with empty bounding boxes don't contribute to the union.
+        if let Some(bottom_right) = bounding_box.bottom_right() {
+            let top_left = bounding_box.top_left;
+            self.corners = Some(match self.corners {
+                Some((min, max)) => (min.component_min(top_left), max.component_max(bottom_right)),
+                None => (top_left, bottom_right),
+            });
+        }
+
+        if self.count == 0 {
+            self.max_advance = device_width;
+            self.min_advance = device_width;
+        } else {
+            self.max_advance = self.max_advance.max(device_width);
+            self.min_advance = self.min_advance.min(device_width);
+        }
+        self.advance_sum += i64::from(device_width);
+        self.count += 1;
+    }
+
+    fn literal(&self) -> proc_macro2::TokenStream {
+        let bounding_box = self.corners.map_or(Rectangle::zero(), |(min, max)| {
+            Rectangle::with_corners(min, max)
+        });
+        let bounding_box = rectangle_constructor(&bounding_box);
+
+        let max_advance = self.max_advance;
+        let count = self.count.max(1);
+        let average_advance = (self.advance_sum * 2 + count).div_euclid(count * 2) as i32;
+        let fixed_advance = match u32::try_from(max_advance) {
+            Ok(advance) if self.count > 0 && self.min_advance == max_advance => {
+                quote! { ::core::option::Option::Some(#advance) }
+            }
+            _ => quote! { ::core::option::Option::None },
+        };
+
+        quote! {
+            ::eg_bdf::FontExtents {
+                max_bounding_box: #bounding_box,
+                max_advance: #max_advance,
+                average_advance: #average_advance,
+                fixed_advance: #fixed_advance,
+            }
+        }
+    }
+}
+
+/// Includes a BDF font.
+///
+/// The path is relative to the manifest directory of the crate and can be followed by a list of
+/// character ranges, e.g. `'A'..='Z' | ' '`, to limit the included glyphs. The glyphs don't need
+/// to be sorted in the BDF file and only the first glyph is included if multiple glyphs use the
+/// same encoding. Large fonts with tens of thousands of glyphs are supported, but including only
+/// the required characters considerably reduces the compile time and the size of the binary.
+///
+/// The `rotate = 90` or `rotate = 270` option rotates all glyph bitmaps clockwise or counter
+/// clockwise, which makes it possible to draw text on displays that are mounted in a rotated
+/// orientation without rotating pixels at runtime. The horizontal advance of the glyphs is
+/// replaced by the height of the font and the original advance is stored as the vertical advance
+/// with the vertical origin at the glyph origin. Rotated fonts are drawn using
+/// `BdfTextStyle::draw_vertical`, clockwise rotated text runs from top to bottom and counter
+/// clockwise rotated text from bottom to top.
+///
+/// ```ignore
+/// const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z', rotate = 90);
+/// ```
+///
+/// The `lenient = true` option enables the lenient mode of the BDF parser, which fixes common
+/// problems of non-conformant BDF files, like missing `ENDCHAR` statements or bitmap rows that
+/// don't match the bounding box, instead of failing. A warning is printed for every fixed
+/// problem.
 #[proc_macro]
 pub fn include_bdf(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as IncludeBdf);
 
+    expand(&input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Returns an integer property or `None` if the property isn't defined.
+///
+/// Errors point to the file name argument of the macro.
+fn int_property(font: &BdfFont, property: Property, filename: &LitStr) -> Result<Option<i32>> {
+    match font.properties.try_get::<i32>(property) {
+        Ok(value) => Ok(Some(value)),
+        Err(PropertyError::Undefined(_)) => Ok(None),
+        Err(error) => Err(Error::new(
+            filename.span(),
+            format!(
+                "invalid {} property in BDF file \"{}\": {}",
+                property,
+                filename.value(),
+                error
+            ),
+        )),
+    }
+}
+
+fn expand(input: &IncludeBdf) -> Result<proc_macro2::TokenStream> {
     let app_path = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
     let filename = input.filename.value();
     let path = app_path.join(&filename);
 
-    let bdf = fs::read(&path).expect("Reading of BDF file failed");
-    let font = BdfFont::parse(&bdf).expect("Parsing of BDF file failed");
+    let bdf = fs::read(&path).map_err(|error| {
+        Error::new(
+            input.filename.span(),
+            format!("couldn't read BDF file \"{}\": {}", path.display(), error),
+        )
+    })?;
+    let font = if input.lenient {
+        BdfFont::parse_lenient(&bdf).map(|(font, warnings)| {
+            for warning in warnings {
+                eprintln!("warning: include_bdf!(\"{}\"): {}", filename, warning);
+            }
+            font
+        })
+    } else {
+        BdfFont::parse(&bdf)
+    }
+    .map_err(|error| {
+        Error::new(
+            input.filename.span(),
+            format!("couldn't parse BDF file \"{}\": {}", filename, error),
+        )
+    })?;
+
+    let pixel_size =
+        int_property(&font, Property::PixelSize, &input.filename)?.unwrap_or_default() as u32;
+    let font_ascent = int_property(&font, Property::FontAscent, &input.filename)?
+        .unwrap_or(pixel_size as i32) as u32;
+    let font_descent = int_property(&font, Property::FontDescent, &input.filename)?
+        .unwrap_or(pixel_size as i32) as u32;
 
     let mut data = Vec::new();
     let mut glyphs = Vec::new();
+    let mut vertical_metrics = Vec::new();
+    let mut scalable_widths = Vec::new();
+    let mut extents = Extents::default();
     let mut replacement_character = None;
 
-    //TODO: sort glyphs to make it possible to use binary search
-    for glyph in font.glyphs.iter() {
-        if let Some(c) = glyph.encoding {
-            if !input.contains(c) {
-                continue;
-            }
+    // The glyphs are sorted by character, because glyphs are looked up using a binary search.
+    // Only the first glyph is included if multiple glyphs use the same encoding.
+    let mut selected: Vec<_> = font
+        .glyphs
+        .iter()
+        .filter_map(|glyph| Some((glyph.encoding?, glyph)))
+        .filter(|(c, _)| input.contains(*c))
+        .collect();
+    selected.sort_by_key(|(c, _)| *c);
+    selected.dedup_by_key(|(c, _)| *c);
+
+    for (c, glyph) in selected {
+        if glyph.bounding_box.size.x < 0 || glyph.bounding_box.size.y < 0 {
+            return Err(Error::new(
+                input.filename.span(),
+                format!(
+                    "glyph \"{}\" in BDF file \"{}\" has a negative bounding box size",
+                    glyph.name, filename
+                ),
+            ));
+        }
 
-            if c == std::char::REPLACEMENT_CHARACTER
-                || (c == ' ' && replacement_character.is_none())
-            {
-                replacement_character = Some(glyphs.len());
-            }
+        let bitmap_len =
+            (glyph.bounding_box.size.x as usize).div_ceil(8) * glyph.bounding_box.size.y as usize;
+        if glyph.bitmap.len() < bitmap_len {
+            return Err(Error::new(
+                input.filename.span(),
+                format!(
+                    "bitmap of glyph \"{}\" in BDF file \"{}\" is smaller than its bounding box, use `lenient = true` to pad it",
+                    glyph.name, filename
+                ),
+            ));
+        }
+
+        if c == std::char::REPLACEMENT_CHARACTER || (c == ' ' && replacement_character.is_none()) {
+            replacement_character = Some(glyphs.len());
+        }
+
+        // TODO: handle height != 0
+        let device_width = match input.rotation {
+            Rotation::None => glyph.device_width.x,
+            _ => (font_ascent + font_descent) as i32,
+        };
 
-            let (glyph_data, literal) = glyph_literal(glyph, data.len());
-            glyphs.push(literal);
-            data.extend_from_slice(&glyph_data);
+        let (glyph_data, literal) = glyph_literal(glyph, data.len(), input.rotation, device_width);
+        glyphs.push(literal);
+        extents.add(
+            &input
+                .rotation
+                .bounding_box(&bounding_box_to_rectangle(&glyph.bounding_box)),
+            device_width,
+        );
+        data.extend_from_slice(&glyph_data);
+
+        // The horizontal advance of rotated glyphs becomes the vertical advance.
+        if input.rotation != Rotation::None {
+            let advance = match input.rotation {
+                Rotation::Clockwise => glyph.device_width.x,
+                _ => -glyph.device_width.x,
+            };
+            vertical_metrics.push(quote! {
+                ::eg_bdf::VerticalMetrics {
+                    origin: ::eg_bdf::__private::Point::new(0, 0),
+                    advance: #advance,
+                }
+            });
+            scalable_widths.push(None);
+            continue;
+        }
+
+        // Convert the scalable width from 1/1000 of the point size into 1/1000 pixels.
+        scalable_widths.push(glyph.scalable_width.map(|width| {
+            i64::from(width.x)
+                * i64::from(font.metadata.point_size)
+                * i64::from(font.metadata.resolution.x)
+                / 72
+        }));
+
+        if font.metadata.metrics_set != 0 {
+            vertical_metrics.push(vertical_metrics_literal(
+                glyph,
+                &font,
+                font_ascent,
+                font_descent,
+            ));
         }
     }
 
+    // An empty glyph table would make every glyph lookup panic at runtime.
+    if glyphs.is_empty() {
+        let message = format!("no glyphs in BDF file \"{}\" match", filename);
+        return Err(match &input.character_ranges {
+            Some(ranges) => {
+                Error::new_spanned(&ranges.ranges, format!("{} the character ranges", message))
+            }
+            None => Error::new(input.filename.span(), "BDF file doesn't contain any glyphs"),
+        });
+    }
+
     // TODO: try to use DEFAULT_CHAR
     let replacement_character = replacement_character.unwrap_or_default();
 
     let data = bits_to_bytes(&data);
 
-    // TODO: report error or calculate fallback value
-    let pixel_size = font.properties.try_get::<i32>(Property::PixelSize).unwrap_or_default() as u32;
-    let font_ascent = font.properties.try_get::<i32>(Property::FontAscent).unwrap_or(pixel_size as i32) as u32;
-    let font_descent = font.properties.try_get::<i32>(Property::FontDescent).unwrap_or(pixel_size as i32) as u32;
+    // Scalable widths are only included if all glyphs provide them, which is never the case for
+    // rotated glyphs.
+    let scalable_widths = scalable_widths
+        .into_iter()
+        .map(|width| width.map(|width| width as i32))
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default();
+
+    let extents = extents.literal();
+
+    if std::env::var_os("EG_BDF_FOOTPRINT").is_some() {
+        eprintln!(
+            "{}",
+            footprint_note(
+                &filename,
+                glyphs.len(),
+                data.len(),
+                scalable_widths.len(),
+                vertical_metrics.len()
+            )
+        );
+    }
+
+    let glyphs = table(quote! { ::eg_bdf::BdfGlyph }, &glyphs);
+    // A byte string literal is a single token, which is much faster to compile than an array
+    // expression with one token per byte.
+    let data = Literal::byte_string(&data);
+    let vertical_metrics = table(quote! { ::eg_bdf::VerticalMetrics }, &vertical_metrics);
+    let scalable_widths = table(quote! { i32 }, &scalable_widths);
 
     let output = quote! {
         ::eg_bdf::BdfFont {
-            glyphs: &[ #( #glyphs ),* ],
-            data: &[ #( #data ),* ],
+            glyphs: #glyphs,
+            data: #data,
             pixel_size: #pixel_size,
             font_ascent: #font_ascent,
             font_descent: #font_descent,
             replacement_character: #replacement_character,
+            vertical_metrics: #vertical_metrics,
+            scalable_widths: #scalable_widths,
+            bitmap_layout: ::eg_bdf::BitmapLayout::new(
+                ::eg_bdf::BitOrder::MsbFirst,
+                ::eg_bdf::RowLayout::Packed,
+            ),
+            extents: #extents,
         }
     };
 
-    output.into()
+    Ok(output)
+}
+
+/// Returns a note with the memory footprint of a font.
+///
+/// The table sizes are calculated for 32 bit targets, which store the start index of a glyph as a
+/// 4 byte `usize`.
+fn footprint_note(
+    filename: &str,
+    glyphs: usize,
+    data: usize,
+    scalable_widths: usize,
+    vertical_metrics: usize,
+) -> String {
+    // Sizes of `BdfGlyph`, scalable widths and `VerticalMetrics`.
+    let tables = glyphs * 28 + scalable_widths * 4 + vertical_metrics * 12;
+
+    format!(
+        "note: include_bdf!(\"{}\") uses {} bytes ({} glyphs, {} bytes bitmap data, {} bytes tables)",
+        filename,
+        data + tables,
+        glyphs,
+        data,
+        tables
+    )
 }
 
 fn bits_to_bytes(bits: &[bool]) -> Vec<u8> {
@@ -236,6 +695,136 @@ mod tests {
         assert_eq!(bits_to_bytes(&[t, f, f, f, f, f, f, t]), vec![0x81]);
     }
 
+    #[test]
+    fn test_footprint_note() {
+        assert_eq!(
+            footprint_note("font.bdf", 2, 10, 2, 0),
+            "note: include_bdf!(\"font.bdf\") uses 74 bytes (2 glyphs, 10 bytes bitmap data, 64 bytes tables)"
+        );
+    }
+
+    fn parse_error(input: &str) -> String {
+        match syn::parse_str::<IncludeBdf>(input) {
+            Ok(_) => panic!("expected error for {}", input),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    fn expand_error(input: &str) -> String {
+        let input = syn::parse_str::<IncludeBdf>(input).unwrap();
+        expand(&input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_argument_errors() {
+        assert_eq!(
+            parse_error(r#""font.bdf", 'Z'..='A'"#),
+            "empty character range, 'Z' is greater than 'A'"
+        );
+        assert_eq!(
+            parse_error(r#""font.bdf", rotate = 90, rotate = 90"#),
+            "duplicate option `rotate`"
+        );
+        assert_eq!(
+            parse_error(r#""font.bdf", size = 8"#),
+            "unknown option, expected `rotate` or `lenient`"
+        );
+        assert_eq!(
+            parse_error(r#""font.bdf", lenient = true, rotate = 90, lenient = false"#),
+            "duplicate option `lenient`"
+        );
+        assert_eq!(
+            parse_error(r#""font.bdf", rotate = 45"#),
+            "unsupported rotation, expected 0, 90 or 270"
+        );
+    }
+
+    #[test]
+    fn test_file_errors() {
+        assert!(expand_error(r#""missing.bdf""#).starts_with("couldn't read BDF file"));
+
+        let path = std::env::temp_dir().join("eg-bdf-macros-invalid.bdf");
+        fs::write(&path, "STARTFONT 2.1\nFONT test\nSIZE 16 75\n").unwrap();
+        assert_eq!(
+            expand_error(&format!("{:?}", path.to_str().unwrap())),
+            format!(
+                "couldn't parse BDF file \"{}\": couldn't parse metadata in line 3",
+                path.display()
+            )
+        );
+
+        // The bitmap row count doesn't match the bounding box and `ENDFONT` is missing.
+        let path = std::env::temp_dir().join("eg-bdf-macros-lenient.bdf");
+        let bdf = "STARTFONT 2.1\nFONT test\nSIZE 16 75 75\nFONTBOUNDINGBOX 8 2 0 0\n\
+            STARTCHAR A\nENCODING 65\nDWIDTH 8 0\nBBX 8 2 0 0\nBITMAP\nFF\nENDCHAR\n";
+        fs::write(&path, bdf).unwrap();
+        let filename = format!("{:?}", path.to_str().unwrap());
+        assert!(expand_error(&filename).contains("use `lenient = true`"));
+        let input = syn::parse_str(&format!("{}, lenient = true", filename)).unwrap();
+        assert!(expand(&input).is_ok());
+
+        assert_eq!(
+            expand_error(r#""../eg-bdf/examples/6x10.bdf", '\u{E000}'"#),
+            "no glyphs in BDF file \"../eg-bdf/examples/6x10.bdf\" match the character ranges"
+        );
+    }
+
+    /// Writes a BDF file with 1x1 pixel glyphs to the temp directory.
+    ///
+    /// Returns the path as a string literal.
+    fn write_bdf(name: &str, glyphs: &[(char, i32)]) -> String {
+        let mut bdf = format!(
+            "STARTFONT 2.1\nFONT test\nSIZE 16 75 75\nFONTBOUNDINGBOX 1 1 0 0\nCHARS {}\n",
+            glyphs.len()
+        );
+        for (c, device_width) in glyphs {
+            bdf += &format!(
+                "STARTCHAR {0}\nENCODING {0}\nDWIDTH {1} 0\nBBX 1 1 0 0\nBITMAP\n80\nENDCHAR\n",
+                *c as u32, device_width
+            );
+        }
+        bdf += "ENDFONT\n";
+
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, bdf).unwrap();
+        format!("{:?}", path.to_str().unwrap())
+    }
+
+    fn expand_str(input: &str) -> String {
+        let input = syn::parse_str::<IncludeBdf>(input).unwrap();
+        expand(&input).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_sorted_glyphs() {
+        let filename = write_bdf(
+            "eg-bdf-macros-unsorted.bdf",
+            &[('C', 3), ('A', 1), ('B', 2), ('A', 4)],
+        );
+        let output = expand_str(&filename);
+
+        // The first glyph for each encoding is used.
+        let a = output.find("glyph ('A' , 0i32 , 0i32 , 1u32 , 1u32 , 1i32 , 0usize ,)");
+        let b = output.find("glyph ('B' , 0i32 , 0i32 , 1u32 , 1u32 , 2i32 , 1usize ,)");
+        let c = output.find("glyph ('C' , 0i32 , 0i32 , 1u32 , 1u32 , 3i32 , 2usize ,)");
+        assert!(a.is_some() && a < b && b < c, "{}", output);
+        assert!(!output.contains("4i32"), "{}", output);
+
+        // The bitmap data is emitted as a byte string.
+        assert!(output.contains(r#"data : b"\xE0" ,"#), "{}", output);
+    }
+
+    #[test]
+    fn test_chunked_tables() {
+        let glyphs: Vec<_> = ('\u{4E00}'..'\u{6000}').rev().map(|c| (c, 1)).collect();
+        let output = expand_str(&write_bdf("eg-bdf-macros-large.bdf", &glyphs));
+
+        assert!(output.contains("const CHUNK_1 : & [:: eg_bdf :: BdfGlyph]"));
+        assert!(!output.contains("const CHUNK_2"));
+        assert!(output.contains("const TABLE : [:: eg_bdf :: BdfGlyph ; 4608usize]"));
+        assert!(output.contains("glyph ('\u{4E00}' , 0i32 , 0i32 , 1u32 , 1u32 , 1i32 , 0usize ,)"));
+    }
+
     #[test]
     fn test_bits_to_bytes_incomplete_byte() {
         let f = false;
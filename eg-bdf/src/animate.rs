@@ -0,0 +1,208 @@
+//! Per glyph animations.
+//!
+//! Text animations, like waving, bouncing or typewriter effects, change the position or color of
+//! individual glyphs over time. [`BdfTextStyle::draw_string_animated`] calls a closure for every
+//! glyph, which returns a [`GlyphTransform`] based on the index of the character and a phase
+//! value, e.g. the current frame number.
+
+use embedded_graphics::{prelude::*, text::Baseline};
+
+use crate::text::BdfTextStyle;
+
+/// Transformation of a single glyph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct GlyphTransform<C> {
+    /// Offset from the regular glyph position.
+    pub offset: Point,
+    /// Glyph color, if `None` the text color of the style is used.
+    pub color: Option<C>,
+    /// Hidden glyphs aren't drawn, but still advance the position of the following glyphs.
+    pub visible: bool,
+}
+
+impl<C: PixelColor> GlyphTransform<C> {
+    /// Creates a transform which doesn't change the glyph.
+    pub const fn new() -> Self {
+        Self {
+            offset: Point::zero(),
+            color: None,
+            visible: true,
+        }
+    }
+
+    /// Sets the offset.
+    pub const fn with_offset(mut self, offset: Point) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the color.
+    pub const fn with_color(mut self, color: C) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Hides the glyph.
+    pub const fn hidden(mut self) -> Self {
+        self.visible = false;
+        self
+    }
+}
+
+impl<C: PixelColor> Default for GlyphTransform<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Draws a string with a transform applied to every glyph.
+    ///
+    /// `transform` is called for every character with the character index, the character and
+    /// `phase`. The glyph is drawn at its regular position plus the offset of the returned
+    /// transform. Offsets don't affect the positions of the following glyphs.
+    ///
+    /// Glyphs are drawn without background and decorations, because they could overlap moved
+    /// glyphs. Returns the position after the last character, using the same baseline as
+    /// `position`.
+    pub fn draw_string_animated<D, F>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        phase: u32,
+        mut transform: F,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        F: FnMut(usize, char, u32) -> GlyphTransform<C>,
+    {
+        let alphabetic = self.alphabetic_position(position, baseline);
+        let font = self.font();
+
+        let mut positions = self.glyph_positions(text, alphabetic.x);
+        for (index, (c, glyph, x)) in positions.by_ref().enumerate() {
+            let glyph_transform = transform(index, c, phase);
+            if !glyph_transform.visible {
+                continue;
+            }
+
+            glyph.draw_with_layout(
+                Point::new(x, alphabetic.y) + glyph_transform.offset,
+                glyph_transform.color.unwrap_or_else(|| self.text_color()),
+                None,
+                font.data,
+                font.bitmap_layout,
+                target,
+            )?;
+        }
+
+        Ok(Point::new(positions.x(), position.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{
+        mock_display::MockDisplay, pixelcolor::BinaryColor, text::renderer::TextRenderer,
+    };
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    fn style() -> BdfTextStyle<'static, BinaryColor> {
+        BdfTextStyle::new(&FONT, BinaryColor::On)
+    }
+
+    #[test]
+    fn identity() {
+        let mut expected = MockDisplay::new();
+        let expected_next = style()
+            .draw_string("ABC", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        let next = style()
+            .draw_string_animated(
+                "ABC",
+                Point::zero(),
+                Baseline::Top,
+                0,
+                |_, _, _| GlyphTransform::new(),
+                &mut display,
+            )
+            .unwrap();
+
+        display.assert_eq(&expected);
+        assert_eq!(next, expected_next);
+    }
+
+    #[test]
+    fn wave_and_color() {
+        let mut expected = MockDisplay::new();
+        style()
+            .draw_string("L", Point::new(0, 1), Baseline::Top, &mut expected)
+            .unwrap();
+        style()
+            .draw_string("I", Point::new(6, 0), Baseline::Top, &mut expected)
+            .unwrap();
+        BdfTextStyle::new(&FONT, BinaryColor::Off)
+            .draw_string("L", Point::new(12, 2), Baseline::Top, &mut expected)
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        style()
+            .draw_string_animated(
+                "LIL",
+                Point::zero(),
+                Baseline::Top,
+                1,
+                |index, c, phase| {
+                    let wave = [1, 0, 2];
+                    let transform = GlyphTransform::new()
+                        .with_offset(Point::new(0, wave[index] * phase as i32));
+                    if c == 'L' && index > 0 {
+                        transform.with_color(BinaryColor::Off)
+                    } else {
+                        transform
+                    }
+                },
+                &mut display,
+            )
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn typewriter() {
+        let mut expected = MockDisplay::new();
+        style()
+            .draw_string("AB", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        let next = style()
+            .draw_string_animated(
+                "ABCD",
+                Point::zero(),
+                Baseline::Top,
+                2,
+                |index, _, phase| {
+                    if index < phase as usize {
+                        GlyphTransform::new()
+                    } else {
+                        GlyphTransform::new().hidden()
+                    }
+                },
+                &mut display,
+            )
+            .unwrap();
+
+        display.assert_eq(&expected);
+        assert_eq!(next.x, 24);
+    }
+}
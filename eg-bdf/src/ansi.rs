@@ -0,0 +1,395 @@
+//! ANSI escape sequences.
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        Baseline, DecorationColor,
+    },
+};
+
+use crate::{
+    text::{pen_to_pixels, BdfTextStyle},
+    widgets::Attributes,
+};
+
+/// Maximum number of parameters in a control sequence, additional parameters are ignored.
+const MAX_PARAMETERS: usize = 8;
+
+/// Escape sequence parser state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum State {
+    Ground,
+    Escape,
+    ControlSequence,
+}
+
+/// Parser output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Action<'a> {
+    /// Printable character or control character.
+    Print(char),
+    /// Control sequence with the given parameters and final character.
+    ControlSequence(&'a [u16], char),
+    /// Character was consumed by an escape sequence.
+    None,
+}
+
+/// Parser for ANSI escape sequences.
+///
+/// The parser recognizes control sequences which start with `ESC [`. All other escape
+/// sequences are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct EscapeParser {
+    state: State,
+    parameters: [u16; MAX_PARAMETERS],
+    count: usize,
+}
+
+impl EscapeParser {
+    pub(crate) const fn new() -> Self {
+        Self {
+            state: State::Ground,
+            parameters: [0; MAX_PARAMETERS],
+            count: 0,
+        }
+    }
+
+    /// Processes the next character.
+    pub(crate) fn advance(&mut self, c: char) -> Action<'_> {
+        match self.state {
+            State::Ground if c == '\x1b' => {
+                self.state = State::Escape;
+                Action::None
+            }
+            State::Ground => Action::Print(c),
+            State::Escape => {
+                self.state = if c == '[' {
+                    self.parameters = [0; MAX_PARAMETERS];
+                    self.count = 0;
+                    State::ControlSequence
+                } else {
+                    State::Ground
+                };
+                Action::None
+            }
+            State::ControlSequence => match c {
+                '0'..='9' => {
+                    let digit = c as u16 - u16::from(b'0');
+                    self.count = self.count.max(1);
+                    if let Some(parameter) = self.parameters.get_mut(self.count - 1) {
+                        *parameter = parameter.saturating_mul(10).saturating_add(digit);
+                    }
+                    Action::None
+                }
+                ';' => {
+                    // An omitted first parameter counts as a parameter with the value zero.
+                    self.count = self.count.max(1) + 1;
+                    Action::None
+                }
+                '\x40'..='\x7e' => {
+                    self.state = State::Ground;
+                    let count = self.count.min(MAX_PARAMETERS);
+                    Action::ControlSequence(&self.parameters[..count], c)
+                }
+                _ => Action::None,
+            },
+        }
+    }
+}
+
+/// Character attributes controlled by SGR (select graphic rendition) sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Rendition<C> {
+    pub(crate) text_color: C,
+    pub(crate) background_color: Option<C>,
+    pub(crate) attributes: Attributes,
+}
+
+impl<C: PixelColor> Rendition<C> {
+    /// Applies the parameters of an SGR sequence.
+    ///
+    /// `default` is used for resets. Colors are only changed if a palette is given.
+    pub(crate) fn apply(&mut self, parameters: &[u16], default: &Self, palette: Option<&[C; 8]>) {
+        if parameters.is_empty() {
+            *self = *default;
+        }
+
+        for parameter in parameters {
+            match *parameter {
+                0 => *self = *default,
+                1 => self.attributes.insert(Attributes::BOLD),
+                4 => self.attributes.insert(Attributes::UNDERLINE),
+                7 => self.attributes.insert(Attributes::INVERSE),
+                9 => self.attributes.insert(Attributes::STRIKETHROUGH),
+                22 => self.attributes.remove(Attributes::BOLD),
+                24 => self.attributes.remove(Attributes::UNDERLINE),
+                27 => self.attributes.remove(Attributes::INVERSE),
+                29 => self.attributes.remove(Attributes::STRIKETHROUGH),
+                39 => self.text_color = default.text_color,
+                49 => self.background_color = default.background_color,
+                code @ (30..=37 | 90..=97) => {
+                    if let Some(palette) = palette {
+                        self.text_color = palette[usize::from(code % 10)];
+                    }
+                }
+                code @ (40..=47 | 100..=107) => {
+                    if let Some(palette) = palette {
+                        self.background_color = Some(palette[usize::from(code % 10)]);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the text and background color after applying the inverse attribute.
+    ///
+    /// `fallback` is used as the text color of inverted characters without a background color.
+    pub(crate) fn colors(&self, fallback: Option<C>) -> (Option<C>, Option<C>) {
+        if self.attributes.contains(Attributes::INVERSE) {
+            (self.background_color.or(fallback), Some(self.text_color))
+        } else {
+            (Some(self.text_color), self.background_color)
+        }
+    }
+}
+
+/// Text with embedded ANSI SGR sequences.
+///
+/// The text is drawn using a style which is modified by SGR (select graphic rendition)
+/// sequences. The following parameters are supported:
+///
+/// | Parameter              | Effect                            |
+/// |------------------------|-----------------------------------|
+/// | `0`                    | Reset to the style                |
+/// | `4` / `24`             | Underline on / off                |
+/// | `7` / `27`             | Inverse on / off                  |
+/// | `9` / `29`             | Strikethrough on / off            |
+/// | `30`-`37`, `90`-`97`   | Text color from the palette       |
+/// | `39`                   | Default text color                |
+/// | `40`-`47`, `100`-`107` | Background color from the palette |
+/// | `49`                   | Default background color          |
+///
+/// Colors are only changed if a palette is set. All other escape sequences are skipped and
+/// newlines start a new line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnsiText<'a, 'b, C> {
+    text: &'b str,
+    style: BdfTextStyle<'a, C>,
+    position: Point,
+    baseline: Baseline,
+    palette: Option<&'b [C; 8]>,
+}
+
+impl<'a, 'b, C: PixelColor> AnsiText<'a, 'b, C> {
+    /// Creates new ANSI text.
+    pub fn new(
+        text: &'b str,
+        style: BdfTextStyle<'a, C>,
+        position: Point,
+        baseline: Baseline,
+    ) -> Self {
+        Self {
+            text,
+            style,
+            position,
+            baseline,
+            palette: None,
+        }
+    }
+
+    /// Sets the palette used for the eight standard colors.
+    ///
+    /// The bright color codes use the same palette.
+    pub fn with_palette(self, palette: &'b [C; 8]) -> Self {
+        Self {
+            palette: Some(palette),
+            ..self
+        }
+    }
+
+    /// Draws a character with the given advance in pixels.
+    fn draw_char<D>(
+        &self,
+        c: char,
+        rendition: &Rendition<C>,
+        position: Point,
+        advance: i32,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let (text_color, background_color) = rendition.colors(self.style.background_color());
+
+        if let Some(color) = background_color {
+            let top_left = position - Point::new(0, self.style.font().font_ascent as i32 - 1);
+            let size = Size::new(advance.max(0) as u32, self.style.caret_height());
+            target.fill_solid(&Rectangle::new(top_left, size), color)?;
+        }
+
+        // Inverted characters without a background color can't draw their text.
+        let text_color = match text_color {
+            Some(color) => color,
+            None => return Ok(()),
+        };
+
+        let mut style = self.style;
+        style.set_text_color(Some(text_color));
+        style.set_background_color(None);
+
+        let decoration = |attribute| {
+            if rendition.attributes.contains(attribute) {
+                DecorationColor::TextColor
+            } else {
+                DecorationColor::None
+            }
+        };
+        style.set_underline_color(decoration(Attributes::UNDERLINE));
+        style.set_strikethrough_color(decoration(Attributes::STRIKETHROUGH));
+
+        style
+            .draw_string(
+                c.encode_utf8(&mut [0; 4]),
+                position,
+                Baseline::Alphabetic,
+                target,
+            )
+            .map(drop)
+    }
+}
+
+impl<C: PixelColor> Drawable for AnsiText<'_, '_, C> {
+    type Color = C;
+    type Output = Point;
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let default = Rendition {
+            text_color: self.style.text_color(),
+            background_color: self.style.background_color(),
+            attributes: Attributes::NONE,
+        };
+        let mut rendition = default;
+        let mut parser = EscapeParser::new();
+
+        let line_height = self.style.line_height() as i32;
+        let start = self.style.alphabetic_position(self.position, self.baseline);
+        let mut line_start = start;
+        // Pen position in thousandths of a pixel, like in `draw_string`.
+        let mut pen = 0;
+
+        for c in self.text.chars() {
+            match parser.advance(c) {
+                Action::Print('\n') => {
+                    line_start.y += line_height;
+                    pen = 0;
+                }
+                Action::Print(c) => {
+                    let x = pen_to_pixels(pen);
+                    pen += self.style.pen_advance(c);
+                    let advance = pen_to_pixels(pen) - x;
+
+                    let position = line_start + Point::new(x, 0);
+                    self.draw_char(c, &rendition, position, advance, target)?;
+                }
+                Action::ControlSequence(parameters, 'm') => {
+                    rendition.apply(parameters, &default, self.palette);
+                }
+                Action::ControlSequence(..) | Action::None => {}
+            }
+        }
+
+        Ok(Point::new(
+            line_start.x + pen_to_pixels(pen),
+            self.position.y + line_start.y - start.y,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn parse_control_sequences() {
+        let mut parser = EscapeParser::new();
+
+        let mut actions =
+            "A\x1b[1;;32mB\x1b[m\x1b(C"
+                .chars()
+                .filter_map(|c| match parser.advance(c) {
+                    Action::ControlSequence(parameters, c) => {
+                        let mut copy = [0; MAX_PARAMETERS];
+                        copy[..parameters.len()].copy_from_slice(parameters);
+                        Some((copy, parameters.len(), c))
+                    }
+                    Action::Print(c) => Some(([0; MAX_PARAMETERS], 0, c)),
+                    Action::None => None,
+                });
+
+        assert_eq!(actions.next(), Some(([0; 8], 0, 'A')));
+        assert_eq!(actions.next(), Some(([1, 0, 32, 0, 0, 0, 0, 0], 3, 'm')));
+        assert_eq!(actions.next(), Some(([0; 8], 0, 'B')));
+        assert_eq!(actions.next(), Some(([0; 8], 0, 'm')));
+        assert_eq!(actions.next(), Some(([0; 8], 0, 'C')));
+        assert_eq!(actions.next(), None);
+    }
+
+    #[test]
+    fn apply_rendition() {
+        let mut palette = [BinaryColor::On; 8];
+        palette[0] = BinaryColor::Off;
+        let default = Rendition {
+            text_color: BinaryColor::On,
+            background_color: None,
+            attributes: Attributes::NONE,
+        };
+
+        let mut rendition = default;
+        rendition.apply(&[7, 4, 40], &default, Some(&palette));
+        assert_eq!(
+            rendition,
+            Rendition {
+                text_color: BinaryColor::On,
+                background_color: Some(BinaryColor::Off),
+                attributes: Attributes::INVERSE | Attributes::UNDERLINE,
+            }
+        );
+
+        rendition.apply(&[], &default, Some(&palette));
+        assert_eq!(rendition, default);
+    }
+
+    #[test]
+    fn draw_inverse() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        AnsiText::new("\x1b[7mI\x1b[0mI", style, Point::zero(), Baseline::Top)
+            .draw(&mut display)
+            .unwrap();
+
+        display.assert_pattern(&[
+            "######......",
+            "#...##.###..",
+            "##.###..#...",
+            "##.###..#...",
+            "##.###..#...",
+            "##.###..#...",
+            "##.###..#...",
+            "#...##.###..",
+            "######......",
+            "######......",
+        ]);
+    }
+}
@@ -0,0 +1,243 @@
+//! Text on a circular arc.
+
+use core::f32::consts::FRAC_PI_2;
+
+use embedded_graphics::prelude::*;
+
+use crate::{
+    cache::RotatedGlyphCache,
+    rotate::{centered_origin, draw_rotated_glyph, Rotation},
+    text::BdfTextStyle,
+    BdfGlyph,
+};
+
+/// Direction in which the text follows the arc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum ArcDirection {
+    /// The text runs clockwise and the top of the glyphs points away from the center.
+    ///
+    /// Text at the top of the circle is upright.
+    Clockwise,
+    /// The text runs counterclockwise and the top of the glyphs points towards the center.
+    ///
+    /// Text at the bottom of the circle is upright.
+    CounterClockwise,
+}
+
+/// Text on a circular arc.
+///
+/// The glyphs are placed on a circle with their alphabetic baseline touching the circle. Every
+/// glyph is rotated so that its baseline is tangent to the circle at the center of its advance.
+/// Angles use the same convention as the embedded-graphics primitives: `0°` points to the right
+/// and positive angles run clockwise.
+///
+/// Rotated glyphs are drawn without background and decorations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArcText<'a, 'b, C> {
+    text: &'b str,
+    style: BdfTextStyle<'a, C>,
+    center: Point,
+    radius: u32,
+    start_angle: Angle,
+    direction: ArcDirection,
+}
+
+impl<'a, 'b, C: PixelColor> ArcText<'a, 'b, C> {
+    /// Creates new arc text.
+    ///
+    /// The text starts at `start_angle` and runs in the given `direction` around the circle with
+    /// the given `center` and `radius`.
+    pub fn new(
+        text: &'b str,
+        style: BdfTextStyle<'a, C>,
+        center: Point,
+        radius: u32,
+        start_angle: Angle,
+        direction: ArcDirection,
+    ) -> Self {
+        Self {
+            text,
+            style,
+            center,
+            radius,
+            start_angle,
+            direction,
+        }
+    }
+
+    /// Returns the angle which corresponds to a distance along the arc.
+    fn angle(&self, distance: f32) -> f32 {
+        let angle = distance / self.radius as f32;
+
+        match self.direction {
+            ArcDirection::Clockwise => self.start_angle.to_radians() + angle,
+            ArcDirection::CounterClockwise => self.start_angle.to_radians() - angle,
+        }
+    }
+}
+
+impl<'a, C: PixelColor> ArcText<'a, '_, C> {
+    /// Draws the text using a rotated glyph cache.
+    ///
+    /// The result is the same as drawing the text using [`draw`](Drawable::draw), but glyphs
+    /// which are drawn with the same rotation and subpixel position as in a previous call are
+    /// taken from the cache. Returns the angle at which the next character would be placed.
+    pub fn draw_cached<D, const N: usize, const BYTES: usize>(
+        &self,
+        cache: &mut RotatedGlyphCache<'a, N, BYTES>,
+        target: &mut D,
+    ) -> Result<Angle, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let font = self.style.font();
+        let color = self.style.text_color();
+
+        self.draw_glyphs(target, |glyph, origin, rotation, target| {
+            cache.draw_rotated_glyph(glyph, font, origin, rotation, color, target)
+        })
+    }
+
+    /// Positions the glyphs and draws them using the `draw_glyph` callback.
+    ///
+    /// The callback is called with the glyph, the origin of the glyph and its rotation.
+    fn draw_glyphs<D, F>(&self, target: &mut D, mut draw_glyph: F) -> Result<Angle, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        F: FnMut(&'a BdfGlyph, (f32, f32), Rotation, &mut D) -> Result<(), D::Error>,
+    {
+        if self.radius == 0 {
+            return Ok(self.start_angle);
+        }
+
+        let radius = self.radius as f32;
+        let center = (self.center.x as f32, self.center.y as f32);
+
+        let mut positions = self.style.glyph_positions(self.text, 0);
+        for (_, glyph, x) in positions.by_ref() {
+            let angle = self.angle(x as f32 + glyph.device_width as f32 / 2.0);
+
+            // Point on the circle and the direction of the baseline at this point.
+            let (cos, sin) = Rotation::from_radians(angle).x_axis();
+            let point = (center.0 + radius * cos, center.1 + radius * sin);
+            let rotation = match self.direction {
+                ArcDirection::Clockwise => Rotation::from_radians(angle + FRAC_PI_2),
+                ArcDirection::CounterClockwise => Rotation::from_radians(angle - FRAC_PI_2),
+            };
+
+            draw_glyph(
+                glyph,
+                centered_origin(glyph, point, rotation),
+                rotation,
+                target,
+            )?;
+        }
+
+        Ok(Angle::from_radians(self.angle(positions.x() as f32)))
+    }
+}
+
+impl<C: PixelColor> Drawable for ArcText<'_, '_, C> {
+    type Color = C;
+    /// The angle at which the next character would be placed.
+    type Output = Angle;
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let font = self.style.font();
+        let color = self.style.text_color();
+
+        self.draw_glyphs(target, |glyph, origin, rotation, target| {
+            draw_rotated_glyph(glyph, font, origin, rotation, color, target)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        pixelcolor::BinaryColor,
+        primitives::Rectangle,
+        text::{renderer::TextRenderer, Baseline},
+    };
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    fn draw(text: &str, start_angle: Angle, direction: ArcDirection) -> MockDisplay<BinaryColor> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        ArcText::new(text, style, Point::new(30, 30), 20, start_angle, direction)
+            .draw(&mut display)
+            .unwrap();
+
+        display
+    }
+
+    fn expected(text: &str, position: Point) -> MockDisplay<BinaryColor> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        style
+            .draw_string(text, position, Baseline::Alphabetic, &mut display)
+            .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn upright_glyphs() {
+        // Half of the advance of a glyph, as an angle on the arc.
+        let half_advance = Angle::from_radians(3.0 / 20.0);
+
+        let display = draw(
+            "L",
+            Angle::from_degrees(-90.0) - half_advance,
+            ArcDirection::Clockwise,
+        );
+        display.assert_eq(&expected("L", Point::new(27, 10)));
+
+        let display = draw(
+            "L",
+            Angle::from_degrees(90.0) + half_advance,
+            ArcDirection::CounterClockwise,
+        );
+        display.assert_eq(&expected("L", Point::new(27, 50)));
+    }
+
+    #[test]
+    fn rotated_glyph() {
+        let half_advance = Angle::from_radians(3.0 / 20.0);
+
+        // The glyph is rotated by 90° at the right side of the circle.
+        let display = draw("L", -half_advance, ArcDirection::Clockwise);
+        assert_eq!(
+            display.affected_area(),
+            Rectangle::new(Point::new(49, 27), Size::new(7, 5))
+        );
+        assert_eq!(display.get_pixel(Point::new(49, 31)), Some(BinaryColor::On));
+        assert_eq!(display.get_pixel(Point::new(55, 27)), Some(BinaryColor::On));
+    }
+
+    #[test]
+    fn next_angle() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let text = ArcText::new(
+            "AB",
+            style,
+            Point::new(30, 30),
+            20,
+            Angle::zero(),
+            ArcDirection::CounterClockwise,
+        );
+
+        let next = text.draw(&mut MockDisplay::new()).unwrap();
+        assert!((next.to_radians() + 12.0 / 20.0).abs() < 1e-6);
+    }
+}
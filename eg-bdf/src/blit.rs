@@ -0,0 +1,581 @@
+//! Direct rendering into monochrome framebuffers.
+//!
+//! The generic [`TextRenderer`] implementation draws glyphs using [`DrawTarget`] operations.
+//! For monochrome displays it can be much faster to copy the glyph bitmaps directly into the
+//! framebuffer. Framebuffers which support this implement the [`Blit`] trait and can be used
+//! with [`BdfTextStyle::blit_string`].
+//!
+//! [`TextRenderer`]: embedded_graphics::text::renderer::TextRenderer
+
+use core::convert::Infallible;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::{text::BdfTextStyle, BdfGlyph, BitOrder, BitmapLayout};
+
+/// Framebuffer which supports copying glyph bitmaps.
+pub trait Blit: DrawTarget<Color = BinaryColor> {
+    /// Copies a glyph bitmap into the framebuffer.
+    ///
+    /// `data` is the bitmap data of the font, which uses the given `layout`. Set pixels are drawn
+    /// in `text_color` and unset pixels in `background_color`. If the background color is `None`
+    /// unset pixels aren't changed. Pixels outside the framebuffer are ignored.
+    fn blit_glyph(
+        &mut self,
+        glyph: &BdfGlyph,
+        data: &[u8],
+        layout: BitmapLayout,
+        top_left: Point,
+        text_color: BinaryColor,
+        background_color: Option<BinaryColor>,
+    );
+}
+
+/// Returns a byte with the `count` most significant bits set.
+fn ones(count: u32) -> u8 {
+    (0xFF00u16 >> count) as u8
+}
+
+/// Reads up to 8 bits starting at bit `index`, aligned to the most significant bit.
+fn read_bits(data: &[u8], layout: BitmapLayout, index: usize, count: u32) -> u8 {
+    if layout.bit_order == BitOrder::LsbFirst {
+        return (0..count)
+            .filter(|bit| layout.bit(data, index + *bit as usize))
+            .fold(0, |bits, bit| bits | 0x80 >> bit);
+    }
+
+    let byte = |i: usize| u16::from(data.get(i).copied().unwrap_or_default());
+    let word = byte(index / 8) << 8 | byte(index / 8 + 1);
+
+    ((word << (index % 8)) >> 8) as u8 & ones(count)
+}
+
+/// Returns a byte with all bits set to the given color.
+fn fill(color: BinaryColor) -> u8 {
+    if color.is_on() {
+        0xFF
+    } else {
+        0x00
+    }
+}
+
+/// Returns the value and mask which is used to draw `bits`.
+fn value_and_mask(
+    bits: u8,
+    count: u32,
+    text_color: BinaryColor,
+    background_color: Option<BinaryColor>,
+) -> (u8, u8) {
+    match background_color {
+        Some(background_color) => (
+            bits & fill(text_color) | !bits & fill(background_color),
+            ones(count),
+        ),
+        None => (fill(text_color), bits),
+    }
+}
+
+/// Monochrome framebuffer with horizontal byte layout.
+///
+/// Each byte contains 8 horizontally adjacent pixels with the leftmost pixel in the most
+/// significant bit. Rows start at multiples of the stride, which is the number of bytes per row.
+///
+/// The buffer can also be used as a horizontal strip of a larger frame, see
+/// [`StripTarget`](crate::strip::StripTarget).
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct HorizontalBuffer<'a> {
+    data: &'a mut [u8],
+    size: Size,
+    stride: usize,
+    top: i32,
+}
+
+impl<'a> HorizontalBuffer<'a> {
+    /// Creates a new framebuffer with the minimal stride for the given width.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is too short.
+    pub fn new(data: &'a mut [u8], size: Size) -> Self {
+        Self::with_stride(data, size, size.width.div_ceil(8) as usize)
+    }
+
+    /// Creates a new framebuffer with a custom stride.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is too short or the stride is too small for the given width.
+    pub fn with_stride(data: &'a mut [u8], size: Size, stride: usize) -> Self {
+        assert!(stride >= size.width.div_ceil(8) as usize);
+        assert!(data.len() >= stride * size.height as usize);
+
+        Self {
+            data,
+            size,
+            stride,
+            top: 0,
+        }
+    }
+
+    /// Returns the framebuffer data.
+    pub fn data(&self) -> &[u8] {
+        self.data
+    }
+
+    /// Returns the stride in bytes.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Returns the y coordinate of the first row.
+    pub fn top(&self) -> i32 {
+        self.top
+    }
+
+    /// Sets the y coordinate of the first row.
+    pub fn set_top(&mut self, top: i32) {
+        self.top = top;
+    }
+
+    /// Returns the color of a pixel.
+    pub fn pixel(&self, point: Point) -> Option<BinaryColor> {
+        self.bounding_box().contains(point).then(|| {
+            let row = (point.y - self.top) as usize;
+            let byte = self.data[row * self.stride + point.x as usize / 8];
+            BinaryColor::from(byte & (0x80 >> (point.x % 8)) != 0)
+        })
+    }
+
+    /// Writes the bits in `mask` to the pixels starting at `x` in row `y`.
+    ///
+    /// `value` and `mask` are aligned to the most significant bit.
+    fn write_bits(&mut self, x: u32, y: i32, value: u8, mask: u8) {
+        let index = (y - self.top) as usize * self.stride + x as usize / 8;
+        let shift = x % 8;
+        let value = (u16::from(value) << 8) >> shift;
+        let mask = (u16::from(mask) << 8) >> shift;
+
+        for (i, &(value, mask)) in [
+            ((value >> 8) as u8, (mask >> 8) as u8),
+            (value as u8, mask as u8),
+        ]
+        .iter()
+        .enumerate()
+        {
+            if mask != 0 {
+                let byte = &mut self.data[index + i];
+                *byte = *byte & !mask | value & mask;
+            }
+        }
+    }
+}
+
+impl Dimensions for HorizontalBuffer<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::new(0, self.top), self.size)
+    }
+}
+
+impl DrawTarget for HorizontalBuffer<'_> {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounding_box = self.bounding_box();
+
+        for Pixel(point, color) in pixels {
+            if bounding_box.contains(point) {
+                let value = if color.is_on() { 0x80 } else { 0x00 };
+                self.write_bits(point.x as u32, point.y, value, 0x80);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Blit for HorizontalBuffer<'_> {
+    fn blit_glyph(
+        &mut self,
+        glyph: &BdfGlyph,
+        data: &[u8],
+        layout: BitmapLayout,
+        top_left: Point,
+        text_color: BinaryColor,
+        background_color: Option<BinaryColor>,
+    ) {
+        let bounding_box = Rectangle::new(top_left, glyph.bounding_box.size);
+        let clipped = bounding_box.intersection(&self.bounding_box());
+        if clipped.is_zero_sized() {
+            return;
+        }
+
+        let row_stride = layout.row_stride(bounding_box.size.width);
+        let first_column = (clipped.top_left.x - top_left.x) as u32;
+        let end_column = first_column + clipped.size.width;
+
+        for y in clipped.rows() {
+            let row = (y - top_left.y) as u32;
+            let row_index = glyph.start_index + row as usize * row_stride;
+
+            let mut column = first_column;
+            while column < end_column {
+                let count = (end_column - column).min(8);
+                let bits = read_bits(data, layout, row_index + column as usize, count);
+                let (value, mask) = value_and_mask(bits, count, text_color, background_color);
+
+                self.write_bits((top_left.x + column as i32) as u32, y, value, mask);
+                column += count;
+            }
+        }
+    }
+}
+
+/// Monochrome framebuffer with vertical byte layout.
+///
+/// This is the page mode memory layout used by SSD1306, SH1106 and similar display controllers.
+/// The framebuffer is divided into pages of 8 rows. Each byte contains 8 vertically adjacent
+/// pixels of a page with the topmost pixel in the least significant bit. The bytes of a page are
+/// stored from left to right and pages are stored from top to bottom.
+///
+/// Glyphs are copied column by column, which requires at most one write per byte.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct PageBuffer<'a> {
+    data: &'a mut [u8],
+    size: Size,
+}
+
+impl<'a> PageBuffer<'a> {
+    /// Creates a new framebuffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is too short.
+    pub fn new(data: &'a mut [u8], size: Size) -> Self {
+        assert!(data.len() >= size.width as usize * size.height.div_ceil(8) as usize);
+
+        Self { data, size }
+    }
+
+    /// Returns the framebuffer data.
+    pub fn data(&self) -> &[u8] {
+        self.data
+    }
+
+    /// Returns the data of a page.
+    pub fn page(&self, page: u32) -> Option<&[u8]> {
+        let width = self.size.width as usize;
+        let start = page as usize * width;
+
+        (page < self.size.height.div_ceil(8)).then(|| &self.data[start..start + width])
+    }
+
+    /// Returns the color of a pixel.
+    pub fn pixel(&self, point: Point) -> Option<BinaryColor> {
+        self.bounding_box().contains(point).then(|| {
+            let byte = self.data[self.index(point.x as u32, point.y as u32)];
+            BinaryColor::from(byte & (1 << (point.y % 8)) != 0)
+        })
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y / 8 * self.size.width + x) as usize
+    }
+
+    /// Writes the bits in `mask` to the byte which contains the pixel at `x`, `y`.
+    fn write_bits(&mut self, x: u32, y: u32, value: u8, mask: u8) {
+        let index = self.index(x, y);
+        let byte = &mut self.data[index];
+        *byte = *byte & !mask | value & mask;
+    }
+}
+
+impl Dimensions for PageBuffer<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), self.size)
+    }
+}
+
+impl DrawTarget for PageBuffer<'_> {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounding_box = self.bounding_box();
+
+        for Pixel(point, color) in pixels {
+            if bounding_box.contains(point) {
+                let mask = 1 << (point.y % 8);
+                let value = if color.is_on() { mask } else { 0 };
+                self.write_bits(point.x as u32, point.y as u32, value, mask);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Blit for PageBuffer<'_> {
+    fn blit_glyph(
+        &mut self,
+        glyph: &BdfGlyph,
+        data: &[u8],
+        layout: BitmapLayout,
+        top_left: Point,
+        text_color: BinaryColor,
+        background_color: Option<BinaryColor>,
+    ) {
+        let bounding_box = Rectangle::new(top_left, glyph.bounding_box.size);
+        let clipped = bounding_box.intersection(&self.bounding_box());
+        let bottom_right = match clipped.bottom_right() {
+            Some(bottom_right) => bottom_right,
+            None => return,
+        };
+
+        for x in clipped.columns() {
+            let mut y = clipped.top_left.y;
+
+            while y <= bottom_right.y {
+                let page_end = (y | 7).min(bottom_right.y);
+
+                let mut bits = 0;
+                let mut count = 0;
+                for row in y..=page_end {
+                    if glyph.pixel_with_layout(data, layout, Point::new(x, row) - top_left) {
+                        bits |= 1 << (row % 8);
+                    }
+                    count += 1;
+                }
+
+                let mask = ones(count) >> (8 - count) << (y % 8);
+                let (value, mask) = match background_color {
+                    Some(background_color) => (
+                        bits & fill(text_color) | !bits & fill(background_color),
+                        mask,
+                    ),
+                    None => (fill(text_color), bits),
+                };
+
+                self.write_bits(x as u32, y as u32, value, mask);
+                y = page_end + 1;
+            }
+        }
+    }
+}
+
+impl BdfTextStyle<'_, BinaryColor> {
+    /// Draws a string by copying the glyph bitmaps into a framebuffer.
+    ///
+    /// The glyphs are positioned like in [`draw_string`], including fractional advances and the
+    /// baseline grid, and without a background color the result is the same. With a background
+    /// color only the glyph bounding boxes are filled. Strings with a padded background or
+    /// synthesized bold or italic glyphs are drawn using [`draw_string`], because the padding and
+    /// the modified glyphs can't be blitted. Returns the position after the last character, using
+    /// the same baseline as `position`.
+    ///
+    /// [`draw_string`]: TextRenderer::draw_string
+    pub fn blit_string<B: Blit>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut B,
+    ) -> Result<Point, B::Error> {
+        if self.has_synthetic_style()
+            || self.background_color().is_some() && self.background_padding() > 0
+        {
+            return self.draw_string(text, position, baseline, target);
+        }
+
+        let alphabetic = self.alphabetic_position(position, baseline);
+
+        let mut positions = self.glyph_positions(text, alphabetic.x);
+        while let Some((_, glyph, x)) = positions.next() {
+            let glyph_position = Point::new(x, alphabetic.y);
+
+            target.blit_glyph(
+                glyph,
+                self.font().data,
+                self.font().bitmap_layout,
+                glyph.bounding_box.top_left + glyph_position,
+                self.text_color(),
+                self.background_color(),
+            );
+            self.draw_decorations(target, positions.x() - x, glyph_position)?;
+        }
+
+        Ok(Point::new(positions.x(), position.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, text::BaselineGrid, BdfFont};
+    use embedded_graphics::mock_display::MockDisplay;
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    /// Checks that `blit_string` draws the same pixels as `draw_string`.
+    fn assert_blit_matches_draw_string(
+        style: BdfTextStyle<'_, BinaryColor>,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+    ) {
+        let mut data = [0; 3 * 24];
+        let mut buffer = HorizontalBuffer::new(&mut data, Size::new(24, 24));
+        let next = style
+            .blit_string(text, position, baseline, &mut buffer)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        expected.set_allow_out_of_bounds_drawing(true);
+        expected.set_allow_overdraw(true);
+        let expected_next = style
+            .draw_string(text, position, baseline, &mut expected)
+            .unwrap();
+        assert_eq!(next, expected_next);
+
+        let mut display = MockDisplay::new();
+        for point in buffer.bounding_box().points() {
+            if let Some(color) = buffer.pixel(point) {
+                if expected.get_pixel(point).is_some() || color.is_on() {
+                    Pixel(point, color).draw(&mut display).unwrap();
+                }
+            }
+        }
+
+        assert_eq!(display, expected);
+    }
+
+    #[test]
+    fn bits() {
+        assert_eq!(ones(3), 0b1110_0000);
+        assert_eq!(ones(8), 0xFF);
+        let data = [0b0000_0101, 0b1100_0000];
+        assert_eq!(read_bits(&data, BitmapLayout::default(), 5, 5), 0b1011_1000);
+
+        let layout = BitmapLayout::new(BitOrder::LsbFirst, crate::RowLayout::Packed);
+        assert_eq!(read_bits(&data, layout, 1, 3), 0b0100_0000);
+    }
+
+    #[test]
+    fn blit_matches_draw_string() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).underline();
+        assert_blit_matches_draw_string(style, "AIC", Point::new(-2, 1), Baseline::Top);
+    }
+
+    #[test]
+    fn blit_with_style_options() {
+        let widths = [6500; 27];
+        let font = BdfFont {
+            scalable_widths: &widths,
+            ..FONT
+        };
+        let style = BdfTextStyle::new(&font, BinaryColor::On)
+            .underline()
+            .with_fractional_advances(true)
+            .with_baseline_grid(BaselineGrid::new(4, 0));
+        assert_blit_matches_draw_string(style, "AIC", Point::new(1, 1), Baseline::Top);
+        assert_blit_matches_draw_string(style, "AIC", Point::new(1, 21), Baseline::Bottom);
+
+        let padded = style
+            .with_bg_color(BinaryColor::Off)
+            .with_background_padding(2);
+        assert_blit_matches_draw_string(padded, "AIC", Point::new(2, 2), Baseline::Top);
+    }
+
+    #[test]
+    fn blit_synthetic_styles() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        for style in [style.bold(), style.italic(), style.bold().italic()] {
+            assert_blit_matches_draw_string(style, "HI", Point::new(1, 1), Baseline::Top);
+
+            let background = style.with_bg_color(BinaryColor::Off);
+            assert_blit_matches_draw_string(background, "HI", Point::new(1, 1), Baseline::Top);
+        }
+    }
+
+    #[test]
+    fn blit_with_background() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::Off).with_bg_color(BinaryColor::On);
+
+        let mut data = [0; 2 * 10];
+        let mut buffer = HorizontalBuffer::with_stride(&mut data, Size::new(8, 10), 2);
+        style
+            .blit_string("I", Point::new(1, 0), Baseline::Top, &mut buffer)
+            .unwrap();
+
+        assert_eq!(
+            buffer.data(),
+            &[
+                0b0111_1110,
+                0, //
+                0b0100_0110,
+                0, //
+                0b0110_1110,
+                0, //
+                0b0110_1110,
+                0, //
+                0b0110_1110,
+                0, //
+                0b0110_1110,
+                0, //
+                0b0110_1110,
+                0, //
+                0b0100_0110,
+                0, //
+                0b0111_1110,
+                0, //
+                0b0111_1110,
+                0, //
+            ]
+        );
+    }
+
+    #[test]
+    fn blit_page_mode() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut data = [0; 2 * 8];
+        let mut buffer = PageBuffer::new(&mut data, Size::new(8, 16));
+        style
+            .blit_string("I", Point::new(1, 4), Baseline::Top, &mut buffer)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("I", Point::new(1, 4), Baseline::Top, &mut expected)
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        for point in buffer.bounding_box().points() {
+            if buffer.pixel(point) == Some(BinaryColor::On) {
+                Pixel(point, BinaryColor::On).draw(&mut display).unwrap();
+            }
+        }
+        assert_eq!(display, expected);
+
+        assert_eq!(
+            buffer.page(0),
+            Some(&[0, 0, 0b0010_0000, 0b1110_0000, 0b0010_0000, 0, 0, 0][..])
+        );
+        assert_eq!(
+            buffer.page(1),
+            Some(&[0, 0, 0b0000_1000, 0b0000_1111, 0b0000_1000, 0, 0, 0][..])
+        );
+        assert_eq!(buffer.page(2), None);
+    }
+}
@@ -0,0 +1,351 @@
+//! Rendering into raw framebuffers.
+//!
+//! Text which is composed off-screen is often stored in a plain byte buffer, which is then sent
+//! to the display by an existing DMA or flush routine. [`render_to_buffer`] draws text directly
+//! into such a buffer, without requiring a [`DrawTarget`] implementation for the buffer.
+//!
+//! ```
+//! use eg_bdf::{
+//!     buffer::{render_to_buffer, BufferFormat},
+//!     include_bdf,
+//!     text::BdfTextStyle,
+//!     BdfFont,
+//! };
+//! use embedded_graphics::{pixelcolor::Rgb565, prelude::*};
+//!
+//! const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z');
+//!
+//! // 30x10 pixels with 2 bytes per pixel and rows which are padded to 64 bytes.
+//! let mut buffer = [0; 64 * 10];
+//! let style = BdfTextStyle::new(&FONT, Rgb565::WHITE);
+//! let size = Size::new(30, 10);
+//! render_to_buffer("HI", &style, &mut buffer, size, 64, BufferFormat::Rgb565Be);
+//! ```
+
+use core::{convert::Infallible, marker::PhantomData};
+
+use embedded_graphics::{
+    pixelcolor::{raw::RawU16, BinaryColor, Rgb565},
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+
+use crate::{text::BdfTextStyle, BitOrder};
+
+/// Pixel format of a framebuffer.
+///
+/// Rows are stored consecutively, starting with the top row. The number of bytes per row is
+/// given by the stride, which can be larger than the minimum number of bytes for the width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum BufferFormat {
+    /// One bit per pixel.
+    ///
+    /// Colors are converted to [`BinaryColor`] and set bits represent [`BinaryColor::On`].
+    Mono(BitOrder),
+    /// RGB565 with the most significant byte of each pixel first.
+    Rgb565Be,
+    /// RGB565 with the least significant byte of each pixel first.
+    Rgb565Le,
+}
+
+impl BufferFormat {
+    /// Returns the number of pixels in a row with the given stride.
+    pub fn width(self, stride: usize) -> usize {
+        match self {
+            BufferFormat::Mono(_) => stride * 8,
+            BufferFormat::Rgb565Be | BufferFormat::Rgb565Le => stride / 2,
+        }
+    }
+}
+
+/// Color which is converted into the pixel format of a framebuffer.
+#[derive(Debug, Clone, Copy)]
+enum RawColor {
+    Mono(BitOrder, bool),
+    Rgb565([u8; 2]),
+}
+
+/// Draw target which writes colors of type `C` into a raw buffer.
+struct RawBuffer<'a, C> {
+    data: &'a mut [u8],
+    stride: usize,
+    format: BufferFormat,
+    size: Size,
+    color: PhantomData<C>,
+}
+
+impl<C> RawBuffer<'_, C>
+where
+    C: Into<BinaryColor> + Into<Rgb565>,
+{
+    fn raw_color(&self, color: C) -> RawColor {
+        match self.format {
+            BufferFormat::Mono(bit_order) => {
+                RawColor::Mono(bit_order, Into::<BinaryColor>::into(color).is_on())
+            }
+            BufferFormat::Rgb565Be => RawColor::Rgb565(
+                RawU16::from(Into::<Rgb565>::into(color))
+                    .into_inner()
+                    .to_be_bytes(),
+            ),
+            BufferFormat::Rgb565Le => RawColor::Rgb565(
+                RawU16::from(Into::<Rgb565>::into(color))
+                    .into_inner()
+                    .to_le_bytes(),
+            ),
+        }
+    }
+
+    /// Sets a pixel, which must be inside the buffer.
+    fn set_pixel(&mut self, x: i32, y: i32, color: RawColor) {
+        let (x, y) = (x as usize, y as usize);
+        let row = &mut self.data[y * self.stride..(y + 1) * self.stride];
+
+        match color {
+            RawColor::Mono(bit_order, on) => {
+                let mask = match bit_order {
+                    BitOrder::MsbFirst => 0x80 >> (x % 8),
+                    BitOrder::LsbFirst => 0x01 << (x % 8),
+                };
+
+                if on {
+                    row[x / 8] |= mask;
+                } else {
+                    row[x / 8] &= !mask;
+                }
+            }
+            RawColor::Rgb565(bytes) => row[x * 2..x * 2 + 2].copy_from_slice(&bytes),
+        }
+    }
+}
+
+impl<C> Dimensions for RawBuffer<'_, C> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), self.size)
+    }
+}
+
+impl<C> DrawTarget for RawBuffer<'_, C>
+where
+    C: PixelColor + Into<BinaryColor> + Into<Rgb565>,
+{
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounding_box = self.bounding_box();
+
+        for Pixel(point, color) in pixels {
+            if bounding_box.contains(point) {
+                let color = self.raw_color(color);
+                self.set_pixel(point.x, point.y, color);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let drawn = area.intersection(&self.bounding_box());
+        if drawn.is_zero_sized() {
+            return Ok(());
+        }
+
+        let (rows, columns) = (drawn.rows(), drawn.columns());
+        let mut colors = colors.into_iter();
+
+        for y in area.rows().take_while(|y| *y < rows.end) {
+            for (x, color) in area.columns().zip(colors.by_ref()) {
+                if rows.contains(&y) && columns.contains(&x) {
+                    let color = self.raw_color(color);
+                    self.set_pixel(x, y, color);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let drawn = area.intersection(&self.bounding_box());
+        let color = self.raw_color(color);
+
+        for y in drawn.rows() {
+            for x in drawn.columns() {
+                self.set_pixel(x, y, color);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Draws text into a raw framebuffer.
+///
+/// The text is drawn with the top of the first line at the top left corner of the buffer, using
+/// [`Baseline::Top`]. Newlines start a new line, like for [`Text`] drawables. Drawing is clipped
+/// to `size`, which makes it possible to use buffers with rows which are padded to a larger
+/// `stride`, and to the rows and columns which fit into `buf` with the given `stride` and
+/// `format`. Returns the position after the last character, like [`Text::draw`](Drawable::draw).
+///
+/// Colors are converted into the pixel format of the buffer, which means that styles for all
+/// color types which can be converted into [`Rgb565`] and [`BinaryColor`] can be used.
+pub fn render_to_buffer<C>(
+    text: &str,
+    style: &BdfTextStyle<'_, C>,
+    buf: &mut [u8],
+    size: Size,
+    stride: usize,
+    format: BufferFormat,
+) -> Point
+where
+    C: PixelColor + Into<BinaryColor> + Into<Rgb565>,
+{
+    let rows = buf.len().checked_div(stride).unwrap_or(0);
+    let size = size.component_min(Size::new(format.width(stride) as u32, rows as u32));
+
+    let mut target = RawBuffer {
+        data: buf,
+        stride,
+        format,
+        size,
+        color: PhantomData,
+    };
+
+    match Text::with_baseline(text, Point::zero(), *style, Baseline::Top).draw(&mut target) {
+        Ok(next) => next,
+        Err(infallible) => match infallible {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::Rgb888};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    /// Draws text into a mock display.
+    fn expected<C: PixelColor>(text: &str, style: BdfTextStyle<'_, C>) -> MockDisplay<C> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        Text::with_baseline(text, Point::zero(), style, Baseline::Top)
+            .draw(&mut display)
+            .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn mono() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        for bit_order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let mut buf = [0; 3 * 20];
+            let next = render_to_buffer(
+                "AB\nC",
+                &style,
+                &mut buf,
+                Size::new(24, 20),
+                3,
+                BufferFormat::Mono(bit_order),
+            );
+            assert_eq!(next, Point::new(6, 10));
+
+            let mut display = MockDisplay::new();
+            for y in 0..20 {
+                for x in 0..24 {
+                    let byte = buf[y * 3 + x / 8];
+                    let bit = match bit_order {
+                        BitOrder::MsbFirst => byte & 0x80 >> (x % 8),
+                        BitOrder::LsbFirst => byte & 0x01 << (x % 8),
+                    };
+                    if bit != 0 {
+                        let point = Point::new(x as i32, y as i32);
+                        display.set_pixel(point, Some(BinaryColor::On));
+                    }
+                }
+            }
+
+            display.assert_eq(&expected("AB\nC", style));
+        }
+    }
+
+    #[test]
+    fn rgb565() {
+        let style = BdfTextStyle::new(&FONT, Rgb888::new(0xFF, 0, 0)).with_bg_color(Rgb888::BLUE);
+
+        let mut buf = [0; 2 * 12 * 10 + 1];
+        render_to_buffer(
+            "AB",
+            &style,
+            &mut buf,
+            Size::new(12, 10),
+            2 * 12,
+            BufferFormat::Rgb565Le,
+        );
+        assert_eq!(buf[2 * 12 * 10], 0, "partial row");
+
+        let mut display = MockDisplay::new();
+        for (i, pixel) in buf.chunks_exact(2).enumerate() {
+            let raw = u16::from_le_bytes([pixel[0], pixel[1]]);
+            let point = Point::new(i as i32 % 12, i as i32 / 12);
+            display.set_pixel(point, Some(Rgb565::from(RawU16::new(raw))));
+        }
+
+        let style = BdfTextStyle::new(&FONT, Rgb565::RED).with_bg_color(Rgb565::BLUE);
+        display.assert_eq(&expected("AB", style));
+
+        let mut be = [0; 2 * 12 * 10];
+        render_to_buffer(
+            "AB",
+            &style,
+            &mut be,
+            Size::new(12, 10),
+            2 * 12,
+            BufferFormat::Rgb565Be,
+        );
+        for (be, le) in be.chunks_exact(2).zip(buf.chunks_exact(2)) {
+            assert_eq!(be, [le[1], le[0]]);
+        }
+    }
+
+    #[test]
+    fn clip_to_size() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        // 10 pixels per row, padded to 2 bytes.
+        let mut buf = [0xFF; 2 * 12];
+        let format = BufferFormat::Mono(BitOrder::MsbFirst);
+        let next = render_to_buffer("AB", &style, &mut buf, Size::new(10, 11), 2, format);
+        assert_eq!(next, Point::new(12, 0));
+
+        let mut display = MockDisplay::new();
+        for y in 0..12 {
+            for x in 0..16 {
+                let on = buf[y * 2 + x / 8] & 0x80 >> (x % 8) != 0;
+                display.set_pixel(Point::new(x as i32, y as i32), Some(on.into()));
+            }
+        }
+
+        let mut expected = expected("AB", style);
+        for y in 0..12 {
+            for x in 0..16 {
+                let point = Point::new(x, y);
+                if x >= 10 || y >= 10 {
+                    expected.set_pixel(point, Some(BinaryColor::On));
+                }
+            }
+        }
+        display.assert_eq(&expected);
+    }
+}
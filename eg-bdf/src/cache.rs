@@ -0,0 +1,526 @@
+//! Glyph cache.
+
+use embedded_graphics::{prelude::*, primitives::Rectangle, text::Baseline};
+
+use crate::{
+    rotate::{Rotation, Transform},
+    text::BdfTextStyle,
+    BdfFont, BdfGlyph, BitmapLayout,
+};
+
+/// Expanded glyph bitmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Entry<'a, const BYTES: usize> {
+    glyph: &'a BdfGlyph,
+    stride: usize,
+    rows: [u8; BYTES],
+    last_used: u32,
+}
+
+impl<const BYTES: usize> Entry<'_, BYTES> {
+    fn is_set(&self, x: u32, y: u32) -> bool {
+        self.rows[y as usize * self.stride + x as usize / 8] & (0x80 >> (x % 8)) != 0
+    }
+
+    fn draw<D>(
+        &self,
+        top_left: Point,
+        color: D::Color,
+        bg_color: Option<D::Color>,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget,
+    {
+        let bounding_box = Rectangle::new(top_left, self.glyph.bounding_box.size);
+        let clipped = bounding_box.intersection(&target.bounding_box());
+        if clipped.is_zero_sized() {
+            return Ok(());
+        }
+
+        if let Some(bg_color) = bg_color {
+            let colors = clipped.points().map(|point| {
+                let offset = point - top_left;
+                if self.is_set(offset.x as u32, offset.y as u32) {
+                    color
+                } else {
+                    bg_color
+                }
+            });
+
+            return target.fill_contiguous(&clipped, colors);
+        }
+
+        let columns = clipped.columns();
+        for y in clipped.rows() {
+            let mut run_start = None;
+
+            for x in columns.start..=columns.end {
+                let offset = Point::new(x, y) - top_left;
+                let set = x < columns.end && self.is_set(offset.x as u32, offset.y as u32);
+
+                match (set, run_start) {
+                    (true, None) => run_start = Some(x),
+                    (false, Some(start)) => {
+                        let run =
+                            Rectangle::new(Point::new(start, y), Size::new((x - start) as u32, 1));
+                        target.fill_solid(&run, color)?;
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Cache for expanded glyph bitmaps.
+///
+/// Glyph bitmaps are stored in the font data without any padding, which requires bit shifting to
+/// access individual rows. The cache stores up to `N` glyphs with byte aligned rows, which makes
+/// repeated drawing of the same characters, like digits, faster. Each cached glyph uses `BYTES`
+/// bytes of storage and glyphs which don't fit are drawn without using the cache. When the cache
+/// is full, the least recently used glyph is replaced.
+///
+/// The cache is used by [`BdfTextStyle::draw_string_cached`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GlyphCache<'a, const N: usize, const BYTES: usize = 32> {
+    entries: [Option<Entry<'a, BYTES>>; N],
+    clock: u32,
+}
+
+impl<'a, const N: usize, const BYTES: usize> GlyphCache<'a, N, BYTES> {
+    /// Creates an empty glyph cache.
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; N],
+            clock: 0,
+        }
+    }
+
+    /// Returns the number of cached glyphs.
+    pub fn len(&self) -> usize {
+        self.entries.iter().flatten().count()
+    }
+
+    /// Returns `true` if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the glyph is cached.
+    pub fn contains(&self, glyph: &BdfGlyph) -> bool {
+        self.entries
+            .iter()
+            .flatten()
+            .any(|entry| core::ptr::eq(entry.glyph, glyph))
+    }
+
+    /// Removes all glyphs from the cache.
+    pub fn clear(&mut self) {
+        self.entries = [None; N];
+    }
+
+    /// Returns the cached entry for a glyph and inserts it if necessary.
+    ///
+    /// Returns `None` if the glyph doesn't fit into an entry.
+    fn get(
+        &mut self,
+        glyph: &'a BdfGlyph,
+        data: &[u8],
+        layout: BitmapLayout,
+    ) -> Option<&Entry<'a, BYTES>> {
+        self.clock = self.clock.wrapping_add(1);
+        let clock = self.clock;
+
+        let index = match self
+            .entries
+            .iter()
+            .position(|entry| entry.is_some_and(|entry| core::ptr::eq(entry.glyph, glyph)))
+        {
+            Some(index) => index,
+            None => {
+                let size = glyph.bounding_box.size;
+                let stride = size.width.div_ceil(8) as usize;
+                if stride * size.height as usize > BYTES || N == 0 {
+                    return None;
+                }
+
+                let mut rows = [0; BYTES];
+                for point in Rectangle::new(Point::zero(), size).points() {
+                    if glyph.pixel_with_layout(data, layout, point) {
+                        rows[point.y as usize * stride + point.x as usize / 8] |=
+                            0x80 >> (point.x % 8);
+                    }
+                }
+
+                let index = replaced_index(&self.entries, |entry| entry.last_used, clock)?;
+
+                self.entries[index] = Some(Entry {
+                    glyph,
+                    stride,
+                    rows,
+                    last_used: clock,
+                });
+
+                index
+            }
+        };
+
+        let entry = self.entries[index].as_mut()?;
+        entry.last_used = clock;
+
+        Some(entry)
+    }
+}
+
+impl<const N: usize, const BYTES: usize> Default for GlyphCache<'_, N, BYTES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the index of an empty entry or, if all entries are used, the least recently used
+/// entry.
+fn replaced_index<T>(
+    entries: &[Option<T>],
+    last_used: impl Fn(&T) -> u32,
+    clock: u32,
+) -> Option<usize> {
+    entries.iter().position(Option::is_none).or_else(|| {
+        (0..entries.len()).max_by_key(|index| {
+            let last_used = entries[*index].as_ref().map_or(0, &last_used);
+            clock.wrapping_sub(last_used)
+        })
+    })
+}
+
+/// Rasterized rotated glyph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RotatedEntry<'a, const BYTES: usize> {
+    glyph: &'a BdfGlyph,
+    transform: Transform,
+    /// Area covered by the rotated glyph, relative to the glyph offset.
+    area: Rectangle,
+    stride: usize,
+    rows: [u8; BYTES],
+    last_used: u32,
+}
+
+impl<const BYTES: usize> RotatedEntry<'_, BYTES> {
+    fn is_set(&self, point: Point) -> bool {
+        let offset = point - self.area.top_left;
+        let (x, y) = (offset.x as usize, offset.y as usize);
+
+        self.rows[y * self.stride + x / 8] & (0x80 >> (x % 8)) != 0
+    }
+}
+
+/// Cache for rotated glyphs.
+///
+/// Rotating a glyph requires mapping every pixel of the rotated glyph back into the glyph bitmap.
+/// This cache stores up to `N` rasterized rotated glyphs, which are keyed by the glyph, the
+/// rotation and the subpixel position of the glyph. Text which is redrawn at the same position
+/// in every frame, like an [`ArcText`](crate::arc::ArcText) label, only needs to be rotated
+/// once. Each cached glyph uses `BYTES` bytes of storage and glyphs which don't fit are drawn
+/// without using the cache. When the cache is full, the least recently used glyph is replaced.
+///
+/// The cache is used by [`ArcText::draw_cached`](crate::arc::ArcText::draw_cached) and
+/// [`PathText::draw_cached`](crate::path::PathText::draw_cached).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotatedGlyphCache<'a, const N: usize, const BYTES: usize = 64> {
+    entries: [Option<RotatedEntry<'a, BYTES>>; N],
+    clock: u32,
+}
+
+impl<'a, const N: usize, const BYTES: usize> RotatedGlyphCache<'a, N, BYTES> {
+    /// Creates an empty rotated glyph cache.
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; N],
+            clock: 0,
+        }
+    }
+
+    /// Returns the number of cached glyphs.
+    pub fn len(&self) -> usize {
+        self.entries.iter().flatten().count()
+    }
+
+    /// Returns `true` if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all glyphs from the cache.
+    pub fn clear(&mut self) {
+        self.entries = [None; N];
+    }
+
+    /// Returns the cached entry for a rotated glyph and inserts it if necessary.
+    ///
+    /// Returns `None` if the rotated glyph doesn't fit into an entry.
+    fn get(
+        &mut self,
+        glyph: &'a BdfGlyph,
+        font: &BdfFont<'_>,
+        transform: Transform,
+    ) -> Option<&RotatedEntry<'a, BYTES>> {
+        self.clock = self.clock.wrapping_add(1);
+        let clock = self.clock;
+
+        let index = match self.entries.iter().position(|entry| {
+            entry.is_some_and(|entry| {
+                core::ptr::eq(entry.glyph, glyph) && entry.transform == transform
+            })
+        }) {
+            Some(index) => index,
+            None => {
+                let area = transform.area(glyph);
+                let stride = area.size.width.div_ceil(8) as usize;
+                if stride * area.size.height as usize > BYTES || N == 0 {
+                    return None;
+                }
+
+                let mut rows = [0; BYTES];
+                for point in area.points() {
+                    if transform.is_set(glyph, font, point) {
+                        let offset = point - area.top_left;
+                        rows[offset.y as usize * stride + offset.x as usize / 8] |=
+                            0x80 >> (offset.x % 8);
+                    }
+                }
+
+                let index = replaced_index(&self.entries, |entry| entry.last_used, clock)?;
+                self.entries[index] = Some(RotatedEntry {
+                    glyph,
+                    transform,
+                    area,
+                    stride,
+                    rows,
+                    last_used: clock,
+                });
+
+                index
+            }
+        };
+
+        let entry = self.entries[index].as_mut()?;
+        entry.last_used = clock;
+
+        Some(entry)
+    }
+
+    /// Draws a rotated glyph using the cache.
+    ///
+    /// The result is identical to drawing the rotated glyph without the cache.
+    pub(crate) fn draw_rotated_glyph<D: DrawTarget>(
+        &mut self,
+        glyph: &'a BdfGlyph,
+        font: &BdfFont<'_>,
+        origin: (f32, f32),
+        rotation: Rotation,
+        color: D::Color,
+        target: &mut D,
+    ) -> Result<(), D::Error> {
+        let (transform, offset) = Transform::new(origin, rotation);
+
+        match self.get(glyph, font, transform) {
+            Some(entry) => {
+                let pixels = entry
+                    .area
+                    .points()
+                    .filter(|point| entry.is_set(*point))
+                    .map(|point| Pixel(point + offset, color));
+
+                target.draw_iter(pixels)
+            }
+            None => crate::rotate::draw_rotated_glyph(glyph, font, origin, rotation, color, target),
+        }
+    }
+}
+
+impl<const N: usize, const BYTES: usize> Default for RotatedGlyphCache<'_, N, BYTES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, C: PixelColor> BdfTextStyle<'a, C> {
+    /// Draws a string using a glyph cache.
+    ///
+    /// The result is the same as drawing the string using
+    /// [`draw_string`](embedded_graphics::text::renderer::TextRenderer::draw_string), except that a background color is only drawn
+    /// inside the glyph bounding boxes. Returns the position after the last character, using the
+    /// same baseline as `position`.
+    pub fn draw_string_cached<D, const N: usize, const BYTES: usize>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        cache: &mut GlyphCache<'a, N, BYTES>,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let font = self.font();
+        let mut alphabetic = self.alphabetic_position(position, baseline);
+
+        for c in text.chars() {
+            let glyph = font.get_glyph(c);
+
+            match cache.get(glyph, font.data, font.bitmap_layout) {
+                Some(entry) => entry.draw(
+                    glyph.bounding_box.top_left + alphabetic,
+                    self.text_color(),
+                    self.background_color(),
+                    target,
+                )?,
+                None => glyph.draw_with_layout(
+                    alphabetic,
+                    self.text_color(),
+                    self.background_color(),
+                    font.data,
+                    font.bitmap_layout,
+                    target,
+                )?,
+            }
+
+            self.draw_decorations(target, glyph.device_width, alphabetic)?;
+            alphabetic.x += glyph.device_width;
+        }
+
+        Ok(Point::new(alphabetic.x, position.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        arc::{ArcDirection, ArcText},
+        include_bdf,
+        path::PathText,
+        BdfFont,
+    };
+    use embedded_graphics::{
+        mock_display::MockDisplay, pixelcolor::BinaryColor, text::renderer::TextRenderer,
+    };
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn draw_cached() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let mut cache = GlyphCache::<2>::new();
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        style
+            .draw_string_cached(
+                "ABA",
+                Point::zero(),
+                Baseline::Top,
+                &mut cache,
+                &mut display,
+            )
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("ABA", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+
+        assert_eq!(display, expected);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn least_recently_used() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let mut cache = GlyphCache::<2>::new();
+        let font = style.font();
+        let glyph = |c| font.get_glyph(c);
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        display.set_allow_out_of_bounds_drawing(true);
+        style
+            .draw_string_cached(
+                "ABAC",
+                Point::zero(),
+                Baseline::Top,
+                &mut cache,
+                &mut display,
+            )
+            .unwrap();
+
+        assert!(cache.contains(glyph('A')));
+        assert!(!cache.contains(glyph('B')));
+        assert!(cache.contains(glyph('C')));
+    }
+
+    #[test]
+    fn glyph_too_large() {
+        let mut cache = GlyphCache::<2, 4>::new();
+
+        assert!(cache
+            .get(FONT.get_glyph('A'), FONT.data, FONT.bitmap_layout)
+            .is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn rotated_glyphs() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let text = ArcText::new(
+            "ABC",
+            style,
+            Point::new(30, 30),
+            20,
+            Angle::from_degrees(-120.0),
+            ArcDirection::Clockwise,
+        );
+        let mut cache = RotatedGlyphCache::<4>::new();
+
+        let mut expected = MockDisplay::new();
+        let next = text.draw(&mut expected).unwrap();
+
+        for _ in 0..2 {
+            let mut display = MockDisplay::new();
+            assert_eq!(text.draw_cached(&mut cache, &mut display).unwrap(), next);
+            display.assert_eq(&expected);
+            assert_eq!(cache.len(), 3);
+        }
+
+        let points = [Point::new(10, 10), Point::new(20, 20), Point::new(20, 40)];
+        let text = PathText::new("ABC", style, &points);
+
+        let mut expected = MockDisplay::new();
+        text.draw(&mut expected).unwrap();
+
+        let mut display = MockDisplay::new();
+        text.draw_cached(&mut cache, &mut display).unwrap();
+        display.assert_eq(&expected);
+        assert_eq!(cache.len(), 4);
+    }
+
+    #[test]
+    fn rotated_glyph_too_large() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let points = [Point::new(10, 10), Point::new(20, 20)];
+        let text = PathText::new("A", style, &points);
+        let mut cache = RotatedGlyphCache::<2, 4>::new();
+
+        let mut expected = MockDisplay::new();
+        text.draw(&mut expected).unwrap();
+
+        let mut display = MockDisplay::new();
+        text.draw_cached(&mut cache, &mut display).unwrap();
+        display.assert_eq(&expected);
+        assert!(cache.is_empty());
+    }
+}
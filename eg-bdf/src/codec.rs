@@ -0,0 +1,493 @@
+//! Pluggable glyph decompression.
+//!
+//! The bitmap data of a [`BdfFont`] is usually stored uncompressed. Fonts which are stored in a
+//! compressed format can be drawn by implementing the [`GlyphCodec`] trait, which decodes the
+//! bitmap of a single glyph before it is drawn. The [`Uncompressed`] codec uses the bitmap data
+//! of the font directly, without any decoding.
+//!
+//! The [`RunLength`] codec decodes the bitmaps of a [`RunLengthFont`], which is generated by the
+//! `--compress` option of `eg-bdf-convert`.
+
+use embedded_graphics::{prelude::*, text::Baseline};
+
+use crate::{text::BdfTextStyle, BdfFont, BdfGlyph, BitmapLayout};
+
+/// Decoded glyph bitmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct GlyphBitmap<'a> {
+    /// Bitmap data.
+    pub data: &'a [u8],
+    /// Layout of the bitmap data.
+    pub layout: BitmapLayout,
+    /// Index of the first bit of the glyph in `data`.
+    pub start_index: usize,
+}
+
+/// Glyph bitmap decoder.
+pub trait GlyphCodec {
+    /// Decodes the bitmap of a glyph.
+    ///
+    /// The meaning of the `start_index` of `glyph` is defined by the codec, e.g. it can be the
+    /// offset of the compressed bitmap in the compressed data. The returned bitmap can borrow
+    /// from a buffer inside the codec, which is reused for the next glyph.
+    fn decode(&mut self, glyph: &BdfGlyph) -> GlyphBitmap<'_>;
+}
+
+/// Codec for uncompressed bitmap data.
+///
+/// This codec returns the bitmap data of the font without decoding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct Uncompressed<'a> {
+    data: &'a [u8],
+    layout: BitmapLayout,
+}
+
+impl<'a> Uncompressed<'a> {
+    /// Creates a codec for the bitmap data of a font.
+    pub fn new(font: &BdfFont<'a>) -> Self {
+        Self {
+            data: font.data,
+            layout: font.bitmap_layout,
+        }
+    }
+}
+
+impl GlyphCodec for Uncompressed<'_> {
+    #[inline]
+    fn decode(&mut self, glyph: &BdfGlyph) -> GlyphBitmap<'_> {
+        GlyphBitmap {
+            data: self.data,
+            layout: self.layout,
+            start_index: glyph.start_index,
+        }
+    }
+}
+
+/// Font with run length encoded bitmap data.
+///
+/// Run length encoded bitmaps can't be drawn without decoding them first, which is why they are
+/// stored outside of the [`BdfFont`]. The `font` contains the glyphs and metrics without any
+/// bitmap data and is used to create the [`BdfTextStyle`] for
+/// [`draw_string_with_codec`](BdfTextStyle::draw_string_with_codec). Drawing it without the
+/// [`RunLength`] codec only draws backgrounds and decorations, and [`BdfFont::validate`] returns
+/// an error.
+///
+/// ```
+/// use eg_bdf::{
+///     codec::{RunLength, RunLengthFont},
+///     text::BdfTextStyle,
+/// };
+/// use embedded_graphics::{pixelcolor::BinaryColor, prelude::*, text::Baseline};
+///
+/// fn draw_hello<D>(font: &RunLengthFont, target: &mut D) -> Result<Point, D::Error>
+/// where
+///     D: DrawTarget<Color = BinaryColor>,
+/// {
+///     let style = BdfTextStyle::new(&font.font, BinaryColor::On);
+///     let mut codec = RunLength::<32>::new(font);
+///
+///     style.draw_string_with_codec("Hello", Point::zero(), Baseline::Top, &mut codec, target)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct RunLengthFont<'a> {
+    /// Glyphs and metrics of the font, without bitmap data.
+    pub font: BdfFont<'a>,
+    /// Run length encoded bitmap data.
+    pub data: &'a [u8],
+}
+
+/// Codec for run length encoded bitmap data.
+///
+/// The first byte of the font data contains the number of bits used to store the length of a run
+/// of background pixels in the upper nibble and the number of bits used for a run of foreground
+/// pixels in the lower nibble. The `start_index` of a glyph is the index of the first bit of its
+/// encoded bitmap, counting from the most significant bit of the first data byte.
+///
+/// A bitmap is encoded as a sequence of pairs of background and foreground run lengths, each
+/// followed by a single bit which is set if the pair is repeated. The pixels are stored row by
+/// row and the rows aren't padded.
+///
+/// The bitmaps are decoded into a buffer of `N` bytes, which must be large enough to hold the
+/// largest glyph with `width * height` bits. Pixels which don't fit into the buffer are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct RunLength<'a, const N: usize> {
+    data: &'a [u8],
+    buffer: [u8; N],
+}
+
+impl<'a, const N: usize> RunLength<'a, N> {
+    /// Creates a codec for the run length encoded bitmap data of a font.
+    pub fn new(font: &RunLengthFont<'a>) -> Self {
+        Self {
+            data: font.data,
+            buffer: [0; N],
+        }
+    }
+}
+
+impl<const N: usize> GlyphCodec for RunLength<'_, N> {
+    fn decode(&mut self, glyph: &BdfGlyph) -> GlyphBitmap<'_> {
+        self.buffer.fill(0);
+
+        let header = self.data.first().copied().unwrap_or_default();
+        let (background_bits, foreground_bits) = (header >> 4, header & 0xF);
+
+        let len = (glyph.bounding_box.size.width as usize)
+            .saturating_mul(glyph.bounding_box.size.height as usize)
+            .min(N * 8);
+        let mut reader = BitReader {
+            data: self.data,
+            index: glyph.start_index,
+        };
+        let mut index = 0;
+
+        // Truncated data ends the bitmap early instead of reading past the end of the data. Every
+        // iteration consumes at least one bit, unless the header is invalid.
+        let valid = background_bits > 0 && foreground_bits > 0;
+        'runs: while valid && index < len {
+            let (background, foreground) =
+                match (reader.read(background_bits), reader.read(foreground_bits)) {
+                    (Some(background), Some(foreground)) => (background, foreground),
+                    _ => break,
+                };
+
+            loop {
+                index += background;
+                for i in index..(index + foreground).min(len) {
+                    self.buffer[i / 8] |= 0x80 >> (i % 8);
+                }
+                index += foreground;
+
+                if index >= len || reader.read(1) != Some(1) {
+                    continue 'runs;
+                }
+            }
+        }
+
+        GlyphBitmap {
+            data: &self.buffer,
+            layout: BitmapLayout::default(),
+            start_index: 0,
+        }
+    }
+}
+
+/// MSB first bit reader.
+struct BitReader<'a> {
+    data: &'a [u8],
+    index: usize,
+}
+
+impl BitReader<'_> {
+    /// Reads an unsigned number with the given number of bits.
+    fn read(&mut self, bits: u8) -> Option<usize> {
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = self.data.get(self.index / 8)?;
+            value = value << 1 | usize::from(byte >> (7 - self.index % 8) & 1);
+            self.index += 1;
+        }
+
+        Some(value)
+    }
+}
+
+impl BdfGlyph {
+    /// Draws the glyph using a codec to decode the bitmap.
+    ///
+    /// See [`draw`](Self::draw) for more information.
+    pub fn draw_with_codec<D: DrawTarget, C: GlyphCodec>(
+        &self,
+        position: Point,
+        color: D::Color,
+        bg_color: Option<D::Color>,
+        codec: &mut C,
+        target: &mut D,
+    ) -> Result<(), D::Error> {
+        let bitmap = codec.decode(self);
+        let glyph = BdfGlyph {
+            start_index: bitmap.start_index,
+            ..*self
+        };
+
+        glyph.draw_with_layout(
+            position,
+            color,
+            bg_color,
+            bitmap.data,
+            bitmap.layout,
+            target,
+        )
+    }
+}
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Draws a string using a codec to decode the glyph bitmaps.
+    ///
+    /// The glyphs are looked up in the font of this style, but the bitmap data of the font is
+    /// ignored and all bitmaps are decoded by `codec` instead. Glyphs are positioned and
+    /// synthesized styles are applied like in [`draw_string`](embedded_graphics::text::renderer::TextRenderer::draw_string). A
+    /// background color is only drawn inside the glyph bounding boxes. Returns the position after
+    /// the last character, using the same baseline as `position`.
+    pub fn draw_string_with_codec<D, K>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        codec: &mut K,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        K: GlyphCodec,
+    {
+        let alphabetic = self.alphabetic_position(position, baseline);
+
+        let mut positions = self.glyph_positions(text, alphabetic.x);
+        while let Some((_, glyph, x)) = positions.next() {
+            let glyph_position = Point::new(x, alphabetic.y);
+            let bitmap = codec.decode(glyph);
+            let decoded = BdfGlyph {
+                start_index: bitmap.start_index,
+                ..*glyph
+            };
+
+            self.draw_glyph_bitmap(
+                &decoded,
+                bitmap.data,
+                bitmap.layout,
+                glyph_position,
+                self.background_color(),
+                target,
+            )?;
+            self.draw_decorations(target, positions.x() - x, glyph_position)?;
+        }
+
+        Ok(Point::new(positions.x(), position.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, tests::FRACTIONAL_FONT, FontError};
+    use alloc::vec::Vec;
+    use embedded_graphics::{
+        mock_display::MockDisplay, pixelcolor::BinaryColor, text::renderer::TextRenderer,
+    };
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    /// Codec which stores the bitmap data inverted.
+    struct Inverted<'a> {
+        data: &'a [u8],
+        buffer: [u8; 16],
+    }
+
+    impl GlyphCodec for Inverted<'_> {
+        fn decode(&mut self, glyph: &BdfGlyph) -> GlyphBitmap<'_> {
+            let start = glyph.start_index / 8;
+            for (output, input) in self.buffer.iter_mut().zip(&self.data[start..]) {
+                *output = !input;
+            }
+
+            GlyphBitmap {
+                data: &self.buffer,
+                layout: BitmapLayout::default(),
+                start_index: glyph.start_index % 8,
+            }
+        }
+    }
+
+    #[test]
+    fn draw_with_codecs() {
+        let inverted: Vec<_> = FONT.data.iter().map(|byte| !byte).collect();
+        let mut codec = Inverted {
+            data: &inverted,
+            buffer: [0; 16],
+        };
+
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).underline();
+
+        let mut expected = MockDisplay::new();
+        let expected_next = style
+            .draw_string("CODEC", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_string_with_codec(
+                "CODEC",
+                Point::zero(),
+                Baseline::Top,
+                &mut codec,
+                &mut display,
+            )
+            .unwrap();
+        assert_eq!(next, expected_next);
+        assert_eq!(display, expected);
+
+        let mut display = MockDisplay::new();
+        style
+            .draw_string_with_codec(
+                "CODEC",
+                Point::zero(),
+                Baseline::Top,
+                &mut Uncompressed::new(&FONT),
+                &mut display,
+            )
+            .unwrap();
+        assert_eq!(display, expected);
+    }
+
+    #[test]
+    fn synthetic_styles_and_fractional_advances() {
+        let styles = [
+            BdfTextStyle::new(&FONT, BinaryColor::On).bold().italic(),
+            BdfTextStyle::new(&FRACTIONAL_FONT, BinaryColor::On)
+                .with_fractional_advances(true)
+                .underline(),
+        ];
+
+        for style in styles {
+            let mut expected = MockDisplay::new();
+            expected.set_allow_overdraw(true);
+            let expected_next = style
+                .draw_string("AAAA", Point::zero(), Baseline::Top, &mut expected)
+                .unwrap();
+
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            let next = style
+                .draw_string_with_codec(
+                    "AAAA",
+                    Point::zero(),
+                    Baseline::Top,
+                    &mut Uncompressed::new(style.font()),
+                    &mut display,
+                )
+                .unwrap();
+            assert_eq!(next, expected_next);
+            assert_eq!(display, expected);
+        }
+    }
+
+    /// Run length encodes the bitmaps of a font using 8 bit runs without repeated pairs.
+    fn run_length_encode(font: &BdfFont) -> (Vec<BdfGlyph>, Vec<u8>) {
+        fn push(bits: &mut Vec<bool>, value: usize) {
+            bits.extend((0..8).rev().map(|i| value >> i & 1 != 0));
+        }
+
+        let mut bits = Vec::new();
+        push(&mut bits, 0x88);
+
+        let mut glyphs = Vec::new();
+        for glyph in font.glyphs {
+            let size = glyph.bounding_box.size;
+            let pixels: Vec<_> = (0..size.height as i32)
+                .flat_map(|y| (0..size.width as i32).map(move |x| Point::new(x, y)))
+                .map(|point| glyph.pixel(font.data, point))
+                .collect();
+
+            glyphs.push(BdfGlyph {
+                start_index: bits.len(),
+                ..*glyph
+            });
+
+            let mut index = 0;
+            while index < pixels.len() {
+                let background = pixels[index..].iter().take_while(|p| !**p).count();
+                index += background;
+                let foreground = pixels[index..].iter().take_while(|p| **p).count();
+                index += foreground;
+
+                push(&mut bits, background);
+                push(&mut bits, foreground);
+                bits.push(false);
+            }
+        }
+
+        let data = bits
+            .chunks(8)
+            .map(|byte| {
+                byte.iter()
+                    .enumerate()
+                    .filter(|(_, b)| **b)
+                    .map(|(i, _)| 0x80 >> i)
+                    .sum()
+            })
+            .collect();
+
+        (glyphs, data)
+    }
+
+    #[test]
+    fn run_length() {
+        // 3x3 checkerboard: a single foreground pixel, followed by four background and foreground
+        // pixel pairs, which are stored as a single pair with three repeats.
+        let data = [0x11, 0b0101_1111];
+        let font = |data| RunLengthFont { font: FONT, data };
+        let glyph = crate::__private::glyph('a', 0, -3, 3, 3, 4, 8);
+
+        let mut codec = RunLength::<2>::new(&font(&data));
+        let bitmap = codec.decode(&glyph);
+        assert_eq!(bitmap.data, &[0b1010_1010, 0b1000_0000]);
+        assert_eq!(bitmap.start_index, 0);
+
+        // Pixels which don't fit into the buffer are ignored.
+        let mut codec = RunLength::<1>::new(&font(&data));
+        assert_eq!(codec.decode(&glyph).data, &[0b1010_1010]);
+
+        // Truncated data doesn't panic.
+        for data in [&data[..1], &[], &[0x10, 0xFF]] {
+            let mut codec = RunLength::<2>::new(&font(data));
+            assert_eq!(codec.decode(&glyph).data, &[0, 0]);
+        }
+    }
+
+    #[test]
+    fn draw_run_length_encoded() {
+        let (glyphs, data) = run_length_encode(&FONT);
+        let font = RunLengthFont {
+            font: BdfFont {
+                glyphs: &glyphs,
+                data: &[],
+                ..FONT
+            },
+            data: &data,
+        };
+        assert_eq!(
+            font.font.validate(),
+            Err(FontError::DataOutOfRange(FONT.glyphs[0].character))
+        );
+
+        let mut expected = MockDisplay::new();
+        BdfTextStyle::new(&FONT, BinaryColor::On)
+            .draw_string("RLE", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+
+        // The encoded bitmaps aren't drawn without decoding them.
+        let mut display = MockDisplay::new();
+        BdfTextStyle::new(&font.font, BinaryColor::On)
+            .draw_string("RLE", Point::zero(), Baseline::Top, &mut display)
+            .unwrap();
+        assert_eq!(display, MockDisplay::new());
+
+        let mut display = MockDisplay::new();
+        BdfTextStyle::new(&font.font, BinaryColor::On)
+            .draw_string_with_codec(
+                "RLE",
+                Point::zero(),
+                Baseline::Top,
+                &mut RunLength::<8>::new(&font),
+                &mut display,
+            )
+            .unwrap();
+        assert_eq!(display, expected);
+    }
+}
@@ -0,0 +1,111 @@
+//! Transparent color keying.
+//!
+//! Text can be rendered into an intermediate buffer, e.g. a sprite which is drawn multiple times,
+//! by using a background color which doesn't appear in the text. The [`ColorKey`] draw target
+//! treats this color as transparent when the buffer is later drawn onto the display, which
+//! punches out the background and only leaves the text.
+
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+/// Draw target wrapper which discards all pixels of a transparent key color.
+///
+/// Pixels, solid fills and contiguous fills which use the key color aren't drawn to the wrapped
+/// draw target. All other pixels are drawn unchanged.
+#[derive(Debug)]
+pub struct ColorKey<'a, D: DrawTarget> {
+    parent: &'a mut D,
+    key: D::Color,
+}
+
+impl<'a, D: DrawTarget> ColorKey<'a, D> {
+    /// Creates a new color key wrapper with the given transparent color.
+    pub fn new(parent: &'a mut D, key: D::Color) -> Self {
+        Self { parent, key }
+    }
+
+    /// Returns the transparent key color.
+    pub fn key(&self) -> D::Color {
+        self.key
+    }
+}
+
+impl<D: DrawTarget> Dimensions for ColorKey<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.parent.bounding_box()
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for ColorKey<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let key = self.key;
+
+        self.parent
+            .draw_iter(pixels.into_iter().filter(|Pixel(_, color)| *color != key))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.draw_iter(
+            area.points()
+                .zip(colors)
+                .map(|(point, color)| Pixel(point, color)),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        if color == self.key {
+            return Ok(());
+        }
+
+        self.parent.fill_solid(area, color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, text::BdfTextStyle, BdfFont};
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        pixelcolor::Rgb565,
+        text::{renderer::TextRenderer, Baseline},
+    };
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn punch_out_background() {
+        let style = BdfTextStyle::new(&FONT, Rgb565::WHITE).with_bg_color(Rgb565::MAGENTA);
+
+        // Render the text into a sprite buffer with a magenta background.
+        let mut sprite = MockDisplay::new();
+        style
+            .draw_string("I", Point::zero(), Baseline::Top, &mut sprite)
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        let mut keyed = ColorKey::new(&mut display, Rgb565::MAGENTA);
+        assert_eq!(keyed.key(), Rgb565::MAGENTA);
+
+        let area = sprite.affected_area();
+        keyed
+            .fill_contiguous(
+                &area,
+                area.points().map(|point| sprite.get_pixel(point).unwrap()),
+            )
+            .unwrap();
+        keyed.fill_solid(&area, Rgb565::MAGENTA).unwrap();
+
+        display.assert_pattern(&[
+            "     ", " WWW ", "  W  ", "  W  ", "  W  ", "  W  ", "  W  ", " WWW ",
+        ]);
+    }
+}
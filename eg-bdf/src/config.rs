@@ -0,0 +1,198 @@
+//! Style configuration.
+//!
+//! Styles reference fonts and use color types which can't be stored in configuration files. The
+//! types in this module describe styles using font names and raw color values instead and can be
+//! converted into [`BdfTextStyle`]s at runtime. With the `serde` feature enabled the types
+//! implement `Serialize` and `Deserialize`, which allows loading UI themes from configuration
+//! files.
+
+use embedded_graphics::{
+    pixelcolor::raw::RawData,
+    prelude::*,
+    text::{renderer::CharacterStyle, DecorationColor},
+};
+
+use crate::{text::BdfTextStyle, BdfFont};
+
+/// Decoration setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Decoration {
+    /// No decoration.
+    #[default]
+    None,
+    /// Decoration with the text color.
+    TextColor,
+    /// Decoration with a custom raw color value.
+    Custom(u32),
+}
+
+impl Decoration {
+    fn to_decoration_color<C>(self) -> DecorationColor<C>
+    where
+        C: PixelColor + From<C::Raw>,
+    {
+        match self {
+            Decoration::None => DecorationColor::None,
+            Decoration::TextColor => DecorationColor::TextColor,
+            Decoration::Custom(raw) => DecorationColor::Custom(C::Raw::from_u32(raw).into()),
+        }
+    }
+}
+
+/// Text style configuration.
+///
+/// Colors are stored as raw values, e.g. `0xF800` for red in `Rgb565`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StyleConfig<'a> {
+    /// Font name.
+    pub font: &'a str,
+
+    /// Raw text color.
+    pub text_color: u32,
+
+    /// Raw background color, if `None` the background isn't drawn.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub background_color: Option<u32>,
+
+    /// Underline.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub underline: Decoration,
+
+    /// Strikethrough.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub strikethrough: Decoration,
+
+    /// Extra space between lines.
+    ///
+    /// The previous name `height_adjust` is accepted as an alias when deserializing.
+    #[cfg_attr(feature = "serde", serde(default, alias = "height_adjust"))]
+    pub line_gap: i32,
+
+    /// Line spacing in percent of the full height of the font.
+    #[cfg_attr(feature = "serde", serde(default = "default_line_spacing_percent"))]
+    pub line_spacing_percent: u16,
+}
+
+#[cfg(feature = "serde")]
+fn default_line_spacing_percent() -> u16 {
+    100
+}
+
+impl<'a> StyleConfig<'a> {
+    /// Creates a new style configuration.
+    pub const fn new(font: &'a str, text_color: u32) -> Self {
+        Self {
+            font,
+            text_color,
+            background_color: None,
+            underline: Decoration::None,
+            strikethrough: Decoration::None,
+            line_gap: 0,
+            line_spacing_percent: 100,
+        }
+    }
+
+    /// Converts the configuration into a text style.
+    ///
+    /// The font is looked up by name in `fonts`. Returns `None` if no font with a matching name
+    /// exists.
+    pub fn to_style<'f, C>(&self, fonts: &[(&str, &'f BdfFont<'f>)]) -> Option<BdfTextStyle<'f, C>>
+    where
+        C: PixelColor + From<C::Raw>,
+    {
+        let font = fonts
+            .iter()
+            .find(|(name, _)| *name == self.font)
+            .map(|(_, font)| *font)?;
+
+        let color = |raw| C::Raw::from_u32(raw).into();
+
+        let mut style = BdfTextStyle::new(font, color(self.text_color));
+        style.set_background_color(self.background_color.map(color));
+        style.set_underline_color(self.underline.to_decoration_color());
+        style.set_strikethrough_color(self.strikethrough.to_decoration_color());
+
+        Some(
+            style
+                .with_line_gap(self.line_gap)
+                .with_line_spacing_percent(self.line_spacing_percent),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::include_bdf;
+    use embedded_graphics::{pixelcolor::Rgb565, text::renderer::TextRenderer};
+
+    const SMALL: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+    const LARGE: BdfFont = include_bdf!("examples/10x20.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn to_style() {
+        let fonts = [("small", &SMALL), ("large", &LARGE)];
+
+        let config = StyleConfig {
+            background_color: Some(0x001F),
+            underline: Decoration::Custom(0x07E0),
+            line_gap: 2,
+            ..StyleConfig::new("large", 0xF800)
+        };
+
+        let style = config.to_style::<Rgb565>(&fonts).unwrap();
+        assert_eq!(style.text_color(), Rgb565::RED);
+        assert_eq!(style.background_color(), Some(Rgb565::BLUE));
+        assert_eq!(
+            style.line_height(),
+            LARGE.font_ascent + LARGE.font_descent + 2,
+            "line gap"
+        );
+
+        let config = StyleConfig {
+            line_spacing_percent: 150,
+            ..StyleConfig::new("small", 0)
+        };
+        let style = config.to_style::<Rgb565>(&fonts).unwrap();
+        assert_eq!(style.line_height(), 15, "line spacing");
+
+        let config = StyleConfig::new("missing", 0);
+        assert_eq!(config.to_style::<Rgb565>(&fonts), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let config = StyleConfig {
+            background_color: Some(0x001F),
+            underline: Decoration::TextColor,
+            strikethrough: Decoration::Custom(0x07E0),
+            line_gap: -1,
+            line_spacing_percent: 120,
+            ..StyleConfig::new("large", 0xF800)
+        };
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(serde_json::from_str::<StyleConfig>(&json).unwrap(), config);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_defaults_and_alias() {
+        let config: StyleConfig =
+            serde_json::from_str(r#"{ "font": "small", "text_color": 1, "height_adjust": 3 }"#)
+                .unwrap();
+
+        assert_eq!(
+            config,
+            StyleConfig {
+                line_gap: 3,
+                ..StyleConfig::new("small", 1)
+            }
+        );
+    }
+}
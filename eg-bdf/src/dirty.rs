@@ -0,0 +1,161 @@
+//! Dirty area tracking.
+
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+/// Draw target wrapper which records the area that was drawn to.
+///
+/// All drawing operations are forwarded to the wrapped draw target. The dirty area is the
+/// smallest rectangle that contains all pixels which were drawn inside the bounding box of the
+/// wrapped target. This can be used to request minimal update windows from e-paper and other
+/// partial refresh displays.
+#[derive(Debug)]
+pub struct DirtyTracker<'a, D> {
+    parent: &'a mut D,
+    area: Option<(Point, Point)>,
+}
+
+impl<'a, D: DrawTarget> DirtyTracker<'a, D> {
+    /// Creates a new dirty tracker.
+    pub fn new(parent: &'a mut D) -> Self {
+        Self { parent, area: None }
+    }
+
+    /// Returns the dirty area.
+    ///
+    /// Returns `None` if no pixels were drawn.
+    pub fn dirty_area(&self) -> Option<Rectangle> {
+        self.area
+            .map(|(top_left, bottom_right)| Rectangle::with_corners(top_left, bottom_right))
+    }
+
+    /// Returns the dirty area and resets the tracked area.
+    pub fn take_dirty_area(&mut self) -> Option<Rectangle> {
+        let area = self.dirty_area();
+        self.area = None;
+        area
+    }
+
+    fn add_rectangle(&mut self, area: &Rectangle) {
+        let area = area.intersection(&self.parent.bounding_box());
+
+        if let Some(bottom_right) = area.bottom_right() {
+            self.area = extend(extend(self.area, area.top_left), bottom_right);
+        }
+    }
+}
+
+/// Extends an area given by its corners to include `point`.
+fn extend(area: Option<(Point, Point)>, point: Point) -> Option<(Point, Point)> {
+    Some(match area {
+        Some((top_left, bottom_right)) => (
+            top_left.component_min(point),
+            bottom_right.component_max(point),
+        ),
+        None => (point, point),
+    })
+}
+
+impl<D: DrawTarget> Dimensions for DirtyTracker<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.parent.bounding_box()
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for DirtyTracker<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounding_box = self.parent.bounding_box();
+        let mut area = self.area;
+
+        let result = self
+            .parent
+            .draw_iter(pixels.into_iter().inspect(|Pixel(point, _)| {
+                if bounding_box.contains(*point) {
+                    area = extend(area, *point);
+                }
+            }));
+
+        self.area = area;
+        result
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.add_rectangle(area);
+        self.parent.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.add_rectangle(area);
+        self.parent.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.add_rectangle(&self.parent.bounding_box());
+        self.parent.clear(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, text::BdfTextStyle, BdfFont};
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        pixelcolor::BinaryColor,
+        text::{Baseline, Text},
+    };
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn track_text() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        let mut tracker = DirtyTracker::new(&mut display);
+        assert_eq!(tracker.dirty_area(), None);
+
+        Text::with_baseline("A", Point::new(2, 3), style, Baseline::Top)
+            .draw(&mut tracker)
+            .unwrap();
+
+        assert_eq!(
+            tracker.take_dirty_area(),
+            Some(Rectangle::new(Point::new(2, 4), Size::new(5, 7)))
+        );
+        assert_eq!(tracker.dirty_area(), None);
+    }
+
+    #[test]
+    fn track_fills() {
+        let mut display = MockDisplay::new();
+        display.set_allow_out_of_bounds_drawing(true);
+        let mut tracker = DirtyTracker::new(&mut display);
+
+        tracker
+            .fill_solid(
+                &Rectangle::new(Point::new(60, 1), Size::new(10, 2)),
+                BinaryColor::On,
+            )
+            .unwrap();
+        tracker
+            .fill_solid(
+                &Rectangle::new(Point::new(4, 5), Size::new(1, 1)),
+                BinaryColor::On,
+            )
+            .unwrap();
+
+        assert_eq!(
+            tracker.dirty_area(),
+            Some(Rectangle::with_corners(Point::new(4, 1), Point::new(63, 5)))
+        );
+    }
+}
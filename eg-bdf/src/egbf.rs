@@ -0,0 +1,498 @@
+//! Binary font format.
+//!
+//! The EGBF format stores a font in a compact binary representation, which can be written to
+//! flash or an SD card at runtime, e.g. after a font was built, merged or subsetted using an
+//! [`OwnedBdfFont`](crate::owned::OwnedBdfFont). Loading an EGBF font with [`load`] doesn't copy
+//! the bitmap data, only the glyph tables are decoded into buffers provided by the caller.
+//!
+//! All values are stored in little endian byte order. The file starts with a header, which is
+//! followed by the glyph table, the optional scalable width and vertical metrics tables and the
+//! bitmap data. The header starts with the magic number `EGBF`, the format version and a CRC-32
+//! checksum of the remaining data, which are validated by the loader to detect corrupted or
+//! incompatible files.
+
+use core::convert::TryFrom;
+
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+
+use crate::{
+    BdfFont, BdfGlyph, BitOrder, BitmapLayout, FontError, FontExtents, RowLayout, VerticalMetrics,
+};
+
+/// Magic number at the start of an EGBF font.
+const MAGIC: [u8; 4] = *b"EGBF";
+
+/// Version of the format.
+pub const VERSION: u16 = 1;
+
+/// Length of the header in bytes.
+const HEADER_LEN: usize = 40;
+
+/// Offset of the first byte which is included in the checksum.
+const CHECKSUM_START: usize = 12;
+
+/// Length of a glyph table entry in bytes.
+const GLYPH_LEN: usize = 28;
+
+/// Length of a vertical metrics table entry in bytes.
+const VERTICAL_METRICS_LEN: usize = 12;
+
+/// Header of an EGBF font.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct Header {
+    /// Format version.
+    pub version: u16,
+    /// CRC-32 checksum of the font data after the checksum field.
+    pub checksum: u32,
+    /// Pixel size.
+    pub pixel_size: u32,
+    /// Ascent above the baseline.
+    pub font_ascent: u32,
+    /// Descent below the baseline.
+    pub font_descent: u32,
+    /// Index of the replacement character.
+    pub replacement_character: usize,
+    /// Number of glyphs.
+    pub glyph_count: usize,
+    /// Length of the bitmap data in bytes.
+    pub data_len: usize,
+    /// Layout of the bitmap data.
+    pub bitmap_layout: BitmapLayout,
+    /// `true` if the font contains scalable widths.
+    pub has_scalable_widths: bool,
+    /// `true` if the font contains vertical metrics.
+    pub has_vertical_metrics: bool,
+}
+
+impl Header {
+    /// Parses the header of an EGBF font.
+    ///
+    /// Returns an error if the input doesn't start with the EGBF magic number or if the format
+    /// version isn't supported. The checksum is only validated by [`load`].
+    pub fn parse(input: &[u8]) -> Result<Self, FontError> {
+        let mut reader = Reader::new(input);
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(FontError::InvalidMagic);
+        }
+        let version = reader.u16()?;
+        if version != VERSION {
+            return Err(FontError::UnsupportedVersion(version));
+        }
+        reader.u16()?;
+        let checksum = reader.u32()?;
+
+        let pixel_size = reader.u32()?;
+        let font_ascent = reader.u32()?;
+        let font_descent = reader.u32()?;
+        let replacement_character = reader.usize()?;
+        let glyph_count = reader.usize()?;
+        let data_len = reader.usize()?;
+
+        let bit_order = match reader.u8()? {
+            0 => BitOrder::MsbFirst,
+            1 => BitOrder::LsbFirst,
+            _ => return Err(FontError::InvalidData),
+        };
+        let row_layout = match reader.u8()? {
+            0 => RowLayout::Packed,
+            1 => RowLayout::ByteAligned,
+            _ => return Err(FontError::InvalidData),
+        };
+        let has_scalable_widths = reader.u8()? != 0;
+        let has_vertical_metrics = reader.u8()? != 0;
+
+        Ok(Self {
+            version,
+            checksum,
+            pixel_size,
+            font_ascent,
+            font_descent,
+            replacement_character,
+            glyph_count,
+            data_len,
+            bitmap_layout: BitmapLayout::new(bit_order, row_layout),
+            has_scalable_widths,
+            has_vertical_metrics,
+        })
+    }
+
+    /// Returns the number of scalable widths in the font.
+    pub fn scalable_width_count(&self) -> usize {
+        if self.has_scalable_widths {
+            self.glyph_count
+        } else {
+            0
+        }
+    }
+
+    /// Returns the number of vertical metrics in the font.
+    pub fn vertical_metrics_count(&self) -> usize {
+        if self.has_vertical_metrics {
+            self.glyph_count
+        } else {
+            0
+        }
+    }
+}
+
+/// Loads an EGBF font.
+///
+/// The bitmap data of the returned font borrows from `input` and the glyph tables are decoded
+/// into the given buffers. The required buffer lengths can be determined by parsing the
+/// [`Header`] first. Longer buffers are allowed, but only the used parts are included in the
+/// font. The loaded font is checked using [`BdfFont::validate`].
+pub fn load<'a>(
+    input: &'a [u8],
+    glyphs: &'a mut [BdfGlyph],
+    scalable_widths: &'a mut [i32],
+    vertical_metrics: &'a mut [VerticalMetrics],
+) -> Result<BdfFont<'a>, FontError> {
+    let header = Header::parse(input)?;
+    if header.glyph_count == 0 || header.replacement_character >= header.glyph_count {
+        return Err(FontError::InvalidData);
+    }
+
+    let glyphs = glyphs
+        .get_mut(..header.glyph_count)
+        .ok_or(FontError::BufferTooSmall)?;
+    let scalable_widths = scalable_widths
+        .get_mut(..header.scalable_width_count())
+        .ok_or(FontError::BufferTooSmall)?;
+    let vertical_metrics = vertical_metrics
+        .get_mut(..header.vertical_metrics_count())
+        .ok_or(FontError::BufferTooSmall)?;
+
+    // The table lengths are limited by the buffer lengths, but the data length isn't.
+    let len = (HEADER_LEN
+        + header.glyph_count * GLYPH_LEN
+        + header.scalable_width_count() * 4
+        + header.vertical_metrics_count() * VERTICAL_METRICS_LEN)
+        .saturating_add(header.data_len);
+    if input.len() < len {
+        return Err(FontError::UnexpectedEnd);
+    }
+    if crc32(&input[CHECKSUM_START..len]) != header.checksum {
+        return Err(FontError::ChecksumMismatch);
+    }
+
+    let mut reader = Reader::new(input);
+    reader.take(HEADER_LEN)?;
+
+    for glyph in glyphs.iter_mut() {
+        let character = char::from_u32(reader.u32()?).ok_or(FontError::InvalidData)?;
+        let top_left = Point::new(reader.i32()?, reader.i32()?);
+        let size = Size::new(reader.u32()?, reader.u32()?);
+
+        *glyph = BdfGlyph {
+            character,
+            bounding_box: Rectangle::new(top_left, size),
+            device_width: reader.i32()?,
+            start_index: reader.usize()?,
+        };
+    }
+
+    for width in scalable_widths.iter_mut() {
+        *width = reader.i32()?;
+    }
+
+    for metrics in vertical_metrics.iter_mut() {
+        *metrics = VerticalMetrics {
+            origin: Point::new(reader.i32()?, reader.i32()?),
+            advance: reader.i32()?,
+        };
+    }
+
+    let data = reader.take(header.data_len)?;
+    let extents = FontExtents::from_glyphs(glyphs);
+
+    let font = BdfFont {
+        replacement_character: header.replacement_character,
+        glyphs,
+        data,
+        pixel_size: header.pixel_size,
+        font_ascent: header.font_ascent,
+        font_descent: header.font_descent,
+        vertical_metrics,
+        scalable_widths,
+        bitmap_layout: header.bitmap_layout,
+        extents,
+    };
+
+    // The glyphs must be sorted, because they are looked up using a binary search.
+    font.validate()?;
+
+    Ok(font)
+}
+
+/// Appends a font in the EGBF format to `output`.
+///
+/// Returns [`FontError::TooLarge`] and leaves `output` unchanged if a value doesn't fit into the
+/// 32 bit fields of the format.
+#[cfg(feature = "alloc")]
+pub(crate) fn write(font: &BdfFont<'_>, output: &mut alloc::vec::Vec<u8>) -> Result<(), FontError> {
+    let to_u32 = |value: usize| u32::try_from(value).map_err(|_| FontError::TooLarge);
+
+    let header_values = [
+        font.pixel_size,
+        font.font_ascent,
+        font.font_descent,
+        to_u32(font.replacement_character)?,
+        to_u32(font.glyphs.len())?,
+        to_u32(font.data.len())?,
+    ];
+    for glyph in font.glyphs {
+        to_u32(glyph.start_index)?;
+    }
+
+    let has_scalable_widths = font.scalable_widths.len() == font.glyphs.len();
+    let has_vertical_metrics = font.vertical_metrics.len() == font.glyphs.len();
+
+    let start = output.len();
+    output.reserve(
+        HEADER_LEN
+            + font.glyphs.len() * GLYPH_LEN
+            + font.scalable_widths.len() * 4
+            + font.vertical_metrics.len() * VERTICAL_METRICS_LEN
+            + font.data.len(),
+    );
+
+    output.extend_from_slice(&MAGIC);
+    output.extend_from_slice(&VERSION.to_le_bytes());
+    output.extend_from_slice(&[0; 2]);
+    // The checksum is filled in after the remaining data was written.
+    output.extend_from_slice(&[0; 4]);
+
+    for value in header_values {
+        output.extend_from_slice(&value.to_le_bytes());
+    }
+    output.extend_from_slice(&[
+        match font.bitmap_layout.bit_order {
+            BitOrder::MsbFirst => 0,
+            BitOrder::LsbFirst => 1,
+        },
+        match font.bitmap_layout.row_layout {
+            RowLayout::Packed => 0,
+            RowLayout::ByteAligned => 1,
+        },
+        u8::from(has_scalable_widths),
+        u8::from(has_vertical_metrics),
+    ]);
+
+    for glyph in font.glyphs {
+        let Rectangle { top_left, size } = glyph.bounding_box;
+
+        output.extend_from_slice(&u32::from(glyph.character).to_le_bytes());
+        output.extend_from_slice(&top_left.x.to_le_bytes());
+        output.extend_from_slice(&top_left.y.to_le_bytes());
+        output.extend_from_slice(&size.width.to_le_bytes());
+        output.extend_from_slice(&size.height.to_le_bytes());
+        output.extend_from_slice(&glyph.device_width.to_le_bytes());
+        // The conversion was checked above.
+        output.extend_from_slice(&to_u32(glyph.start_index)?.to_le_bytes());
+    }
+
+    if has_scalable_widths {
+        for width in font.scalable_widths {
+            output.extend_from_slice(&width.to_le_bytes());
+        }
+    }
+
+    if has_vertical_metrics {
+        for metrics in font.vertical_metrics {
+            output.extend_from_slice(&metrics.origin.x.to_le_bytes());
+            output.extend_from_slice(&metrics.origin.y.to_le_bytes());
+            output.extend_from_slice(&metrics.advance.to_le_bytes());
+        }
+    }
+
+    output.extend_from_slice(font.data);
+
+    let checksum = crc32(&output[start + CHECKSUM_START..]);
+    output[start + CHECKSUM_START - 4..start + CHECKSUM_START]
+        .copy_from_slice(&checksum.to_le_bytes());
+
+    Ok(())
+}
+
+/// Calculates the CRC-32 checksum (IEEE 802.3) of `data`.
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, byte| {
+        (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+            if crc & 1 != 0 {
+                crc >> 1 ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// Reader for little endian values.
+struct Reader<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(input: &'a [u8]) -> Self {
+        Self { input }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], FontError> {
+        if self.input.len() < len {
+            return Err(FontError::UnexpectedEnd);
+        }
+
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, FontError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, FontError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, FontError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, FontError> {
+        Ok(self.u32()? as i32)
+    }
+
+    fn usize(&mut self) -> Result<usize, FontError> {
+        usize::try_from(self.u32()?).map_err(|_| FontError::InvalidData)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, owned::OwnedBdfFont};
+    use alloc::{vec, vec::Vec};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+    const VERTICAL: BdfFont = include_bdf!("examples/vertical.bdf");
+
+    fn round_trip(font: &BdfFont<'_>) {
+        let mut egbf = Vec::new();
+        font.to_owned().to_egbf(&mut egbf).unwrap();
+
+        let header = Header::parse(&egbf).unwrap();
+        assert_eq!(header.glyph_count, font.glyphs.len());
+
+        let mut glyphs = vec![BdfGlyph::default(); header.glyph_count];
+        let mut widths = vec![0; header.scalable_width_count()];
+        let mut metrics = vec![VerticalMetrics::default(); header.vertical_metrics_count()];
+        let loaded = load(&egbf, &mut glyphs, &mut widths, &mut metrics).unwrap();
+
+        assert_eq!(&loaded, font);
+        assert_eq!(OwnedBdfFont::from_egbf(&egbf).unwrap().as_font(), *font);
+    }
+
+    #[test]
+    fn load_font() {
+        round_trip(&FONT);
+        round_trip(&VERTICAL);
+
+        // Glyphs which are inserted into an owned font use the layout of the font.
+        let mut owned = OwnedBdfFont {
+            bitmap_layout: BitmapLayout::new(BitOrder::LsbFirst, RowLayout::ByteAligned),
+            ..OwnedBdfFont::default()
+        };
+        for c in ['A', 'B'] {
+            let glyph = FONT.get_glyph(c);
+            let bitmap = [0xF8, 0x88, 0xF8];
+            owned.insert_glyph(c, glyph.bounding_box, glyph.device_width, &bitmap);
+        }
+        round_trip(&owned.as_font());
+    }
+
+    #[test]
+    fn data_out_of_range() {
+        let mut owned = FONT.subset("AB");
+        owned.data.pop();
+
+        let mut egbf = Vec::new();
+        owned.to_egbf(&mut egbf).unwrap();
+
+        assert_eq!(
+            OwnedBdfFont::from_egbf(&egbf),
+            Err(FontError::DataOutOfRange('B'))
+        );
+    }
+
+    #[test]
+    fn invalid_data() {
+        let mut egbf = Vec::new();
+        FONT.to_owned().to_egbf(&mut egbf).unwrap();
+
+        let mut glyphs = [BdfGlyph::default(); 30];
+        let mut widths = [0; 30];
+        assert_eq!(
+            load(&egbf, &mut glyphs[..10], &mut widths, &mut []),
+            Err(FontError::BufferTooSmall)
+        );
+        assert_eq!(
+            load(&egbf, &mut glyphs, &mut [], &mut []),
+            Err(FontError::BufferTooSmall)
+        );
+        assert_eq!(
+            load(&egbf[..egbf.len() - 1], &mut glyphs, &mut widths, &mut []),
+            Err(FontError::UnexpectedEnd)
+        );
+
+        let mut corrupted = egbf.clone();
+        *corrupted.last_mut().unwrap() ^= 0x01;
+        assert_eq!(
+            load(&corrupted, &mut glyphs, &mut widths, &mut []),
+            Err(FontError::ChecksumMismatch)
+        );
+
+        let mut corrupted = egbf.clone();
+        corrupted[0] = b'B';
+        assert_eq!(Header::parse(&corrupted), Err(FontError::InvalidMagic));
+
+        let mut corrupted = egbf.clone();
+        corrupted[4] = 0;
+        assert_eq!(
+            Header::parse(&corrupted),
+            Err(FontError::UnsupportedVersion(0))
+        );
+
+        // Swap the first two glyphs and update the checksum.
+        let (first, second) = egbf[HEADER_LEN..].split_at_mut(GLYPH_LEN);
+        first.swap_with_slice(&mut second[..GLYPH_LEN]);
+        let checksum = crc32(&egbf[CHECKSUM_START..]);
+        egbf[CHECKSUM_START - 4..CHECKSUM_START].copy_from_slice(&checksum.to_le_bytes());
+        assert_eq!(
+            load(&egbf, &mut glyphs, &mut widths, &mut []),
+            Err(FontError::InvalidData)
+        );
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn too_large() {
+        let mut owned = FONT.subset("AB");
+        owned.glyphs[1].start_index = u32::MAX as usize + 1;
+
+        let mut egbf = vec![1, 2, 3];
+        assert_eq!(owned.to_egbf(&mut egbf), Err(FontError::TooLarge));
+        assert_eq!(egbf, [1, 2, 3]);
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(&[]), 0);
+    }
+}
@@ -0,0 +1,192 @@
+//! BDF export.
+
+use core::fmt::{self, Write};
+
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+
+use crate::{BdfFont, FontExtents};
+
+/// Resolution used in the `SIZE` statement.
+///
+/// A resolution of 72 DPI makes the point size equal to the pixel size.
+const RESOLUTION: u32 = 72;
+
+/// Writes a BDF bounding box.
+fn write_bounding_box<W: Write>(output: &mut W, bounding_box: &Rectangle) -> fmt::Result {
+    let Size { width, height } = bounding_box.size;
+    let offset_y = -(bounding_box.top_left.y + height as i32 - 1);
+
+    write!(
+        output,
+        "{} {} {} {}",
+        width, height, bounding_box.top_left.x, offset_y
+    )
+}
+
+impl BdfFont<'_> {
+    /// Writes the font in the BDF format.
+    ///
+    /// The output contains the glyphs, the pixel size, ascent and descent of the font, which
+    /// makes it possible to inspect fonts which were modified at runtime in standard font tools.
+    /// `name` is used as the font name.
+    pub fn write_bdf<W: Write>(&self, name: &str, output: &mut W) -> fmt::Result {
+        // Fonts which were modified at runtime might contain outdated extents.
+        let font_bounding_box = FontExtents::from_glyphs(self.glyphs).max_bounding_box;
+
+        writeln!(output, "STARTFONT 2.1")?;
+        writeln!(output, "FONT {}", name)?;
+        writeln!(
+            output,
+            "SIZE {} {} {}",
+            self.pixel_size, RESOLUTION, RESOLUTION
+        )?;
+        write!(output, "FONTBOUNDINGBOX ")?;
+        write_bounding_box(output, &font_bounding_box)?;
+        writeln!(output)?;
+        if !self.vertical_metrics.is_empty() {
+            writeln!(output, "METRICSSET 2")?;
+        }
+        writeln!(output, "STARTPROPERTIES 3")?;
+        writeln!(output, "PIXEL_SIZE {}", self.pixel_size)?;
+        writeln!(output, "FONT_ASCENT {}", self.font_ascent)?;
+        writeln!(output, "FONT_DESCENT {}", self.font_descent)?;
+        writeln!(output, "ENDPROPERTIES")?;
+        writeln!(output, "CHARS {}", self.glyphs.len())?;
+
+        for (index, glyph) in self.glyphs.iter().enumerate() {
+            let code = u32::from(glyph.character);
+            // The scalable width is specified in 1/1000 of the point size, which is equal to the
+            // pixel size at the used resolution.
+            let width = self
+                .scalable_widths
+                .get(index)
+                .copied()
+                .unwrap_or(glyph.device_width * 1000);
+            let scalable_width = width / self.pixel_size.max(1) as i32;
+
+            writeln!(output, "STARTCHAR U+{:04X}", code)?;
+            writeln!(output, "ENCODING {}", code)?;
+            writeln!(output, "SWIDTH {} 0", scalable_width)?;
+            writeln!(output, "DWIDTH {} 0", glyph.device_width)?;
+            if let Some(metrics) = self.vertical_metrics.get(index) {
+                writeln!(output, "DWIDTH1 0 {}", -metrics.advance)?;
+                writeln!(
+                    output,
+                    "VVECTOR {} {}",
+                    metrics.origin.x,
+                    1 - metrics.origin.y
+                )?;
+            }
+            write!(output, "BBX ")?;
+            write_bounding_box(output, &glyph.bounding_box)?;
+            writeln!(output)?;
+            writeln!(output, "BITMAP")?;
+
+            let size = glyph.bounding_box.size;
+            for y in 0..size.height as i32 {
+                // Rows are padded to whole bytes.
+                for byte_x in (0..size.width as i32).step_by(8) {
+                    let byte = (0..8)
+                        .filter(|bit| {
+                            let x = byte_x + bit;
+                            x < size.width as i32
+                                && glyph.pixel_with_layout(
+                                    self.data,
+                                    self.bitmap_layout,
+                                    Point::new(x, y),
+                                )
+                        })
+                        .fold(0u8, |byte, bit| byte | 0x80 >> bit);
+
+                    write!(output, "{:02X}", byte)?;
+                }
+                writeln!(output)?;
+            }
+
+            writeln!(output, "ENDCHAR")?;
+        }
+
+        writeln!(output, "ENDFONT")
+    }
+
+    /// Returns the font in the BDF format.
+    ///
+    /// See [`write_bdf`](Self::write_bdf) for more information.
+    #[cfg(feature = "alloc")]
+    pub fn to_bdf(&self, name: &str) -> alloc::string::String {
+        let mut output = alloc::string::String::new();
+        // Writing into a string can't fail.
+        let _ = self.write_bdf(name, &mut output);
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::include_bdf;
+    use alloc::string::String;
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn round_trip() {
+        let mut bdf = String::new();
+        FONT.write_bdf("test", &mut bdf).unwrap();
+
+        let parsed = bdf_parser::BdfFont::parse(bdf.as_bytes()).unwrap();
+        assert_eq!(parsed.metadata.name, "test");
+        assert_eq!(parsed.glyphs.iter().count(), FONT.glyphs.len());
+
+        for glyph in FONT.glyphs {
+            let parsed_glyph = parsed.glyphs.get(glyph.character).unwrap();
+            assert_eq!(parsed_glyph.device_width.x, glyph.device_width);
+
+            let size = glyph.bounding_box.size;
+            for point in Rectangle::new(Point::zero(), size).points() {
+                assert_eq!(
+                    parsed_glyph.pixel(point.x as usize, point.y as usize),
+                    glyph.pixel(FONT.data, point),
+                    "{:?} {:?}",
+                    glyph.character,
+                    point
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn vertical_metrics() {
+        const VERTICAL: BdfFont = include_bdf!("examples/vertical.bdf");
+
+        let mut bdf = String::new();
+        VERTICAL.write_bdf("test", &mut bdf).unwrap();
+
+        let parsed = bdf_parser::BdfFont::parse(bdf.as_bytes()).unwrap();
+        assert_eq!(parsed.metadata.metrics_set, 2);
+
+        let glyph = parsed.glyphs.get('A').unwrap();
+        assert_eq!(
+            glyph.device_width_vertical,
+            Some(bdf_parser::Coord::new(0, -5))
+        );
+        assert_eq!(glyph.vertical_vector, Some(bdf_parser::Coord::new(2, 3)));
+
+        let mut bdf = String::new();
+        FONT.write_bdf("test", &mut bdf).unwrap();
+        assert!(!bdf.contains("METRICSSET"));
+    }
+
+    #[test]
+    fn glyph_statements() {
+        let mut bdf = String::new();
+        FONT.write_bdf("test", &mut bdf).unwrap();
+
+        assert!(bdf.contains(
+            "STARTCHAR U+0049\nENCODING 73\nSWIDTH 600 0\nDWIDTH 6 0\nBBX 6 10 0 -2\nBITMAP\n\
+            00\n70\n20\n20\n20\n20\n20\n70\n00\n00\nENDCHAR\n"
+        ));
+        assert!(bdf.contains("FONTBOUNDINGBOX 6 10 0 -2\n"));
+    }
+}
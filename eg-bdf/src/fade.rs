@@ -0,0 +1,158 @@
+//! Dithered fade effect.
+//!
+//! Monochrome displays can't blend the text with the background. A fade effect can instead be
+//! achieved by only drawing a part of the text pixels, which are selected using an ordered dither
+//! pattern. Increasing the intensity from `0` to `255` over multiple frames fades the text in.
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::text::BdfTextStyle;
+
+/// 4x4 Bayer matrix.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Returns `true` if a pixel is visible at the given intensity.
+fn is_visible(point: Point, intensity: u8) -> bool {
+    let threshold = BAYER[point.y.rem_euclid(4) as usize][point.x.rem_euclid(4) as usize];
+
+    intensity > threshold * 16 + 8
+}
+
+/// Draw target wrapper which dithers all pixels in the text color.
+///
+/// Dithered out pixels are replaced by the background color or discarded if no background color
+/// is set.
+struct DitherFade<'a, D: DrawTarget> {
+    parent: &'a mut D,
+    text_color: D::Color,
+    background_color: Option<D::Color>,
+    intensity: u8,
+}
+
+impl<D: DrawTarget> Dimensions for DitherFade<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.parent.bounding_box()
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for DitherFade<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let text_color = self.text_color;
+        let background_color = self.background_color;
+        let intensity = self.intensity;
+
+        self.parent
+            .draw_iter(pixels.into_iter().filter_map(|Pixel(point, color)| {
+                if color != text_color || is_visible(point, intensity) {
+                    Some(Pixel(point, color))
+                } else {
+                    background_color.map(|color| Pixel(point, color))
+                }
+            }))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        // Only areas in the text color need to be dithered.
+        if color != self.text_color {
+            return self.parent.fill_solid(area, color);
+        }
+
+        self.draw_iter(area.points().map(|point| Pixel(point, color)))
+    }
+}
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Draws a string with a dithered fade effect.
+    ///
+    /// Pixels in the text color, including decorations, are only drawn if the `intensity` exceeds
+    /// the threshold of an ordered 4x4 dither pattern at their position. An intensity of `0`
+    /// hides the text and `255` draws it completely. If a background color is set, hidden pixels
+    /// are drawn in the background color, which makes it possible to fade out text without
+    /// clearing it first.
+    ///
+    /// The dither pattern is aligned to the display coordinates, which keeps it stable if the
+    /// text is moved. Returns the position after the last character.
+    pub fn draw_string_faded<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        intensity: u8,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let mut fade = DitherFade {
+            parent: target,
+            text_color: self.text_color(),
+            background_color: self.background_color(),
+            intensity,
+        };
+
+        self.draw_string(text, position, baseline, &mut fade)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    fn draw(style: BdfTextStyle<BinaryColor>, intensity: u8) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        style
+            .draw_string_faded("I", Point::zero(), Baseline::Top, intensity, &mut display)
+            .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn intensities() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("I", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+        draw(style, 255).assert_eq(&expected);
+
+        assert_eq!(draw(style, 0), MockDisplay::new());
+
+        // Half intensity results in a checkerboard pattern.
+        draw(style, 128).assert_pattern(&[
+            "     ", " # # ", "  #  ", "     ", "  #  ", "     ", "  #  ", " # # ",
+        ]);
+    }
+
+    #[test]
+    fn fade_out_with_background() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        let mut expected = MockDisplay::new();
+        BdfTextStyle::new(&FONT, BinaryColor::Off)
+            .with_bg_color(BinaryColor::Off)
+            .draw_string("I", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+        draw(style, 0).assert_eq(&expected);
+
+        let display = draw(style.underline(), 128);
+        assert_eq!(display.get_pixel(Point::new(0, 8)), Some(BinaryColor::On));
+        assert_eq!(display.get_pixel(Point::new(1, 8)), Some(BinaryColor::Off));
+    }
+}
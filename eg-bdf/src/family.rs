@@ -0,0 +1,194 @@
+//! Font families.
+//!
+//! A [`FontFamily`] groups the regular, bold, italic and bold italic faces of a font. Text styles
+//! which use a family select the face based on their [`FontStyle`], which makes it possible to
+//! request a bold or italic variant of a style, e.g. for segments of [`Spans`](crate::spans::Spans).
+//! Missing faces are replaced by a synthesized version of the closest available face.
+
+use core::ops::{BitOr, BitOrAssign};
+
+use crate::BdfFont;
+
+/// Font style flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct FontStyle(u8);
+
+impl FontStyle {
+    /// Regular style.
+    pub const REGULAR: Self = Self(0);
+
+    /// Bold weight.
+    pub const BOLD: Self = Self(1 << 0);
+
+    /// Italic slant.
+    pub const ITALIC: Self = Self(1 << 1);
+
+    /// Bold weight and italic slant.
+    pub const BOLD_ITALIC: Self = Self(Self::BOLD.0 | Self::ITALIC.0);
+
+    /// Returns `true` if all flags in `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Sets all flags in `other`.
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Clears all flags in `other`.
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /// Returns the flags which are set in `self` but not in `other`.
+    pub(crate) const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+}
+
+impl BitOr for FontStyle {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for FontStyle {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs)
+    }
+}
+
+/// Font family.
+///
+/// The regular face is required, all other faces are optional.
+///
+/// ```
+/// use eg_bdf::{family::FontFamily, include_bdf, BdfFont};
+///
+/// const REGULAR: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z');
+/// const BOLD: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z');
+///
+/// const FAMILY: FontFamily = FontFamily::new(&REGULAR).with_bold(&BOLD);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct FontFamily<'a> {
+    regular: &'a BdfFont<'a>,
+    bold: Option<&'a BdfFont<'a>>,
+    italic: Option<&'a BdfFont<'a>>,
+    bold_italic: Option<&'a BdfFont<'a>>,
+}
+
+impl<'a> FontFamily<'a> {
+    /// Creates a new font family with a regular face.
+    pub const fn new(regular: &'a BdfFont<'a>) -> Self {
+        Self {
+            regular,
+            bold: None,
+            italic: None,
+            bold_italic: None,
+        }
+    }
+
+    /// Sets the bold face.
+    pub const fn with_bold(mut self, bold: &'a BdfFont<'a>) -> Self {
+        self.bold = Some(bold);
+        self
+    }
+
+    /// Sets the italic face.
+    pub const fn with_italic(mut self, italic: &'a BdfFont<'a>) -> Self {
+        self.italic = Some(italic);
+        self
+    }
+
+    /// Sets the bold italic face.
+    pub const fn with_bold_italic(mut self, bold_italic: &'a BdfFont<'a>) -> Self {
+        self.bold_italic = Some(bold_italic);
+        self
+    }
+
+    /// Returns the regular face.
+    pub const fn regular(&self) -> &'a BdfFont<'a> {
+        self.regular
+    }
+
+    /// Returns the face for a style and the flags which need to be synthesized.
+    ///
+    /// If the family doesn't contain a face for `style` the closest face is returned. For bold
+    /// italic text the bold face is preferred over the italic face, because a synthesized slant
+    /// is less noticeable than a synthesized weight.
+    pub const fn face(&self, style: FontStyle) -> (&'a BdfFont<'a>, FontStyle) {
+        let candidates = [
+            (self.bold_italic, FontStyle::BOLD_ITALIC),
+            (self.bold, FontStyle::BOLD),
+            (self.italic, FontStyle::ITALIC),
+        ];
+
+        let mut i = 0;
+        while i < candidates.len() {
+            if let (Some(font), face_style) = candidates[i] {
+                if style.contains(face_style) {
+                    return (font, style.difference(face_style));
+                }
+            }
+            i += 1;
+        }
+
+        (self.regular, style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::include_bdf;
+
+    const REGULAR: BdfFont = include_bdf!("examples/6x10.bdf", 'A');
+    const BOLD: BdfFont = include_bdf!("examples/6x10.bdf", 'B');
+    const ITALIC: BdfFont = include_bdf!("examples/6x10.bdf", 'I');
+
+    #[test]
+    fn face_selection() {
+        let family = FontFamily::new(&REGULAR).with_bold(&BOLD);
+
+        assert_eq!(
+            family.face(FontStyle::REGULAR),
+            (&REGULAR, FontStyle::REGULAR)
+        );
+        assert_eq!(family.face(FontStyle::BOLD), (&BOLD, FontStyle::REGULAR));
+        assert_eq!(
+            family.face(FontStyle::ITALIC),
+            (&REGULAR, FontStyle::ITALIC)
+        );
+        assert_eq!(
+            family.face(FontStyle::BOLD_ITALIC),
+            (&BOLD, FontStyle::ITALIC)
+        );
+
+        let family = family.with_italic(&ITALIC);
+        assert_eq!(
+            family.face(FontStyle::ITALIC),
+            (&ITALIC, FontStyle::REGULAR)
+        );
+        assert_eq!(
+            family.face(FontStyle::BOLD_ITALIC),
+            (&BOLD, FontStyle::ITALIC)
+        );
+    }
+
+    #[test]
+    fn flags() {
+        let mut style = FontStyle::BOLD | FontStyle::ITALIC;
+        assert_eq!(style, FontStyle::BOLD_ITALIC);
+        assert!(style.contains(FontStyle::BOLD));
+
+        style.remove(FontStyle::BOLD);
+        assert_eq!(style, FontStyle::ITALIC);
+        assert!(!style.contains(FontStyle::BOLD));
+    }
+}
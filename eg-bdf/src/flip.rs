@@ -0,0 +1,255 @@
+//! Pixel flip minimization.
+//!
+//! Flip-dot panels and e-paper displays benefit from changing as few pixels as possible. The
+//! functions in this module only draw pixels whose state actually changes, which reduces
+//! mechanical wear and ghosting.
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor, prelude::*, primitives::Rectangle, text::Baseline,
+};
+
+use crate::{
+    blit::HorizontalBuffer,
+    text::{BdfTextStyle, StringColors},
+};
+
+/// Draw target wrapper which only draws changed pixels.
+///
+/// The previous frame content is kept in a shadow framebuffer. Pixels which already have the
+/// drawn color in the shadow framebuffer are discarded, all other pixels are drawn to the wrapped
+/// draw target and stored in the shadow framebuffer. Pixels outside the shadow framebuffer are
+/// always drawn.
+#[derive(Debug)]
+pub struct FlipFilter<'a, 'b, D> {
+    parent: &'a mut D,
+    shadow: &'a mut HorizontalBuffer<'b>,
+}
+
+impl<'a, 'b, D> FlipFilter<'a, 'b, D>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    /// Creates a new flip filter.
+    ///
+    /// The shadow framebuffer needs to contain the current content of the display.
+    pub fn new(parent: &'a mut D, shadow: &'a mut HorizontalBuffer<'b>) -> Self {
+        Self { parent, shadow }
+    }
+}
+
+impl<D: DrawTarget<Color = BinaryColor>> Dimensions for FlipFilter<'_, '_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.parent.bounding_box()
+    }
+}
+
+impl<D: DrawTarget<Color = BinaryColor>> DrawTarget for FlipFilter<'_, '_, D> {
+    type Color = BinaryColor;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let shadow = &mut *self.shadow;
+
+        self.parent
+            .draw_iter(pixels.into_iter().filter(|Pixel(point, color)| {
+                if shadow.pixel(*point) == Some(*color) {
+                    return false;
+                }
+
+                Pixel(*point, *color).draw(shadow).ok();
+                true
+            }))
+    }
+}
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Draws `text` over the previously drawn string `previous` and only draws changed pixels.
+    ///
+    /// The pixels of both strings, including their decorations and background padding, are
+    /// compared inside the area covered by both strings. Only pixels which differ are drawn. If
+    /// the style has no background color, pixels of `previous` which aren't part of `text` can't
+    /// be erased and only the pixels which are newly set by `text` are drawn.
+    ///
+    /// Returns the position after the last character, using the same baseline as `position`.
+    pub fn draw_string_flips<D>(
+        &self,
+        previous: &str,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let alphabetic = self.alphabetic_position(position, baseline);
+        let next = position + Point::new(self.text_advance(text), 0);
+
+        let area = union(
+            self.flip_area(previous, alphabetic),
+            self.flip_area(text, alphabetic),
+        );
+        let area = match area {
+            Some(area) => area.intersection(&target.bounding_box()),
+            None => return Ok(next),
+        };
+
+        if area.is_zero_sized() {
+            return Ok(next);
+        }
+
+        let colors = |text| {
+            let text_color = Some(self.text_color());
+            let background_color = self.background_color();
+
+            area.points()
+                .zip(StringColors::with_values(
+                    self,
+                    text,
+                    alphabetic,
+                    area,
+                    text_color,
+                    background_color,
+                ))
+                .map(move |(point, color)| {
+                    // Decorations are drawn on top of the glyphs.
+                    self.decoration_at(text, alphabetic, point).or(color)
+                })
+        };
+
+        target.draw_iter(
+            area.points()
+                .zip(colors(previous).zip(colors(text)))
+                .filter_map(|(point, (old, new))| match new {
+                    Some(new) if old != Some(new) => Some(Pixel(point, new)),
+                    _ => None,
+                }),
+        )?;
+
+        Ok(next)
+    }
+
+    /// Returns the area which is changed by drawing a string.
+    ///
+    /// `position` is the start of the string on the alphabetic baseline.
+    fn flip_area(&self, text: &str, position: Point) -> Option<Rectangle> {
+        let bounding_box = self
+            .measure_string_bounds(text, position, Baseline::Alphabetic)
+            .bounding_box;
+
+        // Underlines can extend below the bounding box of fonts without a descent.
+        union(Some(bounding_box), self.decoration_area(text, position))
+    }
+}
+
+/// Returns the smallest rectangle which contains both rectangles.
+fn union(a: Option<Rectangle>, b: Option<Rectangle>) -> Option<Rectangle> {
+    let a = a.filter(|a| !a.is_zero_sized());
+    let b = b.filter(|b| !b.is_zero_sized());
+
+    match (a, b) {
+        (Some(a), Some(b)) => Some(Rectangle::with_corners(
+            a.top_left.component_min(b.top_left),
+            a.bottom_right()?.component_max(b.bottom_right()?),
+        )),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, text::renderer::TextRenderer};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn flip_changed_pixels() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_string_flips("O", "Q", Point::zero(), Baseline::Top, &mut display)
+            .unwrap();
+        assert_eq!(next, Point::new(6, 0));
+
+        display.assert_pattern(&[
+            "      ", "      ", "      ", "      ", "      ", "      ", "  #   ", "      ",
+            "    # ",
+        ]);
+    }
+
+    #[test]
+    fn flip_decorations_and_padding() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On)
+            .with_bg_color(BinaryColor::Off)
+            .with_background_padding(1)
+            .underline()
+            .strikethrough();
+
+        let mut expected = [0xAA; 24];
+        let mut buffer = HorizontalBuffer::new(&mut expected, Size::new(16, 12));
+        style
+            .draw_string("OB", Point::new(1, 1), Baseline::Top, &mut buffer)
+            .unwrap();
+        style
+            .draw_string("QI", Point::new(1, 1), Baseline::Top, &mut buffer)
+            .unwrap();
+
+        let mut flipped = [0xAA; 24];
+        let mut buffer = HorizontalBuffer::new(&mut flipped, Size::new(16, 12));
+        style
+            .draw_string("OB", Point::new(1, 1), Baseline::Top, &mut buffer)
+            .unwrap();
+        style
+            .draw_string_flips("OB", "QI", Point::new(1, 1), Baseline::Top, &mut buffer)
+            .unwrap();
+
+        assert_eq!(flipped, expected);
+    }
+
+    #[test]
+    fn flip_without_background() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).underline();
+
+        let mut display = MockDisplay::new();
+        style
+            .draw_string_flips("I", "II", Point::zero(), Baseline::Top, &mut display)
+            .unwrap();
+
+        display.assert_pattern(&[
+            "            ",
+            "       ###  ",
+            "        #   ",
+            "        #   ",
+            "        #   ",
+            "        #   ",
+            "        #   ",
+            "       ###  ",
+            "      ######",
+        ]);
+    }
+
+    #[test]
+    fn shadow_framebuffer() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut data = [0; 10];
+        let mut shadow = HorizontalBuffer::new(&mut data, Size::new(8, 10));
+        style
+            .draw_string("I", Point::zero(), Baseline::Top, &mut shadow)
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        let mut filter = FlipFilter::new(&mut display, &mut shadow);
+        style
+            .draw_string("T", Point::zero(), Baseline::Top, &mut filter)
+            .unwrap();
+
+        display.assert_pattern(&["      ", "#   # ", "      "]);
+    }
+}
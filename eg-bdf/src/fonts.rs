@@ -0,0 +1,93 @@
+//! Bundled fonts.
+//!
+//! This module contains a small collection of public domain fonts from the X11 `misc-fixed`
+//! family, which can be used without converting BDF files first. The text fonts contain the
+//! printable ASCII and Latin-1 characters, other characters are drawn using the replacement
+//! character `U+FFFD`.
+
+use crate::{include_bdf, BdfFont};
+
+/// 5x8 pixel font.
+pub const FONT_5X8: BdfFont =
+    include_bdf!("fonts/5x8.bdf", ' '..='~' | '\u{A0}'..='\u{FF}' | '\u{FFFD}');
+
+/// 6x10 pixel font.
+pub const FONT_6X10: BdfFont =
+    include_bdf!("fonts/6x10.bdf", ' '..='~' | '\u{A0}'..='\u{FF}' | '\u{FFFD}');
+
+/// 6x13 pixel font.
+pub const FONT_6X13: BdfFont =
+    include_bdf!("fonts/6x13.bdf", ' '..='~' | '\u{A0}'..='\u{FF}' | '\u{FFFD}');
+
+/// 9x15 pixel font.
+pub const FONT_9X15: BdfFont =
+    include_bdf!("fonts/9x15.bdf", ' '..='~' | '\u{A0}'..='\u{FF}' | '\u{FFFD}');
+
+/// 10x20 pixel font.
+pub const FONT_10X20: BdfFont =
+    include_bdf!("fonts/10x20.bdf", ' '..='~' | '\u{A0}'..='\u{FF}' | '\u{FFFD}');
+
+/// 6x13 pixel symbol font.
+///
+/// Contains the following symbols:
+///
+/// | Characters                             | Symbols                |
+/// |----------------------------------------|------------------------|
+/// | `U+2190`-`U+2195`                      | Arrows                 |
+/// | `U+25B2`, `U+25BA`, `U+25BC`, `U+25C4` | Triangles              |
+/// | `U+25CB`, `U+25CF`                     | Circles                |
+/// | `U+2588`, `U+2591`-`U+2593`            | Blocks and shades      |
+/// | `U+2600`, `U+2601`                     | Sun and cloud          |
+/// | `U+2610`, `U+2611`                     | Ballot boxes           |
+/// | `U+266B`                               | Musical note           |
+/// | `U+2713`, `U+2717`                     | Check and ballot marks |
+pub const SYMBOLS_6X13: BdfFont = include_bdf!(
+    "fonts/6x13.bdf",
+    ' ' | '\u{2190}'..='\u{2195}'
+        | '\u{25B2}'
+        | '\u{25BA}'
+        | '\u{25BC}'
+        | '\u{25C4}'
+        | '\u{25CB}'
+        | '\u{25CF}'
+        | '\u{2588}'
+        | '\u{2591}'..='\u{2593}'
+        | '\u{2600}'
+        | '\u{2601}'
+        | '\u{2610}'
+        | '\u{2611}'
+        | '\u{2713}'
+        | '\u{2717}'
+        | '\u{266B}'
+        | '\u{FFFD}'
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics() {
+        let fonts = [
+            (FONT_5X8, 5, 7, 1),
+            (FONT_6X10, 6, 8, 2),
+            (FONT_6X13, 6, 11, 2),
+            (FONT_9X15, 9, 12, 3),
+            (FONT_10X20, 10, 16, 4),
+        ];
+
+        for (font, width, ascent, descent) in fonts.iter() {
+            assert_eq!(font.get_glyph('A').device_width, *width);
+            assert_eq!((font.font_ascent, font.font_descent), (*ascent, *descent));
+            assert_eq!(font.glyphs.len(), 95 + 96 + 1);
+            assert_eq!(font.get_glyph('\u{2190}').character, '\u{FFFD}');
+        }
+    }
+
+    #[test]
+    fn symbols() {
+        assert_eq!(SYMBOLS_6X13.glyphs.len(), 25);
+        assert_eq!(SYMBOLS_6X13.get_glyph('\u{2713}').character, '\u{2713}');
+        assert_eq!(SYMBOLS_6X13.get_glyph('A').character, '\u{FFFD}');
+    }
+}
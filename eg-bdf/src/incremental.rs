@@ -0,0 +1,266 @@
+//! Cooperative and incremental rendering.
+
+use embedded_graphics::{
+    prelude::*,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::text::BdfTextStyle;
+
+/// Budget for a single incremental drawing step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum Budget {
+    /// Maximum number of glyphs.
+    Glyphs(usize),
+    /// Maximum number of pixels, counted as the area of the glyph bounding boxes.
+    Pixels(u32),
+}
+
+/// State required to resume incremental drawing.
+///
+/// The token is only valid for the text and style which were used to create it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct ResumeToken {
+    offset: usize,
+    line_start: i32,
+    position: Point,
+}
+
+impl ResumeToken {
+    /// Returns the byte offset of the next character which will be drawn.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the position of the next character on the alphabetic baseline.
+    pub fn position(&self) -> Point {
+        self.position
+    }
+}
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Draws a string and calls a hook after each glyph.
+    ///
+    /// The hook is called with the character index of the glyph which was drawn and can be used
+    /// to yield to other tasks, feed a watchdog or service time critical peripherals while long
+    /// strings are drawn. Returns the position after the last character, using the same baseline
+    /// as `position`.
+    pub fn draw_string_with_hook<D, F>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+        mut hook: F,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        F: FnMut(usize),
+    {
+        let mut alphabetic = self.alphabetic_position(position, baseline);
+
+        for (index, c) in text.chars().enumerate() {
+            alphabetic = self.draw_string(
+                c.encode_utf8(&mut [0; 4]),
+                alphabetic,
+                Baseline::Alphabetic,
+                target,
+            )?;
+
+            hook(index);
+        }
+
+        Ok(Point::new(alphabetic.x, position.y))
+    }
+
+    /// Draws a part of a string within the given budget.
+    ///
+    /// Drawing starts at `position` if `resume` is `None` and otherwise continues at the position
+    /// stored in the token, in which case `position` and `baseline` are ignored. At least one
+    /// glyph is drawn per call, even if it exceeds the budget. Newlines start a new line and don't
+    /// count towards the budget.
+    ///
+    /// Returns a token to continue drawing in a later call or `None` if the whole string was
+    /// drawn.
+    pub fn draw_string_incremental<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        resume: Option<ResumeToken>,
+        budget: Budget,
+        target: &mut D,
+    ) -> Result<Option<ResumeToken>, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let mut token = resume.unwrap_or_else(|| {
+            let position = self.alphabetic_position(position, baseline);
+
+            ResumeToken {
+                offset: 0,
+                line_start: position.x,
+                position,
+            }
+        });
+
+        let mut drawn = false;
+        let mut glyphs = 0;
+        let mut pixels = 0u32;
+
+        for (offset, c) in text.get(token.offset..).unwrap_or_default().char_indices() {
+            if c == '\n' {
+                token.position = Point::new(
+                    token.line_start,
+                    token.position.y + self.line_height() as i32,
+                );
+                continue;
+            }
+
+            let size = self.font().get_glyph(c).bounding_box.size;
+            glyphs += 1;
+            pixels = pixels.saturating_add(size.width * size.height);
+
+            let exceeded = match budget {
+                Budget::Glyphs(max) => glyphs > max,
+                Budget::Pixels(max) => pixels > max,
+            };
+            if exceeded && drawn {
+                token.offset += offset;
+                return Ok(Some(token));
+            }
+
+            token.position = self.draw_string(
+                c.encode_utf8(&mut [0; 4]),
+                token.position,
+                Baseline::Alphabetic,
+                target,
+            )?;
+            drawn = true;
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn hook_is_called_per_glyph() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut calls = [None; 3];
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_string_with_hook("AB", Point::zero(), Baseline::Top, &mut display, |index| {
+                calls[index] = Some(index)
+            })
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        let expected_next = style
+            .draw_string("AB", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+
+        assert_eq!(display, expected);
+        assert_eq!(next, expected_next);
+        assert_eq!(calls, [Some(0), Some(1), None]);
+    }
+
+    fn draw_in_steps(budget: Budget) -> (MockDisplay<BinaryColor>, usize) {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        let mut resume = None;
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            resume = style
+                .draw_string_incremental(
+                    "AB\nIT",
+                    Point::zero(),
+                    Baseline::Top,
+                    resume,
+                    budget,
+                    &mut display,
+                )
+                .unwrap();
+
+            if resume.is_none() {
+                return (display, steps);
+            }
+        }
+    }
+
+    fn expected() -> MockDisplay<BinaryColor> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("AB", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+        style
+            .draw_string(
+                "IT",
+                Point::new(0, style.line_height() as i32),
+                Baseline::Top,
+                &mut expected,
+            )
+            .unwrap();
+
+        expected
+    }
+
+    #[test]
+    fn glyph_budget() {
+        let (display, steps) = draw_in_steps(Budget::Glyphs(3));
+
+        assert_eq!(display, expected());
+        assert_eq!(steps, 2);
+    }
+
+    #[test]
+    fn pixel_budget() {
+        // Each glyph covers 6x10 pixels.
+        let (display, steps) = draw_in_steps(Budget::Pixels(120));
+        assert_eq!(display, expected());
+        assert_eq!(steps, 2);
+
+        // At least one glyph is drawn per step.
+        let (display, steps) = draw_in_steps(Budget::Pixels(0));
+        assert_eq!(display, expected());
+        assert_eq!(steps, 4);
+    }
+
+    #[test]
+    fn resume_token() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        let token = style
+            .draw_string_incremental(
+                "AB\nIT",
+                Point::zero(),
+                Baseline::Top,
+                None,
+                Budget::Glyphs(2),
+                &mut display,
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(token.offset(), 3);
+        assert_eq!(
+            token.position(),
+            Point::new(0, 7 + style.line_height() as i32)
+        );
+    }
+}
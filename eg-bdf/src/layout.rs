@@ -0,0 +1,989 @@
+//! Layout helpers.
+
+use core::{fmt, marker::PhantomData, ops::Range};
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        Baseline,
+    },
+};
+
+use crate::{
+    text::{pen_to_pixels, BdfTextStyle, GlyphPositions, TextBounds},
+    wrap::LineBreaks,
+    BdfFont, BdfGlyph, CapacityError, FontError,
+};
+
+/// Vertical alignment of text inside a bounding rectangle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum VerticalAlignment {
+    /// Align the top of the first line with the top edge of the rectangle.
+    Top,
+    /// Center the text block vertically inside the rectangle.
+    Middle,
+    /// Align the bottom of the last line with the bottom edge of the rectangle.
+    Bottom,
+}
+
+/// Iterator over the advances of the characters in a string.
+///
+/// Each item is a tuple of the character, its horizontal advance and the horizontal offset of
+/// the character relative to the start of the string. The values are identical to the values
+/// used by the renderer. Newlines aren't treated specially, the iterator is intended to be used
+/// on single lines of text.
+///
+/// This iterator is created by [`BdfTextStyle::advances`].
+#[derive(Debug, Clone)]
+pub struct Advances<'a, 'b, C> {
+    positions: GlyphPositions<'b, 'a>,
+    color: PhantomData<C>,
+}
+
+impl<C: PixelColor> Iterator for Advances<'_, '_, C> {
+    type Item = (char, i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (c, _, x) = self.positions.next()?;
+
+        // The advance ends at the position of the next character.
+        Some((c, self.positions.x() - x, x))
+    }
+}
+
+/// Metrics of a single line of a measured text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct LineMetrics {
+    /// Byte range of the line inside the measured text.
+    pub range: Range<usize>,
+
+    /// Position the line is drawn at.
+    pub position: Point,
+
+    /// Bounds of the line.
+    pub bounds: TextBounds,
+}
+
+/// Iterator over the metrics of the lines of a text.
+///
+/// This iterator is created by [`BdfTextStyle::measure_lines`].
+#[derive(Debug, Clone)]
+pub struct MeasuredLines<'a, 'b, C> {
+    style: &'b BdfTextStyle<'b, C>,
+    text: &'a str,
+    line_breaks: LineBreaks<'a, 'b, C>,
+    position: Point,
+    baseline: Baseline,
+}
+
+impl<C: PixelColor> Iterator for MeasuredLines<'_, '_, C> {
+    type Item = LineMetrics;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let range = self.line_breaks.next()?;
+        let position = self.position;
+        self.position.y += self.style.line_height() as i32;
+
+        let bounds =
+            self.style
+                .measure_string_bounds(&self.text[range.clone()], position, self.baseline);
+
+        Some(LineMetrics {
+            range,
+            position,
+            bounds,
+        })
+    }
+}
+
+/// Metrics of a measured text.
+///
+/// Returned by [`BdfTextStyle::measure_text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct TextBlockMetrics {
+    /// Bounding box of all lines.
+    ///
+    /// The box covers the advance and ink boxes of all lines, which includes the ascent of the
+    /// first line and the descent of the last line.
+    pub bounding_box: Rectangle,
+
+    /// Number of lines.
+    pub lines: u32,
+
+    /// Position after the last character of the last line.
+    pub next_position: Point,
+}
+
+/// Metrics of a single character.
+///
+/// Returned by [`BdfTextStyle::measure_char`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct CharMetrics {
+    /// Horizontal advance of the character.
+    ///
+    /// This is the integer device width of the glyph, even if fractional advances are enabled.
+    pub advance: i32,
+
+    /// Bounding box of the glyph, relative to the origin of the character on the alphabetic
+    /// baseline.
+    pub ink_box: Rectangle,
+
+    /// `true` if the font doesn't contain the character and the replacement glyph is used.
+    pub is_fallback: bool,
+}
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Returns an iterator over the metrics of the lines of `text`.
+    ///
+    /// Lines are broken at newlines and, if `max_width` is set, wrapped using the same rules as
+    /// [`line_breaks`](Self::line_breaks). Consecutive lines are [`line_height`] pixels apart.
+    ///
+    /// [`line_height`]: TextRenderer::line_height
+    pub fn measure_lines<'a, 'b>(
+        &'b self,
+        text: &'a str,
+        position: Point,
+        baseline: Baseline,
+        max_width: Option<u32>,
+    ) -> MeasuredLines<'a, 'b, C> {
+        MeasuredLines {
+            style: self,
+            text,
+            line_breaks: self.line_breaks(text, max_width.unwrap_or(u32::MAX)),
+            position,
+            baseline,
+        }
+    }
+
+    /// Measures a text which can contain multiple lines.
+    ///
+    /// Unlike [`measure_string`](TextRenderer::measure_string), which only supports a single
+    /// line, this method handles newlines and optional wrapping to `max_width`. The metrics of the
+    /// individual lines are returned by [`measure_lines`](Self::measure_lines).
+    pub fn measure_text(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        max_width: Option<u32>,
+    ) -> TextBlockMetrics {
+        let mut lines = 0;
+        let mut next_position = position;
+        // Vertical extent of all lines and horizontal extent of all non empty lines.
+        let mut y_range = (i32::MAX, i32::MIN);
+        let mut x_range: Option<(i32, i32)> = None;
+
+        for line in self.measure_lines(text, position, baseline, max_width) {
+            let bounding_box = line.bounds.bounding_box;
+            let bottom = bounding_box.top_left.y + bounding_box.size.height as i32 - 1;
+            y_range = (
+                y_range.0.min(bounding_box.top_left.y),
+                y_range.1.max(bottom),
+            );
+
+            if let Some(bottom_right) = bounding_box.bottom_right() {
+                let (left, right) = x_range.unwrap_or((i32::MAX, i32::MIN));
+                x_range = Some((left.min(bounding_box.top_left.x), right.max(bottom_right.x)));
+            }
+
+            lines += 1;
+            next_position = line.bounds.next_position;
+        }
+
+        // The line iterator always returns at least one line.
+        let (top, bottom) = y_range;
+        let bounding_box = match x_range {
+            Some((left, right)) => {
+                Rectangle::with_corners(Point::new(left, top), Point::new(right, bottom))
+            }
+            None => Rectangle::new(
+                Point::new(position.x, top),
+                Size::new(0, (bottom - top + 1) as u32),
+            ),
+        };
+
+        TextBlockMetrics {
+            bounding_box,
+            lines,
+            next_position,
+        }
+    }
+
+    /// Returns an iterator over the advances of the characters in `text`.
+    pub fn advances<'a, 'b>(&'b self, text: &'a str) -> Advances<'a, 'b, C> {
+        Advances {
+            positions: self.glyph_positions(text, 0),
+            color: PhantomData,
+        }
+    }
+
+    /// Returns the metrics of a single character.
+    ///
+    /// If the font doesn't contain a glyph for `c` the metrics of the replacement glyph are
+    /// returned and [`is_fallback`](CharMetrics::is_fallback) is set, which can be used to check
+    /// if user input is covered by the font.
+    pub fn measure_char(&self, c: char) -> CharMetrics {
+        let glyph = self.font().get_glyph(c);
+
+        CharMetrics {
+            advance: glyph.device_width,
+            ink_box: glyph.bounding_box,
+            is_fallback: glyph.character != c,
+        }
+    }
+
+    /// Returns the width of a single line of text.
+    ///
+    /// This is the horizontal distance to the position after the last character, which is also
+    /// returned by [`measure_string`](TextRenderer::measure_string). Only the advances are summed
+    /// up, which makes this method cheaper than measuring the string if no bounding box is
+    /// required, e.g. while wrapping or centering text. Negative widths are returned as `0`.
+    pub fn string_advance(&self, text: &str) -> u32 {
+        self.text_advance(text).max(0) as u32
+    }
+
+    /// Returns the height of a block of `lines` lines of text.
+    ///
+    /// The first line starts at the font ascent and the last line ends at the font descent,
+    /// all lines in between use the line height of this style.
+    pub fn block_height(&self, lines: u32) -> u32 {
+        if lines == 0 {
+            return 0;
+        }
+
+        let font = self.font();
+        let inner = (lines - 1) as i32 * self.line_height() as i32;
+
+        (font.font_ascent as i32 + font.font_descent as i32 + inner).max(0) as u32
+    }
+
+    /// Returns the position of the first baseline of `text` aligned inside `bounds`.
+    ///
+    /// The returned point is intended to be used with [`Baseline::Alphabetic`]. Embedded
+    /// newlines are taken into account to determine the height of the text block.
+    ///
+    /// [`Baseline::Alphabetic`]: embedded_graphics::text::Baseline::Alphabetic
+    pub fn aligned_position(
+        &self,
+        text: &str,
+        bounds: &Rectangle,
+        alignment: VerticalAlignment,
+    ) -> Point {
+        let lines = text.split('\n').count() as u32;
+        self.aligned_lines_position(lines, bounds, alignment)
+    }
+
+    /// Returns the position of the first baseline of a block of `lines` lines aligned inside
+    /// `bounds`.
+    ///
+    /// The returned point is intended to be used with [`Baseline::Alphabetic`].
+    ///
+    /// [`Baseline::Alphabetic`]: embedded_graphics::text::Baseline::Alphabetic
+    pub fn aligned_lines_position(
+        &self,
+        lines: u32,
+        bounds: &Rectangle,
+        alignment: VerticalAlignment,
+    ) -> Point {
+        let block_height = self.block_height(lines) as i32;
+        let free_space = bounds.size.height as i32 - block_height;
+
+        let top = match alignment {
+            VerticalAlignment::Top => 0,
+            VerticalAlignment::Middle => free_space / 2,
+            VerticalAlignment::Bottom => free_space,
+        };
+
+        // The baseline row is part of the ascent.
+        let ascent = self.font().font_ascent as i32 - 1;
+
+        bounds.top_left + Point::new(0, top + ascent)
+    }
+
+    /// Returns the index of the character at `point`.
+    ///
+    /// `origin` is the position the text is drawn at using [`Baseline::Alphabetic`]. The
+    /// returned value is a character index, not a byte index. Newline characters are counted as
+    /// characters, but are never returned because they don't occupy any space. `None` is
+    /// returned if `point` isn't inside the advance box of any character.
+    ///
+    /// [`Baseline::Alphabetic`]: embedded_graphics::text::Baseline::Alphabetic
+    pub fn char_index_at(&self, text: &str, origin: Point, point: Point) -> Option<usize> {
+        let ascent = self.font().font_ascent as i32 - 1;
+        let descent = self.font().font_descent as i32;
+        let line_height = self.line_height() as i32;
+
+        let mut line_start = 0;
+
+        for (line_number, line) in text.split('\n').enumerate() {
+            let baseline = origin.y + line_number as i32 * line_height;
+
+            if (baseline - ascent..=baseline + descent).contains(&point.y) {
+                let x = point.x - origin.x;
+
+                return self
+                    .advances(line)
+                    .position(|(_, advance, offset)| (offset..offset + advance).contains(&x))
+                    .map(|index| line_start + index);
+            }
+
+            line_start += line.chars().count() + 1;
+        }
+
+        None
+    }
+
+    /// Returns the top left corner of a text cursor placed before the character at `index`.
+    ///
+    /// `origin` is the position the text is drawn at using [`Baseline::Alphabetic`] and `index`
+    /// is a character index. Indices past the end of the text place the cursor after the last
+    /// character. The height of the cursor is returned by [`caret_height`](Self::caret_height).
+    ///
+    /// [`Baseline::Alphabetic`]: embedded_graphics::text::Baseline::Alphabetic
+    pub fn caret_position(&self, text: &str, origin: Point, index: usize) -> Point {
+        let line_height = self.line_height() as i32;
+
+        // The pen is tracked like in `draw_string` to match the drawn glyph positions.
+        let mut y = origin.y;
+        let mut pen = 0;
+        for c in text.chars().take(index) {
+            if c == '\n' {
+                y += line_height;
+                pen = 0;
+            } else {
+                pen += self.pen_advance(c);
+            }
+        }
+
+        Point::new(
+            origin.x + pen_to_pixels(pen),
+            y - (self.font().font_ascent as i32 - 1),
+        )
+    }
+
+    /// Returns the height of a text cursor, which spans the font ascent and descent.
+    pub fn caret_height(&self) -> u32 {
+        self.font().font_ascent + self.font().font_descent
+    }
+}
+
+impl<'a, C: PixelColor> BdfTextStyle<'a, C> {
+    /// Returns this style with the largest font from `fonts` in which `text` fits into `size`.
+    ///
+    /// `fonts` must be sorted from the smallest to the largest font. The text is measured using
+    /// [`measure_text`](Self::measure_text) without wrapping, which means that newlines are taken
+    /// into account. Returns `None` if the text doesn't fit using any of the fonts.
+    pub fn fit_font(&self, text: &str, fonts: &'a [BdfFont<'a>], size: Size) -> Option<Self> {
+        fonts
+            .iter()
+            .rev()
+            .map(|font| self.with_font(font))
+            .find(|style| {
+                let bounding_box = style
+                    .measure_text(text, Point::zero(), Baseline::Alphabetic, None)
+                    .bounding_box;
+
+                bounding_box.size.width <= size.width && bounding_box.size.height <= size.height
+            })
+    }
+
+    /// Draws `text` using the largest font from `fonts` in which it fits into `bounds`.
+    ///
+    /// The font is selected by [`fit_font`](Self::fit_font). If the text doesn't fit using any of
+    /// the fonts, the smallest font is used. The text is aligned to the left edge of `bounds` and
+    /// vertically aligned according to `alignment`. Returns the style which was used to draw the
+    /// text.
+    pub fn draw_fitted<D>(
+        &self,
+        text: &str,
+        fonts: &'a [BdfFont<'a>],
+        bounds: &Rectangle,
+        alignment: VerticalAlignment,
+        target: &mut D,
+    ) -> Result<Self, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let style = self
+            .fit_font(text, fonts, bounds.size)
+            .or_else(|| fonts.first().map(|font| self.with_font(font)))
+            .unwrap_or(*self);
+
+        let position = style.aligned_position(text, bounds, alignment);
+        for line in style.measure_lines(text, position, Baseline::Alphabetic, None) {
+            style.draw_string(
+                &text[line.range],
+                line.position,
+                Baseline::Alphabetic,
+                target,
+            )?;
+        }
+
+        Ok(style)
+    }
+}
+
+/// Error returned if laying out text fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum LayoutError {
+    /// The font can't be used to lay out the text, e.g. because it doesn't contain a glyph.
+    Font(FontError),
+    /// The text contains more glyphs than the layout can store.
+    BufferTooSmall,
+}
+
+impl From<FontError> for LayoutError {
+    fn from(error: FontError) -> Self {
+        Self::Font(error)
+    }
+}
+
+impl From<CapacityError> for LayoutError {
+    fn from(_: CapacityError) -> Self {
+        Self::BufferTooSmall
+    }
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Font(error) => error.fmt(f),
+            Self::BufferTooSmall => f.write_str("buffer too small for laid out glyphs"),
+        }
+    }
+}
+
+/// Glyph in a [`LaidOutText`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct LaidOutGlyph<'a> {
+    /// Glyph.
+    pub glyph: &'a BdfGlyph,
+
+    /// Origin of the glyph on the alphabetic baseline.
+    pub position: Point,
+
+    /// Horizontal advance of the glyph.
+    pub advance: i32,
+
+    /// Character index of the glyph in the text.
+    ///
+    /// Newline characters are counted as characters, but don't have a glyph.
+    pub index: usize,
+}
+
+/// Glyph which is used to initialize unused entries.
+const EMPTY_GLYPH: &BdfGlyph = &BdfGlyph {
+    character: '\0',
+    bounding_box: Rectangle::zero(),
+    device_width: 0,
+    start_index: 0,
+};
+
+/// Text with precomputed glyph positions.
+///
+/// Glyph lookup and advance calculations are done once by [`BdfTextStyle::layout`]. The result
+/// stores up to `N` glyphs and can be drawn repeatedly, for example with changing colors for
+/// blinking text, or be used for hit testing. Newlines start a new line, which is
+/// [`line_height`](TextRenderer::line_height) pixels below the previous line.
+///
+/// The background is filled in the advance box of each glyph, which spans the advance of the
+/// glyph and the ascent and descent of the font. This can differ from the background drawn by
+/// [`draw_string`](TextRenderer::draw_string) for glyphs which extend outside the advance box.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LaidOutText<'a, C, const N: usize> {
+    style: BdfTextStyle<'a, C>,
+    glyphs: [LaidOutGlyph<'a>; N],
+    len: usize,
+}
+
+impl<'a, C: PixelColor, const N: usize> LaidOutText<'a, C, N> {
+    /// Returns the style which was used to lay out the text.
+    pub fn style(&self) -> &BdfTextStyle<'a, C> {
+        &self.style
+    }
+
+    /// Returns the laid out glyphs.
+    pub fn glyphs(&self) -> &[LaidOutGlyph<'a>] {
+        &self.glyphs[..self.len]
+    }
+
+    /// Returns the advance box of a glyph.
+    fn advance_box(&self, glyph: &LaidOutGlyph<'_>) -> Rectangle {
+        let ascent = self.style.font().font_ascent as i32 - 1;
+
+        Rectangle::new(
+            glyph.position - Point::new(0, ascent),
+            Size::new(glyph.advance.max(0) as u32, self.style.full_height()),
+        )
+    }
+
+    /// Returns the union of the advance boxes of all glyphs.
+    ///
+    /// Returns `None` if the text doesn't contain any glyphs with a positive advance.
+    pub fn bounding_box(&self) -> Option<Rectangle> {
+        self.glyphs()
+            .iter()
+            .map(|glyph| self.advance_box(glyph))
+            .filter_map(|area| Some((area.top_left, area.bottom_right()?)))
+            .reduce(|(min, max), (top_left, bottom_right)| {
+                (min.component_min(top_left), max.component_max(bottom_right))
+            })
+            .map(|(top_left, bottom_right)| Rectangle::with_corners(top_left, bottom_right))
+    }
+
+    /// Returns the character index of the glyph at `point`.
+    ///
+    /// Returns `None` if `point` isn't inside the advance box of any glyph. See
+    /// [`char_index_at`](BdfTextStyle::char_index_at) for more information.
+    pub fn char_index_at(&self, point: Point) -> Option<usize> {
+        self.glyphs()
+            .iter()
+            .find(|glyph| self.advance_box(glyph).contains(point))
+            .map(|glyph| glyph.index)
+    }
+
+    /// Draws the text with different colors.
+    ///
+    /// All other properties of the style, like decorations, are unchanged.
+    pub fn draw_with_colors<D>(
+        &self,
+        text_color: C,
+        background_color: Option<C>,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let mut style = self.style;
+        style.set_text_color(Some(text_color));
+
+        for glyph in self.glyphs() {
+            if let Some(color) = background_color {
+                target.fill_solid(&self.advance_box(glyph), color)?;
+            }
+
+            style.draw_glyph(glyph.glyph, glyph.position, target)?;
+            style.draw_decorations(target, glyph.advance, glyph.position)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: PixelColor, const N: usize> Drawable for LaidOutText<'_, C, N> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.draw_with_colors(
+            self.style.text_color(),
+            self.style.background_color(),
+            target,
+        )
+    }
+}
+
+impl<'a, C: PixelColor> BdfTextStyle<'a, C> {
+    /// Lays out text for repeated drawing and hit testing.
+    ///
+    /// Returns an error if the text contains more than `N` characters, not counting newlines.
+    pub fn layout<const N: usize>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+    ) -> Result<LaidOutText<'a, C, N>, CapacityError> {
+        let mut laid_out = LaidOutText {
+            style: *self,
+            glyphs: [LaidOutGlyph {
+                glyph: EMPTY_GLYPH,
+                position: Point::zero(),
+                advance: 0,
+                index: 0,
+            }; N],
+            len: 0,
+        };
+
+        let origin = self.alphabetic_position(position, baseline);
+        let line_height = self.line_height() as i32;
+        let mut index = 0;
+
+        for (line_number, line) in text.split('\n').enumerate() {
+            let y = origin.y + line_number as i32 * line_height;
+
+            let mut positions = self.glyph_positions(line, origin.x);
+            while let Some((_, glyph, x)) = positions.next() {
+                *laid_out.glyphs.get_mut(laid_out.len).ok_or(CapacityError)? = LaidOutGlyph {
+                    glyph,
+                    position: Point::new(x, y),
+                    advance: positions.x() - x,
+                    index,
+                };
+                laid_out.len += 1;
+                index += 1;
+            }
+
+            // Skip the newline.
+            index += 1;
+        }
+
+        Ok(laid_out)
+    }
+
+    /// Lays out text without falling back to the replacement character.
+    ///
+    /// Works like [`layout`](Self::layout), but returns [`FontError::UnsupportedGlyph`] if the
+    /// font doesn't contain a glyph for a character of the text.
+    pub fn try_layout<const N: usize>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+    ) -> Result<LaidOutText<'a, C, N>, LayoutError> {
+        for c in text.chars().filter(|&c| c != '\n') {
+            self.font().try_get_glyph(c)?;
+        }
+
+        Ok(self.layout(text, position, baseline)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, tests::FRACTIONAL_FONT};
+    use alloc::vec::Vec;
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor, text::Text};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+    const LARGE_FONT: BdfFont = include_bdf!("examples/10x20.bdf", 'A'..='Z' | ' ');
+    const FONTS: [BdfFont; 2] = [FONT, LARGE_FONT];
+
+    fn bounds() -> Rectangle {
+        Rectangle::new(Point::new(5, 10), Size::new(40, 30))
+    }
+
+    #[test]
+    fn block_height() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        assert_eq!(style.block_height(0), 0);
+        assert_eq!(style.block_height(1), 10);
+        assert_eq!(style.block_height(2), 20);
+    }
+
+    #[test]
+    fn align_single_line() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        assert_eq!(
+            style.aligned_position("A", &bounds(), VerticalAlignment::Top),
+            Point::new(5, 17)
+        );
+        assert_eq!(
+            style.aligned_position("A", &bounds(), VerticalAlignment::Middle),
+            Point::new(5, 27)
+        );
+        assert_eq!(
+            style.aligned_position("A", &bounds(), VerticalAlignment::Bottom),
+            Point::new(5, 37)
+        );
+    }
+
+    #[test]
+    fn align_multiple_lines() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        assert_eq!(
+            style.aligned_position("A\nB", &bounds(), VerticalAlignment::Bottom),
+            Point::new(5, 27)
+        );
+    }
+
+    #[test]
+    fn hit_test() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let origin = Point::new(10, 20);
+
+        assert_eq!(
+            style.char_index_at("AB", origin, Point::new(10, 20)),
+            Some(0)
+        );
+        assert_eq!(
+            style.char_index_at("AB", origin, Point::new(15, 13)),
+            Some(0)
+        );
+        assert_eq!(
+            style.char_index_at("AB", origin, Point::new(16, 22)),
+            Some(1)
+        );
+        assert_eq!(style.char_index_at("AB", origin, Point::new(22, 20)), None);
+        assert_eq!(style.char_index_at("AB", origin, Point::new(9, 20)), None);
+        assert_eq!(style.char_index_at("AB", origin, Point::new(10, 12)), None);
+        assert_eq!(style.char_index_at("AB", origin, Point::new(10, 23)), None);
+    }
+
+    #[test]
+    fn hit_test_multiple_lines() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let origin = Point::new(10, 20);
+
+        assert_eq!(
+            style.char_index_at("AB\nCD", origin, Point::new(17, 28)),
+            Some(4)
+        );
+        assert_eq!(
+            style.char_index_at("AB\nCD", origin, Point::new(17, 40)),
+            None
+        );
+    }
+
+    #[test]
+    fn advances() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        assert_eq!(
+            style.advances("AB C").collect::<Vec<_>>(),
+            [('A', 6, 0), ('B', 6, 6), (' ', 6, 12), ('C', 6, 18)]
+        );
+    }
+
+    #[test]
+    fn fractional_caret_and_wrap() {
+        let style =
+            BdfTextStyle::new(&FRACTIONAL_FONT, BinaryColor::On).with_fractional_advances(true);
+        let text = "AAAAAA";
+
+        let mut offsets: Vec<_> = style.advances(text).map(|(_, _, x)| x).collect();
+        offsets.push(style.string_advance(text) as i32);
+        assert_eq!(offsets, [0, 4, 8, 12, 17, 21, 25]);
+
+        for (index, x) in offsets.iter().enumerate() {
+            assert_eq!(style.caret_position(text, Point::zero(), index).x, *x);
+        }
+
+        // The line is 25 pixels wide, not 6 times the device width of 4 pixels.
+        let lines: Vec<_> = style.line_breaks(text, 24).lines().collect();
+        assert_eq!(lines, ["AAAAA", "A"]);
+        let lines: Vec<_> = style.line_breaks(text, 25).lines().collect();
+        assert_eq!(lines, ["AAAAAA"]);
+        let lines: Vec<_> = style.line_breaks("AAA AAA", 24).lines().collect();
+        assert_eq!(lines, ["AAA", "AAA"]);
+    }
+
+    #[test]
+    fn measure_char() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        assert_eq!(
+            style.measure_char('A'),
+            CharMetrics {
+                advance: 6,
+                ink_box: Rectangle::new(Point::new(0, -7), Size::new(6, 10)),
+                is_fallback: false,
+            }
+        );
+
+        let fallback = style.measure_char('a');
+        assert!(fallback.is_fallback);
+        assert_eq!(fallback.advance, 6);
+    }
+
+    #[test]
+    fn string_advance() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        for text in ["", "A", "AB C"].iter() {
+            let metrics = style.measure_string(text, Point::zero(), Baseline::Top);
+            assert_eq!(style.string_advance(text), metrics.next_position.x as u32);
+        }
+        assert_eq!(style.string_advance("AB C"), 24);
+    }
+
+    #[test]
+    fn measure_text() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let position = Point::new(10, 20);
+
+        let lines: Vec<_> = style
+            .measure_lines("AB\nCDE", position, Baseline::Alphabetic, None)
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].range, 3..6);
+        assert_eq!(lines[1].position, Point::new(10, 30));
+        assert_eq!(
+            lines[1].bounds.advance_box,
+            Rectangle::new(Point::new(10, 23), Size::new(18, 10))
+        );
+
+        assert_eq!(
+            style.measure_text("AB\nCDE", position, Baseline::Alphabetic, None),
+            TextBlockMetrics {
+                bounding_box: Rectangle::new(Point::new(10, 13), Size::new(18, 20)),
+                lines: 2,
+                next_position: Point::new(28, 30),
+            }
+        );
+    }
+
+    #[test]
+    fn measure_wrapped_text() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let metrics = style.measure_text("AB CD EF", Point::zero(), Baseline::Top, Some(30));
+        assert_eq!(metrics.lines, 2);
+        assert_eq!(
+            metrics.bounding_box,
+            Rectangle::new(Point::zero(), Size::new(30, 20))
+        );
+
+        let metrics = style.measure_text("\n", Point::zero(), Baseline::Top, None);
+        assert_eq!(metrics.lines, 2);
+        assert_eq!(
+            metrics.bounding_box,
+            Rectangle::new(Point::zero(), Size::new(0, 20))
+        );
+    }
+
+    #[test]
+    fn fit_font() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let size = Size::new(40, 20);
+
+        let font = |text| style.fit_font(text, &FONTS, size).map(|style| style.font());
+        assert_eq!(font("ABCD"), Some(&FONTS[1]));
+        assert_eq!(font("ABCDE"), Some(&FONTS[0]));
+        assert_eq!(font("AB\nCD"), Some(&FONTS[0]));
+        assert_eq!(font("ABCDEFG"), None);
+    }
+
+    #[test]
+    fn draw_fitted() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), Size::new(40, 20));
+
+        let mut display = MockDisplay::new();
+        let used_style = style
+            .draw_fitted("AB", &FONTS, &bounds, VerticalAlignment::Top, &mut display)
+            .unwrap();
+        assert_eq!(used_style.font(), &FONTS[1]);
+
+        let mut expected = MockDisplay::new();
+        used_style
+            .draw_string("AB", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+        display.assert_eq(&expected);
+
+        // The smallest font is used if the text doesn't fit.
+        let mut display = MockDisplay::new();
+        let used_style = style
+            .draw_fitted(
+                "ABCDEFG",
+                &FONTS,
+                &bounds,
+                VerticalAlignment::Top,
+                &mut display,
+            )
+            .unwrap();
+        assert_eq!(used_style.font(), &FONTS[0]);
+    }
+
+    #[test]
+    fn caret() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let origin = Point::new(10, 20);
+
+        assert_eq!(
+            style.caret_position("AB\nCD", origin, 0),
+            Point::new(10, 13)
+        );
+        assert_eq!(
+            style.caret_position("AB\nCD", origin, 2),
+            Point::new(22, 13)
+        );
+        assert_eq!(
+            style.caret_position("AB\nCD", origin, 3),
+            Point::new(10, 23)
+        );
+        assert_eq!(
+            style.caret_position("AB\nCD", origin, 10),
+            Point::new(22, 23)
+        );
+        assert_eq!(style.caret_height(), 10);
+    }
+
+    #[test]
+    fn laid_out_text() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).underline();
+        let text = style
+            .layout::<3>("AB\nC", Point::new(1, 2), Baseline::Top)
+            .unwrap();
+
+        let indices: Vec<_> = text.glyphs().iter().map(|glyph| glyph.index).collect();
+        assert_eq!(indices, [0, 1, 3]);
+        assert_eq!(text.glyphs()[1].position, Point::new(7, 9));
+        assert_eq!(
+            text.bounding_box(),
+            Some(Rectangle::new(Point::new(1, 2), Size::new(12, 20)))
+        );
+        assert_eq!(text.char_index_at(Point::new(8, 5)), Some(1));
+        assert_eq!(text.char_index_at(Point::new(8, 15)), None);
+        assert_eq!(text.char_index_at(Point::new(1, 21)), Some(3));
+
+        let mut display = MockDisplay::new();
+        text.draw(&mut display).unwrap();
+
+        let mut expected = MockDisplay::new();
+        Text::with_baseline("AB\nC", Point::new(1, 2), style, Baseline::Top)
+            .draw(&mut expected)
+            .unwrap();
+        display.assert_eq(&expected);
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        text.draw_with_colors(BinaryColor::Off, Some(BinaryColor::On), &mut display)
+            .unwrap();
+        assert_eq!(display.affected_area(), text.bounding_box().unwrap());
+        assert_eq!(display.get_pixel(Point::new(1, 2)), Some(BinaryColor::On));
+        assert_eq!(
+            display.get_pixel(Point::new(1, 10)),
+            Some(BinaryColor::Off),
+            "underline"
+        );
+
+        assert_eq!(
+            style.layout::<2>("AB\nC", Point::zero(), Baseline::Top),
+            Err(CapacityError)
+        );
+    }
+
+    #[test]
+    fn try_layout() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let text = style
+            .try_layout::<3>("AB\nC", Point::zero(), Baseline::Top)
+            .unwrap();
+        assert_eq!(text.glyphs().len(), 3);
+
+        assert_eq!(
+            style.try_layout::<3>("Ab", Point::zero(), Baseline::Top),
+            Err(LayoutError::Font(FontError::UnsupportedGlyph('b')))
+        );
+        assert_eq!(
+            style.try_layout::<2>("AB\nC", Point::zero(), Baseline::Top),
+            Err(LayoutError::BufferTooSmall)
+        );
+    }
+}
@@ -1,16 +1,165 @@
 #![no_std]
 
-use embedded_graphics::{
-    iterator::raw::RawDataSlice,
-    pixelcolor::raw::{LittleEndian, RawU1},
-    prelude::*,
-    primitives::Rectangle,
-};
+//! BDF text renderer for embedded-graphics.
+//!
+//! The `embedded-graphics` feature, which is enabled by default, adds the text renderer and all
+//! other modules which depend on the full `embedded-graphics` crate. Without this feature the
+//! crate only depends on `embedded-graphics-core` and provides the font data types, which can be
+//! used to draw individual glyphs.
+//!
+//! The optional `defmt` feature implements `defmt::Format` for the public data types and the
+//! optional `serde` feature adds serialization support for font metadata, style configurations
+//! and, together with the `alloc` feature, owned fonts. The optional `fonts` feature adds a
+//! collection of bundled fonts and the optional `alloc` feature enables functions which require a
+//! global allocator. The `test-helpers` feature adds helpers for snapshot-testing text layout,
+//! which require `alloc`.
+
+use core::{convert::TryFrom, fmt, mem::size_of_val};
+
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+
+#[cfg(any(test, feature = "alloc"))]
+extern crate alloc;
+
+// Allows `include_bdf` to be used inside this crate.
+#[cfg(any(test, feature = "fonts"))]
+extern crate self as eg_bdf;
 
 pub use eg_bdf_macros::include_bdf;
+
+/// Types used by the code generated by [`include_bdf`] and the testing macros.
+#[doc(hidden)]
+pub mod __private {
+    pub use embedded_graphics_core::{
+        geometry::{Point, Size},
+        primitives::Rectangle,
+    };
+
+    #[cfg(feature = "test-helpers")]
+    pub use embedded_graphics::text::Baseline;
+
+    use crate::BdfGlyph;
+
+    /// Creates a glyph.
+    ///
+    /// Calling this function requires considerably fewer tokens than a struct expression, which
+    /// reduces the size of the generated code for fonts with many glyphs.
+    #[allow(clippy::too_many_arguments)]
+    pub const fn glyph(
+        character: char,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        device_width: i32,
+        start_index: usize,
+    ) -> BdfGlyph {
+        BdfGlyph {
+            character,
+            bounding_box: Rectangle::new(Point::new(x, y), Size::new(width, height)),
+            device_width,
+            start_index,
+        }
+    }
+
+    /// Concatenates the chunks of a table.
+    ///
+    /// Large tables are generated in chunks, because very long array expressions are slow to
+    /// compile. `N` must be the total length of all chunks.
+    pub const fn concat<T: Copy, const N: usize>(chunks: &[&[T]]) -> [T; N] {
+        assert!(N > 0 && !chunks.is_empty() && !chunks[0].is_empty());
+
+        let mut table = [chunks[0][0]; N];
+        let mut index = 0;
+        let mut chunk = 0;
+        while chunk < chunks.len() {
+            let mut i = 0;
+            while i < chunks[chunk].len() {
+                table[index] = chunks[chunk][i];
+                index += 1;
+                i += 1;
+            }
+            chunk += 1;
+        }
+        assert!(index == N, "wrong table length");
+
+        table
+    }
+}
+
+#[cfg(feature = "embedded-graphics")]
+pub mod animate;
+#[cfg(feature = "embedded-graphics")]
+pub mod ansi;
+#[cfg(feature = "embedded-graphics")]
+pub mod arc;
+#[cfg(feature = "embedded-graphics")]
+pub mod blit;
+#[cfg(feature = "embedded-graphics")]
+pub mod buffer;
+#[cfg(feature = "embedded-graphics")]
+pub mod cache;
+#[cfg(feature = "embedded-graphics")]
+pub mod codec;
+#[cfg(feature = "embedded-graphics")]
+pub mod color_key;
+#[cfg(feature = "embedded-graphics")]
+pub mod config;
+#[cfg(feature = "embedded-graphics")]
+pub mod dirty;
+pub mod egbf;
+mod export;
+#[cfg(feature = "embedded-graphics")]
+pub mod fade;
+#[cfg(feature = "embedded-graphics")]
+pub mod family;
+#[cfg(feature = "embedded-graphics")]
+pub mod flip;
+#[cfg(feature = "fonts")]
+pub mod fonts;
+#[cfg(feature = "embedded-graphics")]
+pub mod incremental;
+#[cfg(feature = "embedded-graphics")]
+pub mod layout;
+#[cfg(feature = "embedded-graphics")]
+pub mod measure;
+#[cfg(feature = "embedded-graphics")]
+pub mod mono;
+#[cfg(feature = "embedded-graphics")]
+pub mod numbers;
+#[cfg(feature = "alloc")]
+pub mod owned;
+#[cfg(feature = "embedded-graphics")]
+pub mod path;
+#[cfg(feature = "embedded-graphics")]
+mod redraw;
+#[cfg(feature = "embedded-graphics")]
+mod rotate;
+#[cfg(feature = "embedded-graphics")]
+pub mod runs;
+#[cfg(feature = "serde")]
+mod serde_remote;
+#[cfg(feature = "embedded-graphics")]
+pub mod spans;
+#[cfg(feature = "embedded-graphics")]
+pub mod strip;
+#[cfg(feature = "test-helpers")]
+pub mod testing;
+#[cfg(feature = "embedded-graphics")]
 pub mod text;
+#[cfg(feature = "embedded-graphics")]
+pub mod theme;
+#[cfg(feature = "embedded-graphics")]
+pub mod vertical;
+#[cfg(feature = "embedded-graphics")]
+pub mod widgets;
+#[cfg(feature = "embedded-graphics")]
+pub mod wrap;
+#[cfg(feature = "embedded-graphics")]
+pub mod writer;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
 pub struct BdfFont<'a> {
     pub replacement_character: usize,
     pub glyphs: &'a [BdfGlyph],
@@ -19,15 +168,455 @@ pub struct BdfFont<'a> {
     pub pixel_size: u32,
     pub font_ascent: u32,
     pub font_descent: u32,
+
+    /// Vertical metrics of the glyphs, in the same order as `glyphs`.
+    ///
+    /// This slice is empty if the font doesn't provide vertical metrics.
+    pub vertical_metrics: &'a [VerticalMetrics],
+
+    /// Scalable advance widths of the glyphs in thousandths of a pixel, in the same order as
+    /// `glyphs`.
+    ///
+    /// The widths are calculated from the `SWIDTH` statements and the size and resolution of BDF
+    /// fonts. This slice is empty if the font doesn't provide scalable widths.
+    pub scalable_widths: &'a [i32],
+
+    /// Layout of the bitmap data.
+    pub bitmap_layout: BitmapLayout,
+
+    /// Extents of the glyphs, which are calculated when the font is converted.
+    pub extents: FontExtents,
+}
+
+/// Extents of all glyphs in a font.
+///
+/// The extents make it possible to reserve space for the worst case without iterating over all
+/// glyphs at runtime.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontExtents {
+    /// Union of the bounding boxes of all glyphs.
+    ///
+    /// The bounding box is relative to the glyph origin on the alphabetic baseline.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_remote::RectangleDef"))]
+    pub max_bounding_box: Rectangle,
+    /// Maximum advance width.
+    pub max_advance: i32,
+    /// Average advance width, rounded to the nearest integer.
+    pub average_advance: i32,
+    /// Advance width shared by all glyphs.
+    ///
+    /// `None` if the advance widths differ, are negative or the font doesn't contain any glyphs.
+    pub fixed_advance: Option<u32>,
+}
+
+impl FontExtents {
+    /// Calculates the extents of the given glyphs.
+    pub fn from_glyphs(glyphs: &[BdfGlyph]) -> Self {
+        // Glyphs with empty bounding boxes don't contribute to the union.
+        let max_bounding_box = glyphs
+            .iter()
+            .filter_map(|glyph| {
+                let bottom_right = glyph.bounding_box.bottom_right()?;
+                Some((glyph.bounding_box.top_left, bottom_right))
+            })
+            .reduce(|(min, max), (top_left, bottom_right)| {
+                (min.component_min(top_left), max.component_max(bottom_right))
+            })
+            .map_or(Rectangle::zero(), |(min, max)| {
+                Rectangle::with_corners(min, max)
+            });
+
+        let max_advance = glyphs
+            .iter()
+            .map(|glyph| glyph.device_width)
+            .max()
+            .unwrap_or_default();
+
+        let count = glyphs.len().max(1) as i64;
+        let sum: i64 = glyphs
+            .iter()
+            .map(|glyph| i64::from(glyph.device_width))
+            .sum();
+        let average_advance = (sum * 2 + count).div_euclid(count * 2) as i32;
+
+        let fixed_advance = glyphs.first().and_then(|first| {
+            let advance = u32::try_from(first.device_width).ok()?;
+            if glyphs
+                .iter()
+                .all(|glyph| glyph.device_width == first.device_width)
+            {
+                Some(advance)
+            } else {
+                None
+            }
+        });
+
+        Self {
+            max_bounding_box,
+            max_advance,
+            average_advance,
+            fixed_advance,
+        }
+    }
+}
+
+/// Order of the pixels inside the bytes of the bitmap data.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BitOrder {
+    /// The leftmost pixel is stored in the most significant bit.
+    #[default]
+    MsbFirst,
+    /// The leftmost pixel is stored in the least significant bit.
+    LsbFirst,
+}
+
+/// Layout of the glyph rows in the bitmap data.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RowLayout {
+    /// Rows are stored as a continuous stream of bits without any padding.
+    #[default]
+    Packed,
+    /// Each row is padded to a whole number of bytes.
+    ///
+    /// This is the layout used by BDF files and many other bitmap font formats. The
+    /// `start_index` of each glyph must be a multiple of 8.
+    ByteAligned,
+}
+
+/// Layout of the bitmap data of a font.
+///
+/// Fonts generated by [`include_bdf`] use MSB first bit order and packed rows, which is also the
+/// default layout. Other layouts make it possible to use bitmap data from other tools without
+/// converting it first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BitmapLayout {
+    /// Bit order.
+    pub bit_order: BitOrder,
+    /// Row layout.
+    pub row_layout: RowLayout,
+}
+
+impl BitmapLayout {
+    /// Creates a new bitmap layout.
+    pub const fn new(bit_order: BitOrder, row_layout: RowLayout) -> Self {
+        Self {
+            bit_order,
+            row_layout,
+        }
+    }
+
+    /// Returns the distance between the start of two rows in bits.
+    pub fn row_stride(&self, width: u32) -> usize {
+        match self.row_layout {
+            RowLayout::Packed => width as usize,
+            RowLayout::ByteAligned => width.div_ceil(8) as usize * 8,
+        }
+    }
+
+    /// Returns `true` if the bit at `index` is set.
+    pub fn bit(&self, data: &[u8], index: usize) -> bool {
+        data.get(index / 8)
+            .is_some_and(|byte| byte & self.mask(index) != 0)
+    }
+
+    /// Returns the mask for bit `index` inside its byte.
+    pub(crate) fn mask(&self, index: usize) -> u8 {
+        match self.bit_order {
+            BitOrder::MsbFirst => 0x80 >> (index % 8),
+            BitOrder::LsbFirst => 1 << (index % 8),
+        }
+    }
+}
+
+/// Error returned if loading or validating a font fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum FontError {
+    /// The font data ended unexpectedly.
+    UnexpectedEnd,
+    /// The font data contains an invalid value.
+    InvalidData,
+    /// A buffer is too small to store the font tables.
+    BufferTooSmall,
+    /// The font data doesn't start with the expected magic number.
+    InvalidMagic,
+    /// The format version of the font data isn't supported.
+    UnsupportedVersion(u16),
+    /// The checksum of the font data doesn't match, which indicates corrupted data.
+    ChecksumMismatch,
+    /// The font doesn't contain a glyph for a character.
+    UnsupportedGlyph(char),
+    /// The bitmap of the glyph for a character extends beyond the bitmap data of the font.
+    DataOutOfRange(char),
+    /// The font is too large to be stored in the binary format.
+    TooLarge,
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::UnexpectedEnd => "unexpected end of font data",
+            Self::InvalidData => "invalid font data",
+            Self::BufferTooSmall => "buffer too small for font tables",
+            Self::InvalidMagic => "invalid magic number",
+            Self::UnsupportedVersion(version) => {
+                return write!(f, "unsupported format version {}", version)
+            }
+            Self::ChecksumMismatch => "checksum mismatch",
+            Self::UnsupportedGlyph(c) => return write!(f, "unsupported glyph {:?}", c),
+            Self::DataOutOfRange(c) => {
+                return write!(f, "bitmap data of glyph {:?} out of range", c)
+            }
+            Self::TooLarge => "font too large for binary format",
+        })
+    }
+}
+
+/// Error returned if the capacity of a fixed size buffer is exceeded.
+///
+/// Used by widgets and by layout functions which store their results in buffers with a
+/// capacity that is set by a const generic parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct CapacityError;
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("insufficient buffer capacity")
+    }
+}
+
+/// Vertical metrics of a glyph.
+///
+/// Vertical metrics are used to stack glyphs from top to bottom in vertical text, e.g. in CJK
+/// fonts. They are read from the `VVECTOR` and `DWIDTH1` statements of BDF fonts with
+/// `METRICSSET` 1 or 2.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VerticalMetrics {
+    /// Offset from the horizontal origin to the vertical origin.
+    ///
+    /// The vertical origin is the position of the pen in vertical text, which is usually
+    /// located at the horizontal center of the top row of the glyph.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_remote::PointDef"))]
+    pub origin: Point,
+
+    /// Vertical advance of the pen position after this glyph.
+    ///
+    /// Positive values move the pen downwards.
+    pub advance: i32,
+}
+
+/// Font metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontMetadata {
+    /// Pixel size.
+    pub pixel_size: u32,
+    /// Ascent above the baseline.
+    pub font_ascent: u32,
+    /// Descent below the baseline.
+    pub font_descent: u32,
+    /// Number of glyphs.
+    pub glyph_count: usize,
+}
+
+/// Memory used by the data of a font.
+///
+/// The sizes only include the data referenced by a [`BdfFont`] and not the size of the struct
+/// itself. Table sizes depend on the target, because glyphs store their start index as a `usize`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct MemoryFootprint {
+    /// Size of the bitmap data in bytes.
+    pub data: usize,
+    /// Size of the glyph, scalable width and vertical metrics tables in bytes.
+    pub tables: usize,
+}
+
+impl MemoryFootprint {
+    /// Returns the total size in bytes.
+    pub const fn total(&self) -> usize {
+        self.data + self.tables
+    }
 }
 
 impl<'a> BdfFont<'a> {
-    fn get_glyph(&self, c: char) -> &'a BdfGlyph {
-        if let Ok(found_idx) = self.glyphs.binary_search_by(|g| g.character.cmp(&c)) {
-            &self.glyphs[found_idx]
-        } else {
-            &self.glyphs[self.replacement_character]
+    /// Returns the font metadata.
+    pub fn metadata(&self) -> FontMetadata {
+        FontMetadata {
+            pixel_size: self.pixel_size,
+            font_ascent: self.font_ascent,
+            font_descent: self.font_descent,
+            glyph_count: self.glyphs.len(),
+        }
+    }
+
+    /// Returns the memory used by the font data.
+    ///
+    /// Fonts are usually stored in flash, which makes it possible to check the size of all used
+    /// fonts at compile time:
+    ///
+    /// ```
+    /// use eg_bdf::{include_bdf, BdfFont};
+    ///
+    /// const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z');
+    /// const _: () = assert!(FONT.memory_footprint().total() < 2048);
+    /// ```
+    ///
+    /// Setting the `EG_BDF_FOOTPRINT` environment variable during the build makes
+    /// [`include_bdf`] print the sizes of every included font.
+    pub const fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint {
+            data: self.data.len(),
+            tables: size_of_val(self.glyphs)
+                + size_of_val(self.scalable_widths)
+                + size_of_val(self.vertical_metrics),
         }
+    }
+
+    /// Returns the union of the bounding boxes of all glyphs.
+    ///
+    /// The bounding box is relative to the glyph origin on the alphabetic baseline.
+    pub fn max_bounding_box(&self) -> Rectangle {
+        self.extents.max_bounding_box
+    }
+
+    /// Returns the maximum advance width of all glyphs.
+    pub fn max_advance(&self) -> i32 {
+        self.extents.max_advance
+    }
+
+    /// Returns the average advance width of all glyphs, rounded to the nearest integer.
+    pub fn average_advance(&self) -> i32 {
+        self.extents.average_advance
+    }
+
+    /// Returns the maximum advance width of the characters used to display numbers.
+    ///
+    /// The maximum is taken over the digits `0` to `9`, the signs `+` and `-` and the decimal
+    /// point `.`. Numeric fields which reserve this width for every character don't change their
+    /// width when the value changes. Characters which aren't included in the font use the advance
+    /// of the replacement character.
+    pub fn digit_width(&self) -> u32 {
+        ('0'..='9')
+            .chain(['+', '-', '.'].iter().copied())
+            .map(|c| self.get_glyph(c).device_width.max(0) as u32)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if all glyphs have the same advance width.
+    pub fn is_monospace(&self) -> bool {
+        self.extents.fixed_advance.is_some()
+    }
+
+    /// Returns the advance width shared by all glyphs.
+    ///
+    /// Returns `None` if the font isn't monospaced. Grid based widgets can use the fixed advance
+    /// as their cell width.
+    pub fn fixed_advance(&self) -> Option<u32> {
+        self.extents.fixed_advance
+    }
+
+    /// Returns the vertical metrics for a character.
+    ///
+    /// Returns `None` if the font doesn't provide vertical metrics. The metrics of the replacement
+    /// character are returned if the font doesn't contain a glyph for `c`.
+    pub fn vertical_metrics(&self, c: char) -> Option<VerticalMetrics> {
+        self.vertical_metrics.get(self.glyph_index(c)).copied()
+    }
+
+    /// Returns the scalable advance width for a character in thousandths of a pixel.
+    ///
+    /// Returns `None` if the font doesn't provide scalable widths. The width of the replacement
+    /// character is returned if the font doesn't contain a glyph for `c`.
+    pub fn scalable_width(&self, c: char) -> Option<i32> {
+        self.scalable_widths.get(self.glyph_index(c)).copied()
+    }
+
+    /// Returns the glyph for a character.
+    ///
+    /// The replacement character is returned if the font doesn't contain a glyph for `c`.
+    pub fn get_glyph(&self, c: char) -> &'a BdfGlyph {
+        &self.glyphs[self.glyph_index(c)]
+    }
+
+    /// Returns the glyph for a character without falling back to the replacement character.
+    ///
+    /// Returns [`FontError::UnsupportedGlyph`] if the font doesn't contain a glyph for `c`.
+    pub fn try_get_glyph(&self, c: char) -> Result<&'a BdfGlyph, FontError> {
+        self.glyphs
+            .binary_search_by(|g| g.character.cmp(&c))
+            .map(|index| &self.glyphs[index])
+            .map_err(|_| FontError::UnsupportedGlyph(c))
+    }
+
+    /// Checks the consistency of the font tables.
+    ///
+    /// The rendering code assumes that the glyphs are sorted by character, that the replacement
+    /// character refers to an existing glyph and that the optional tables have the same length as
+    /// the glyph table. Fonts which violate these assumptions can cause panics or wrong glyphs
+    /// to be drawn. Fonts generated by [`include_bdf`] and loaded by [`egbf::load`] are always
+    /// valid, but fonts which are constructed manually should be checked before they are used.
+    ///
+    /// Returns [`FontError::DataOutOfRange`] if the bitmap of a glyph isn't completely contained
+    /// in the bitmap data and [`FontError::InvalidData`] for all other errors.
+    pub fn validate(&self) -> Result<(), FontError> {
+        if self.replacement_character >= self.glyphs.len()
+            || self
+                .glyphs
+                .windows(2)
+                .any(|glyphs| glyphs[0].character >= glyphs[1].character)
+        {
+            return Err(FontError::InvalidData);
+        }
+
+        for len in [self.scalable_widths.len(), self.vertical_metrics.len()] {
+            if len != 0 && len != self.glyphs.len() {
+                return Err(FontError::InvalidData);
+            }
+        }
+
+        for glyph in self.glyphs {
+            if self.bitmap_layout.row_layout == RowLayout::ByteAligned && glyph.start_index % 8 != 0
+            {
+                return Err(FontError::InvalidData);
+            }
+
+            let Size { width, height } = glyph.bounding_box.size;
+            if width == 0 || height == 0 {
+                continue;
+            }
+
+            let row_stride = self.bitmap_layout.row_stride(width);
+            let end = (height as usize - 1)
+                .checked_mul(row_stride)
+                .and_then(|offset| offset.checked_add(glyph.start_index))
+                .and_then(|start| start.checked_add(width as usize));
+            if end.is_none_or(|end| end > self.data.len().saturating_mul(8)) {
+                return Err(FontError::DataOutOfRange(glyph.character));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the index of the glyph for a character.
+    pub(crate) fn glyph_index(&self, c: char) -> usize {
+        self.glyphs
+            .binary_search_by(|g| g.character.cmp(&c))
+            .unwrap_or(self.replacement_character)
 
         // We assume sorted glyphs for doing the binary search.. linear
         // self.glyphs
@@ -38,16 +627,44 @@ impl<'a> BdfFont<'a> {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BdfGlyph {
     pub character: char,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_remote::RectangleDef"))]
     pub bounding_box: Rectangle,
-    pub device_width: u32,
+    /// Horizontal advance of the pen position after this glyph.
+    ///
+    /// The advance can be negative, which moves the pen to the left, e.g. for overstrike glyphs.
+    pub device_width: i32,
     pub start_index: usize,
 }
 
 impl BdfGlyph {
-    fn draw<D: DrawTarget>(
+    /// Returns `true` if the pixel at `point`, relative to the top left corner of the bounding
+    /// box, is set.
+    ///
+    /// `data` must use the default [`BitmapLayout`].
+    pub fn pixel(&self, data: &[u8], point: Point) -> bool {
+        self.pixel_with_layout(data, BitmapLayout::default(), point)
+    }
+
+    /// Returns `true` if the pixel at `point`, relative to the top left corner of the bounding
+    /// box, is set in bitmap data with the given layout.
+    pub fn pixel_with_layout(&self, data: &[u8], layout: BitmapLayout, point: Point) -> bool {
+        let row_stride = layout.row_stride(self.bounding_box.size.width);
+        let index = self.start_index + point.y as usize * row_stride + point.x as usize;
+
+        layout.bit(data, index)
+    }
+
+    /// Draws the glyph.
+    ///
+    /// `position` is the origin of the glyph on the alphabetic baseline and `data` must be the
+    /// bitmap data of the font which contains the glyph, using the default [`BitmapLayout`]. If
+    /// a background color is given, the whole bounding box of the glyph is filled.
+    pub fn draw<D: DrawTarget>(
         &self,
         position: Point,
         color: D::Color,
@@ -55,25 +672,414 @@ impl BdfGlyph {
         data: &[u8],
         target: &mut D,
     ) -> Result<(), D::Error> {
-        let mut data_iter = RawDataSlice::<RawU1, LittleEndian>::new(data).into_iter();
+        self.draw_with_layout(
+            position,
+            color,
+            bg_color,
+            data,
+            BitmapLayout::default(),
+            target,
+        )
+    }
 
-        if self.start_index > 0 {
-            data_iter.nth(self.start_index - 1);
+    /// Draws the glyph using bitmap data with the given layout.
+    ///
+    /// See [`draw`](Self::draw) for more information.
+    pub fn draw_with_layout<D: DrawTarget>(
+        &self,
+        position: Point,
+        color: D::Color,
+        bg_color: Option<D::Color>,
+        data: &[u8],
+        layout: BitmapLayout,
+        target: &mut D,
+    ) -> Result<(), D::Error> {
+        // Skip glyphs which are completely outside the draw target.
+        let bounding_box = Rectangle::new(
+            self.bounding_box.top_left + position,
+            self.bounding_box.size,
+        );
+        let clipped = bounding_box.intersection(&target.bounding_box());
+        if clipped.is_zero_sized() {
+            return Ok(());
         }
-        let zip = self
-            .bounding_box
-            .translate(position)
-            .points()
-            .zip(data_iter);
+
+        let columns = clipped.columns();
+        let row_stride = layout.row_stride(bounding_box.size.width);
+
+        // Only the pixels inside the draw target are read from the bitmap data.
+        let is_set = |point: Point| {
+            let point = point - bounding_box.top_left;
+            layout.bit(
+                data,
+                self.start_index + point.y as usize * row_stride + point.x as usize,
+            )
+        };
 
         if let Some(bg_color) = bg_color {
-            zip.map(|(p, c)| (p, if c == RawU1::new(1) { color } else { bg_color }))
-                .map(|(p, c)| Pixel(p, c))
-                .draw(target)
-        } else {
-            zip.filter(|(_p, c)| *c == RawU1::new(1))
-                .map(|(p, _c)| Pixel(p, color))
-                .draw(target)
+            let colors = clipped
+                .points()
+                .map(|p| if is_set(p) { color } else { bg_color });
+
+            return target.fill_contiguous(&clipped, colors);
+        }
+
+        // Draw horizontal runs of set pixels, which is faster than drawing individual pixels
+        // on draw targets that support fill operations.
+        for y in clipped.rows() {
+            let mut run_start = None;
+
+            for x in columns.start..=columns.end {
+                let set = x < columns.end && is_set(Point::new(x, y));
+
+                match (set, run_start) {
+                    (true, None) => run_start = Some(x),
+                    (false, Some(start)) => {
+                        let run =
+                            Rectangle::new(Point::new(start, y), Size::new((x - start) as u32, 1));
+                        target.fill_solid(&run, color)?;
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    /// Font with a 2x2 block glyph for `A`, a space and an advance of 4.166 pixels.
+    ///
+    /// The device widths of 4 pixels differ from the fractional advances after a few characters.
+    pub(crate) const FRACTIONAL_FONT: BdfFont = BdfFont {
+        replacement_character: 0,
+        glyphs: &[
+            __private::glyph(' ', 0, 0, 0, 0, 4, 0),
+            __private::glyph('A', 0, -1, 2, 2, 4, 0),
+        ],
+        data: &[0xF0],
+        pixel_size: 4,
+        font_ascent: 2,
+        font_descent: 0,
+        vertical_metrics: &[],
+        scalable_widths: &[4166, 4166],
+        bitmap_layout: BitmapLayout::new(BitOrder::MsbFirst, RowLayout::Packed),
+        extents: FontExtents {
+            max_bounding_box: Rectangle::new(Point::new(0, -1), Size::new(2, 2)),
+            max_advance: 4,
+            average_advance: 4,
+            fixed_advance: Some(4),
+        },
+    };
+
+    /// Draw target which counts the draw calls.
+    pub(crate) struct CallCounter {
+        pub(crate) display: MockDisplay<BinaryColor>,
+        pub(crate) pixels: usize,
+        pub(crate) fills: usize,
+        pub(crate) contiguous_fills: usize,
+    }
+
+    impl CallCounter {
+        pub(crate) fn new() -> Self {
+            Self {
+                display: MockDisplay::new(),
+                pixels: 0,
+                fills: 0,
+                contiguous_fills: 0,
+            }
+        }
+    }
+
+    impl Dimensions for CallCounter {
+        fn bounding_box(&self) -> Rectangle {
+            self.display.bounding_box()
+        }
+    }
+
+    impl DrawTarget for CallCounter {
+        type Color = BinaryColor;
+        type Error = core::convert::Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            self.pixels += 1;
+            self.display.draw_iter(pixels)
+        }
+
+        fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Self::Color>,
+        {
+            self.contiguous_fills += 1;
+            self.display.fill_contiguous(area, colors)
+        }
+
+        fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+            self.fills += 1;
+            self.display.fill_solid(area, color)
         }
     }
+
+    #[test]
+    fn draw_glyph_runs() {
+        let mut target = CallCounter::new();
+
+        let glyph = FONT.get_glyph('I');
+        glyph
+            .draw(
+                Point::new(0, 7),
+                BinaryColor::On,
+                None,
+                FONT.data,
+                &mut target,
+            )
+            .unwrap();
+
+        assert_eq!(target.pixels, 0);
+        assert_eq!(target.fills, 7);
+        assert!(!glyph.pixel(FONT.data, Point::new(0, 1)));
+        assert!(glyph.pixel(FONT.data, Point::new(1, 1)));
+        target.display.assert_pattern(&[
+            "     ", " ### ", "  #  ", "  #  ", "  #  ", "  #  ", "  #  ", " ### ",
+        ]);
+    }
+
+    #[test]
+    fn extents() {
+        assert_eq!(
+            FONT.max_bounding_box(),
+            Rectangle::new(Point::new(0, -7), Size::new(6, 10))
+        );
+        assert_eq!(FONT.max_advance(), 6);
+        assert_eq!(FONT.average_advance(), 6);
+        assert_eq!(FONT.extents, FontExtents::from_glyphs(FONT.glyphs));
+
+        let glyph = |device_width| BdfGlyph {
+            device_width,
+            ..BdfGlyph::default()
+        };
+        let extents = FontExtents::from_glyphs(&[glyph(2), glyph(3), glyph(-1)]);
+        assert_eq!(extents.max_bounding_box, Rectangle::zero());
+        assert_eq!((extents.max_advance, extents.average_advance), (3, 1));
+        assert_eq!(FontExtents::from_glyphs(&[]), FontExtents::default());
+    }
+
+    #[test]
+    fn digit_width() {
+        let glyph = |character, device_width| BdfGlyph {
+            character,
+            device_width,
+            ..BdfGlyph::default()
+        };
+        let glyphs = [
+            glyph(' ', 3),
+            glyph('-', 4),
+            glyph('.', 2),
+            glyph('1', 3),
+            glyph('8', 5),
+            glyph('W', 9),
+        ];
+        let font = BdfFont {
+            replacement_character: 0,
+            glyphs: &glyphs,
+            data: &[],
+            pixel_size: 8,
+            font_ascent: 6,
+            font_descent: 2,
+            vertical_metrics: &[],
+            scalable_widths: &[],
+            bitmap_layout: BitmapLayout::default(),
+            extents: FontExtents::from_glyphs(&glyphs),
+        };
+
+        assert_eq!(font.digit_width(), 5);
+        assert_eq!(FONT.digit_width(), 6);
+    }
+
+    #[test]
+    fn memory_footprint() {
+        let footprint = FONT.memory_footprint();
+
+        // 'A' to 'Z' and the space character with scalable widths.
+        assert_eq!(footprint.data, FONT.data.len());
+        assert_eq!(
+            footprint.tables,
+            27 * (core::mem::size_of::<BdfGlyph>() + core::mem::size_of::<i32>())
+        );
+        assert_eq!(footprint.total(), footprint.data + footprint.tables);
+    }
+
+    #[test]
+    fn try_get_glyph() {
+        assert_eq!(FONT.try_get_glyph('A'), Ok(FONT.get_glyph('A')));
+        assert_eq!(
+            FONT.try_get_glyph('a'),
+            Err(FontError::UnsupportedGlyph('a'))
+        );
+    }
+
+    #[test]
+    fn validate() {
+        assert_eq!(FONT.validate(), Ok(()));
+
+        let font = BdfFont {
+            replacement_character: 27,
+            ..FONT
+        };
+        assert_eq!(font.validate(), Err(FontError::InvalidData));
+
+        let font = BdfFont {
+            scalable_widths: &[0; 3],
+            ..FONT
+        };
+        assert_eq!(font.validate(), Err(FontError::InvalidData));
+
+        let font = BdfFont {
+            data: &FONT.data[..FONT.data.len() - 1],
+            ..FONT
+        };
+        assert_eq!(font.validate(), Err(FontError::DataOutOfRange('Z')));
+
+        let mut glyphs = [FONT.glyphs[1], FONT.glyphs[0]];
+        let font = BdfFont {
+            replacement_character: 0,
+            glyphs: &glyphs,
+            ..FONT
+        };
+        assert_eq!(font.validate(), Err(FontError::InvalidData));
+
+        glyphs.swap(0, 1);
+        let font = BdfFont {
+            replacement_character: 0,
+            glyphs: &glyphs,
+            scalable_widths: &[],
+            bitmap_layout: BitmapLayout::new(BitOrder::MsbFirst, RowLayout::ByteAligned),
+            ..FONT
+        };
+        assert_eq!(font.validate(), Err(FontError::InvalidData));
+    }
+
+    #[test]
+    fn monospace() {
+        assert!(FONT.is_monospace());
+        assert_eq!(FONT.fixed_advance(), Some(6));
+
+        let glyph = |device_width| BdfGlyph {
+            device_width,
+            ..BdfGlyph::default()
+        };
+        let fixed_advance = |glyphs: &[BdfGlyph]| FontExtents::from_glyphs(glyphs).fixed_advance;
+        assert_eq!(fixed_advance(&[glyph(4), glyph(4)]), Some(4));
+        assert_eq!(fixed_advance(&[glyph(4), glyph(3)]), None);
+        assert_eq!(fixed_advance(&[glyph(-1), glyph(-1)]), None);
+        assert_eq!(fixed_advance(&[]), None);
+    }
+
+    #[test]
+    fn bitmap_layouts() {
+        let glyph = BdfGlyph {
+            character: 'a',
+            bounding_box: Rectangle::new(Point::new(0, -1), Size::new(3, 2)),
+            device_width: 4,
+            start_index: 8,
+        };
+
+        let layouts: [(BitmapLayout, &[u8]); 3] = [
+            (BitmapLayout::default(), &[0xFF, 0b1010_1000]),
+            (
+                BitmapLayout::new(BitOrder::MsbFirst, RowLayout::ByteAligned),
+                &[0xFF, 0b1010_0000, 0b0100_0000],
+            ),
+            (
+                BitmapLayout::new(BitOrder::LsbFirst, RowLayout::ByteAligned),
+                &[0xFF, 0b0000_0101, 0b0000_0010],
+            ),
+        ];
+
+        for (layout, data) in layouts {
+            assert!(glyph.pixel_with_layout(data, layout, Point::new(2, 0)));
+            assert!(!glyph.pixel_with_layout(data, layout, Point::new(2, 1)));
+
+            let mut display = MockDisplay::new();
+            glyph
+                .draw_with_layout(
+                    Point::new(0, 1),
+                    BinaryColor::On,
+                    None,
+                    data,
+                    layout,
+                    &mut display,
+                )
+                .unwrap();
+            display.assert_pattern(&["# #", " # "]);
+
+            let mut display = MockDisplay::new();
+            glyph
+                .draw_with_layout(
+                    Point::new(0, 1),
+                    BinaryColor::On,
+                    Some(BinaryColor::Off),
+                    data,
+                    layout,
+                    &mut display,
+                )
+                .unwrap();
+            display.assert_pattern(&["#.#", ".#."]);
+        }
+    }
+
+    #[test]
+    fn skip_clipped_glyphs() {
+        let glyph = FONT.get_glyph('A');
+
+        let mut target = CallCounter::new();
+        glyph
+            .draw(
+                Point::new(-3, 3),
+                BinaryColor::On,
+                None,
+                FONT.data,
+                &mut target,
+            )
+            .unwrap();
+        glyph
+            .draw(
+                Point::new(-6, 3),
+                BinaryColor::On,
+                None,
+                FONT.data,
+                &mut target,
+            )
+            .unwrap();
+
+        assert_eq!(target.fills, 4);
+        target.display.assert_pattern(&[" #", "##", " #", " #"]);
+
+        let mut target = CallCounter::new();
+        glyph
+            .draw(
+                Point::new(-3, 3),
+                BinaryColor::On,
+                Some(BinaryColor::Off),
+                FONT.data,
+                &mut target,
+            )
+            .unwrap();
+
+        assert_eq!(target.contiguous_fills, 1);
+        target
+            .display
+            .assert_pattern(&[".#.", "##.", ".#.", ".#.", "...", "..."]);
+    }
 }
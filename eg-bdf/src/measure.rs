@@ -0,0 +1,173 @@
+//! Text measurement.
+//!
+//! The [`TextMeasurer`] trait provides the metrics which are required to lay out text, without
+//! requiring a [`DrawTarget`](embedded_graphics::draw_target::DrawTarget) or a color type. Layout
+//! code which is written against this trait can be used with [`BdfTextStyle`] and can be tested
+//! on the host with a simple mock implementation.
+//!
+//! ```
+//! use eg_bdf::measure::TextMeasurer;
+//!
+//! /// Returns the horizontal offset which centers `text` in a box of the given width.
+//! fn center_offset<M: TextMeasurer>(measurer: &M, text: &str, width: u32) -> i32 {
+//!     (width as i32 - measurer.string_width(text)) / 2
+//! }
+//!
+//! /// Measurer for a fixed width font, which is used to test the layout code.
+//! struct Fixed;
+//!
+//! impl TextMeasurer for Fixed {
+//!     fn char_advance(&self, _c: char) -> i32 {
+//!         6
+//!     }
+//!
+//!     fn ascent(&self) -> u32 {
+//!         8
+//!     }
+//!
+//!     fn descent(&self) -> u32 {
+//!         2
+//!     }
+//! }
+//!
+//! assert_eq!(center_offset(&Fixed, "ABC", 30), 6);
+//! ```
+
+use embedded_graphics::{prelude::*, text::renderer::TextRenderer};
+
+use crate::text::BdfTextStyle;
+
+/// Text metrics used for layout.
+///
+/// The method names differ from the methods of
+/// [`TextRenderer`](embedded_graphics::text::renderer::TextRenderer) to prevent ambiguous method
+/// calls if both traits are in scope.
+pub trait TextMeasurer {
+    /// Returns the horizontal advance of a character.
+    ///
+    /// The advance is negative for characters which move the pen to the left.
+    fn char_advance(&self, c: char) -> i32;
+
+    /// Returns the number of rows above the baseline, including the baseline row.
+    fn ascent(&self) -> u32;
+
+    /// Returns the number of rows below the baseline.
+    fn descent(&self) -> u32;
+
+    /// Returns the horizontal distance from the start of `text` to the position after the last
+    /// character.
+    ///
+    /// The default implementation returns the sum of the character advances. Implementations
+    /// which position characters differently, e.g. using fractional advances, should override
+    /// this method.
+    fn string_width(&self, text: &str) -> i32 {
+        text.chars().map(|c| self.char_advance(c)).sum()
+    }
+
+    /// Returns the vertical distance between the baselines of two consecutive lines.
+    ///
+    /// The default implementation returns the sum of the ascent and descent.
+    fn line_spacing(&self) -> u32 {
+        self.ascent() + self.descent()
+    }
+}
+
+impl<M: TextMeasurer + ?Sized> TextMeasurer for &M {
+    fn char_advance(&self, c: char) -> i32 {
+        (**self).char_advance(c)
+    }
+
+    fn ascent(&self) -> u32 {
+        (**self).ascent()
+    }
+
+    fn descent(&self) -> u32 {
+        (**self).descent()
+    }
+
+    fn string_width(&self, text: &str) -> i32 {
+        (**self).string_width(text)
+    }
+
+    fn line_spacing(&self) -> u32 {
+        (**self).line_spacing()
+    }
+}
+
+impl<C: PixelColor> TextMeasurer for BdfTextStyle<'_, C> {
+    fn char_advance(&self, c: char) -> i32 {
+        BdfTextStyle::char_advance(self, c)
+    }
+
+    fn ascent(&self) -> u32 {
+        self.font().font_ascent
+    }
+
+    fn descent(&self) -> u32 {
+        self.font().font_descent
+    }
+
+    fn string_width(&self, text: &str) -> i32 {
+        self.text_advance(text)
+    }
+
+    /// Returns the line height, including the line gap of the style.
+    fn line_spacing(&self) -> u32 {
+        self.line_height()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{pixelcolor::BinaryColor, text::Baseline};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    /// Returns the number of characters which fit into `width`.
+    fn fitting_chars<M: TextMeasurer>(measurer: M, text: &str, width: i32) -> usize {
+        text.char_indices()
+            .take_while(|(index, c)| measurer.string_width(&text[..index + c.len_utf8()]) <= width)
+            .count()
+    }
+
+    struct Fixed;
+
+    impl TextMeasurer for Fixed {
+        fn char_advance(&self, c: char) -> i32 {
+            if c == ' ' {
+                2
+            } else {
+                4
+            }
+        }
+
+        fn ascent(&self) -> u32 {
+            5
+        }
+
+        fn descent(&self) -> u32 {
+            1
+        }
+    }
+
+    #[test]
+    fn default_methods() {
+        assert_eq!(Fixed.string_width("AB C"), 14);
+        assert_eq!(Fixed.line_spacing(), 6);
+        assert_eq!(fitting_chars(&Fixed, "AB C", 10), 3);
+    }
+
+    #[test]
+    fn text_style() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_line_gap(2);
+
+        let bounds = style.measure_string_bounds("AB C", Point::zero(), Baseline::Top);
+        assert_eq!(style.string_width("AB C"), bounds.next_position.x);
+        assert_eq!(TextMeasurer::char_advance(&style, 'A'), 6);
+        assert_eq!((style.ascent(), style.descent()), (8, 2));
+        assert_eq!(style.line_spacing(), 12);
+        assert_eq!(fitting_chars(style, "AB C", 20), 3);
+    }
+}
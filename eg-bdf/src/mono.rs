@@ -0,0 +1,369 @@
+//! Interoperability with embedded-graphics mono fonts.
+
+use embedded_graphics::{
+    image::{GetPixel, ImageRaw},
+    mono_font::{mapping::GlyphMapping, DecorationDimensions, MonoFont},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+
+use crate::{BdfFont, BdfGlyph, BitmapLayout, FontExtents};
+
+/// Number of glyphs per row in the glyph atlas.
+const GLYPHS_PER_ROW: u32 = 16;
+
+/// Error returned by [`BdfFont::to_mono_font`] and [`BdfFont::from_mono_font`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum MonoFontError {
+    /// The font is empty or the glyphs don't have the same advance width.
+    NotMonospaced,
+    /// The bounding box of a glyph extends outside the character cell.
+    GlyphOutsideCell(char),
+    /// The buffer is too small to store the glyph data.
+    BufferTooSmall,
+    /// No characters were passed to [`BdfFont::from_mono_font`].
+    NoCharacters,
+}
+
+impl GlyphMapping for BdfFont<'_> {
+    fn index(&self, c: char) -> usize {
+        self.glyph_index(c)
+    }
+}
+
+impl BdfFont<'_> {
+    /// Returns the size of a character cell if the font is monospaced.
+    fn character_size(&self) -> Option<Size> {
+        let width = self.glyphs.first()?.device_width;
+        if width < 0 || self.glyphs.iter().any(|glyph| glyph.device_width != width) {
+            return None;
+        }
+
+        Some(Size::new(
+            width as u32,
+            self.font_ascent + self.font_descent,
+        ))
+    }
+
+    /// Returns the size of the buffer required by [`to_mono_font`](Self::to_mono_font).
+    ///
+    /// Returns `0` if the font isn't monospaced.
+    pub fn mono_font_data_len(&self) -> usize {
+        let size = match self.character_size() {
+            Some(size) => size,
+            None => return 0,
+        };
+
+        let stride = (size.width * GLYPHS_PER_ROW).div_ceil(8) as usize;
+        let rows = (self.glyphs.len() as u32).div_ceil(GLYPHS_PER_ROW) as usize;
+
+        stride * rows * size.height as usize
+    }
+
+    /// Converts a monospaced font into a [`MonoFont`].
+    ///
+    /// The glyph atlas is stored in `data`, which needs to be at least
+    /// [`mono_font_data_len`](Self::mono_font_data_len) bytes long. The character cell covers the
+    /// ascent and descent of the font and the decorations are placed at the same positions as
+    /// they are drawn by [`BdfTextStyle`](crate::text::BdfTextStyle).
+    pub fn to_mono_font<'b>(&'b self, data: &'b mut [u8]) -> Result<MonoFont<'b>, MonoFontError> {
+        let size = self.character_size().ok_or(MonoFontError::NotMonospaced)?;
+        let data = data
+            .get_mut(..self.mono_font_data_len())
+            .ok_or(MonoFontError::BufferTooSmall)?;
+        for byte in data.iter_mut() {
+            *byte = 0;
+        }
+
+        let baseline = self.font_ascent.saturating_sub(1);
+        let width = size.width * GLYPHS_PER_ROW;
+        let stride = width.div_ceil(8) as usize;
+        let cell = Rectangle::new(Point::zero(), size);
+
+        for (index, glyph) in self.glyphs.iter().enumerate() {
+            let index = index as u32;
+            let bounding_box = glyph.bounding_box.translate(Point::new(0, baseline as i32));
+            if !bounding_box.is_zero_sized() && cell.intersection(&bounding_box) != bounding_box {
+                return Err(MonoFontError::GlyphOutsideCell(glyph.character));
+            }
+
+            let origin = Point::new(
+                ((index % GLYPHS_PER_ROW) * size.width) as i32,
+                ((index / GLYPHS_PER_ROW) * size.height) as i32,
+            ) + bounding_box.top_left;
+
+            for point in Rectangle::new(Point::zero(), bounding_box.size).points() {
+                if glyph.pixel_with_layout(self.data, self.bitmap_layout, point) {
+                    let point = origin + point;
+                    data[point.y as usize * stride + point.x as usize / 8] |= 0x80 >> (point.x % 8);
+                }
+            }
+        }
+
+        let data: &'b [u8] = data;
+
+        Ok(MonoFont {
+            image: ImageRaw::new(data, width),
+            character_size: size,
+            character_spacing: 0,
+            baseline,
+            strikethrough: DecorationDimensions::new(size.height.saturating_sub(1) / 2 + 1, 1),
+            underline: DecorationDimensions::new(baseline + 1, 1),
+            glyph_mapping: self,
+        })
+    }
+}
+
+impl<'a> BdfFont<'a> {
+    /// Returns the size of the data buffer required by [`from_mono_font`](Self::from_mono_font).
+    pub fn from_mono_font_data_len(font: &MonoFont<'_>, chars: &str) -> usize {
+        let size = font.character_size;
+        let bits = chars.chars().count() * size.width as usize * size.height as usize;
+
+        bits.div_ceil(8)
+    }
+
+    /// Creates a font from a [`MonoFont`].
+    ///
+    /// Mono fonts don't store which characters they contain, which is why the characters need to
+    /// be listed in `chars`. Each glyph covers the full character cell and the character spacing
+    /// is added to the advance width. The glyphs are stored in `glyphs`, which needs to be at
+    /// least as long as the number of characters, and the glyph bitmaps are stored in `data`,
+    /// which needs to be at least [`from_mono_font_data_len`](Self::from_mono_font_data_len)
+    /// bytes long.
+    ///
+    /// Characters which are listed more than once in `chars` are only included once. The
+    /// replacement character is used for characters which aren't included in `chars`. If `chars`
+    /// doesn't contain a replacement character or a space the first glyph is used.
+    ///
+    /// Returns [`MonoFontError::NoCharacters`] if `chars` is empty.
+    pub fn from_mono_font(
+        font: &MonoFont<'_>,
+        chars: &str,
+        glyphs: &'a mut [BdfGlyph],
+        data: &'a mut [u8],
+    ) -> Result<Self, MonoFontError> {
+        let size = font.character_size;
+        let count = chars.chars().count();
+        if count == 0 {
+            return Err(MonoFontError::NoCharacters);
+        }
+        let glyphs = glyphs
+            .get_mut(..count)
+            .ok_or(MonoFontError::BufferTooSmall)?;
+        let data = data
+            .get_mut(..Self::from_mono_font_data_len(font, chars))
+            .ok_or(MonoFontError::BufferTooSmall)?;
+        for byte in data.iter_mut() {
+            *byte = 0;
+        }
+
+        let glyphs_per_row = (font.image.size().width / size.width.max(1)).max(1);
+        let bounding_box = Rectangle::new(Point::new(0, -(font.baseline as i32)), size);
+
+        let mut start_index = 0;
+        for (glyph, c) in glyphs.iter_mut().zip(chars.chars()) {
+            let index = font.glyph_mapping.index(c) as u32;
+            let cell = Point::new(
+                ((index % glyphs_per_row) * size.width) as i32,
+                ((index / glyphs_per_row) * size.height) as i32,
+            );
+
+            for (offset, point) in Rectangle::new(Point::zero(), size).points().enumerate() {
+                if font.image.pixel(cell + point) == Some(BinaryColor::On) {
+                    let index = start_index + offset;
+                    data[index / 8] |= 0x80 >> (index % 8);
+                }
+            }
+
+            *glyph = BdfGlyph {
+                character: c,
+                bounding_box,
+                device_width: (size.width + font.character_spacing) as i32,
+                start_index,
+            };
+            start_index += (size.width * size.height) as usize;
+        }
+
+        glyphs.sort_unstable_by_key(|glyph| glyph.character);
+
+        // Remove duplicate characters, which would break the binary search in `get_glyph`.
+        let mut len = 1;
+        for index in 1..glyphs.len() {
+            if glyphs[index].character != glyphs[len - 1].character {
+                glyphs[len] = glyphs[index];
+                len += 1;
+            }
+        }
+        let (glyphs, _) = glyphs.split_at_mut(len);
+
+        let find = |c| glyphs.iter().position(|glyph| glyph.character == c);
+        let replacement_character = find(char::REPLACEMENT_CHARACTER)
+            .or_else(|| find(' '))
+            .unwrap_or_default();
+
+        let glyphs: &'a [BdfGlyph] = glyphs;
+        let data: &'a [u8] = data;
+
+        Ok(Self {
+            replacement_character,
+            glyphs,
+            data,
+            pixel_size: size.height,
+            font_ascent: font.baseline + 1,
+            font_descent: size.height.saturating_sub(font.baseline + 1),
+            vertical_metrics: &[],
+            scalable_widths: &[],
+            bitmap_layout: BitmapLayout::default(),
+            extents: FontExtents::from_glyphs(glyphs),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, text::BdfTextStyle, BdfGlyph};
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X10, MonoTextStyle, MonoTextStyleBuilder},
+        text::{renderer::CharacterStyle, Baseline, DecorationColor, Text},
+    };
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn draw_mono_font() {
+        let mut data = [0; 240];
+        assert_eq!(FONT.mono_font_data_len(), 240);
+        let mono_font = FONT.to_mono_font(&mut data).unwrap();
+
+        let mono_style = MonoTextStyleBuilder::new()
+            .font(&mono_font)
+            .text_color(BinaryColor::On)
+            .underline()
+            .strikethrough()
+            .build();
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        Text::with_baseline("AQZ", Point::new(0, 8), mono_style, Baseline::Alphabetic)
+            .draw(&mut display)
+            .unwrap();
+
+        let mut style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        style.set_underline_color(DecorationColor::TextColor);
+        style.set_strikethrough_color(DecorationColor::TextColor);
+        let mut expected = MockDisplay::new();
+        expected.set_allow_overdraw(true);
+        Text::with_baseline("AQZ", Point::new(0, 8), style, Baseline::Alphabetic)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn from_mono_font() {
+        let chars = "XA ";
+        let mut glyphs = [FONT.glyphs[0]; 3];
+        let mut data = [0; 23];
+        assert_eq!(BdfFont::from_mono_font_data_len(&FONT_6X10, chars), 23);
+        let font = BdfFont::from_mono_font(&FONT_6X10, chars, &mut glyphs, &mut data).unwrap();
+
+        assert_eq!(font.glyphs[0].character, ' ');
+        assert_eq!(font.replacement_character, 0);
+        assert_eq!((font.font_ascent, font.font_descent), (8, 2));
+
+        let mut display = MockDisplay::new();
+        Text::new(
+            "AX?",
+            Point::new(0, 7),
+            BdfTextStyle::new(&font, BinaryColor::On),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        let mut expected = MockDisplay::new();
+        Text::new(
+            "AX ",
+            Point::new(0, 7),
+            MonoTextStyle::new(&FONT_6X10, BinaryColor::On),
+        )
+        .draw(&mut expected)
+        .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn errors() {
+        let mut data = [0; 16];
+        assert_eq!(
+            FONT.to_mono_font(&mut data).unwrap_err(),
+            MonoFontError::BufferTooSmall
+        );
+
+        let glyphs = [
+            BdfGlyph {
+                character: 'a',
+                bounding_box: Rectangle::new(Point::new(0, -1), Size::new(2, 2)),
+                device_width: 2,
+                start_index: 0,
+            },
+            BdfGlyph {
+                character: 'b',
+                bounding_box: Rectangle::new(Point::new(0, -1), Size::new(2, 2)),
+                device_width: 3,
+                start_index: 0,
+            },
+        ];
+        let mut font = BdfFont {
+            replacement_character: 0,
+            glyphs: &glyphs,
+            data: &[0],
+            pixel_size: 2,
+            font_ascent: 2,
+            font_descent: 0,
+            vertical_metrics: &[],
+            scalable_widths: &[],
+            bitmap_layout: BitmapLayout::default(),
+            extents: FontExtents::default(),
+        };
+        assert_eq!(
+            font.to_mono_font(&mut data).unwrap_err(),
+            MonoFontError::NotMonospaced
+        );
+
+        font.glyphs = &glyphs[..1];
+        font.font_ascent = 1;
+        assert_eq!(
+            font.to_mono_font(&mut data).unwrap_err(),
+            MonoFontError::GlyphOutsideCell('a')
+        );
+
+        let mut glyphs = [glyphs[0]; 1];
+        assert_eq!(
+            BdfFont::from_mono_font(&FONT_6X10, "ab", &mut glyphs, &mut data).unwrap_err(),
+            MonoFontError::BufferTooSmall
+        );
+        assert_eq!(
+            BdfFont::from_mono_font(&FONT_6X10, "", &mut glyphs, &mut data).unwrap_err(),
+            MonoFontError::NoCharacters
+        );
+    }
+
+    #[test]
+    fn from_mono_font_duplicates() {
+        let chars = "BABA";
+        let mut glyphs = [FONT.glyphs[0]; 4];
+        let mut data = [0; 30];
+        let font = BdfFont::from_mono_font(&FONT_6X10, chars, &mut glyphs, &mut data).unwrap();
+
+        let characters = font.glyphs.iter().map(|glyph| glyph.character);
+        assert!(characters.eq(['A', 'B']));
+        assert_eq!(font.validate(), Ok(()));
+        assert_eq!(font.get_glyph('B').character, 'B');
+    }
+}
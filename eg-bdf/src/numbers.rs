@@ -0,0 +1,445 @@
+//! Number drawing.
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::text::BdfTextStyle;
+
+/// Padding used to fill numbers to the minimum width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum Padding {
+    /// Leading spaces before the sign.
+    Space,
+    /// Leading zeros after the sign.
+    Zero,
+}
+
+/// Sign display mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum Sign {
+    /// Only display a sign for negative numbers.
+    Negative,
+    /// Display a sign for all numbers, including zero.
+    Always,
+}
+
+/// Horizontal alignment of numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum NumberAlignment {
+    /// The right edge of the number is aligned to the position.
+    Right,
+    /// The left edge of the decimal separator is aligned to the position.
+    ///
+    /// Numbers without decimals are aligned like [`Right`](Self::Right).
+    DecimalSeparator,
+}
+
+/// Number format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct NumberFormat {
+    width: u32,
+    padding: Padding,
+    sign: Sign,
+    decimal_separator: char,
+}
+
+impl NumberFormat {
+    /// Creates a new number format.
+    ///
+    /// The default format has no minimum width, only displays a sign for negative numbers and
+    /// uses `.` as the decimal separator.
+    pub const fn new() -> Self {
+        Self {
+            width: 0,
+            padding: Padding::Space,
+            sign: Sign::Negative,
+            decimal_separator: '.',
+        }
+    }
+
+    /// Sets the minimum width in characters.
+    pub const fn with_width(self, width: u32, padding: Padding) -> Self {
+        Self {
+            width,
+            padding,
+            ..self
+        }
+    }
+
+    /// Sets the sign display mode.
+    pub const fn with_sign(self, sign: Sign) -> Self {
+        Self { sign, ..self }
+    }
+
+    /// Sets the decimal separator.
+    pub const fn with_decimal_separator(self, decimal_separator: char) -> Self {
+        Self {
+            decimal_separator,
+            ..self
+        }
+    }
+
+    /// Formats a fixed point number into a stack allocated buffer.
+    pub(crate) fn format(&self, value: i32, decimals: u32) -> NumberBuffer {
+        let mut buffer = NumberBuffer {
+            bytes: [0; NUMBER_BUFFER_SIZE],
+            start: NUMBER_BUFFER_SIZE,
+        };
+
+        let mut magnitude = value.unsigned_abs();
+        let mut digits = 0;
+        let mut chars = 0;
+
+        while magnitude > 0 || digits <= decimals {
+            if digits == decimals && decimals > 0 {
+                buffer.push_char(self.decimal_separator);
+                chars += 1;
+            }
+
+            buffer.push_byte(b'0' + (magnitude % 10) as u8);
+            magnitude /= 10;
+            digits += 1;
+            chars += 1;
+        }
+
+        let sign = if value < 0 {
+            Some(b'-')
+        } else if self.sign == Sign::Always {
+            Some(b'+')
+        } else {
+            None
+        };
+        let sign_width = u32::from(sign.is_some());
+
+        if self.padding == Padding::Zero {
+            while chars + sign_width < self.width && buffer.start > 1 {
+                buffer.push_byte(b'0');
+                chars += 1;
+            }
+        }
+
+        if let Some(sign) = sign {
+            buffer.push_byte(sign);
+            chars += 1;
+        }
+
+        while chars < self.width && buffer.start > 0 {
+            buffer.push_byte(b' ');
+            chars += 1;
+        }
+
+        buffer
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const NUMBER_BUFFER_SIZE: usize = 40;
+
+/// Stack allocated buffer for formatted numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct NumberBuffer {
+    bytes: [u8; NUMBER_BUFFER_SIZE],
+    start: usize,
+}
+
+impl NumberBuffer {
+    fn push_byte(&mut self, byte: u8) {
+        self.start -= 1;
+        self.bytes[self.start] = byte;
+    }
+
+    fn push_char(&mut self, c: char) {
+        let mut encoded = [0; 4];
+        let encoded = c.encode_utf8(&mut encoded).as_bytes();
+
+        self.start -= encoded.len();
+        self.bytes[self.start..self.start + encoded.len()].copy_from_slice(encoded);
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        // The buffer only contains ASCII characters and complete UTF-8 sequences.
+        core::str::from_utf8(&self.bytes[self.start..]).unwrap()
+    }
+}
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Draws an integer.
+    ///
+    /// The number is formatted without using `core::fmt`. Returns the position after the last
+    /// character.
+    pub fn draw_integer<D>(
+        &self,
+        value: i32,
+        format: &NumberFormat,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        self.draw_fixed_point(value, 0, format, position, baseline, target)
+    }
+
+    /// Draws a fixed point number.
+    ///
+    /// The displayed value is `value / 10^decimals`, e.g. a `value` of `-1234` with two
+    /// `decimals` is drawn as `-12.34`. The number is formatted without using `core::fmt`.
+    /// Returns the position after the last character.
+    pub fn draw_fixed_point<D>(
+        &self,
+        value: i32,
+        decimals: u32,
+        format: &NumberFormat,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let buffer = format.format(value, decimals);
+        self.draw_string(buffer.as_str(), position, baseline, target)
+    }
+
+    /// Draws an aligned fixed point number.
+    ///
+    /// The number is formatted like [`draw_fixed_point`](Self::draw_fixed_point) and positioned
+    /// so that its right edge or decimal separator lands on `position.x`. If a background color
+    /// is set, the cell reserved for the number is cleared, which erases longer numbers that
+    /// were drawn before. The cell spans the minimum width of `format`, with each character using
+    /// the [`digit_width`](crate::BdfFont::digit_width) of the font. The characters after the
+    /// decimal separator are reserved to the right of `position.x`, all other characters to the
+    /// left.
+    ///
+    /// Returns the position after the last character.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_fixed_point_aligned<D>(
+        &self,
+        value: i32,
+        decimals: u32,
+        format: &NumberFormat,
+        alignment: NumberAlignment,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let buffer = format.format(value, decimals);
+        let text = buffer.as_str();
+
+        let (integer, fraction) = match alignment {
+            NumberAlignment::DecimalSeparator if decimals > 0 => {
+                text.split_at(text.find(format.decimal_separator).unwrap_or(text.len()))
+            }
+            _ => (text, ""),
+        };
+
+        let start = position.x - self.text_advance(integer);
+        let end = position.x + self.text_advance(fraction);
+
+        if let Some(background_color) = self.background_color() {
+            let digit_width = self.font().digit_width() as i32;
+            let fraction_chars = fraction.chars().count() as i32;
+            let left = position.x - (format.width as i32 - fraction_chars).max(0) * digit_width;
+            let right = position.x + fraction_chars * digit_width;
+
+            let top = self.alphabetic_position(position, baseline).y
+                - (self.font().font_ascent as i32 - 1);
+            let height = self.full_height();
+
+            for &(from, to) in [(left, start), (end, right)].iter() {
+                if to > from {
+                    let area = Rectangle::new(
+                        Point::new(from, top),
+                        Size::new((to - from) as u32, height),
+                    );
+                    target.fill_solid(&area, background_color)?;
+                }
+            }
+        }
+
+        self.draw_string(text, Point::new(start, position.y), baseline, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont, BdfGlyph, BitmapLayout, FontExtents};
+    use alloc::string::{String, ToString};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", '0'..='9' | '+' | '-' | '.' | ' ');
+
+    fn format(value: i32, decimals: u32, format: NumberFormat) -> String {
+        format.format(value, decimals).as_str().to_string()
+    }
+
+    #[test]
+    fn format_integer() {
+        let default = NumberFormat::new();
+
+        assert_eq!(format(0, 0, default), "0");
+        assert_eq!(format(1234, 0, default), "1234");
+        assert_eq!(format(-56, 0, default), "-56");
+        assert_eq!(format(i32::MIN, 0, default), "-2147483648");
+        assert_eq!(format(7, 0, default.with_sign(Sign::Always)), "+7");
+    }
+
+    #[test]
+    fn format_padding() {
+        let spaces = NumberFormat::new().with_width(5, Padding::Space);
+        let zeros = NumberFormat::new().with_width(5, Padding::Zero);
+
+        assert_eq!(format(-12, 0, spaces), "  -12");
+        assert_eq!(format(-12, 0, zeros), "-0012");
+        assert_eq!(format(123456, 0, zeros), "123456");
+    }
+
+    #[test]
+    fn format_fixed_point() {
+        let default = NumberFormat::new();
+
+        assert_eq!(format(1234, 2, default), "12.34");
+        assert_eq!(format(-5, 2, default), "-0.05");
+        assert_eq!(format(0, 1, default), "0.0");
+        assert_eq!(
+            format(15, 1, default.with_decimal_separator('٫')),
+            "1\u{66b}5"
+        );
+        assert_eq!(
+            format(15, 1, default.with_width(6, Padding::Zero)),
+            "0001.5"
+        );
+    }
+
+    #[test]
+    fn draw_number() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let format = NumberFormat::new().with_width(4, Padding::Zero);
+
+        let mut expected = MockDisplay::new();
+        let expected_next = style
+            .draw_string("-0.5", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_fixed_point(-5, 1, &format, Point::zero(), Baseline::Top, &mut display)
+            .unwrap();
+
+        assert_eq!(display, expected);
+        assert_eq!(next, expected_next);
+    }
+
+    #[test]
+    fn draw_aligned() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let format = NumberFormat::new();
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("-0.5", Point::new(6, 0), Baseline::Top, &mut expected)
+            .unwrap();
+
+        for (alignment, x) in [
+            (NumberAlignment::Right, 30),
+            (NumberAlignment::DecimalSeparator, 18),
+        ] {
+            let mut display = MockDisplay::new();
+            let next = style
+                .draw_fixed_point_aligned(
+                    -5,
+                    1,
+                    &format,
+                    alignment,
+                    Point::new(x, 0),
+                    Baseline::Top,
+                    &mut display,
+                )
+                .unwrap();
+
+            assert_eq!(display, expected);
+            assert_eq!(next, Point::new(30, 0));
+        }
+    }
+
+    #[test]
+    fn clear_aligned_cell() {
+        // Proportional font with empty glyphs, which only draws the cleared cell.
+        let glyph = |character, device_width| BdfGlyph {
+            character,
+            device_width,
+            ..BdfGlyph::default()
+        };
+        let mut glyphs = [glyph('0', 4); 12];
+        glyphs[0] = glyph(' ', 2);
+        glyphs[1] = glyph('.', 2);
+        for (glyph, character) in glyphs[2..].iter_mut().zip('0'..='9') {
+            glyph.character = character;
+        }
+        let font = BdfFont {
+            replacement_character: 0,
+            glyphs: &glyphs,
+            data: &[],
+            pixel_size: 4,
+            font_ascent: 3,
+            font_descent: 1,
+            vertical_metrics: &[],
+            scalable_widths: &[],
+            bitmap_layout: BitmapLayout::default(),
+            extents: FontExtents::from_glyphs(&glyphs),
+        };
+        let style = BdfTextStyle::new(&font, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_fixed_point_aligned(
+                12,
+                0,
+                &NumberFormat::new().with_width(3, Padding::Space),
+                NumberAlignment::Right,
+                Point::new(12, 0),
+                Baseline::Top,
+                &mut display,
+            )
+            .unwrap();
+        assert_eq!(next.x, 12);
+        display.assert_pattern(&["..", "..", "..", ".."]);
+
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_fixed_point_aligned(
+                15,
+                1,
+                &NumberFormat::new().with_width(4, Padding::Space),
+                NumberAlignment::DecimalSeparator,
+                Point::new(8, 0),
+                Baseline::Top,
+                &mut display,
+            )
+            .unwrap();
+        assert_eq!(next.x, 14);
+        display.assert_pattern(&[
+            "..            ..",
+            "..            ..",
+            "..            ..",
+            "..            ..",
+        ]);
+    }
+}
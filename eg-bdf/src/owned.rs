@@ -0,0 +1,630 @@
+//! Owned fonts.
+//!
+//! [`OwnedBdfFont`] stores the glyph and bitmap tables in vectors, which makes it possible to
+//! construct, modify and cache fonts at runtime. All rendering code uses the borrowed
+//! [`BdfFont`] type, which can be obtained by calling [`OwnedBdfFont::as_font`].
+//!
+//! Fonts can also be composed from raw glyph bitmaps using a [`FontBuilder`].
+
+use alloc::{vec, vec::Vec};
+
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+
+use crate::{egbf, BdfFont, BdfGlyph, BitmapLayout, FontError, FontExtents, VerticalMetrics};
+
+/// Policy for characters which are included in both fonts in [`OwnedBdfFont::merge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum MergePolicy {
+    /// Keep the existing glyph (first wins).
+    KeepExisting,
+    /// Replace the existing glyph with the glyph from the merged font (last wins).
+    ReplaceExisting,
+}
+
+/// Font with owned glyph and bitmap tables.
+///
+/// The fields have the same meaning as the fields of [`BdfFont`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedBdfFont {
+    pub replacement_character: usize,
+    pub glyphs: Vec<BdfGlyph>,
+    pub data: Vec<u8>,
+
+    pub pixel_size: u32,
+    pub font_ascent: u32,
+    pub font_descent: u32,
+
+    /// Vertical metrics of the glyphs, in the same order as `glyphs`.
+    pub vertical_metrics: Vec<VerticalMetrics>,
+    /// Scalable advance widths of the glyphs in thousandths of a pixel.
+    pub scalable_widths: Vec<i32>,
+
+    /// Layout of the bitmap data.
+    pub bitmap_layout: BitmapLayout,
+
+    /// Extents of the glyphs.
+    ///
+    /// The extents are updated automatically if glyphs are added using the methods of this type.
+    pub extents: FontExtents,
+}
+
+impl OwnedBdfFont {
+    /// Returns a borrowed font, which can be used for rendering.
+    pub fn as_font(&self) -> BdfFont<'_> {
+        BdfFont {
+            replacement_character: self.replacement_character,
+            glyphs: &self.glyphs,
+            data: &self.data,
+            pixel_size: self.pixel_size,
+            font_ascent: self.font_ascent,
+            font_descent: self.font_descent,
+            vertical_metrics: &self.vertical_metrics,
+            scalable_widths: &self.scalable_widths,
+            bitmap_layout: self.bitmap_layout,
+            extents: self.extents,
+        }
+    }
+
+    /// Loads a font in the EGBF format.
+    ///
+    /// Unlike [`egbf::load`] this function copies the bitmap data.
+    pub fn from_egbf(input: &[u8]) -> Result<Self, FontError> {
+        let header = egbf::Header::parse(input)?;
+
+        let mut glyphs = vec![BdfGlyph::default(); header.glyph_count];
+        let mut scalable_widths = vec![0; header.scalable_width_count()];
+        let mut vertical_metrics =
+            vec![VerticalMetrics::default(); header.vertical_metrics_count()];
+        let font = egbf::load(
+            input,
+            &mut glyphs,
+            &mut scalable_widths,
+            &mut vertical_metrics,
+        )?;
+
+        Ok(Self::from(&font))
+    }
+
+    /// Appends the font in the EGBF format to `output`.
+    ///
+    /// The EGBF format can be used to store fonts which were built or modified at runtime, e.g.
+    /// in flash or on an SD card, and to load them without copying using [`egbf::load`].
+    ///
+    /// Returns [`FontError::TooLarge`] if the font exceeds the limits of the format.
+    pub fn to_egbf(&self, output: &mut Vec<u8>) -> Result<(), FontError> {
+        egbf::write(&self.as_font(), output)
+    }
+
+    /// Adds a glyph or replaces the glyph for an existing character.
+    ///
+    /// `bounding_box` is relative to the origin of the glyph on the alphabetic baseline and
+    /// `bitmap` contains the rows of the glyph, padded to whole bytes, with the leftmost pixel in
+    /// the most significant bit. The glyph table is kept sorted and the replacement character
+    /// keeps pointing to the same glyph. Returns the replaced glyph.
+    ///
+    /// If the font contains scalable widths or vertical metrics, the new glyph uses its device
+    /// width as scalable width and default vertical metrics. The bitmap of a replaced glyph
+    /// isn't removed from the bitmap data.
+    pub fn insert_glyph(
+        &mut self,
+        character: char,
+        bounding_box: Rectangle,
+        device_width: i32,
+        bitmap: &[u8],
+    ) -> Option<BdfGlyph> {
+        let stride = bounding_box.size.width.div_ceil(8) as usize;
+        let glyph = BdfGlyph {
+            character,
+            bounding_box,
+            device_width,
+            start_index: 0,
+        };
+
+        let replaced = self.insert(glyph, None, None, |point| {
+            let x = point.x as usize;
+            bitmap
+                .get(point.y as usize * stride + x / 8)
+                .is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0)
+        });
+        self.extents = FontExtents::from_glyphs(&self.glyphs);
+
+        replaced
+    }
+
+    /// Merges the glyphs of another font into this font.
+    ///
+    /// Characters which are only included in `other` are added to this font and `policy`
+    /// determines which glyph is used for characters which are included in both fonts. The
+    /// bitmaps are converted into the bitmap layout of this font and the font metrics of this
+    /// font aren't changed.
+    ///
+    /// Scalable widths and vertical metrics of the merged glyphs are copied from `other`, if this
+    /// font also provides them.
+    pub fn merge(&mut self, other: &BdfFont<'_>, policy: MergePolicy) {
+        for (index, glyph) in other.glyphs.iter().enumerate() {
+            if policy == MergePolicy::KeepExisting && self.glyph_position(glyph.character).is_ok() {
+                continue;
+            }
+
+            self.insert(
+                *glyph,
+                other.scalable_widths.get(index).copied(),
+                other.vertical_metrics.get(index).copied(),
+                |point| glyph.pixel_with_layout(other.data, other.bitmap_layout, point),
+            );
+        }
+
+        self.extents = FontExtents::from_glyphs(&self.glyphs);
+    }
+
+    /// Returns the position of the glyph for a character in the glyph table.
+    ///
+    /// If the font doesn't contain the character, the position where it would be inserted is
+    /// returned as an error.
+    fn glyph_position(&self, c: char) -> Result<usize, usize> {
+        self.glyphs
+            .binary_search_by_key(&c, |glyph| glyph.character)
+    }
+
+    /// Adds or replaces a glyph.
+    ///
+    /// The bitmap is read from `is_set`, which is called with points relative to the top left
+    /// corner of the bounding box, and the start index of `glyph` is ignored. Default values are
+    /// used if the scalable width or vertical metrics aren't given. The extents must be updated
+    /// by the caller.
+    fn insert(
+        &mut self,
+        mut glyph: BdfGlyph,
+        scalable_width: Option<i32>,
+        vertical_metrics: Option<VerticalMetrics>,
+        is_set: impl Fn(Point) -> bool,
+    ) -> Option<BdfGlyph> {
+        glyph.start_index = self.push_bitmap(glyph.bounding_box.size, is_set);
+
+        let scalable_width = scalable_width.unwrap_or(glyph.device_width * 1000);
+        let vertical_metrics = vertical_metrics.unwrap_or(VerticalMetrics {
+            origin: Point::new(glyph.device_width / 2, -(self.font_ascent as i32 - 1)),
+            advance: (self.font_ascent + self.font_descent) as i32,
+        });
+
+        match self.glyph_position(glyph.character) {
+            Ok(index) => {
+                if let Some(width) = self.scalable_widths.get_mut(index) {
+                    *width = scalable_width;
+                }
+                if let Some(metrics) = self.vertical_metrics.get_mut(index) {
+                    *metrics = vertical_metrics;
+                }
+
+                Some(core::mem::replace(&mut self.glyphs[index], glyph))
+            }
+            Err(index) => {
+                if !self.scalable_widths.is_empty() {
+                    self.scalable_widths.insert(index, scalable_width);
+                }
+                if !self.vertical_metrics.is_empty() {
+                    self.vertical_metrics.insert(index, vertical_metrics);
+                }
+                if index <= self.replacement_character && !self.glyphs.is_empty() {
+                    self.replacement_character += 1;
+                }
+
+                self.glyphs.insert(index, glyph);
+                None
+            }
+        }
+    }
+
+    /// Appends a glyph bitmap to the bitmap data and returns the start index.
+    ///
+    /// The pixels are read from `is_set` and stored in the layout of the font.
+    fn push_bitmap(&mut self, size: Size, is_set: impl Fn(Point) -> bool) -> usize {
+        // Glyphs always start at a byte boundary, which is required for byte aligned rows.
+        let start_index = self.data.len() * 8;
+        let row_stride = self.bitmap_layout.row_stride(size.width);
+
+        let bits = row_stride * size.height as usize;
+        self.data.resize(self.data.len() + bits.div_ceil(8), 0);
+
+        for point in Rectangle::new(Point::zero(), size).points() {
+            if is_set(point) {
+                let index = start_index + point.y as usize * row_stride + point.x as usize;
+                self.data[index / 8] |= self.bitmap_layout.mask(index);
+            }
+        }
+
+        start_index
+    }
+}
+
+/// Builder for fonts which are composed at runtime.
+///
+/// Glyphs are added from raw bitmaps, e.g. for glyphs which are generated on the device. The
+/// glyphs don't need to be added in a specific order and adding a glyph for a character which
+/// was already added replaces the previous glyph.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FontBuilder {
+    font: OwnedBdfFont,
+    replacement_character: Option<char>,
+}
+
+impl FontBuilder {
+    /// Creates a new font builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the pixel size.
+    pub fn pixel_size(mut self, pixel_size: u32) -> Self {
+        self.font.pixel_size = pixel_size;
+        self
+    }
+
+    /// Sets the ascent above the baseline.
+    pub fn ascent(mut self, ascent: u32) -> Self {
+        self.font.font_ascent = ascent;
+        self
+    }
+
+    /// Sets the descent below the baseline.
+    pub fn descent(mut self, descent: u32) -> Self {
+        self.font.font_descent = descent;
+        self
+    }
+
+    /// Sets the layout of the bitmap data of the built font.
+    ///
+    /// This must be set before any glyphs are added.
+    pub fn bitmap_layout(mut self, bitmap_layout: BitmapLayout) -> Self {
+        self.font.bitmap_layout = bitmap_layout;
+        self
+    }
+
+    /// Sets the replacement character.
+    ///
+    /// The replacement character is used for characters which aren't included in the font. If
+    /// it isn't set, `U+FFFD`, the space character or the first glyph is used instead.
+    pub fn replacement_character(mut self, replacement_character: char) -> Self {
+        self.replacement_character = Some(replacement_character);
+        self
+    }
+
+    /// Adds a glyph.
+    ///
+    /// `bounding_box` is relative to the origin of the glyph on the alphabetic baseline and
+    /// `bitmap` contains the rows of the glyph, padded to whole bytes, with the leftmost pixel in
+    /// the most significant bit. This is the same format which is used in BDF files.
+    pub fn glyph(
+        mut self,
+        character: char,
+        bounding_box: Rectangle,
+        device_width: i32,
+        bitmap: &[u8],
+    ) -> Self {
+        self.font
+            .insert_glyph(character, bounding_box, device_width, bitmap);
+        self
+    }
+
+    /// Builds the font.
+    ///
+    /// Returns [`FontError::UnsupportedGlyph`] if the font doesn't contain a glyph for the
+    /// replacement character which was set using [`replacement_character`] and
+    /// [`FontError::InvalidData`] if no glyphs were added.
+    ///
+    /// [`replacement_character`]: Self::replacement_character
+    pub fn build(self) -> Result<OwnedBdfFont, FontError> {
+        let mut font = self.font;
+
+        let find = |c| font.glyph_position(c).ok();
+        font.replacement_character = match self.replacement_character {
+            Some(c) => find(c).ok_or(FontError::UnsupportedGlyph(c))?,
+            None => find(char::REPLACEMENT_CHARACTER)
+                .or_else(|| find(' '))
+                .unwrap_or_default(),
+        };
+
+        font.as_font().validate()?;
+
+        Ok(font)
+    }
+}
+
+impl From<&BdfFont<'_>> for OwnedBdfFont {
+    fn from(font: &BdfFont<'_>) -> Self {
+        Self {
+            replacement_character: font.replacement_character,
+            glyphs: font.glyphs.to_vec(),
+            data: font.data.to_vec(),
+            pixel_size: font.pixel_size,
+            font_ascent: font.font_ascent,
+            font_descent: font.font_descent,
+            vertical_metrics: font.vertical_metrics.to_vec(),
+            scalable_widths: font.scalable_widths.to_vec(),
+            bitmap_layout: font.bitmap_layout,
+            extents: font.extents,
+        }
+    }
+}
+
+impl BdfFont<'_> {
+    /// Returns a copy of the font with owned glyph and bitmap tables.
+    pub fn to_owned(&self) -> OwnedBdfFont {
+        OwnedBdfFont::from(self)
+    }
+
+    /// Returns a copy of the font which only contains the characters in `chars`.
+    ///
+    /// The replacement character is always included. The bitmap data of the subset only
+    /// contains the bitmaps of the included glyphs, which makes it possible to reduce the memory
+    /// usage of large fonts.
+    pub fn subset(&self, chars: &str) -> OwnedBdfFont {
+        let mut font = OwnedBdfFont {
+            pixel_size: self.pixel_size,
+            font_ascent: self.font_ascent,
+            font_descent: self.font_descent,
+            bitmap_layout: self.bitmap_layout,
+            ..OwnedBdfFont::default()
+        };
+
+        let mut chars: Vec<char> = chars.chars().collect();
+        chars.sort_unstable();
+        chars.dedup();
+
+        for (index, glyph) in self.glyphs.iter().enumerate() {
+            if index == self.replacement_character {
+                font.replacement_character = font.glyphs.len();
+            } else if chars.binary_search(&glyph.character).is_err() {
+                continue;
+            }
+
+            let start_index = font.push_bitmap(glyph.bounding_box.size, |point| {
+                glyph.pixel_with_layout(self.data, self.bitmap_layout, point)
+            });
+            font.glyphs.push(BdfGlyph {
+                start_index,
+                ..*glyph
+            });
+            font.scalable_widths.extend(self.scalable_widths.get(index));
+            font.vertical_metrics
+                .extend(self.vertical_metrics.get(index));
+        }
+        font.extents = FontExtents::from_glyphs(&font.glyphs);
+
+        font
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BitOrder, RowLayout};
+    use alloc::string::String;
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn round_trip() {
+        let owned = FONT.to_owned();
+        assert_eq!(owned.glyphs.len(), FONT.glyphs.len());
+        assert_eq!(owned.as_font(), FONT);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut owned = FONT.to_owned();
+        owned.vertical_metrics = vec![
+            VerticalMetrics {
+                origin: Point::new(-3, -8),
+                advance: 10,
+            };
+            owned.glyphs.len()
+        ];
+        owned.bitmap_layout = BitmapLayout::new(BitOrder::LsbFirst, RowLayout::ByteAligned);
+
+        let json = serde_json::to_string(&owned).unwrap();
+        assert_eq!(serde_json::from_str::<OwnedBdfFont>(&json).unwrap(), owned);
+    }
+
+    #[test]
+    fn build_font() {
+        let bar = Rectangle::new(Point::new(0, -3), Size::new(2, 4));
+        let font = FontBuilder::new()
+            .pixel_size(4)
+            .ascent(4)
+            .glyph('b', bar, 3, &[0x80, 0x80, 0xC0, 0xC0])
+            .glyph('a', bar, 3, &[0x00, 0x00, 0x00, 0xC0])
+            .glyph('?', Rectangle::zero(), 1, &[])
+            .replacement_character('?')
+            .build()
+            .unwrap();
+
+        let font = font.as_font();
+        assert_eq!(font.glyphs.len(), 3);
+        assert_eq!(font.get_glyph('x').character, '?');
+
+        let glyph = font.get_glyph('b');
+        assert_eq!(glyph.bounding_box, bar);
+        assert!(glyph.pixel(font.data, Point::new(0, 0)));
+        assert!(!glyph.pixel(font.data, Point::new(1, 1)));
+        assert!(glyph.pixel(font.data, Point::new(1, 3)));
+        assert!(font.get_glyph('a').pixel(font.data, Point::new(1, 3)));
+    }
+
+    #[test]
+    fn build_font_with_layout() {
+        let layout = BitmapLayout::new(BitOrder::LsbFirst, RowLayout::ByteAligned);
+        let owned = FontBuilder::new()
+            .bitmap_layout(layout)
+            .glyph(
+                'a',
+                Rectangle::new(Point::new(0, -1), Size::new(3, 2)),
+                4,
+                &[0b1010_0000, 0b0100_0000],
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(owned.data, [0b0000_0101, 0b0000_0010]);
+    }
+
+    #[test]
+    fn build_invalid_font() {
+        assert_eq!(FontBuilder::new().build(), Err(FontError::InvalidData));
+
+        let space = FontBuilder::new().glyph(' ', Rectangle::zero(), 4, &[]);
+        assert_eq!(
+            space.clone().replacement_character('?').build(),
+            Err(FontError::UnsupportedGlyph('?'))
+        );
+        assert_eq!(space.build().unwrap().replacement_character, 0);
+    }
+
+    #[test]
+    fn insert_glyphs() {
+        let mut owned = FONT.to_owned();
+        assert_eq!(owned.as_font().get_glyph('x').character, ' ');
+
+        let logo = Rectangle::new(Point::new(0, -7), Size::new(6, 8));
+        let replaced = owned.insert_glyph('A', logo, 6, &[0xFC; 8]);
+        assert_eq!(replaced, Some(*FONT.get_glyph('A')));
+        assert_eq!(owned.insert_glyph('\u{A9}', logo, 6, &[0xFC; 8]), None);
+        assert_eq!(owned.insert_glyph('!', logo, 6, &[0x00; 8]), None);
+
+        let font = owned.as_font();
+        assert_eq!(font.glyphs.len(), FONT.glyphs.len() + 2);
+        assert_eq!(
+            font.max_bounding_box(),
+            Rectangle::new(Point::new(0, -7), Size::new(6, 10))
+        );
+        assert!(font
+            .glyphs
+            .windows(2)
+            .all(|glyphs| glyphs[0].character < glyphs[1].character));
+        assert_eq!(font.get_glyph('x').character, ' ');
+        assert_eq!(font.get_glyph('B'), FONT.get_glyph('B'));
+
+        for c in ['A', '\u{A9}'] {
+            let glyph = font.get_glyph(c);
+            assert_eq!(glyph.character, c);
+            assert!(Rectangle::new(Point::zero(), logo.size)
+                .points()
+                .all(|point| glyph.pixel(font.data, point)));
+        }
+    }
+
+    #[test]
+    fn insert_glyph_with_scalable_widths() {
+        let mut owned = FONT.to_owned();
+        owned.scalable_widths = vec![6000; owned.glyphs.len()];
+
+        owned.insert_glyph('a', Rectangle::zero(), 5, &[]);
+        assert_eq!(owned.as_font().scalable_width('a'), Some(5000));
+        assert_eq!(owned.scalable_widths.len(), owned.glyphs.len());
+    }
+
+    #[test]
+    fn merge_fonts() {
+        const DIGITS: BdfFont = include_bdf!("examples/10x20.bdf", '0'..='9' | 'A');
+
+        let mut owned = FONT.to_owned();
+        owned.merge(&DIGITS, MergePolicy::KeepExisting);
+
+        let font = owned.as_font();
+        assert_eq!(font.glyphs.len(), FONT.glyphs.len() + 10);
+        assert_eq!(font.max_advance(), 10);
+        assert_eq!(font.average_advance(), 7);
+        assert_eq!(font.get_glyph('A'), FONT.get_glyph('A'));
+        assert_eq!(font.get_glyph('x').character, ' ');
+
+        let glyph = font.get_glyph('5');
+        let expected = DIGITS.get_glyph('5');
+        assert_eq!(glyph.bounding_box, expected.bounding_box);
+        assert!(Rectangle::new(Point::zero(), glyph.bounding_box.size)
+            .points()
+            .all(|point| glyph.pixel(font.data, point) == expected.pixel(DIGITS.data, point)));
+
+        let mut owned = FONT.to_owned();
+        owned.bitmap_layout = BitmapLayout::new(BitOrder::LsbFirst, RowLayout::ByteAligned);
+        owned.data.clear();
+        owned.glyphs.clear();
+        owned.merge(&FONT, MergePolicy::KeepExisting);
+        owned.merge(&DIGITS, MergePolicy::ReplaceExisting);
+
+        let font = owned.as_font();
+        let glyph = font.get_glyph('A');
+        let expected = DIGITS.get_glyph('A');
+        assert_eq!(glyph.device_width, 10);
+        assert!(Rectangle::new(Point::zero(), glyph.bounding_box.size)
+            .points()
+            .all(|point| {
+                glyph.pixel_with_layout(font.data, font.bitmap_layout, point)
+                    == expected.pixel(DIGITS.data, point)
+            }));
+    }
+
+    #[test]
+    fn subset() {
+        let subset = FONT.subset("HELLO");
+        assert_eq!(subset.data.len(), 5 * 8);
+
+        let font = subset.as_font();
+        let characters: String = font.glyphs.iter().map(|glyph| glyph.character).collect();
+        assert_eq!(characters, " EHLO");
+        assert_eq!(font.get_glyph('A').character, ' ');
+
+        for c in "HELO".chars() {
+            let glyph = font.get_glyph(c);
+            let expected = FONT.get_glyph(c);
+            assert_eq!(glyph.bounding_box, expected.bounding_box);
+            assert!(Rectangle::new(Point::zero(), glyph.bounding_box.size)
+                .points()
+                .all(|point| glyph.pixel(font.data, point) == expected.pixel(FONT.data, point)));
+        }
+
+        const VERTICAL: BdfFont = include_bdf!("examples/vertical.bdf");
+        let subset = VERTICAL.subset("B");
+        assert_eq!(subset.glyphs.len(), subset.vertical_metrics.len());
+        assert_eq!(
+            subset.as_font().vertical_metrics('B'),
+            VERTICAL.vertical_metrics('B')
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-graphics")]
+    fn modify_owned_font() {
+        use crate::text::BdfTextStyle;
+        use embedded_graphics::{
+            mock_display::MockDisplay, pixelcolor::BinaryColor, prelude::*, text::Text,
+        };
+
+        let mut owned = FONT.to_owned();
+        for glyph in &mut owned.glyphs {
+            glyph.device_width += 1;
+        }
+
+        let font = owned.as_font();
+        let style = BdfTextStyle::new(&font, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        let next = Text::new("II", Point::new(0, 7), style)
+            .draw(&mut display)
+            .unwrap();
+
+        assert_eq!(next, Point::new(14, 7));
+        display.assert_pattern(&[
+            "             ",
+            " ###    ###  ",
+            "  #      #   ",
+            "  #      #   ",
+            "  #      #   ",
+            "  #      #   ",
+            "  #      #   ",
+            " ###    ###  ",
+        ]);
+    }
+}
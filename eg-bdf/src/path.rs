@@ -0,0 +1,219 @@
+//! Text along a path.
+
+use embedded_graphics::prelude::*;
+
+use crate::{
+    cache::RotatedGlyphCache,
+    rotate::{centered_origin, draw_rotated_glyph, sqrt, Rotation},
+    text::BdfTextStyle,
+    BdfGlyph,
+};
+
+/// Text along a piecewise linear path.
+///
+/// The path is a polyline through the given points. The glyphs are placed along the path with
+/// their alphabetic baseline on the path, starting at the first point. Every glyph is rotated so
+/// that its baseline is parallel to the segment which contains the center of its advance. Glyphs
+/// whose center is beyond the end of the path aren't drawn.
+///
+/// Rotated glyphs are drawn without background and decorations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PathText<'a, 'b, C> {
+    text: &'b str,
+    style: BdfTextStyle<'a, C>,
+    points: &'b [Point],
+    offset: u32,
+}
+
+impl<'a, 'b, C: PixelColor> PathText<'a, 'b, C> {
+    /// Creates new path text.
+    pub fn new(text: &'b str, style: BdfTextStyle<'a, C>, points: &'b [Point]) -> Self {
+        Self {
+            text,
+            style,
+            points,
+            offset: 0,
+        }
+    }
+
+    /// Sets the distance along the path at which the text starts.
+    pub fn with_offset(self, offset: u32) -> Self {
+        Self { offset, ..self }
+    }
+}
+
+impl<'a, C: PixelColor> PathText<'a, '_, C> {
+    /// Draws the text using a rotated glyph cache.
+    ///
+    /// The result is the same as drawing the text using [`draw`](Drawable::draw), but glyphs
+    /// which are drawn with the same rotation and subpixel position as in a previous call are
+    /// taken from the cache.
+    pub fn draw_cached<D, const N: usize, const BYTES: usize>(
+        &self,
+        cache: &mut RotatedGlyphCache<'a, N, BYTES>,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let font = self.style.font();
+        let color = self.style.text_color();
+
+        self.draw_glyphs(target, |glyph, origin, rotation, target| {
+            cache.draw_rotated_glyph(glyph, font, origin, rotation, color, target)
+        })
+    }
+
+    /// Positions the glyphs and draws them using the `draw_glyph` callback.
+    ///
+    /// The callback is called with the glyph, the origin of the glyph and its rotation.
+    fn draw_glyphs<D, F>(&self, target: &mut D, mut draw_glyph: F) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        F: FnMut(&'a BdfGlyph, (f32, f32), Rotation, &mut D) -> Result<(), D::Error>,
+    {
+        let mut segments = self.points.windows(2);
+
+        // Start point, direction and length of the current segment.
+        let mut segment: Option<((f32, f32), Rotation, f32)> = None;
+        // Distance along the path at the start of the current segment.
+        let mut segment_start = 0.0;
+
+        for (_, glyph, x) in self.style.glyph_positions(self.text, 0) {
+            let distance = self.offset as f32 + x as f32 + glyph.device_width as f32 / 2.0;
+
+            let (start, rotation) = loop {
+                if let Some((start, rotation, length)) = segment {
+                    if distance < segment_start + length {
+                        break (start, rotation);
+                    }
+                    segment_start += length;
+                    segment = None;
+                }
+
+                let (a, b) = match segments.next() {
+                    Some(&[a, b]) => (a, b),
+                    _ => return Ok(()),
+                };
+                let vector = ((b.x - a.x) as f32, (b.y - a.y) as f32);
+
+                // Segments with zero length are skipped.
+                if let Some(rotation) = Rotation::from_vector(vector) {
+                    let length = sqrt(vector.0 * vector.0 + vector.1 * vector.1);
+                    segment = Some(((a.x as f32, a.y as f32), rotation, length));
+                }
+            };
+
+            let (dx, dy) = rotation.x_axis();
+            let offset = distance - segment_start;
+            let anchor = (start.0 + dx * offset, start.1 + dy * offset);
+
+            draw_glyph(
+                glyph,
+                centered_origin(glyph, anchor, rotation),
+                rotation,
+                target,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: PixelColor> Drawable for PathText<'_, '_, C> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let font = self.style.font();
+        let color = self.style.text_color();
+
+        self.draw_glyphs(target, |glyph, origin, rotation, target| {
+            draw_rotated_glyph(glyph, font, origin, rotation, color, target)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        pixelcolor::BinaryColor,
+        text::{renderer::TextRenderer, Baseline},
+    };
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    fn style() -> BdfTextStyle<'static, BinaryColor> {
+        BdfTextStyle::new(&FONT, BinaryColor::On)
+    }
+
+    fn draw(text: &str, points: &[Point], offset: u32) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        PathText::new(text, style(), points)
+            .with_offset(offset)
+            .draw(&mut display)
+            .unwrap();
+
+        display
+    }
+
+    fn expected(text: &str, position: Point) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        style()
+            .draw_string(text, position, Baseline::Alphabetic, &mut display)
+            .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn straight_path() {
+        let points = [Point::new(0, 10), Point::new(10, 10), Point::new(40, 10)];
+
+        draw("LAB", &points, 0).assert_eq(&expected("LAB", Point::new(0, 10)));
+        draw("LAB", &points, 3).assert_eq(&expected("LAB", Point::new(3, 10)));
+    }
+
+    #[test]
+    fn end_of_path() {
+        let points = [Point::new(0, 10), Point::new(8, 10)];
+
+        draw("LL", &points, 0).assert_eq(&expected("L", Point::new(0, 10)));
+        draw("L", &[Point::new(0, 10)], 0).assert_eq(&MockDisplay::new());
+    }
+
+    #[test]
+    fn bent_path() {
+        let points = [
+            Point::new(0, 10),
+            Point::new(6, 10),
+            Point::new(6, 10),
+            Point::new(6, 40),
+        ];
+
+        // The second glyph is rotated by 90° and centered on the vertical segment.
+        draw("LL", &points, 0).assert_pattern(&[
+            "            ",
+            "            ",
+            "            ",
+            "            ",
+            "#           ",
+            "#           ",
+            "#           ",
+            "#           ",
+            "#           ",
+            "#           ",
+            "############",
+            "     #      ",
+            "     #      ",
+            "     #      ",
+            "     #      ",
+        ]);
+    }
+}
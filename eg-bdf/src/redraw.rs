@@ -0,0 +1,121 @@
+//! Partial redrawing.
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::text::BdfTextStyle;
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Returns `true` if `other` contains the character `c` with the same advance at offset `x`.
+    fn contains_cell(&self, other: &str, cell: (char, i32, i32)) -> bool {
+        // Offsets aren't sorted if the string contains glyphs with negative advances.
+        self.advances(other).any(|other_cell| other_cell == cell)
+    }
+
+    /// Returns the area covered by a character cell.
+    fn cell_rectangle(&self, baseline: Point, x: i32, advance: i32) -> Rectangle {
+        let font = self.font();
+
+        Rectangle::new(
+            baseline + Point::new(x, 1 - font.font_ascent as i32),
+            Size::new(advance.max(0) as u32, font.font_ascent + font.font_descent),
+        )
+    }
+
+    /// Draws `text` over the previously drawn string `previous` and only redraws changed characters.
+    ///
+    /// A character is considered unchanged if the previous string contained the same character at
+    /// the same horizontal offset. All other character cells of the previous string are cleared
+    /// with the background color and the changed characters of the new string are redrawn. A
+    /// character cell covers the advance width of the character and the font ascent and descent.
+    ///
+    /// If the style has no background color, old characters can't be erased and only the changed
+    /// characters are drawn. Ink which extends outside the advance box of a glyph isn't erased.
+    pub fn draw_string_diff<D>(
+        &self,
+        previous: &str,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let alphabetic = self.alphabetic_position(position, baseline);
+
+        if let Some(color) = self.background_color() {
+            for (c, advance, x) in self.advances(previous) {
+                if !self.contains_cell(text, (c, advance, x)) {
+                    target.fill_solid(&self.cell_rectangle(alphabetic, x, advance), color)?;
+                }
+            }
+        }
+
+        let mut width = 0;
+
+        for (c, advance, x) in self.advances(text) {
+            if !self.contains_cell(previous, (c, advance, x)) {
+                self.draw_string(
+                    c.encode_utf8(&mut [0; 4]),
+                    alphabetic + Point::new(x, 0),
+                    Baseline::Alphabetic,
+                    target,
+                )?;
+            }
+
+            width = x + advance;
+        }
+
+        Ok(position + Point::new(width, 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn redraw_changed_characters() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let next = style
+            .draw_string_diff("ABB", "AC", Point::zero(), Baseline::Top, &mut display)
+            .unwrap();
+
+        assert_eq!(next, Point::new(12, 0));
+        display.assert_pattern(&[
+            "      ............",
+            "      .###........",
+            "      #...#.......",
+            "      #...........",
+            "      #...........",
+            "      #...........",
+            "      #...#.......",
+            "      .###........",
+            "      ............",
+            "      ............",
+        ]);
+    }
+
+    #[test]
+    fn unchanged_string() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        let mut display = MockDisplay::new();
+        style
+            .draw_string_diff("AB", "AB", Point::zero(), Baseline::Top, &mut display)
+            .unwrap();
+
+        assert_eq!(display, MockDisplay::new());
+    }
+}
@@ -0,0 +1,294 @@
+//! Rotated glyph drawing.
+//!
+//! `core` doesn't provide trigonometric functions, which is why this module contains a small
+//! polynomial approximation of sine and cosine. The approximation is more than accurate enough to
+//! place and rotate glyphs at display resolution.
+
+use core::f32::consts::FRAC_PI_2;
+
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+use crate::{BdfFont, BdfGlyph};
+
+/// Rounds towards negative infinity.
+pub(crate) fn floor(value: f32) -> i32 {
+    let truncated = value as i32;
+    if truncated as f32 > value {
+        truncated - 1
+    } else {
+        truncated
+    }
+}
+
+/// Returns the square root of a value.
+///
+/// Returns zero for negative values.
+pub(crate) fn sqrt(value: f32) -> f32 {
+    if value <= 0.0 {
+        return 0.0;
+    }
+
+    // Initial estimate based on the exponent, which is refined using Newton's method.
+    let mut root = f32::from_bits((value.to_bits() >> 1) + 0x1FC0_0000);
+    for _ in 0..4 {
+        root = 0.5 * (root + value / root);
+    }
+
+    root
+}
+
+/// Rotation around the origin.
+///
+/// Positive angles rotate clockwise on the display, because the y axis points down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Rotation {
+    sin: f32,
+    cos: f32,
+}
+
+impl Rotation {
+    /// Creates a rotation from an angle in radians.
+    pub(crate) fn from_radians(angle: f32) -> Self {
+        // Reduce the angle to [-PI/4, PI/4] and the quadrant.
+        let quadrants = angle / FRAC_PI_2;
+        let quadrant = if quadrants >= 0.0 {
+            (quadrants + 0.5) as i32
+        } else {
+            (quadrants - 0.5) as i32
+        };
+        let x = angle - quadrant as f32 * FRAC_PI_2;
+        let x2 = x * x;
+
+        let sin = x * (1.0 - x2 / 6.0 * (1.0 - x2 / 20.0 * (1.0 - x2 / 42.0)));
+        let cos = 1.0 - x2 / 2.0 * (1.0 - x2 / 12.0 * (1.0 - x2 / 30.0 * (1.0 - x2 / 56.0)));
+
+        let (sin, cos) = match quadrant.rem_euclid(4) {
+            0 => (sin, cos),
+            1 => (cos, -sin),
+            2 => (-sin, -cos),
+            _ => (-cos, sin),
+        };
+
+        Self { sin, cos }
+    }
+
+    /// Creates a rotation which maps the x axis to the direction of a vector.
+    ///
+    /// Returns `None` if the vector has zero length.
+    pub(crate) fn from_vector((x, y): (f32, f32)) -> Option<Self> {
+        let length = sqrt(x * x + y * y);
+        if length == 0.0 {
+            return None;
+        }
+
+        Some(Self {
+            sin: y / length,
+            cos: x / length,
+        })
+    }
+
+    /// Returns the image of the x axis.
+    pub(crate) fn x_axis(&self) -> (f32, f32) {
+        (self.cos, self.sin)
+    }
+
+    /// Rotates a vector.
+    pub(crate) fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (x * self.cos - y * self.sin, x * self.sin + y * self.cos)
+    }
+
+    /// Rotates a vector in the opposite direction.
+    fn invert(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (x * self.cos + y * self.sin, y * self.cos - x * self.sin)
+    }
+}
+
+/// Transformation of a rotated glyph.
+///
+/// Rotated glyphs are rasterized relative to the integer part of their origin, which makes the
+/// result only depend on the rotation and the fractional part of the origin. The transform is
+/// used as the key of cached rotated glyphs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Transform {
+    rotation: Rotation,
+    fraction: (f32, f32),
+}
+
+impl Transform {
+    /// Splits a glyph origin into a transform and the integer offset of the glyph.
+    pub(crate) fn new(origin: (f32, f32), rotation: Rotation) -> (Self, Point) {
+        let offset = Point::new(floor(origin.0), floor(origin.1));
+        let fraction = (origin.0 - offset.x as f32, origin.1 - offset.y as f32);
+
+        (Self { rotation, fraction }, offset)
+    }
+
+    /// Returns the area covered by the rotated glyph, relative to the glyph offset.
+    pub(crate) fn area(&self, glyph: &BdfGlyph) -> Rectangle {
+        let top_left = glyph.bounding_box.top_left;
+        let size = glyph.bounding_box.size;
+        let (left, top) = (top_left.x as f32, top_left.y as f32);
+        let (right, bottom) = (left + size.width as f32, top + size.height as f32);
+
+        let corners = [(left, top), (right, top), (left, bottom), (right, bottom)];
+        let (mut min, mut max) = ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN));
+        for (x, y) in corners.iter().map(|corner| self.rotation.apply(*corner)) {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+
+        Rectangle::with_corners(
+            Point::new(
+                floor(self.fraction.0 + min.0),
+                floor(self.fraction.1 + min.1),
+            ),
+            Point::new(
+                floor(self.fraction.0 + max.0),
+                floor(self.fraction.1 + max.1),
+            ),
+        )
+    }
+
+    /// Returns `true` if a pixel, relative to the glyph offset, is set in the rotated glyph.
+    ///
+    /// The pixel center is mapped back into the glyph bitmap and the pixel is set if the nearest
+    /// bitmap pixel is set.
+    pub(crate) fn is_set(&self, glyph: &BdfGlyph, font: &BdfFont<'_>, point: Point) -> bool {
+        let top_left = glyph.bounding_box.top_left;
+        let size = glyph.bounding_box.size;
+
+        let offset = (
+            point.x as f32 + 0.5 - self.fraction.0,
+            point.y as f32 + 0.5 - self.fraction.1,
+        );
+        let (x, y) = self.rotation.invert(offset);
+        let bitmap_point = Point::new(floor(x - top_left.x as f32), floor(y - top_left.y as f32));
+
+        bitmap_point.x >= 0
+            && bitmap_point.y >= 0
+            && (bitmap_point.x as u32) < size.width
+            && (bitmap_point.y as u32) < size.height
+            && glyph.pixel_with_layout(font.data, font.bitmap_layout, bitmap_point)
+    }
+}
+
+/// Returns the origin of a rotated glyph centered on a point.
+///
+/// The glyph is positioned so that the center of its advance on the alphabetic baseline is
+/// located at `anchor`.
+pub(crate) fn centered_origin(
+    glyph: &BdfGlyph,
+    anchor: (f32, f32),
+    rotation: Rotation,
+) -> (f32, f32) {
+    let half_advance = glyph.device_width as f32 / 2.0;
+    let (dx, dy) = rotation.x_axis();
+
+    (anchor.0 - dx * half_advance, anchor.1 - dy * half_advance)
+}
+
+/// Draws a rotated glyph.
+///
+/// `origin` is the position of the glyph origin on the display. Each display pixel is mapped back
+/// into the glyph bitmap and drawn if the nearest bitmap pixel is set. Background pixels aren't
+/// drawn.
+pub(crate) fn draw_rotated_glyph<D: DrawTarget>(
+    glyph: &BdfGlyph,
+    font: &BdfFont<'_>,
+    origin: (f32, f32),
+    rotation: Rotation,
+    color: D::Color,
+    target: &mut D,
+) -> Result<(), D::Error> {
+    let (transform, offset) = Transform::new(origin, rotation);
+
+    let pixels = transform
+        .area(glyph)
+        .points()
+        .filter(|point| transform.is_set(glyph, font, *point))
+        .map(|point| Pixel(point + offset, color));
+
+    target.draw_iter(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    use crate::include_bdf;
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn sin_cos() {
+        for step in -16..=16 {
+            let angle = step as f32 * PI / 7.0;
+            let rotation = Rotation::from_radians(angle);
+
+            assert!((rotation.sin - angle.sin()).abs() < 1e-6, "{}", angle);
+            assert!((rotation.cos - angle.cos()).abs() < 1e-6, "{}", angle);
+        }
+    }
+
+    #[test]
+    fn square_root() {
+        for value in [0.0, 1e-6, 0.5, 2.0, 9.0, 1234.5, 1e9].iter() {
+            assert!((sqrt(*value) - value.sqrt()).abs() <= value.sqrt() * 1e-6);
+        }
+        assert_eq!(sqrt(-1.0), 0.0);
+
+        let rotation = Rotation::from_vector((-3.0, 4.0)).unwrap();
+        assert_eq!(rotation.x_axis(), (-0.6, 0.8));
+        assert_eq!(Rotation::from_vector((0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn rotate_glyph() {
+        let glyph = FONT.get_glyph('L');
+
+        let mut expected = MockDisplay::new();
+        glyph
+            .draw(
+                Point::new(2, 8),
+                BinaryColor::On,
+                None,
+                FONT.data,
+                &mut expected,
+            )
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        draw_rotated_glyph(
+            glyph,
+            &FONT,
+            (2.0, 8.0),
+            Rotation::from_radians(0.0),
+            BinaryColor::On,
+            &mut display,
+        )
+        .unwrap();
+        display.assert_eq(&expected);
+
+        let mut display = MockDisplay::new();
+        draw_rotated_glyph(
+            glyph,
+            &FONT,
+            (8.0, 2.0),
+            Rotation::from_radians(FRAC_PI_2),
+            BinaryColor::On,
+            &mut display,
+        )
+        .unwrap();
+        display.assert_pattern(&[
+            "              ",
+            "              ",
+            "       #######",
+            "       #      ",
+            "       #      ",
+            "       #      ",
+            "       #      ",
+        ]);
+    }
+}
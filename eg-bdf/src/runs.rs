@@ -0,0 +1,295 @@
+//! Glyph runs.
+//!
+//! The set pixels of glyphs can be returned as horizontal runs or rectangles instead of being
+//! drawn to a [`DrawTarget`]. This makes it possible to output text to devices which aren't
+//! pixel based, e.g. as tool paths for a laser engraver or as segments of an LED strip.
+//!
+//! ```
+//! use eg_bdf::{include_bdf, text::BdfTextStyle, BdfFont};
+//! use embedded_graphics::{
+//!     pixelcolor::BinaryColor, prelude::*, primitives::Rectangle, text::Baseline,
+//! };
+//!
+//! const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z');
+//!
+//! let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+//!
+//! // The vertical stroke of the `L` is returned as a single rectangle.
+//! let mut rectangles = style.text_runs("L", Point::zero(), Baseline::Top).merge_rows();
+//! assert_eq!(
+//!     rectangles.next(),
+//!     Some(Rectangle::new(Point::new(0, 1), Size::new(1, 6)))
+//! );
+//! ```
+
+use embedded_graphics::{prelude::*, primitives::Rectangle, text::Baseline};
+
+use crate::{
+    text::{BdfTextStyle, GlyphPositions},
+    BdfGlyph,
+};
+
+/// Iterator over the runs of set pixels in a glyph.
+///
+/// Each item is a rectangle with a height of one pixel, which contains a horizontal run of set
+/// pixels. The runs are returned row by row, from top to bottom and left to right. Synthesized
+/// bold and italic styles are applied to the glyph.
+///
+/// This iterator is created by [`BdfTextStyle::glyph_runs`].
+#[derive(Debug, Clone)]
+pub struct GlyphRuns<'a, C> {
+    style: BdfTextStyle<'a, C>,
+    glyph: &'a BdfGlyph,
+    position: Point,
+    area: Rectangle,
+    /// Next point to check, relative to the glyph origin.
+    next: Point,
+    merge_rows: bool,
+}
+
+impl<'a, C: PixelColor> GlyphRuns<'a, C> {
+    fn new(style: BdfTextStyle<'a, C>, glyph: &'a BdfGlyph, position: Point) -> Self {
+        let area = style.glyph_area(glyph.bounding_box);
+
+        Self {
+            style,
+            glyph,
+            position,
+            area,
+            next: area.top_left,
+            merge_rows: false,
+        }
+    }
+
+    /// Merges identical runs in consecutive rows into rectangles.
+    ///
+    /// Each run is only part of one rectangle. Glyphs with vertical strokes result in
+    /// considerably fewer items than the horizontal runs.
+    pub fn merge_rows(self) -> Self {
+        Self {
+            merge_rows: true,
+            ..self
+        }
+    }
+
+    /// Returns the next run, relative to the glyph origin.
+    fn next_run(&mut self) -> Option<Rectangle> {
+        let right = self.area.top_left.x + self.area.size.width as i32;
+        let bottom = self.area.top_left.y + self.area.size.height as i32;
+
+        while self.next.y < bottom {
+            let y = self.next.y;
+            let mut x = self.next.x;
+
+            while x < right && !self.is_set(x, y) {
+                x += 1;
+            }
+            let start = x;
+            while x < right && self.is_set(x, y) {
+                x += 1;
+            }
+
+            if x < right {
+                self.next.x = x;
+            } else {
+                self.next = Point::new(self.area.top_left.x, y + 1);
+            }
+
+            if start < x {
+                return Some(Rectangle::new(
+                    Point::new(start, y),
+                    Size::new((x - start) as u32, 1),
+                ));
+            }
+        }
+
+        None
+    }
+
+    fn is_set(&self, x: i32, y: i32) -> bool {
+        self.style.glyph_pixel(self.glyph, Point::new(x, y))
+    }
+
+    /// Returns `true` if `run` is a complete run of set pixels.
+    fn is_run(&self, run: &Rectangle) -> bool {
+        let y = run.top_left.y;
+        let columns = run.columns();
+
+        !self.is_set(columns.start - 1, y)
+            && !self.is_set(columns.end, y)
+            && columns.clone().all(|x| self.is_set(x, y))
+    }
+}
+
+impl<C: PixelColor> Iterator for GlyphRuns<'_, C> {
+    type Item = Rectangle;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut run = self.next_run()?;
+
+        if self.merge_rows {
+            // Runs which continue a run in the previous row are part of an earlier rectangle.
+            while self.is_run(&run.translate(Point::new(0, -1))) {
+                run = self.next_run()?;
+            }
+
+            let mut below = run.translate(Point::new(0, 1));
+            while self.is_run(&below) {
+                run.size.height += 1;
+                below.top_left.y += 1;
+            }
+        }
+
+        Some(run.translate(self.position))
+    }
+}
+
+/// Iterator over the runs of set pixels in a string.
+///
+/// Works like [`GlyphRuns`], but returns the runs of all glyphs in a string. The runs are
+/// returned glyph by glyph. Newlines aren't treated specially.
+///
+/// This iterator is created by [`BdfTextStyle::text_runs`].
+#[derive(Debug, Clone)]
+pub struct TextRuns<'a, 'b, C> {
+    style: BdfTextStyle<'a, C>,
+    positions: GlyphPositions<'a, 'b>,
+    y: i32,
+    glyph_runs: Option<GlyphRuns<'a, C>>,
+    merge_rows: bool,
+}
+
+impl<C: PixelColor> TextRuns<'_, '_, C> {
+    /// Merges identical runs in consecutive rows into rectangles.
+    ///
+    /// See [`GlyphRuns::merge_rows`].
+    pub fn merge_rows(self) -> Self {
+        Self {
+            merge_rows: true,
+            ..self
+        }
+    }
+}
+
+impl<C: PixelColor> Iterator for TextRuns<'_, '_, C> {
+    type Item = Rectangle;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(run) = self.glyph_runs.as_mut().and_then(Iterator::next) {
+                return Some(run);
+            }
+
+            let (_, glyph, x) = self.positions.next()?;
+            let runs = GlyphRuns::new(self.style, glyph, Point::new(x, self.y));
+            self.glyph_runs = Some(if self.merge_rows {
+                runs.merge_rows()
+            } else {
+                runs
+            });
+        }
+    }
+}
+
+impl<'a, C: PixelColor> BdfTextStyle<'a, C> {
+    /// Returns the runs of set pixels in the glyph for a character.
+    ///
+    /// `position` is the origin of the glyph on the alphabetic baseline.
+    pub fn glyph_runs(&self, c: char, position: Point) -> GlyphRuns<'a, C> {
+        GlyphRuns::new(*self, self.font().get_glyph(c), position)
+    }
+
+    /// Returns the runs of set pixels in a string.
+    ///
+    /// The runs are at the same positions as the pixels which are drawn by
+    /// [`draw_string`](embedded_graphics::text::renderer::TextRenderer::draw_string).
+    /// Backgrounds and decorations aren't included.
+    pub fn text_runs<'b>(
+        &self,
+        text: &'b str,
+        position: Point,
+        baseline: Baseline,
+    ) -> TextRuns<'a, 'b, C> {
+        let position = self.alphabetic_position(position, baseline);
+
+        TextRuns {
+            style: *self,
+            positions: self.glyph_positions(text, position.x),
+            y: position.y,
+            glyph_runs: None,
+            merge_rows: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{family::FontStyle, include_bdf, BdfFont};
+    use alloc::vec::Vec;
+    use embedded_graphics::{
+        mock_display::MockDisplay, pixelcolor::BinaryColor, text::renderer::TextRenderer,
+    };
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    /// Draws runs as filled rectangles.
+    fn fill(runs: impl Iterator<Item = Rectangle>) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        for run in runs {
+            display.fill_solid(&run, BinaryColor::On).unwrap();
+        }
+
+        display
+    }
+
+    #[test]
+    fn glyph_runs() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let runs: Vec<_> = style.glyph_runs('T', Point::new(0, 7)).collect();
+        assert_eq!(runs.len(), 7);
+        assert_eq!(
+            runs[0],
+            Rectangle::new(Point::new(0, 1), Size::new(5, 1)),
+            "bar"
+        );
+        assert_eq!(
+            runs[1],
+            Rectangle::new(Point::new(2, 2), Size::new(1, 1)),
+            "stem"
+        );
+
+        let rectangles: Vec<_> = style
+            .glyph_runs('T', Point::new(0, 7))
+            .merge_rows()
+            .collect();
+        assert_eq!(
+            rectangles,
+            [
+                Rectangle::new(Point::new(0, 1), Size::new(5, 1)),
+                Rectangle::new(Point::new(2, 2), Size::new(1, 6)),
+            ]
+        );
+
+        assert_eq!(style.glyph_runs(' ', Point::zero()).next(), None);
+    }
+
+    #[test]
+    fn text_runs() {
+        let mut style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        for synthetic_style in [FontStyle::REGULAR, FontStyle::BOLD_ITALIC] {
+            style = style.with_font_style(synthetic_style);
+
+            let mut expected = MockDisplay::new();
+            style
+                .draw_string("HELLO", Point::new(2, 3), Baseline::Top, &mut expected)
+                .unwrap();
+
+            let runs = style.text_runs("HELLO", Point::new(2, 3), Baseline::Top);
+            fill(runs.clone()).assert_eq(&expected);
+            fill(runs.merge_rows()).assert_eq(&expected);
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Serde definitions for `embedded-graphics-core` types.
+//!
+//! `embedded-graphics-core` doesn't implement `Serialize` and `Deserialize`. The definitions in
+//! this module mirror the fields of the geometry types and are used with `#[serde(with = ...)]`.
+
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Point")]
+pub(crate) struct PointDef {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Size")]
+pub(crate) struct SizeDef {
+    width: u32,
+    height: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Rectangle")]
+pub(crate) struct RectangleDef {
+    #[serde(with = "PointDef")]
+    top_left: Point,
+    #[serde(with = "SizeDef")]
+    size: Size,
+}
@@ -0,0 +1,293 @@
+//! Rich text.
+
+use embedded_graphics::{
+    prelude::*,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::text::{pen_to_pixels, BdfTextStyle};
+
+/// Text made of segments with different styles.
+///
+/// All segments are drawn on a common baseline. The line height is the largest line height of
+/// all segment styles and the vertical position is determined by the style with the largest
+/// ascent, or the largest descent for [`Baseline::Bottom`].
+///
+/// If a maximum width is set, lines are wrapped at spaces. Words can span multiple segments and
+/// are never broken; words which are wider than the maximum width overflow. Spaces at a soft line
+/// break aren't drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Spans<'a, 'b, C> {
+    segments: &'b [(&'b str, &'b BdfTextStyle<'a, C>)],
+    position: Point,
+    baseline: Baseline,
+    max_width: Option<u32>,
+}
+
+/// Position of a character inside the segments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cursor {
+    segment: usize,
+    index: usize,
+}
+
+impl<'a, 'b, C: PixelColor> Spans<'a, 'b, C> {
+    /// Creates new spans.
+    pub fn new(
+        segments: &'b [(&'b str, &'b BdfTextStyle<'a, C>)],
+        position: Point,
+        baseline: Baseline,
+    ) -> Self {
+        Self {
+            segments,
+            position,
+            baseline,
+            max_width: None,
+        }
+    }
+
+    /// Sets the maximum width.
+    pub fn with_max_width(self, max_width: u32) -> Self {
+        Self {
+            max_width: Some(max_width),
+            ..self
+        }
+    }
+
+    /// Returns the line height.
+    pub fn line_height(&self) -> u32 {
+        self.segments
+            .iter()
+            .map(|(_, style)| style.line_height())
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn baseline_offset(&self) -> i32 {
+        let offsets = self
+            .segments
+            .iter()
+            .map(|(_, style)| style.baseline_offset(self.baseline));
+
+        let offset = match self.baseline {
+            Baseline::Bottom => offsets.max(),
+            _ => offsets.min(),
+        };
+
+        offset.unwrap_or_default()
+    }
+
+    /// Returns the character at `cursor` and the position of the following character.
+    fn next_char(&self, mut cursor: Cursor) -> Option<(char, &'b BdfTextStyle<'a, C>, Cursor)> {
+        loop {
+            let (text, style) = self.segments.get(cursor.segment)?;
+
+            if let Some(c) = text[cursor.index..].chars().next() {
+                cursor.index += c.len_utf8();
+                return Some((c, style, cursor));
+            }
+
+            cursor = Cursor {
+                segment: cursor.segment + 1,
+                index: 0,
+            };
+        }
+    }
+
+    /// Returns the end of the word starting at `cursor` and its width in thousandths of a pixel.
+    fn word_end(&self, mut cursor: Cursor) -> (Cursor, i64) {
+        let mut width = 0;
+
+        while let Some((c, style, next)) = self.next_char(cursor) {
+            if c == ' ' || c == '\n' {
+                break;
+            }
+
+            width += style.pen_advance(c);
+            cursor = next;
+        }
+
+        (cursor, width)
+    }
+
+    /// Returns the width of the characters between `start` and `end` in thousandths of a pixel.
+    fn width(&self, mut start: Cursor, end: Cursor) -> i64 {
+        let mut width = 0;
+
+        while start != end {
+            let (c, style, next) = self.next_char(start).unwrap();
+            width += style.pen_advance(c);
+            start = next;
+        }
+
+        width
+    }
+
+    /// Draws the characters between `start` and `end`.
+    ///
+    /// `pen` is the position relative to `line_start` in thousandths of a pixel, which keeps
+    /// fractional advances across characters and segments.
+    fn draw_range<D>(
+        &self,
+        mut start: Cursor,
+        end: Cursor,
+        line_start: Point,
+        pen: &mut i64,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        while start != end {
+            let (c, style, next) = self.next_char(start).unwrap();
+            style.draw_string(
+                c.encode_utf8(&mut [0; 4]),
+                line_start + Point::new(pen_to_pixels(*pen), 0),
+                Baseline::Alphabetic,
+                target,
+            )?;
+            *pen += style.pen_advance(c);
+            start = next;
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: PixelColor> Drawable for Spans<'_, '_, C> {
+    type Color = C;
+    type Output = Point;
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let line_height = self.line_height() as i32;
+        let mut line_start = self.position - Point::new(0, self.baseline_offset());
+        let mut pen = 0;
+
+        let mut cursor = Cursor {
+            segment: 0,
+            index: 0,
+        };
+        let mut spaces = None;
+
+        while let Some((c, _, next)) = self.next_char(cursor) {
+            match c {
+                '\n' => {
+                    if let Some(start) = spaces.take() {
+                        self.draw_range(start, cursor, line_start, &mut pen, target)?;
+                    }
+                    line_start.y += line_height;
+                    pen = 0;
+                    cursor = next;
+                }
+                ' ' => {
+                    spaces.get_or_insert(cursor);
+                    cursor = next;
+                }
+                _ => {
+                    let (end, width) = self.word_end(cursor);
+                    let spaces_start = spaces.take().unwrap_or(cursor);
+
+                    let wrap = self.max_width.is_some_and(|max_width| {
+                        let spaces_width = self.width(spaces_start, cursor);
+                        pen_to_pixels(pen) > 0
+                            && pen_to_pixels(pen + spaces_width + width) > max_width as i32
+                    });
+
+                    if wrap {
+                        line_start.y += line_height;
+                        pen = 0;
+                    } else {
+                        self.draw_range(spaces_start, cursor, line_start, &mut pen, target)?;
+                    }
+
+                    self.draw_range(cursor, end, line_start, &mut pen, target)?;
+                    cursor = end;
+                }
+            }
+        }
+
+        if let Some(start) = spaces {
+            self.draw_range(start, cursor, line_start, &mut pen, target)?;
+        }
+
+        Ok(line_start + Point::new(pen_to_pixels(pen), self.baseline_offset()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, tests::FRACTIONAL_FONT, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn mixed_styles() {
+        let on = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let off = BdfTextStyle::new(&FONT, BinaryColor::Off);
+
+        let mut display = MockDisplay::new();
+        let next = Spans::new(&[("A", &on), ("I", &off)], Point::zero(), Baseline::Top)
+            .draw(&mut display)
+            .unwrap();
+        assert_eq!(next, Point::new(12, 0));
+
+        let mut expected = MockDisplay::new();
+        on.draw_string("A", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+        off.draw_string("I", Point::new(6, 0), Baseline::Top, &mut expected)
+            .unwrap();
+
+        assert_eq!(display, expected);
+    }
+
+    #[test]
+    fn fractional_advances() {
+        let style =
+            BdfTextStyle::new(&FRACTIONAL_FONT, BinaryColor::On).with_fractional_advances(true);
+
+        let mut display = MockDisplay::new();
+        let next = Spans::new(
+            &[("AAA", &style), ("AAA", &style)],
+            Point::zero(),
+            Baseline::Top,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        let mut expected = MockDisplay::new();
+        let expected_next = style
+            .draw_string("AAAAAA", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+
+        assert_eq!(next, expected_next);
+        assert_eq!(display, expected);
+    }
+
+    #[test]
+    fn wrap_words_across_segments() {
+        let on = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let off = BdfTextStyle::new(&FONT, BinaryColor::Off);
+
+        let mut display = MockDisplay::new();
+        let next = Spans::new(&[("A I", &on), ("A", &off)], Point::zero(), Baseline::Top)
+            .with_max_width(18)
+            .draw(&mut display)
+            .unwrap();
+        assert_eq!(next, Point::new(12, 10));
+
+        let mut expected = MockDisplay::new();
+        on.draw_string("A", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+        on.draw_string("I", Point::new(0, 10), Baseline::Top, &mut expected)
+            .unwrap();
+        off.draw_string("A", Point::new(6, 10), Baseline::Top, &mut expected)
+            .unwrap();
+
+        assert_eq!(display, expected);
+    }
+}
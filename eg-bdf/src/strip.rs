@@ -0,0 +1,240 @@
+//! Strip rendering.
+//!
+//! Large displays often don't have enough RAM for a full framebuffer. Instead, a frame can be
+//! rendered into a small buffer which covers a horizontal strip of the display. After a strip is
+//! rendered it is transferred to the display, e.g. using DMA, and the buffer is reused for the next
+//! strip.
+
+use embedded_graphics::{
+    pixelcolor::raw::RawData,
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::{blit::HorizontalBuffer, text::BdfTextStyle};
+
+/// Draw target which covers a horizontal strip of a larger frame.
+///
+/// The bounding box of the draw target is the area of the strip in frame coordinates.
+pub trait StripTarget: DrawTarget {
+    /// Moves the strip to start at the given row of the frame.
+    fn set_top(&mut self, top: i32);
+}
+
+impl StripTarget for HorizontalBuffer<'_> {
+    fn set_top(&mut self, top: i32) {
+        HorizontalBuffer::set_top(self, top)
+    }
+}
+
+/// Strip buffer which stores raw color values.
+///
+/// Each pixel is stored as the raw storage type of the color type, e.g. `u16` for `Rgb565`.
+/// Pixels are stored in row-major order without padding. The raw data can be passed directly to
+/// the display driver, but the byte order might need to be adjusted.
+pub struct Strip<'a, C: PixelColor> {
+    data: &'a mut [<C::Raw as RawData>::Storage],
+    size: Size,
+    top: i32,
+}
+
+impl<'a, C> Strip<'a, C>
+where
+    C: PixelColor + Into<C::Raw>,
+{
+    /// Creates a new strip buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is too short.
+    pub fn new(data: &'a mut [<C::Raw as RawData>::Storage], size: Size) -> Self {
+        assert!(data.len() >= size.width as usize * size.height as usize);
+
+        Self { data, size, top: 0 }
+    }
+
+    /// Returns the raw data.
+    pub fn data(&self) -> &[<C::Raw as RawData>::Storage] {
+        self.data
+    }
+
+    /// Returns the y coordinate of the first row.
+    pub fn top(&self) -> i32 {
+        self.top
+    }
+
+    fn index(&self, point: Point) -> usize {
+        (point.y - self.top) as usize * self.size.width as usize + point.x as usize
+    }
+}
+
+impl<C> Dimensions for Strip<'_, C>
+where
+    C: PixelColor + Into<C::Raw>,
+{
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::new(0, self.top), self.size)
+    }
+}
+
+impl<C> DrawTarget for Strip<'_, C>
+where
+    C: PixelColor + Into<C::Raw>,
+{
+    type Color = C;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounding_box = self.bounding_box();
+
+        for Pixel(point, color) in pixels {
+            if bounding_box.contains(point) {
+                let index = self.index(point);
+                self.data[index] = color.into().into_inner();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+
+        for y in area.rows() {
+            let start = self.index(Point::new(area.top_left.x, y));
+
+            for raw in &mut self.data[start..start + area.size.width as usize] {
+                *raw = color.into().into_inner();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<C> StripTarget for Strip<'_, C>
+where
+    C: PixelColor + Into<C::Raw>,
+{
+    fn set_top(&mut self, top: i32) {
+        self.top = top;
+    }
+}
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Draws a line of text strip by strip.
+    ///
+    /// The area between the top of the ascent and the bottom of the descent is split into strips
+    /// with the height of the strip buffer. Each strip is cleared with the background color, the
+    /// text is drawn into the strip and `flush` is called to transfer the strip to the display.
+    /// If the style has no background color the strip isn't cleared and `flush` needs to prepare
+    /// the buffer for the next strip.
+    ///
+    /// Returns the position after the last character, using the same baseline as `position`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the strip has a height of zero.
+    pub fn draw_string_strips<S, F>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        strip: &mut S,
+        mut flush: F,
+    ) -> Result<Point, S::Error>
+    where
+        S: StripTarget<Color = C>,
+        F: FnMut(&mut S) -> Result<(), S::Error>,
+    {
+        let strip_height = strip.bounding_box().size.height as i32;
+        assert!(strip_height > 0);
+
+        let alphabetic = self.alphabetic_position(position, baseline);
+        let top = alphabetic.y - (self.font().font_ascent as i32 - 1);
+        let bottom = top + self.caret_height() as i32;
+
+        let mut next = position;
+        let mut y = top;
+        while y < bottom {
+            strip.set_top(y);
+
+            if let Some(color) = self.background_color() {
+                strip.clear(color)?;
+            }
+
+            next = self.draw_string(text, position, baseline, strip)?;
+            flush(strip)?;
+
+            y += strip_height;
+        }
+
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        pixelcolor::{BinaryColor, Rgb565},
+    };
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn rgb565_strips() {
+        let style = BdfTextStyle::new(&FONT, Rgb565::WHITE).with_bg_color(Rgb565::BLACK);
+
+        let mut data = [0u16; 12 * 4];
+        let mut strip = Strip::<Rgb565>::new(&mut data, Size::new(12, 4));
+
+        let mut tops = [0; 3];
+        let mut rows = 0;
+        let next = style
+            .draw_string_strips("AI", Point::zero(), Baseline::Top, &mut strip, |strip| {
+                tops[rows / 4] = strip.top();
+                rows += 4;
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(next, Point::new(12, 0));
+        assert_eq!(tops, [0, 4, 8]);
+        // Last strip contains the two empty rows at the bottom of the glyphs.
+        assert!(data[..24].iter().all(|raw| *raw == 0));
+    }
+
+    #[test]
+    fn monochrome_strips() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("A", Point::new(0, 2), Baseline::Top, &mut expected)
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        let mut data = [0; 3];
+        let mut strip = HorizontalBuffer::new(&mut data, Size::new(6, 3));
+        style
+            .draw_string_strips("A", Point::new(0, 2), Baseline::Top, &mut strip, |strip| {
+                // The last strip extends below the line.
+                for point in strip.bounding_box().points().filter(|p| p.y < 12) {
+                    Pixel(point, strip.pixel(point).unwrap())
+                        .draw(&mut display)
+                        .unwrap();
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+}
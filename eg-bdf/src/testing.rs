@@ -0,0 +1,445 @@
+//! Helpers for testing text layout.
+//!
+//! This module requires the `test-helpers` feature. It renders text into ASCII-art patterns, which
+//! use the same color characters as the patterns of
+//! [`MockDisplay`](embedded_graphics::mock_display::MockDisplay), and makes it possible to
+//! snapshot-test text layout without a simulator window.
+//!
+//! The [`assert_text_eq`](crate::assert_text_eq) and [`assert_glyph_eq`](crate::assert_glyph_eq)
+//! macros compare the rendered output against an expected pattern and print both patterns side
+//! by side if they don't match.
+//!
+//! ```
+//! use eg_bdf::{include_bdf, testing::render_to_string, text::BdfTextStyle, BdfFont};
+//! use embedded_graphics::{pixelcolor::BinaryColor, text::Baseline};
+//!
+//! const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z');
+//!
+//! let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+//! let pattern = render_to_string("I", &style, Baseline::Top);
+//!
+//! assert_eq!(
+//!     pattern.lines().collect::<Vec<_>>(),
+//!     [
+//!         "      ", //
+//!         " ###  ", //
+//!         "  #   ", //
+//!         "  #   ", //
+//!         "  #   ", //
+//!         "  #   ", //
+//!         "  #   ", //
+//!         " ###  ", //
+//!         "      ", //
+//!         "      ", //
+//!     ]
+//! );
+//! ```
+
+use alloc::{collections::BTreeMap, string::String};
+use core::fmt::{self, Write};
+
+use embedded_graphics::{
+    mock_display::ColorMapping,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+
+use crate::{text::BdfTextStyle, BdfFont};
+
+/// Draw target which records all drawn pixels.
+///
+/// Unlike `MockDisplay` the canvas isn't limited to a fixed size and allows pixels to be drawn at
+/// negative coordinates and to be overdrawn. Its [`Display`](fmt::Display) implementation
+/// renders the smallest rectangle which contains all drawn pixels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsciiCanvas<C> {
+    /// Drawn pixels, indexed by `(y, x)` to iterate over them in row order.
+    pixels: BTreeMap<(i32, i32), C>,
+}
+
+impl<C: PixelColor + ColorMapping> AsciiCanvas<C> {
+    /// Creates an empty canvas.
+    pub fn new() -> Self {
+        Self {
+            pixels: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the color of a pixel, or `None` if the pixel wasn't drawn.
+    pub fn get_pixel(&self, point: Point) -> Option<C> {
+        self.pixels.get(&(point.y, point.x)).copied()
+    }
+
+    /// Returns the smallest rectangle which contains all drawn pixels.
+    ///
+    /// A zero sized rectangle at the origin is returned if no pixels were drawn.
+    pub fn affected_area(&self) -> Rectangle {
+        let mut points = self.pixels.keys().map(|&(y, x)| Point::new(x, y));
+
+        let first = match points.next() {
+            Some(point) => point,
+            None => return Rectangle::zero(),
+        };
+        let (min, max) = points.fold((first, first), |(min, max), point| {
+            (min.component_min(point), max.component_max(point))
+        });
+
+        Rectangle::with_corners(min, max)
+    }
+
+    /// Renders an area of the canvas into a pattern.
+    ///
+    /// Each row of the area is converted into one line, which are separated by `\n`. Drawn pixels
+    /// are represented by the character returned by [`ColorMapping::color_to_char`] and pixels
+    /// which weren't drawn by a space.
+    pub fn to_pattern(&self, area: &Rectangle) -> String {
+        let mut pattern = String::new();
+
+        for y in area.rows() {
+            if y != area.top_left.y {
+                pattern.push('\n');
+            }
+            for x in area.columns() {
+                pattern.push(
+                    self.get_pixel(Point::new(x, y))
+                        .map_or(' ', ColorMapping::color_to_char),
+                );
+            }
+        }
+
+        pattern
+    }
+}
+
+impl<C: PixelColor + ColorMapping> Default for AsciiCanvas<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: PixelColor + ColorMapping> fmt::Display for AsciiCanvas<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_pattern(&self.affected_area()))
+    }
+}
+
+impl<C: PixelColor> Dimensions for AsciiCanvas<C> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::with_center(Point::zero(), Size::new_equal(u16::MAX as u32))
+    }
+}
+
+impl<C: PixelColor> DrawTarget for AsciiCanvas<C> {
+    type Color = C;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            self.pixels.insert((point.y, point.x), color);
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders text into an ASCII-art pattern.
+///
+/// The text is drawn at the origin using the given baseline. Multiple lines, which are separated
+/// by `\n`, are drawn like [`Text`] draws them. The returned pattern covers the bounding box of
+/// the text, which includes the full character cells, and any pixels drawn outside of it. See
+/// [`AsciiCanvas::to_pattern`] for a description of the pattern format.
+pub fn render_to_string<C>(text: &str, style: &BdfTextStyle<'_, C>, baseline: Baseline) -> String
+where
+    C: PixelColor + ColorMapping,
+{
+    let mut canvas = AsciiCanvas::new();
+    let text = Text::with_baseline(text, Point::zero(), *style, baseline);
+    text.draw(&mut canvas).unwrap();
+
+    let text_area = text.bounding_box();
+    let drawn_area = canvas.affected_area();
+    let area = match (text_area.bottom_right(), drawn_area.bottom_right()) {
+        (Some(a), Some(b)) => Rectangle::with_corners(
+            text_area.top_left.component_min(drawn_area.top_left),
+            a.component_max(b),
+        ),
+        (None, Some(_)) => drawn_area,
+        _ => text_area,
+    };
+
+    canvas.to_pattern(&area)
+}
+
+/// Renders the bitmap of a glyph into an ASCII-art pattern.
+///
+/// The pattern covers the bounding box of the glyph, set pixels are represented by `#` and unset
+/// pixels by `.`. The replacement glyph is rendered if the font doesn't contain a glyph for `c`.
+pub fn render_glyph(font: &BdfFont<'_>, c: char) -> String {
+    let glyph = font.get_glyph(c);
+    let area = Rectangle::new(Point::zero(), glyph.bounding_box.size);
+
+    let mut pattern = String::new();
+    for y in area.rows() {
+        if y != 0 {
+            pattern.push('\n');
+        }
+        for x in area.columns() {
+            let set = glyph.pixel_with_layout(font.data, font.bitmap_layout, Point::new(x, y));
+            pattern.push(if set { '#' } else { '.' });
+        }
+    }
+
+    pattern
+}
+
+/// Returns a side by side comparison of two patterns if they don't match.
+///
+/// Lines which differ are marked with `<`.
+pub fn pattern_diff(actual: &str, expected: &[&str]) -> Option<String> {
+    let actual = actual.lines().collect::<alloc::vec::Vec<_>>();
+    if actual == expected {
+        return None;
+    }
+
+    let width = expected.iter().map(|line| line.len()).max().unwrap_or(0);
+    let width = width.max("expected".len());
+
+    let mut diff = String::new();
+    writeln!(diff, "   | {:width$} | actual", "expected", width = width).unwrap();
+    for row in 0..actual.len().max(expected.len()) {
+        let expected_line = expected.get(row).copied();
+        let actual_line = actual.get(row).copied();
+        let marker = if expected_line == actual_line {
+            ""
+        } else {
+            " <"
+        };
+
+        writeln!(
+            diff,
+            "{:2} | {:width$} | {}{}",
+            row,
+            expected_line.unwrap_or(""),
+            actual_line.unwrap_or(""),
+            marker,
+            width = width,
+        )
+        .unwrap();
+    }
+
+    Some(diff)
+}
+
+/// Panics with a side by side comparison if a pattern doesn't match the expected lines.
+#[track_caller]
+pub fn assert_pattern_eq(actual: &str, expected: &[&str]) {
+    if let Some(diff) = pattern_diff(actual, expected) {
+        panic!("patterns don't match\n{}", diff);
+    }
+}
+
+/// Asserts that text is rendered as the expected pattern.
+///
+/// The text is rendered using [`render_to_string`](crate::testing::render_to_string) and
+/// compared to a slice of pattern lines. If no baseline is given, the text is drawn using
+/// `Baseline::Top`. On failure both patterns are printed side by side.
+///
+/// This macro requires the `test-helpers` feature.
+///
+/// ```
+/// use eg_bdf::{assert_text_eq, include_bdf, text::BdfTextStyle, BdfFont};
+/// use embedded_graphics::pixelcolor::BinaryColor;
+///
+/// const FONT: BdfFont = include_bdf!("examples/6x10.bdf", '-');
+///
+/// let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+/// assert_text_eq!(
+///     "-",
+///     style,
+///     [
+///         "      ", //
+///         "      ", //
+///         "      ", //
+///         "      ", //
+///         "##### ", //
+///         "      ", //
+///         "      ", //
+///         "      ", //
+///         "      ", //
+///         "      ", //
+///     ]
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_text_eq {
+    ($text:expr, $style:expr, $expected:expr $(,)?) => {
+        $crate::assert_text_eq!($text, $style, $crate::__private::Baseline::Top, $expected)
+    };
+    ($text:expr, $style:expr, $baseline:expr, $expected:expr $(,)?) => {
+        $crate::testing::assert_pattern_eq(
+            &$crate::testing::render_to_string($text, &$style, $baseline),
+            &$expected[..],
+        )
+    };
+}
+
+/// Asserts that a glyph bitmap matches the expected pattern.
+///
+/// The glyph is rendered using [`render_glyph`](crate::testing::render_glyph) and compared to a
+/// slice of pattern lines. On failure both patterns are printed side by side.
+///
+/// This macro requires the `test-helpers` feature.
+///
+/// ```
+/// use eg_bdf::{assert_glyph_eq, include_bdf, BdfFont};
+///
+/// const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'T');
+///
+/// assert_glyph_eq!(
+///     FONT,
+///     'T',
+///     [
+///         "......", //
+///         "#####.", //
+///         "..#...", //
+///         "..#...", //
+///         "..#...", //
+///         "..#...", //
+///         "..#...", //
+///         "..#...", //
+///         "......", //
+///         "......", //
+///     ]
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_glyph_eq {
+    ($font:expr, $c:expr, $expected:expr $(,)?) => {
+        $crate::testing::assert_pattern_eq(
+            &$crate::testing::render_glyph(&$font, $c),
+            &$expected[..],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use alloc::{string::ToString, vec};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn matches_mock_display() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+        let pattern = render_to_string("AB\nC", &style, Baseline::Top);
+
+        let mut display = MockDisplay::new();
+        Text::with_baseline("AB\nC", Point::zero(), style, Baseline::Top)
+            .draw(&mut display)
+            .unwrap();
+
+        let expected = pattern.lines().collect::<alloc::vec::Vec<_>>();
+        display.assert_pattern(&expected);
+        assert_eq!(expected.len(), 20);
+        assert!(expected.iter().all(|line| line.len() == 12));
+    }
+
+    #[test]
+    fn negative_coordinates() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let pattern = render_to_string("I", &style, Baseline::Alphabetic);
+
+        // The pattern starts at the top of the character cell, above the baseline.
+        assert_eq!(pattern.lines().nth(1), Some(" ###  "));
+        assert_eq!(pattern.lines().count(), 10);
+        assert_eq!(render_to_string("", &style, Baseline::Top), "");
+    }
+
+    #[test]
+    fn canvas() {
+        let mut canvas = AsciiCanvas::new();
+        Pixel(Point::new(-1, 2), BinaryColor::On)
+            .draw(&mut canvas)
+            .unwrap();
+        Pixel(Point::new(1, 3), BinaryColor::Off)
+            .draw(&mut canvas)
+            .unwrap();
+
+        assert_eq!(canvas.to_string(), "#  \n  .");
+        assert_eq!(
+            canvas.affected_area(),
+            Rectangle::new(Point::new(-1, 2), Size::new(3, 2))
+        );
+        assert_eq!(canvas.get_pixel(Point::new(0, 2)), None);
+    }
+
+    #[test]
+    fn diff() {
+        assert_eq!(pattern_diff("#.\n.#", &["#.", ".#"]), None);
+
+        let diff = pattern_diff("#.\n##\n#", &["#.", ".#"]).unwrap();
+        assert_eq!(
+            diff.lines().collect::<alloc::vec::Vec<_>>(),
+            [
+                "   | expected | actual",
+                " 0 | #.       | #.",
+                " 1 | .#       | ## <",
+                " 2 |          | # <",
+            ]
+        );
+    }
+
+    #[test]
+    fn assertion_macros() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        crate::assert_text_eq!(
+            "I",
+            style,
+            Baseline::Bottom,
+            [
+                "      ", //
+                " ###  ", //
+                "  #   ", //
+                "  #   ", //
+                "  #   ", //
+                "  #   ", //
+                "  #   ", //
+                " ###  ", //
+                "      ", //
+                "      ", //
+            ]
+        );
+        crate::assert_glyph_eq!(
+            FONT,
+            'I',
+            vec![
+                "......", //
+                ".###..", //
+                "..#...", //
+                "..#...", //
+                "..#...", //
+                "..#...", //
+                "..#...", //
+                ".###..", //
+                "......", //
+                "......", //
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "patterns don't match")]
+    fn assertion_failure() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        crate::assert_text_eq!(" ", style, ["      "]);
+    }
+}
@@ -7,9 +7,74 @@ use embedded_graphics::{
     },
 };
 
-use crate::BdfFont;
+use core::{convert::TryFrom, str::Chars};
+
+use crate::{
+    family::{FontFamily, FontStyle},
+    BdfFont, BdfGlyph, BitmapLayout,
+};
+
+/// Bounds of a measured string.
+///
+/// Returned by [`BdfTextStyle::measure_string_bounds`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct TextBounds {
+    /// Union of the advance box and the ink box.
+    ///
+    /// All pixels drawn by the string are inside this box.
+    pub bounding_box: Rectangle,
+
+    /// Box covering the advance widths and the ascent and descent of the font.
+    pub advance_box: Rectangle,
+
+    /// Union of the glyph bounding boxes.
+    ///
+    /// The box is zero sized if no glyph has a non-empty bounding box.
+    pub ink_box: Rectangle,
+
+    /// Position of the next character.
+    pub next_position: Point,
+}
+
+/// Vertical grid which line baselines are snapped to.
+///
+/// The grid lines are `spacing` pixels apart and one grid line is located at `origin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct BaselineGrid {
+    /// Distance between two grid lines in pixels.
+    pub spacing: u32,
+
+    /// Y coordinate of one of the grid lines.
+    pub origin: i32,
+}
+
+impl BaselineGrid {
+    /// Creates a new baseline grid.
+    pub const fn new(spacing: u32, origin: i32) -> Self {
+        Self { spacing, origin }
+    }
+
+    /// Returns the first grid line at or below `y`.
+    pub fn snap(&self, y: i32) -> i32 {
+        if self.spacing == 0 {
+            return y;
+        }
+
+        let spacing = self.spacing as i32;
+        let offset = (y - self.origin).rem_euclid(spacing);
+
+        if offset == 0 {
+            y
+        } else {
+            y + spacing - offset
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
 pub struct BdfTextStyle<'a, C> {
     font: &'a BdfFont<'a>,
 
@@ -25,21 +90,157 @@ pub struct BdfTextStyle<'a, C> {
     /// Strike through decoration color
     strikethrough_color: DecorationColor<C>,
 
-    /// Set relative line spacing, default when zero
-    /// Use with care, use small values to tune certain fonts. YMMV
-    height_adjust: i32,
+    /// Extra space between lines in pixels, negative values reduce the line spacing.
+    line_gap: i32,
+
+    /// Line spacing in percent of the full height of the font.
+    line_spacing_percent: u16,
+
+    /// Position glyphs using the scalable widths of the font.
+    fractional_advances: bool,
+
+    /// Padding around the text box which is filled with the background color.
+    background_padding: u32,
+
+    /// Font family which is used to select the font for the font style.
+    family: Option<&'a FontFamily<'a>>,
+
+    /// Requested font style.
+    font_style: FontStyle,
+
+    /// Font style flags which aren't provided by the font and are synthesized while drawing.
+    synthetic_style: FontStyle,
+
+    /// Grid which the baselines of all lines are snapped to.
+    baseline_grid: Option<BaselineGrid>,
 }
 
+/// Number of rows per pixel of horizontal offset in synthesized italic text.
+const ITALIC_SLANT: i32 = 4;
+
 impl<'a, C: PixelColor> BdfTextStyle<'a, C> {
     pub fn new(font: &'a BdfFont<'a>, color: C) -> Self {
         Self {
             font,
             text_color: color,
             background_color: None,
-            height_adjust: 0,
+            line_gap: 0,
+            line_spacing_percent: 100,
             underline_color: DecorationColor::None,
             strikethrough_color: DecorationColor::None,
+            fractional_advances: false,
+            background_padding: 0,
+            family: None,
+            font_style: FontStyle::REGULAR,
+            synthetic_style: FontStyle::REGULAR,
+            baseline_grid: None,
+        }
+    }
+
+    /// Returns the font used by this style.
+    pub fn font(&self) -> &'a BdfFont<'a> {
+        self.font
+    }
+
+    /// Sets the font.
+    ///
+    /// All other style settings, like colors and decorations, are kept. A previously set font
+    /// family is removed and the font is treated as a regular face, which means that bold and
+    /// italic font styles are synthesized.
+    pub fn with_font(mut self, font: &'a BdfFont<'a>) -> Self {
+        self.set_font(font);
+        self
+    }
+
+    /// Sets the font.
+    ///
+    /// All other style settings, like colors and decorations, are kept. See
+    /// [`with_font`](Self::with_font) for the interaction with font families.
+    pub fn set_font(&mut self, font: &'a BdfFont<'a>) {
+        *self = Self {
+            font,
+            family: None,
+            ..*self
+        }
+        .resolve_font();
+    }
+
+    /// Sets the font family.
+    ///
+    /// The font is selected from the family based on the [font style](Self::with_font_style).
+    /// Styles which aren't available in the family are synthesized from the closest face, see
+    /// [`FontFamily::face`].
+    pub fn with_family(self, family: &'a FontFamily<'a>) -> Self {
+        Self {
+            family: Some(family),
+            ..self
+        }
+        .resolve_font()
+    }
+
+    /// Returns the font family, if one is set.
+    pub fn family(&self) -> Option<&'a FontFamily<'a>> {
+        self.family
+    }
+
+    /// Sets the font style.
+    ///
+    /// If a font family is set, the matching face is selected. Bold and italic styles which
+    /// aren't provided by the selected font are synthesized: synthesized bold glyphs are drawn
+    /// twice with a horizontal offset of one pixel and synthesized italic glyphs are slanted by
+    /// shifting every fourth row above the baseline one pixel to the right. The advances aren't
+    /// changed by synthesized styles.
+    ///
+    /// Synthesized styles are only applied by the text renderer and are ignored by other drawing
+    /// functions, like rotated or vertical text.
+    pub fn with_font_style(self, font_style: FontStyle) -> Self {
+        Self { font_style, ..self }.resolve_font()
+    }
+
+    /// Sets the font style.
+    ///
+    /// See [`with_font_style`](Self::with_font_style) for more information.
+    pub fn set_font_style(&mut self, font_style: FontStyle) {
+        *self = self.with_font_style(font_style);
+    }
+
+    /// Returns the font style.
+    pub fn font_style(&self) -> FontStyle {
+        self.font_style
+    }
+
+    /// Enables bold text.
+    pub fn bold(self) -> Self {
+        self.with_font_style(self.font_style | FontStyle::BOLD)
+    }
+
+    /// Enables italic text.
+    pub fn italic(self) -> Self {
+        self.with_font_style(self.font_style | FontStyle::ITALIC)
+    }
+
+    /// Selects the font from the family and updates the synthesized style flags.
+    const fn resolve_font(mut self) -> Self {
+        match self.family {
+            Some(family) => {
+                let (font, synthetic_style) = family.face(self.font_style);
+                self.font = font;
+                self.synthetic_style = synthetic_style;
+            }
+            None => self.synthetic_style = self.font_style,
         }
+
+        self
+    }
+
+    /// Returns the text color.
+    pub fn text_color(&self) -> C {
+        self.text_color
+    }
+
+    /// Returns the background color.
+    pub fn background_color(&self) -> Option<C> {
+        self.background_color
     }
 
     pub fn with_bg_color(self, bg_color: C) -> Self {
@@ -49,6 +250,25 @@ impl<'a, C: PixelColor> BdfTextStyle<'a, C> {
         }
     }
 
+    /// Sets the background padding.
+    ///
+    /// The background fill is extended by `padding` pixels on each side of the measured bounding
+    /// box of the string, which can be used to draw labels over images without drawing a
+    /// rectangle first. The padding only has an effect if a background color is set and is
+    /// included in the bounding box returned by
+    /// [`measure_string`](TextRenderer::measure_string).
+    pub fn with_background_padding(self, padding: u32) -> Self {
+        Self {
+            background_padding: padding,
+            ..self
+        }
+    }
+
+    /// Returns the background padding.
+    pub fn background_padding(&self) -> u32 {
+        self.background_padding
+    }
+
     pub fn strikethrough(self) -> Self {
         Self {
             strikethrough_color: DecorationColor::TextColor,
@@ -76,54 +296,543 @@ impl<'a, C: PixelColor> BdfTextStyle<'a, C> {
         }
     }
 
-    pub fn with_height_adjust(self, height_adjust: i32) -> Self {
+    /// Sets the line gap.
+    ///
+    /// The line gap is added to the line height and only changes the distance between the
+    /// baselines of consecutive lines. Negative values reduce the line spacing. The ascent and
+    /// descent of the font, and therefore the baseline positions and text metrics of a single
+    /// line, aren't affected.
+    pub fn with_line_gap(self, line_gap: i32) -> Self {
+        Self { line_gap, ..self }
+    }
+
+    /// Sets the line gap.
+    ///
+    /// See [`with_line_gap`](Self::with_line_gap) for more information.
+    pub fn set_line_gap(&mut self, line_gap: i32) {
+        self.line_gap = line_gap;
+    }
+
+    /// Returns the line gap.
+    pub fn line_gap(&self) -> i32 {
+        self.line_gap
+    }
+
+    /// Sets the line spacing in percent of the full height of the font.
+    ///
+    /// The line height is the [full height](Self::full_height) of the font scaled by `percent`
+    /// and rounded to the nearest pixel, plus the [line gap](Self::with_line_gap). The default
+    /// value is `100`, a value of `120` increases the distance between the baselines by 20
+    /// percent. Like the line gap this only affects the distance between consecutive lines.
+    pub fn with_line_spacing_percent(self, percent: u16) -> Self {
         Self {
-            height_adjust,
+            line_spacing_percent: percent,
             ..self
         }
     }
 
+    /// Sets the line spacing in percent of the full height of the font.
+    ///
+    /// See [`with_line_spacing_percent`](Self::with_line_spacing_percent) for more information.
+    pub fn set_line_spacing_percent(&mut self, percent: u16) {
+        self.line_spacing_percent = percent;
+    }
+
+    /// Returns the line spacing in percent of the full height of the font.
+    pub fn line_spacing_percent(&self) -> u16 {
+        self.line_spacing_percent
+    }
+
+    #[deprecated(note = "use `with_line_gap` instead")]
+    pub fn with_height_adjust(self, height_adjust: i32) -> Self {
+        self.with_line_gap(height_adjust)
+    }
+
+    #[deprecated(note = "use `set_line_gap` instead")]
     pub fn set_height_adjust(&mut self, height_adjust: i32) {
-        self.height_adjust = height_adjust;
+        self.set_line_gap(height_adjust);
     }
 
+    /// Sets a baseline grid.
+    ///
+    /// The alphabetic baseline of every line is moved down to the next line of the grid and the
+    /// [line height](TextRenderer::line_height) is rounded up to a multiple of the grid spacing.
+    /// Text which is drawn with different fonts, e.g. in multiple columns, stays aligned if all
+    /// styles use the same grid.
+    pub fn with_baseline_grid(self, baseline_grid: BaselineGrid) -> Self {
+        Self {
+            baseline_grid: Some(baseline_grid),
+            ..self
+        }
+    }
+
+    /// Removes the baseline grid.
+    pub fn reset_baseline_grid(self) -> Self {
+        Self {
+            baseline_grid: None,
+            ..self
+        }
+    }
+
+    /// Returns the baseline grid.
+    pub fn baseline_grid(&self) -> Option<BaselineGrid> {
+        self.baseline_grid
+    }
+
+    /// Returns the height of the character cell, which spans the font ascent and descent.
     pub fn full_height(&self) -> u32 {
-        ((self.font.font_ascent + self.font.font_descent) as i32 + self.height_adjust) as u32
+        self.font.font_ascent + self.font.font_descent
     }
 
-    fn baseline_offset(&self, baseline: Baseline) -> i32 {
+    /// Enables or disables fractional advances.
+    ///
+    /// If fractional advances are enabled, glyphs are positioned using the scalable widths of the
+    /// font instead of the integer device widths. The fractional part of each advance is carried
+    /// over to the following glyphs and every glyph is drawn at the nearest pixel position, which
+    /// keeps the overall string width close to the scalable width. This affects drawing, string
+    /// measurement and [`advances`](Self::advances). Fonts without scalable widths always use
+    /// the device widths.
+    pub fn with_fractional_advances(self, fractional_advances: bool) -> Self {
+        Self {
+            fractional_advances,
+            ..self
+        }
+    }
+
+    /// Returns `true` if fractional advances are enabled.
+    pub fn fractional_advances(&self) -> bool {
+        self.fractional_advances
+    }
+
+    /// Returns the horizontal advance of a character.
+    ///
+    /// The advance is negative for glyphs which move the pen to the left. This is always the
+    /// integer device width, even if fractional advances are enabled.
+    pub(crate) fn char_advance(&self, c: char) -> i32 {
+        self.font.get_glyph(c).device_width
+    }
+
+    /// Returns an iterator over the glyphs of `text` and their horizontal pen positions.
+    pub(crate) fn glyph_positions<'b>(&self, text: &'b str, x: i32) -> GlyphPositions<'a, 'b> {
+        GlyphPositions::new(self.font, text, x, self.fractional_advances)
+    }
+
+    /// Returns the advance of a character in thousandths of a pixel.
+    ///
+    /// This is the distance the pen moves in [`glyph_positions`](Self::glyph_positions), which
+    /// uses the scalable width if fractional advances are enabled. Sums of pen advances must be
+    /// rounded using [`pen_to_pixels`] to match the positions of the drawn glyphs.
+    pub(crate) fn pen_advance(&self, c: char) -> i64 {
+        let mut buffer = [0; 4];
+        let mut positions = self.glyph_positions(c.encode_utf8(&mut buffer), 0);
+        positions.next();
+
+        positions.pen
+    }
+
+    /// Returns the sum of the advances of all characters in `text`.
+    pub(crate) fn text_advance(&self, text: &str) -> i32 {
+        let mut positions = self.glyph_positions(text, 0);
+        positions.by_ref().for_each(drop);
+
+        positions.x()
+    }
+
+    /// Returns the leftmost and rightmost pen positions, relative to the start of the string.
+    ///
+    /// Negative advances can move the pen left of the start position.
+    fn pen_range(&self, text: &str) -> (i32, i32) {
+        let mut positions = self.glyph_positions(text, 0);
+        let mut range = (0, 0);
+
+        while positions.next().is_some() {
+            let pen = positions.x();
+            range = (range.0.min(pen), range.1.max(pen));
+        }
+
+        range
+    }
+
+    /// Returns the vertical offset from the alphabetic baseline to the given baseline.
+    ///
+    /// The offsets follow the conventions used by `MonoTextStyle`, with the ascent and descent
+    /// of the font forming the character cell: `Top` is the top row of the ascent, `Bottom` the
+    /// bottom row of the descent and `Middle` the center row of the cell.
+    pub(crate) fn baseline_offset(&self, baseline: Baseline) -> i32 {
+        let ascent = self.font.font_ascent as i32;
+        let height = (self.font.font_ascent + self.font.font_descent) as i32;
+
         match baseline {
-            Baseline::Top => -(self.line_height() as i32 - 1),
-            Baseline::Middle => -(self.line_height() as i32 - 1) / 2,
+            Baseline::Top => -(ascent - 1),
+            Baseline::Middle => (height - 1) / 2 - (ascent - 1),
             Baseline::Alphabetic => 0,
             Baseline::Bottom => self.font.font_descent as i32,
         }
     }
 
-    fn draw_decorations<T>(
+    /// Converts a position with the given baseline into a position on the alphabetic baseline.
+    ///
+    /// The position is snapped to the baseline grid, if one is set.
+    pub(crate) fn alphabetic_position(&self, position: Point, baseline: Baseline) -> Point {
+        let y = position.y - self.baseline_offset(baseline);
+
+        match self.baseline_grid {
+            Some(grid) => Point::new(position.x, grid.snap(y)),
+            None => Point::new(position.x, y),
+        }
+    }
+
+    pub(crate) fn draw_decorations<T>(
         &self,
         target: &mut T,
-        width: u32,
+        width: i32,
         position: Point,
     ) -> Result<(), T::Error>
     where
         T: DrawTarget<Color = C>,
     {
-        if let Some(color) = self.decoration_color_to_color(self.strikethrough_color) {
-            let y = Point::new(0, self.baseline_offset(Baseline::Middle) + 1);
-            let rect = Rectangle::new(position + y, Size::new(width, 1));
-            target.fill_solid(&rect, color)?;
-        }
+        // Glyphs with negative advances, which move the pen backwards, aren't decorated.
+        let width = width.max(0) as u32;
 
-        if let Some(color) = self.decoration_color_to_color(self.underline_color) {
-            let y = Point::new(0, self.baseline_offset(Baseline::Alphabetic) + 1);
-            let rect = Rectangle::new(position + y, Size::new(width, 1));
+        for (color, y) in self.decorations() {
+            let rect = Rectangle::new(position + Point::new(0, y), Size::new(width, 1));
             target.fill_solid(&rect, color)?;
         }
 
         Ok(())
     }
 
+    /// Returns the colors and rows of the enabled decorations in drawing order.
+    ///
+    /// The rows are relative to the alphabetic baseline.
+    fn decorations(&self) -> impl Iterator<Item = (C, i32)> {
+        let strikethrough = self
+            .decoration_color_to_color(self.strikethrough_color)
+            .map(|color| (color, self.baseline_offset(Baseline::Middle) + 1));
+        let underline = self
+            .decoration_color_to_color(self.underline_color)
+            .map(|color| (color, self.baseline_offset(Baseline::Alphabetic) + 1));
+
+        strikethrough.into_iter().chain(underline)
+    }
+
+    /// Returns the area covered by the decorations of a string.
+    ///
+    /// `position` is the start of the string on the alphabetic baseline. Returns `None` if no
+    /// decorations are enabled.
+    pub(crate) fn decoration_area(&self, text: &str, position: Point) -> Option<Rectangle> {
+        let (top, bottom) = self
+            .decorations()
+            .map(|(_, y)| (y, y))
+            .reduce(|(top, bottom), (y, _)| (top.min(y), bottom.max(y)))?;
+        let (left, right) = self.pen_range(text);
+
+        Some(Rectangle::new(
+            position + Point::new(left, top),
+            Size::new((right - left) as u32, (bottom - top + 1) as u32),
+        ))
+    }
+
+    /// Returns the decoration color at `point`, like it is drawn by `draw_string`.
+    ///
+    /// `position` is the start of the string on the alphabetic baseline. Returns `None` if the
+    /// point isn't covered by a decoration.
+    pub(crate) fn decoration_at(&self, text: &str, position: Point, point: Point) -> Option<C> {
+        // Later decorations are drawn on top of earlier ones.
+        let (color, _) = self
+            .decorations()
+            .filter(|(_, y)| position.y + y == point.y)
+            .last()?;
+
+        let mut positions = self.glyph_positions(text, position.x);
+        while let Some((_, _, x)) = positions.next() {
+            if (x..positions.x()).contains(&point.x) {
+                return Some(color);
+            }
+        }
+
+        None
+    }
+
+    /// Returns the horizontal offset of a row in synthesized italic text.
+    ///
+    /// `y` is relative to the alphabetic baseline.
+    fn slant_offset(&self, y: i32) -> i32 {
+        if self.synthetic_style.contains(FontStyle::ITALIC) {
+            (-y).div_euclid(ITALIC_SLANT)
+        } else {
+            0
+        }
+    }
+
+    /// Returns the area which is covered by a glyph, including synthesized styles.
+    ///
+    /// The bounding box is relative to the alphabetic baseline.
+    pub(crate) fn glyph_area(&self, bounding_box: Rectangle) -> Rectangle {
+        let bottom_right = match bounding_box.bottom_right() {
+            Some(bottom_right) => bottom_right,
+            None => return bounding_box,
+        };
+        let bold = self.synthetic_style.contains(FontStyle::BOLD) as i32;
+
+        Rectangle::with_corners(
+            bounding_box.top_left + Point::new(self.slant_offset(bottom_right.y), 0),
+            bottom_right + Point::new(self.slant_offset(bounding_box.top_left.y) + bold, 0),
+        )
+    }
+
+    /// Returns `true` if bold or italic glyphs are synthesized while drawing.
+    pub(crate) fn has_synthetic_style(&self) -> bool {
+        self.synthetic_style != FontStyle::REGULAR
+    }
+
+    /// Returns `true` if a point, relative to the glyph origin, is set in a glyph.
+    ///
+    /// Synthesized styles are applied to the glyph bitmap.
+    pub(crate) fn glyph_pixel(&self, glyph: &BdfGlyph, point: Point) -> bool {
+        self.bitmap_pixel(glyph, self.font.data, self.font.bitmap_layout, point)
+    }
+
+    /// Returns `true` if a point, relative to the glyph origin, is set in a glyph bitmap.
+    ///
+    /// Like [`glyph_pixel`](Self::glyph_pixel), but the bitmap is read from `data` instead of the
+    /// bitmap data of the font.
+    fn bitmap_pixel(
+        &self,
+        glyph: &BdfGlyph,
+        data: &[u8],
+        layout: BitmapLayout,
+        point: Point,
+    ) -> bool {
+        let is_set = |point: Point| {
+            glyph.bounding_box.contains(point)
+                && glyph.pixel_with_layout(data, layout, point - glyph.bounding_box.top_left)
+        };
+
+        let point = point - Point::new(self.slant_offset(point.y), 0);
+        is_set(point)
+            || self.synthetic_style.contains(FontStyle::BOLD) && is_set(point - Point::new(1, 0))
+    }
+
+    /// Draws a glyph without background.
+    ///
+    /// `position` is the origin of the glyph on the alphabetic baseline.
+    pub(crate) fn draw_glyph<D>(
+        &self,
+        glyph: &BdfGlyph,
+        position: Point,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        self.draw_glyph_bitmap(
+            glyph,
+            self.font.data,
+            self.font.bitmap_layout,
+            position,
+            None,
+            target,
+        )
+    }
+
+    /// Draws a glyph using the bitmap in `data`, instead of the bitmap data of the font.
+    ///
+    /// `position` is the origin of the glyph on the alphabetic baseline. The background is only
+    /// drawn inside the area covered by the glyph, including synthesized styles.
+    pub(crate) fn draw_glyph_bitmap<D>(
+        &self,
+        glyph: &BdfGlyph,
+        data: &[u8],
+        layout: BitmapLayout,
+        position: Point,
+        background_color: Option<C>,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        if !self.has_synthetic_style() {
+            return glyph.draw_with_layout(
+                position,
+                self.text_color,
+                background_color,
+                data,
+                layout,
+                target,
+            );
+        }
+
+        let area = self.glyph_area(glyph.bounding_box);
+        let is_set = |point: &Point| self.bitmap_pixel(glyph, data, layout, *point);
+
+        match background_color {
+            Some(background_color) => target.fill_contiguous(
+                &area.translate(position),
+                area.points().map(|point| {
+                    if is_set(&point) {
+                        self.text_color
+                    } else {
+                        background_color
+                    }
+                }),
+            ),
+            None => target.draw_iter(
+                area.points()
+                    .filter(is_set)
+                    .map(|point| Pixel(point + position, self.text_color)),
+            ),
+        }
+    }
+
+    /// Returns the union of the glyph bounding boxes of a string.
+    ///
+    /// `position` is the start of the string on the alphabetic baseline. Returns `None` if no
+    /// glyph has a non-empty bounding box.
+    fn ink_box(&self, text: &str, position: Point) -> Option<Rectangle> {
+        let mut corners: Option<(Point, Point)> = None;
+
+        for (_, glyph, x) in self.glyph_positions(text, position.x) {
+            let bounding_box = self
+                .glyph_area(glyph.bounding_box)
+                .translate(Point::new(x, position.y));
+
+            if let Some(bottom_right) = bounding_box.bottom_right() {
+                let top_left = bounding_box.top_left;
+                corners = Some(match corners {
+                    Some((min, max)) => {
+                        (min.component_min(top_left), max.component_max(bottom_right))
+                    }
+                    None => (top_left, bottom_right),
+                });
+            }
+        }
+
+        corners.map(|(top_left, bottom_right)| Rectangle::with_corners(top_left, bottom_right))
+    }
+
+    /// Returns the union of the glyph bounding boxes and the advance widths of a string.
+    ///
+    /// `position` is the start of the string on the alphabetic baseline. Returns `None` if no
+    /// glyph has a non-empty bounding box.
+    pub(crate) fn string_area(&self, text: &str, position: Point) -> Option<Rectangle> {
+        let ink_box = self.ink_box(text, position)?;
+        let bottom_right = ink_box.bottom_right()?;
+        let (left, right) = self.pen_range(text);
+
+        Some(Rectangle::with_corners(
+            Point::new(
+                ink_box.top_left.x.min(position.x + left),
+                ink_box.top_left.y,
+            ),
+            Point::new(bottom_right.x.max(position.x + right - 1), bottom_right.y),
+        ))
+    }
+
+    /// Measures a string.
+    ///
+    /// In addition to the combined bounding box returned by
+    /// [`measure_string`](TextRenderer::measure_string), the returned bounds contain the advance
+    /// box, which covers the advance widths and the ascent and descent of the font, and the ink
+    /// box, which covers all glyph bounding boxes. Glyphs in italic or decorated fonts can extend
+    /// outside the advance box. If the string contains glyphs with negative advances, the advance
+    /// box spans from the leftmost to the rightmost pen position.
+    pub fn measure_string_bounds(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+    ) -> TextBounds {
+        let alphabetic = self.alphabetic_position(position, baseline);
+        let (left, right) = self.pen_range(text);
+
+        let advance_box = Rectangle::new(
+            alphabetic + Point::new(left, -(self.font.font_ascent as i32 - 1)),
+            Size::new((right - left) as u32, self.full_height()),
+        );
+        let ink_box = self
+            .ink_box(text, alphabetic)
+            .unwrap_or_else(|| Rectangle::new(alphabetic, Size::zero()));
+
+        let bounding_box = match (advance_box.bottom_right(), ink_box.bottom_right()) {
+            (Some(a), Some(b)) => Rectangle::with_corners(
+                advance_box.top_left.component_min(ink_box.top_left),
+                a.component_max(b),
+            ),
+            (None, Some(_)) => ink_box,
+            _ => advance_box,
+        };
+        let bounding_box = match self.background_color {
+            Some(_) if self.background_padding > 0 => {
+                pad_rectangle(&bounding_box, self.background_padding)
+            }
+            _ => bounding_box,
+        };
+
+        TextBounds {
+            bounding_box,
+            advance_box,
+            ink_box,
+            next_position: position + Point::new(self.text_advance(text), 0),
+        }
+    }
+
+    /// Draws a string after clearing its bounding box.
+    ///
+    /// The full bounding box returned by [`measure_string`](TextRenderer::measure_string),
+    /// including the background padding, is filled with the background color before the glyphs
+    /// are drawn. This erases any previous content underneath the string, e.g. a longer string
+    /// which was drawn at the same position before. If no background color is set, the string is
+    /// drawn like [`draw_string`](TextRenderer::draw_string).
+    pub fn draw_string_opaque<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        if let Some(background_color) = self.background_color {
+            let bounding_box = self.measure_string(text, position, baseline).bounding_box;
+            target.fill_solid(&bounding_box, background_color)?;
+        }
+
+        let transparent = Self {
+            background_color: None,
+            ..*self
+        };
+        transparent.draw_string(text, position, baseline, target)
+    }
+
+    /// Draws all glyphs of a string with a single `fill_contiguous` call.
+    ///
+    /// The filled area is the union of the glyph bounding boxes and the advance widths, clipped to
+    /// the bounding box of the draw target.
+    fn draw_string_blit<D>(
+        &self,
+        text: &str,
+        position: Point,
+        background_color: C,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let area = match self.string_area(text, position) {
+            Some(area) => area.intersection(&target.bounding_box()),
+            None => return Ok(()),
+        };
+
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+
+        let colors = StringColors::new(self, text, position, area, background_color);
+        target.fill_contiguous(&area, colors)
+    }
+
     fn decoration_color_to_color(&self, dc: DecorationColor<C>) -> Option<C> {
         match dc {
             DecorationColor::TextColor => Some(self.text_color),
@@ -133,6 +842,381 @@ impl<'a, C: PixelColor> BdfTextStyle<'a, C> {
     }
 }
 
+/// Returns a rectangle which is extended by `padding` pixels on each side.
+fn pad_rectangle(rectangle: &Rectangle, padding: u32) -> Rectangle {
+    let offset = i32::try_from(padding).unwrap_or(i32::MAX);
+
+    Rectangle::new(
+        rectangle.top_left - Point::new(offset, offset),
+        rectangle.size + Size::new(padding, padding) * 2,
+    )
+}
+
+/// Iterator over the glyphs of a string and their horizontal pen positions.
+///
+/// Pen positions are tracked in thousandths of a pixel, which makes it possible to accumulate
+/// fractional advances. Each glyph is positioned at the nearest pixel.
+#[derive(Debug, Clone)]
+pub(crate) struct GlyphPositions<'a, 'b> {
+    font: &'a BdfFont<'a>,
+    chars: Chars<'b>,
+    pen: i64,
+    fractional: bool,
+}
+
+impl<'a, 'b> GlyphPositions<'a, 'b> {
+    fn new(font: &'a BdfFont<'a>, text: &'b str, x: i32, fractional: bool) -> Self {
+        Self {
+            font,
+            chars: text.chars(),
+            pen: i64::from(x) * 1000,
+            // Fractional advances require scalable widths for all glyphs.
+            fractional: fractional && font.scalable_widths.len() == font.glyphs.len(),
+        }
+    }
+
+    /// Returns the current pen position rounded to the nearest pixel.
+    pub(crate) fn x(&self) -> i32 {
+        pen_to_pixels(self.pen)
+    }
+}
+
+/// Converts a pen position in thousandths of a pixel into the nearest pixel position.
+pub(crate) fn pen_to_pixels(pen: i64) -> i32 {
+    (pen + 500).div_euclid(1000) as i32
+}
+
+impl<'a> Iterator for GlyphPositions<'a, '_> {
+    type Item = (char, &'a BdfGlyph, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = self.chars.next()?;
+        let index = self.font.glyph_index(c);
+        let glyph = &self.font.glyphs[index];
+        let x = self.x();
+
+        self.pen += if self.fractional {
+            i64::from(self.font.scalable_widths[index])
+        } else {
+            i64::from(glyph.device_width) * 1000
+        };
+
+        Some((c, glyph, x))
+    }
+}
+
+/// Color iterator for a string, which returns the colors of all points in an area in row-major
+/// order.
+///
+/// The returned values for points inside and outside of the glyphs default to the text and
+/// background colors, other values can be used to get a mask of the glyph pixels.
+///
+/// Each point is checked against all glyphs whose pen position is close enough for the glyph to
+/// cover the point, which includes glyphs that overhang multiple neighbouring characters. If the
+/// string contains glyphs with negative advances, which can overlap any other glyph, each point
+/// is checked against all glyphs instead.
+pub(crate) struct StringColors<'a, 'b, C, T = C> {
+    style: BdfTextStyle<'a, C>,
+    /// Glyph positions from the start of the string.
+    start: GlyphPositions<'a, 'b>,
+    /// Glyph positions from the first glyph which can cover the current point.
+    window: GlyphPositions<'a, 'b>,
+    origin: Point,
+    area: Rectangle,
+    text_color: T,
+    background_color: T,
+    point: Point,
+    /// Maximum distance of the glyph areas left and right of the pen position.
+    reach: (i32, i32),
+    /// `true` if the string contains glyphs with negative advances.
+    overstrike: bool,
+}
+
+impl<'a, 'b, C: PixelColor> StringColors<'a, 'b, C> {
+    pub(crate) fn new(
+        style: &BdfTextStyle<'a, C>,
+        text: &'b str,
+        origin: Point,
+        area: Rectangle,
+        background_color: C,
+    ) -> Self {
+        Self::with_values(
+            style,
+            text,
+            origin,
+            area,
+            style.text_color,
+            background_color,
+        )
+    }
+}
+
+impl<'a, 'b, C: PixelColor, T: Copy> StringColors<'a, 'b, C, T> {
+    /// Creates an iterator which returns `text_color` for points covered by a glyph and
+    /// `background_color` for all other points.
+    pub(crate) fn with_values(
+        style: &BdfTextStyle<'a, C>,
+        text: &'b str,
+        origin: Point,
+        area: Rectangle,
+        text_color: T,
+        background_color: T,
+    ) -> Self {
+        let start = style.glyph_positions(text, origin.x);
+
+        let mut reach = (0, 0);
+        let mut overstrike = false;
+        for (_, glyph, _) in start.clone() {
+            let area = style.glyph_area(glyph.bounding_box);
+            if let Some(bottom_right) = area.bottom_right() {
+                reach = (reach.0.max(-area.top_left.x), reach.1.max(bottom_right.x));
+            }
+            overstrike |= glyph.device_width < 0;
+        }
+
+        Self {
+            style: *style,
+            window: start.clone(),
+            start,
+            origin,
+            area,
+            text_color,
+            background_color,
+            point: area.top_left,
+            reach,
+            overstrike,
+        }
+    }
+
+    fn glyph_is_set(&self, glyph: &BdfGlyph, x: i32, point: Point) -> bool {
+        self.style
+            .glyph_pixel(glyph, point - Point::new(x, self.origin.y))
+    }
+
+    fn is_set(&self, point: Point) -> bool {
+        if self.overstrike {
+            return self
+                .start
+                .clone()
+                .any(|(_, glyph, x)| self.glyph_is_set(glyph, x, point));
+        }
+
+        let right = point.x + self.reach.0;
+        self.window
+            .clone()
+            .take_while(|(_, _, x)| *x <= right)
+            .any(|(_, glyph, x)| self.glyph_is_set(glyph, x, point))
+    }
+}
+
+impl<C: PixelColor, T: Copy> Iterator for StringColors<'_, '_, C, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bottom_right = self.area.bottom_right()?;
+        if self.point.y > bottom_right.y {
+            return None;
+        }
+
+        // Skip glyphs which end left of the current point. The pen positions are increasing if the
+        // string doesn't contain negative advances.
+        if !self.overstrike {
+            let left = self.point.x - self.reach.1;
+            while let Some((_, _, x)) = self.window.clone().next() {
+                if x >= left {
+                    break;
+                }
+                self.window.next();
+            }
+        }
+
+        let color = if self.is_set(self.point) {
+            self.text_color
+        } else {
+            self.background_color
+        };
+
+        self.point.x += 1;
+        if self.point.x > bottom_right.x {
+            self.point = Point::new(self.area.top_left.x, self.point.y + 1);
+            self.window = self.start.clone();
+        }
+
+        Some(color)
+    }
+}
+
+/// Text style builder for BDF fonts.
+///
+/// Use this builder to create [`BdfTextStyle`]s. All builder methods are `const` and can be used
+/// to create styles in constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct BdfTextStyleBuilder<'a, C> {
+    style: BdfTextStyle<'a, C>,
+}
+
+impl<'a, C: PixelColor> BdfTextStyleBuilder<'a, C> {
+    /// Creates a new text style builder.
+    pub const fn new(font: &'a BdfFont<'a>, text_color: C) -> Self {
+        Self {
+            style: BdfTextStyle {
+                font,
+                text_color,
+                background_color: None,
+                underline_color: DecorationColor::None,
+                strikethrough_color: DecorationColor::None,
+                line_gap: 0,
+                line_spacing_percent: 100,
+                fractional_advances: false,
+                background_padding: 0,
+                family: None,
+                font_style: FontStyle::REGULAR,
+                synthetic_style: FontStyle::REGULAR,
+                baseline_grid: None,
+            },
+        }
+    }
+
+    /// Sets the font.
+    ///
+    /// See [`BdfTextStyle::with_font`].
+    pub const fn font(mut self, font: &'a BdfFont<'a>) -> Self {
+        self.style.font = font;
+        self.style.family = None;
+        self.style = self.style.resolve_font();
+        self
+    }
+
+    /// Sets the font family.
+    ///
+    /// See [`BdfTextStyle::with_family`].
+    pub const fn family(mut self, family: &'a FontFamily<'a>) -> Self {
+        self.style.family = Some(family);
+        self.style = self.style.resolve_font();
+        self
+    }
+
+    /// Sets the font style.
+    ///
+    /// See [`BdfTextStyle::with_font_style`].
+    pub const fn font_style(mut self, font_style: FontStyle) -> Self {
+        self.style.font_style = font_style;
+        self.style = self.style.resolve_font();
+        self
+    }
+
+    /// Sets the text color.
+    pub const fn text_color(mut self, text_color: C) -> Self {
+        self.style.text_color = text_color;
+        self
+    }
+
+    /// Sets the background color.
+    pub const fn background_color(mut self, background_color: C) -> Self {
+        self.style.background_color = Some(background_color);
+        self
+    }
+
+    /// Resets the background color to transparent.
+    pub const fn reset_background_color(mut self) -> Self {
+        self.style.background_color = None;
+        self
+    }
+
+    /// Enables underline using the text color.
+    pub const fn underline(mut self) -> Self {
+        self.style.underline_color = DecorationColor::TextColor;
+        self
+    }
+
+    /// Enables underline with a custom color.
+    pub const fn underline_with_color(mut self, underline_color: C) -> Self {
+        self.style.underline_color = DecorationColor::Custom(underline_color);
+        self
+    }
+
+    /// Disables underline.
+    pub const fn reset_underline(mut self) -> Self {
+        self.style.underline_color = DecorationColor::None;
+        self
+    }
+
+    /// Enables strikethrough using the text color.
+    pub const fn strikethrough(mut self) -> Self {
+        self.style.strikethrough_color = DecorationColor::TextColor;
+        self
+    }
+
+    /// Enables strikethrough with a custom color.
+    pub const fn strikethrough_with_color(mut self, strikethrough_color: C) -> Self {
+        self.style.strikethrough_color = DecorationColor::Custom(strikethrough_color);
+        self
+    }
+
+    /// Disables strikethrough.
+    pub const fn reset_strikethrough(mut self) -> Self {
+        self.style.strikethrough_color = DecorationColor::None;
+        self
+    }
+
+    /// Sets the line gap.
+    ///
+    /// See [`BdfTextStyle::with_line_gap`].
+    pub const fn line_gap(mut self, line_gap: i32) -> Self {
+        self.style.line_gap = line_gap;
+        self
+    }
+
+    /// Sets the line spacing in percent of the full height of the font.
+    ///
+    /// See [`BdfTextStyle::with_line_spacing_percent`].
+    pub const fn line_spacing_percent(mut self, percent: u16) -> Self {
+        self.style.line_spacing_percent = percent;
+        self
+    }
+
+    /// Enables fractional advances.
+    ///
+    /// See [`BdfTextStyle::with_fractional_advances`].
+    pub const fn fractional_advances(mut self) -> Self {
+        self.style.fractional_advances = true;
+        self
+    }
+
+    /// Sets the background padding.
+    ///
+    /// See [`BdfTextStyle::with_background_padding`].
+    pub const fn background_padding(mut self, padding: u32) -> Self {
+        self.style.background_padding = padding;
+        self
+    }
+
+    /// Sets a baseline grid.
+    ///
+    /// See [`BdfTextStyle::with_baseline_grid`].
+    pub const fn baseline_grid(mut self, baseline_grid: BaselineGrid) -> Self {
+        self.style.baseline_grid = Some(baseline_grid);
+        self
+    }
+
+    #[deprecated(note = "use `line_gap` instead")]
+    pub const fn height_adjust(self, height_adjust: i32) -> Self {
+        self.line_gap(height_adjust)
+    }
+
+    /// Builds the text style.
+    pub const fn build(self) -> BdfTextStyle<'a, C> {
+        self.style
+    }
+}
+
+impl<'a, C: PixelColor> From<&BdfTextStyle<'a, C>> for BdfTextStyleBuilder<'a, C> {
+    fn from(style: &BdfTextStyle<'a, C>) -> Self {
+        Self { style: *style }
+    }
+}
+
 impl<C: PixelColor> CharacterStyle for BdfTextStyle<'_, C> {
     type Color = C;
 
@@ -161,66 +1245,810 @@ impl<C: PixelColor> TextRenderer for BdfTextStyle<'_, C> {
     fn draw_string<D>(
         &self,
         text: &str,
-        mut position: Point,
+        original_position: Point,
         baseline: Baseline,
         target: &mut D,
     ) -> Result<Point, D::Error>
     where
         D: DrawTarget<Color = Self::Color>,
     {
-        position -= Point::new(0, self.baseline_offset(baseline));
+        // Padded backgrounds are filled before the glyphs are drawn on top of them.
+        let mut blit_background = self.background_color;
+        if let Some(background_color) = self.background_color {
+            if self.background_padding > 0 {
+                let bounding_box = self
+                    .measure_string(text, original_position, baseline)
+                    .bounding_box;
+                target.fill_solid(&bounding_box, background_color)?;
+                blit_background = None;
+            }
+        }
 
-        for c in text.chars() {
-            let glyph = self.font.get_glyph(c);
+        let position = self.alphabetic_position(original_position, baseline);
 
-            glyph.draw(
-                position,
-                self.text_color,
-                self.background_color,
-                self.font.data,
-                target,
-            )?;
+        if let Some(background_color) = blit_background {
+            self.draw_string_blit(text, position, background_color, target)?;
+        }
+
+        let mut positions = self.glyph_positions(text, position.x);
+        while let Some((_, glyph, x)) = positions.next() {
+            let glyph_position = Point::new(x, position.y);
 
-            self.draw_decorations(target, glyph.device_width, position)?;
+            if blit_background.is_none() {
+                self.draw_glyph(glyph, glyph_position, target)?;
+            }
 
-            position.x += glyph.device_width as i32;
+            self.draw_decorations(target, positions.x() - x, glyph_position)?;
         }
 
-        Ok(position)
+        // Like `MonoTextStyle`, the next position uses the baseline of the caller.
+        Ok(Point::new(positions.x(), original_position.y))
     }
 
     fn draw_whitespace<D>(
         &self,
         width: u32,
         position: Point,
-        _baseline: Baseline,
-        _target: &mut D,
+        baseline: Baseline,
+        target: &mut D,
     ) -> Result<Point, D::Error>
     where
         D: DrawTarget<Color = Self::Color>,
     {
+        // Decorations are continued across the whitespace, to prevent gaps between words.
+        let decoration_position = self.alphabetic_position(position, baseline);
+        let decoration_width = i32::try_from(width).unwrap_or(i32::MAX);
+        self.draw_decorations(target, decoration_width, decoration_position)?;
+
         Ok(position + Size::new(width, 0))
     }
 
     fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
-        let string_width = text
-            .chars()
-            .map(|c| self.font.get_glyph(c).device_width)
-            .sum();
-
-        let height = self.line_height() as i32; //+ self.font.font_descent;
-        let full_height = height + self.font.font_descent as i32;
-
-        let pos_adj = position - Point::new(0, self.baseline_offset(baseline) + height);
-        let size = Size::new(string_width, full_height as u32);
+        let bounds = self.measure_string_bounds(text, position, baseline);
 
         TextMetrics {
-            bounding_box: Rectangle::new(pos_adj, size),
-            next_position: position + size.x_axis(),
+            bounding_box: bounds.bounding_box,
+            next_position: bounds.next_position,
         }
     }
 
+    /// Returns the line height, which is the sum of the font ascent, descent and line gap.
+    ///
+    /// The ascent and descent are scaled by the line spacing percentage before the line gap is
+    /// added. If a baseline grid is set, the line height is rounded up to a multiple of the grid
+    /// spacing.
     fn line_height(&self) -> u32 {
-        (self.font.font_ascent as i32 + self.height_adjust).max(0) as u32
+        let scaled = (self.full_height() * u32::from(self.line_spacing_percent) + 50) / 100;
+        let line_height = (scaled as i32 + self.line_gap).max(0) as u32;
+
+        match self.baseline_grid {
+            Some(grid) if grid.spacing > 0 => line_height.div_ceil(grid.spacing) * grid.spacing,
+            _ => line_height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, tests::CallCounter, BitmapLayout, FontExtents};
+    use alloc::vec::Vec;
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor, text::Text};
+
+    const SMALL: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+    const LARGE: BdfFont = include_bdf!("examples/10x20.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn baselines_match_mono_font() {
+        use embedded_graphics::{
+            mono_font::MonoTextStyle,
+            text::{Text, TextStyle},
+        };
+
+        for font in [&SMALL, &LARGE].iter() {
+            let mut data = [0; 800];
+            let mono_font = font.to_mono_font(&mut data).unwrap();
+
+            for baseline in [
+                Baseline::Top,
+                Baseline::Middle,
+                Baseline::Alphabetic,
+                Baseline::Bottom,
+            ]
+            .iter()
+            {
+                let text_style = TextStyle::with_baseline(*baseline);
+                let position = Point::new(0, 10);
+
+                let mut display = MockDisplay::new();
+                display.set_allow_out_of_bounds_drawing(true);
+                Text::with_text_style(
+                    "AQ\nQA",
+                    position,
+                    BdfTextStyle::new(font, BinaryColor::On),
+                    text_style,
+                )
+                .draw(&mut display)
+                .unwrap();
+
+                let mut expected = MockDisplay::new();
+                expected.set_allow_out_of_bounds_drawing(true);
+                Text::with_text_style(
+                    "AQ\nQA",
+                    position,
+                    MonoTextStyle::new(&mono_font, BinaryColor::On),
+                    text_style,
+                )
+                .draw(&mut expected)
+                .unwrap();
+
+                display.assert_eq(&expected);
+            }
+        }
+    }
+
+    #[test]
+    fn switch_font() {
+        let style = BdfTextStyle::new(&SMALL, BinaryColor::On)
+            .with_bg_color(BinaryColor::Off)
+            .underline();
+
+        let mut large = style.with_font(&LARGE);
+        assert_eq!(large.char_advance('A'), 10);
+        assert_eq!(large.background_color(), Some(BinaryColor::Off));
+        assert_eq!(large.underline_color, DecorationColor::TextColor);
+
+        large.set_font(&SMALL);
+        assert_eq!(large, style);
+    }
+
+    #[test]
+    fn draw_string_with_background() {
+        let style = BdfTextStyle::new(&SMALL, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        let mut target = CallCounter::new();
+        let next = style
+            .draw_string("AI", Point::zero(), Baseline::Top, &mut target)
+            .unwrap();
+        assert_eq!(next, Point::new(12, 0));
+
+        assert_eq!(target.contiguous_fills, 1);
+        assert_eq!(target.pixels, 0);
+        target.display.assert_pattern(&[
+            "............",
+            "..#....###..",
+            ".#.#....#...",
+            "#...#...#...",
+            "#...#...#...",
+            "#####...#...",
+            "#...#...#...",
+            "#...#..###..",
+            "............",
+            "............",
+        ]);
+
+        // The next position uses the baseline of the caller, like `MonoTextStyle`.
+        for baseline in [
+            Baseline::Top,
+            Baseline::Middle,
+            Baseline::Alphabetic,
+            Baseline::Bottom,
+        ] {
+            let mut display = MockDisplay::new();
+            display.set_allow_out_of_bounds_drawing(true);
+            let position = Point::new(1, 5);
+            let next = style
+                .draw_string("AI", position, baseline, &mut display)
+                .unwrap();
+            assert_eq!(next, Point::new(13, 5), "{:?}", baseline);
+            assert_eq!(
+                next,
+                style.measure_string("AI", position, baseline).next_position
+            );
+        }
+    }
+
+    #[test]
+    fn chained_draw_string() {
+        let style = BdfTextStyle::new(&SMALL, BinaryColor::On);
+
+        for baseline in [Baseline::Top, Baseline::Middle, Baseline::Bottom] {
+            let mut display = MockDisplay::new();
+            display.set_allow_out_of_bounds_drawing(true);
+            let next = Text::with_baseline("A", Point::new(0, 10), style, baseline)
+                .draw(&mut display)
+                .unwrap();
+            Text::with_baseline("I", next, style, baseline)
+                .draw(&mut display)
+                .unwrap();
+
+            let mut expected = MockDisplay::new();
+            expected.set_allow_out_of_bounds_drawing(true);
+            Text::with_baseline("AI", Point::new(0, 10), style, baseline)
+                .draw(&mut expected)
+                .unwrap();
+
+            display.assert_eq(&expected);
+        }
+    }
+
+    #[test]
+    fn background_padding() {
+        let style = BdfTextStyle::new(&SMALL, BinaryColor::On)
+            .with_bg_color(BinaryColor::Off)
+            .with_background_padding(2);
+        assert_eq!(style.background_padding(), 2);
+
+        let metrics = style.measure_string("I", Point::new(2, 2), Baseline::Top);
+        assert_eq!(
+            metrics.bounding_box,
+            Rectangle::new(Point::zero(), Size::new(10, 14))
+        );
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let next = style
+            .draw_string("I", Point::new(2, 2), Baseline::Top, &mut display)
+            .unwrap();
+        assert_eq!(next, metrics.next_position);
+        assert_eq!(next, Point::new(8, 2));
+
+        for baseline in [Baseline::Middle, Baseline::Bottom] {
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            display.set_allow_out_of_bounds_drawing(true);
+            let next = style
+                .draw_string("I", Point::new(2, 20), baseline, &mut display)
+                .unwrap();
+            assert_eq!(next, Point::new(8, 20), "{:?}", baseline);
+        }
+        display.assert_pattern(&[
+            "..........",
+            "..........",
+            "..........",
+            "...###....",
+            "....#.....",
+            "....#.....",
+            "....#.....",
+            "....#.....",
+            "....#.....",
+            "...###....",
+            "..........",
+            "..........",
+            "..........",
+            "..........",
+        ]);
+
+        // Padding has no effect without a background color.
+        let transparent = BdfTextStyle::new(&SMALL, BinaryColor::On).with_background_padding(2);
+        assert_eq!(
+            transparent.measure_string("I", Point::zero(), Baseline::Top),
+            BdfTextStyle::new(&SMALL, BinaryColor::On).measure_string(
+                "I",
+                Point::zero(),
+                Baseline::Top
+            )
+        );
+    }
+
+    #[test]
+    fn draw_string_opaque() {
+        let style = BdfTextStyle::new(&SMALL, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        display
+            .fill_solid(
+                &Rectangle::new(Point::zero(), Size::new(6, 10)),
+                BinaryColor::On,
+            )
+            .unwrap();
+
+        let next = style
+            .draw_string_opaque("I", Point::zero(), Baseline::Top, &mut display)
+            .unwrap();
+        assert_eq!(next, Point::new(6, 0));
+
+        for baseline in [Baseline::Middle, Baseline::Bottom] {
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            display.set_allow_out_of_bounds_drawing(true);
+            let next = style
+                .draw_string_opaque("I", Point::new(0, 20), baseline, &mut display)
+                .unwrap();
+            assert_eq!(next, Point::new(6, 20), "{:?}", baseline);
+        }
+        display.assert_pattern(&[
+            "......", ".###..", "..#...", "..#...", "..#...", "..#...", "..#...", ".###..",
+            "......", "......",
+        ]);
+
+        let mut display = MockDisplay::new();
+        BdfTextStyle::new(&SMALL, BinaryColor::On)
+            .draw_string_opaque("I", Point::zero(), Baseline::Top, &mut display)
+            .unwrap();
+        display.assert_pattern(&[
+            "     ", " ### ", "  #  ", "  #  ", "  #  ", "  #  ", "  #  ", " ### ",
+        ]);
+    }
+
+    #[test]
+    fn decorated_whitespace() {
+        let style = BdfTextStyle::new(&SMALL, BinaryColor::On)
+            .underline()
+            .strikethrough();
+
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_whitespace(4, Point::new(1, 0), Baseline::Top, &mut display)
+            .unwrap();
+
+        assert_eq!(next, Point::new(5, 0));
+        display.assert_pattern(&[
+            "     ", "     ", "     ", "     ", "     ", " ####", "     ", "     ", " ####",
+        ]);
+
+        let mut display = MockDisplay::new();
+        BdfTextStyle::new(&SMALL, BinaryColor::On)
+            .draw_whitespace(4, Point::new(1, 0), Baseline::Top, &mut display)
+            .unwrap();
+        assert!(display.affected_area().is_zero_sized());
+    }
+
+    #[test]
+    fn measure_string_cell() {
+        let style = BdfTextStyle::new(&SMALL, BinaryColor::On);
+
+        let bounds = style.measure_string_bounds("AB", Point::new(1, 2), Baseline::Top);
+        let cell = Rectangle::new(Point::new(1, 2), Size::new(12, 10));
+        assert_eq!(bounds.advance_box, cell);
+        assert_eq!(bounds.bounding_box, cell);
+        assert_eq!(bounds.next_position, Point::new(13, 2));
+        assert_eq!(
+            style
+                .measure_string("AB", Point::new(1, 2), Baseline::Top)
+                .bounding_box,
+            cell
+        );
+    }
+
+    #[test]
+    fn measure_string_ink_overhang() {
+        use crate::BdfGlyph;
+
+        // Italic glyph which extends one pixel left of the origin and one pixel right of the
+        // advance width.
+        let glyphs = [BdfGlyph {
+            character: 'a',
+            bounding_box: Rectangle::new(Point::new(-1, -3), Size::new(5, 3)),
+            device_width: 3,
+            start_index: 0,
+        }];
+        let font = BdfFont {
+            replacement_character: 0,
+            glyphs: &glyphs,
+            data: &[0xFF, 0xFF],
+            pixel_size: 4,
+            font_ascent: 3,
+            font_descent: 1,
+            vertical_metrics: &[],
+            scalable_widths: &[],
+            bitmap_layout: BitmapLayout::default(),
+            extents: FontExtents::default(),
+        };
+        let style = BdfTextStyle::new(&font, BinaryColor::On);
+
+        let bounds = style.measure_string_bounds("a", Point::new(5, 5), Baseline::Alphabetic);
+        assert_eq!(
+            bounds.advance_box,
+            Rectangle::new(Point::new(5, 3), Size::new(3, 4))
+        );
+        assert_eq!(
+            bounds.ink_box,
+            Rectangle::new(Point::new(4, 2), Size::new(5, 3))
+        );
+        assert_eq!(
+            bounds.bounding_box,
+            Rectangle::new(Point::new(4, 2), Size::new(5, 5))
+        );
+
+        // All drawn pixels are inside the measured bounding box.
+        let mut display = MockDisplay::new();
+        style
+            .draw_string("a", Point::new(5, 5), Baseline::Alphabetic, &mut display)
+            .unwrap();
+        assert_eq!(
+            display.affected_area(),
+            Rectangle::new(Point::new(4, 2), Size::new(5, 3))
+        );
+        assert!(bounds.bounding_box.contains(Point::new(4, 2)));
+        assert!(bounds.bounding_box.contains(Point::new(8, 4)));
+    }
+
+    #[test]
+    fn background_glyph_overhang() {
+        use crate::BdfGlyph;
+
+        // Glyphs whose ink extends two cells to the left and right of their advance.
+        let glyphs = [
+            BdfGlyph {
+                character: ',',
+                bounding_box: Rectangle::new(Point::new(-4, -1), Size::new(1, 1)),
+                device_width: 2,
+                start_index: 8,
+            },
+            BdfGlyph {
+                character: '-',
+                bounding_box: Rectangle::new(Point::new(0, -1), Size::new(7, 1)),
+                device_width: 2,
+                start_index: 0,
+            },
+            BdfGlyph {
+                character: '.',
+                bounding_box: Rectangle::zero(),
+                device_width: 2,
+                start_index: 0,
+            },
+        ];
+        let font = BdfFont {
+            replacement_character: 2,
+            glyphs: &glyphs,
+            data: &[0xFE, 0x80],
+            pixel_size: 2,
+            font_ascent: 1,
+            font_descent: 0,
+            vertical_metrics: &[],
+            scalable_widths: &[],
+            bitmap_layout: BitmapLayout::default(),
+            extents: FontExtents::default(),
+        };
+        let style = BdfTextStyle::new(&font, BinaryColor::On).with_bg_color(BinaryColor::Off);
+
+        let mut display = MockDisplay::new();
+        style
+            .draw_string("-..", Point::new(0, 1), Baseline::Alphabetic, &mut display)
+            .unwrap();
+        display.assert_pattern(&["#######"]);
+
+        let mut display = MockDisplay::new();
+        style
+            .draw_string("..,", Point::new(0, 1), Baseline::Alphabetic, &mut display)
+            .unwrap();
+        display.assert_pattern(&["#....."]);
+    }
+
+    #[test]
+    fn negative_advance() {
+        use crate::BdfGlyph;
+
+        // Backspace glyph, which moves the pen back to overstrike the previous character.
+        let glyphs = [
+            BdfGlyph {
+                character: '\u{8}',
+                bounding_box: Rectangle::zero(),
+                device_width: -3,
+                start_index: 0,
+            },
+            BdfGlyph {
+                character: '_',
+                bounding_box: Rectangle::new(Point::new(0, 1), Size::new(3, 1)),
+                device_width: 3,
+                start_index: 9,
+            },
+            BdfGlyph {
+                character: 'a',
+                bounding_box: Rectangle::new(Point::new(0, -2), Size::new(3, 3)),
+                device_width: 3,
+                start_index: 0,
+            },
+        ];
+        let font = BdfFont {
+            replacement_character: 0,
+            glyphs: &glyphs,
+            data: &[0xF7, 0xF0],
+            pixel_size: 4,
+            font_ascent: 3,
+            font_descent: 1,
+            vertical_metrics: &[],
+            scalable_widths: &[],
+            bitmap_layout: BitmapLayout::default(),
+            extents: FontExtents::default(),
+        };
+        let style = BdfTextStyle::new(&font, BinaryColor::On);
+        let position = Point::new(0, 2);
+
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_string("a\u{8}_", position, Baseline::Alphabetic, &mut display)
+            .unwrap();
+        assert_eq!(next, Point::new(3, 2));
+        display.assert_pattern(&["###", "# #", "###", "###"]);
+
+        let mut display = MockDisplay::new();
+        style
+            .with_bg_color(BinaryColor::Off)
+            .draw_string("a\u{8}_", position, Baseline::Alphabetic, &mut display)
+            .unwrap();
+        display.assert_pattern(&["###", "#.#", "###", "###"]);
+
+        let bounds = style.measure_string_bounds("\u{8}", position, Baseline::Alphabetic);
+        assert_eq!(
+            bounds.advance_box,
+            Rectangle::new(Point::new(-3, 0), Size::new(3, 4))
+        );
+        assert_eq!(bounds.next_position, Point::new(-3, 2));
+    }
+
+    #[test]
+    fn fractional_advances() {
+        use crate::BdfGlyph;
+
+        let glyphs = [BdfGlyph {
+            character: 'a',
+            bounding_box: Rectangle::new(Point::zero(), Size::new(1, 1)),
+            device_width: 3,
+            start_index: 0,
+        }];
+        let font = BdfFont {
+            replacement_character: 0,
+            glyphs: &glyphs,
+            data: &[0x80],
+            pixel_size: 4,
+            font_ascent: 1,
+            font_descent: 0,
+            vertical_metrics: &[],
+            scalable_widths: &[2500],
+            bitmap_layout: BitmapLayout::default(),
+            extents: FontExtents::default(),
+        };
+        assert_eq!(font.scalable_width('a'), Some(2500));
+        // SWIDTH 576 at 10 points and 75 DPI.
+        assert_eq!(SMALL.scalable_width('A'), Some(6000));
+
+        let style = BdfTextStyle::new(&font, BinaryColor::On);
+        let fractional = style.with_fractional_advances(true);
+        assert!(fractional.fractional_advances());
+
+        let mut display = MockDisplay::new();
+        let next = fractional
+            .draw_string("aaaa", Point::zero(), Baseline::Alphabetic, &mut display)
+            .unwrap();
+        assert_eq!(next, Point::new(10, 0));
+        display.assert_pattern(&["#  # #  #"]);
+
+        // The background is drawn using the same glyph positions.
+        let mut display = MockDisplay::new();
+        fractional
+            .with_bg_color(BinaryColor::Off)
+            .draw_string("aaaa", Point::zero(), Baseline::Alphabetic, &mut display)
+            .unwrap();
+        display.assert_pattern(&["#..#.#..#."]);
+
+        assert_eq!(
+            fractional
+                .measure_string("aaaa", Point::zero(), Baseline::Alphabetic)
+                .next_position,
+            Point::new(10, 0)
+        );
+        assert_eq!(
+            fractional.advances("aaa").collect::<Vec<_>>(),
+            [('a', 3, 0), ('a', 2, 3), ('a', 3, 5)]
+        );
+        assert_eq!(
+            style
+                .measure_string("aaaa", Point::zero(), Baseline::Alphabetic)
+                .next_position,
+            Point::new(12, 0)
+        );
+    }
+
+    #[test]
+    fn line_gap() {
+        let style = BdfTextStyle::new(&SMALL, BinaryColor::On);
+        let spaced = style.with_line_gap(3);
+
+        assert_eq!(spaced.line_gap(), 3);
+        assert_eq!(spaced.line_height(), style.line_height() + 3);
+        assert_eq!(spaced.full_height(), style.full_height());
+        assert_eq!(
+            spaced.measure_string("AB", Point::zero(), Baseline::Bottom),
+            style.measure_string("AB", Point::zero(), Baseline::Bottom)
+        );
+        assert_eq!(style.with_line_gap(-100).line_height(), 0);
+    }
+
+    #[test]
+    fn line_spacing_percent() {
+        let style = BdfTextStyle::new(&SMALL, BinaryColor::On).with_line_spacing_percent(125);
+
+        assert_eq!(style.line_spacing_percent(), 125);
+        assert_eq!(style.line_height(), 13);
+        assert_eq!(style.with_line_gap(-1).line_height(), 12);
+        assert_eq!(style.with_line_spacing_percent(0).line_height(), 0);
+        assert_eq!(style.block_height(3), 10 + 2 * 13);
+
+        let large = BdfTextStyle::new(&LARGE, BinaryColor::On).with_line_spacing_percent(120);
+        assert_eq!(large.line_height(), 24);
+        assert_eq!(
+            large.measure_string("AB", Point::zero(), Baseline::Bottom),
+            large.with_line_spacing_percent(100).measure_string(
+                "AB",
+                Point::zero(),
+                Baseline::Bottom
+            )
+        );
+    }
+
+    #[test]
+    fn builder() {
+        const STYLE: BdfTextStyle<BinaryColor> = BdfTextStyleBuilder::new(&SMALL, BinaryColor::Off)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .underline()
+            .strikethrough_with_color(BinaryColor::Off)
+            .line_gap(2)
+            .build();
+
+        let expected = BdfTextStyle::new(&SMALL, BinaryColor::On)
+            .with_bg_color(BinaryColor::Off)
+            .underline()
+            .with_line_gap(2);
+        assert_eq!(
+            STYLE,
+            BdfTextStyle {
+                strikethrough_color: DecorationColor::Custom(BinaryColor::Off),
+                ..expected
+            }
+        );
+
+        let builder = BdfTextStyleBuilder::from(&STYLE)
+            .reset_background_color()
+            .reset_underline()
+            .reset_strikethrough();
+        assert_eq!(
+            builder.build(),
+            BdfTextStyle::new(&SMALL, BinaryColor::On).with_line_gap(2)
+        );
+    }
+
+    fn draw_top(style: BdfTextStyle<BinaryColor>, text: &str) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        style
+            .draw_string(text, Point::zero(), Baseline::Top, &mut display)
+            .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn baseline_grid() {
+        use embedded_graphics::text::Text;
+
+        let grid = BaselineGrid::new(8, 2);
+        assert_eq!(
+            [-7, -6, 0, 2, 3, 10].map(|y| grid.snap(y)),
+            [-6, -6, 2, 2, 10, 10]
+        );
+
+        // Both columns use the same grid, which keeps the baselines aligned.
+        let small = BdfTextStyle::new(&SMALL, BinaryColor::On).with_baseline_grid(grid);
+        let large = BdfTextStyle::new(&LARGE, BinaryColor::On).with_baseline_grid(grid);
+        assert_eq!((small.line_height(), large.line_height()), (16, 24));
+
+        for style in [small, large].iter() {
+            let metrics = style.measure_string("A", Point::new(0, 5), Baseline::Alphabetic);
+            let bottom = metrics.bounding_box.bottom_right().unwrap().y;
+            assert_eq!(bottom - style.font.font_descent as i32, 10);
+        }
+
+        let mut display = MockDisplay::new();
+        Text::with_baseline("A\nA", Point::new(0, 1), small, Baseline::Top)
+            .draw(&mut display)
+            .unwrap();
+        // The glyphs end on the baselines at y = 10 and y = 26.
+        let rows = display.affected_area().rows();
+        assert_eq!((rows.start, rows.end), (4, 27));
+
+        const STYLE: BdfTextStyle<BinaryColor> = BdfTextStyleBuilder::new(&SMALL, BinaryColor::On)
+            .baseline_grid(BaselineGrid::new(8, 2))
+            .build();
+        assert_eq!(STYLE, small);
+        assert_eq!(small.reset_baseline_grid().line_height(), 10);
+    }
+
+    #[test]
+    fn synthetic_styles() {
+        let style = BdfTextStyle::new(&SMALL, BinaryColor::On);
+
+        draw_top(style.bold(), "I").assert_pattern(&[
+            "      ", //
+            " #### ", //
+            "  ##  ", //
+            "  ##  ", //
+            "  ##  ", //
+            "  ##  ", //
+            "  ##  ", //
+            " #### ", //
+        ]);
+        draw_top(style.italic(), "I").assert_pattern(&[
+            "      ", //
+            "  ### ", //
+            "   #  ", //
+            "   #  ", //
+            "  #   ", //
+            "  #   ", //
+            "  #   ", //
+            " ###  ", //
+        ]);
+
+        // The advances and the line height aren't changed.
+        let bold = style.bold().italic();
+        assert_eq!(bold.text_advance("AB"), style.text_advance("AB"));
+        assert_eq!(bold.line_height(), style.line_height());
+        assert_eq!(
+            bold.measure_string("I", Point::zero(), Baseline::Top)
+                .bounding_box,
+            Rectangle::new(Point::new(-1, 0), Size::new(9, 10))
+        );
+    }
+
+    #[test]
+    fn synthetic_styles_with_background() {
+        for style in [
+            BdfTextStyle::new(&SMALL, BinaryColor::On).bold(),
+            BdfTextStyle::new(&SMALL, BinaryColor::On).italic(),
+            BdfTextStyle::new(&SMALL, BinaryColor::On).bold().italic(),
+        ]
+        .iter()
+        {
+            let mut expected = MockDisplay::new();
+            expected.set_allow_overdraw(true);
+            let bounding_box = style
+                .measure_string("HI", Point::zero(), Baseline::Top)
+                .bounding_box
+                .translate(Point::new(1, 0));
+            expected
+                .fill_solid(&bounding_box, BinaryColor::Off)
+                .unwrap();
+            style
+                .draw_string("HI", Point::new(1, 0), Baseline::Top, &mut expected)
+                .unwrap();
+
+            let mut display = MockDisplay::new();
+            style
+                .with_bg_color(BinaryColor::Off)
+                .draw_string("HI", Point::new(1, 0), Baseline::Top, &mut display)
+                .unwrap();
+            display.assert_eq(&expected);
+        }
+    }
+
+    #[test]
+    fn font_family() {
+        const BOLD: BdfFont = include_bdf!("examples/10x20.bdf", 'A'..='Z');
+        const FAMILY: FontFamily = FontFamily::new(&SMALL).with_bold(&BOLD);
+
+        let style = BdfTextStyle::new(&LARGE, BinaryColor::On).with_family(&FAMILY);
+        assert!(core::ptr::eq(style.font(), &SMALL));
+
+        let bold = style.bold();
+        assert!(core::ptr::eq(bold.font(), &BOLD));
+        assert_eq!(bold.font_style(), FontStyle::BOLD);
+        assert_eq!(bold.synthetic_style, FontStyle::REGULAR);
+
+        let bold_italic = bold.italic();
+        assert!(core::ptr::eq(bold_italic.font(), &BOLD));
+        assert_eq!(bold_italic.synthetic_style, FontStyle::ITALIC);
+
+        // Setting a font removes the family.
+        let regular = bold.with_font(&SMALL);
+        assert_eq!(regular.family(), None);
+        assert_eq!(regular.synthetic_style, FontStyle::BOLD);
+
+        const STYLE: BdfTextStyle<BinaryColor> = BdfTextStyleBuilder::new(&LARGE, BinaryColor::On)
+            .family(&FAMILY)
+            .font_style(FontStyle::BOLD)
+            .build();
+        assert_eq!(STYLE, bold);
     }
 }
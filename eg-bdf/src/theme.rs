@@ -0,0 +1,201 @@
+//! Themes.
+//!
+//! A [`Theme`] contains the font family and colors of a user interface and creates text styles
+//! for a small set of [`StylePreset`]s. Widgets and application code can request a style by its
+//! preset, instead of creating every style individually, which makes it easy to change the
+//! appearance of the whole user interface in a single place.
+//!
+//! ```
+//! use eg_bdf::{
+//!     family::FontFamily,
+//!     include_bdf,
+//!     theme::{StylePreset, Theme},
+//!     BdfFont,
+//! };
+//! use embedded_graphics::{pixelcolor::Rgb565, prelude::*};
+//!
+//! const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+//! const FAMILY: FontFamily = FontFamily::new(&FONT);
+//!
+//! const THEME: Theme<Rgb565> = Theme::new(&FAMILY, Rgb565::WHITE, Some(Rgb565::BLACK))
+//!     .with_accent_color(Rgb565::YELLOW)
+//!     .with_disabled_color(Rgb565::CSS_GRAY);
+//!
+//! let title = THEME.style(StylePreset::Emphasized);
+//! assert_eq!(title.text_color(), Rgb565::YELLOW);
+//!
+//! let selected = THEME.style(StylePreset::Inverted);
+//! assert_eq!(selected.background_color(), Some(Rgb565::WHITE));
+//! ```
+
+use embedded_graphics::{prelude::*, text::renderer::CharacterStyle};
+
+use crate::{family::FontFamily, text::BdfTextStyle};
+
+/// Style preset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StylePreset {
+    /// Regular text.
+    #[default]
+    Normal,
+    /// Bold text in the accent color.
+    Emphasized,
+    /// Text with swapped text and background colors, e.g. for selected items.
+    Inverted,
+    /// Text in the disabled color.
+    Disabled,
+}
+
+/// Theme.
+///
+/// All presets use the regular face of the font family, except [`StylePreset::Emphasized`],
+/// which uses the bold face or a synthesized bold version of the regular face.
+///
+/// The background color of the theme is set in all styles. Themes without a background color
+/// create transparent styles, except for [`StylePreset::Inverted`], which uses the text color as
+/// background color and the accent color for the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct Theme<'a, C> {
+    family: &'a FontFamily<'a>,
+    text_color: C,
+    background_color: Option<C>,
+    accent_color: C,
+    disabled_color: C,
+}
+
+impl<'a, C: PixelColor> Theme<'a, C> {
+    /// Creates a new theme.
+    ///
+    /// The accent and disabled colors are initialized to the text color. If `background_color`
+    /// is `None` the background isn't drawn.
+    pub const fn new(
+        family: &'a FontFamily<'a>,
+        text_color: C,
+        background_color: Option<C>,
+    ) -> Self {
+        Self {
+            family,
+            text_color,
+            background_color,
+            accent_color: text_color,
+            disabled_color: text_color,
+        }
+    }
+
+    /// Sets the accent color, which is used for emphasized text.
+    pub const fn with_accent_color(mut self, accent_color: C) -> Self {
+        self.accent_color = accent_color;
+        self
+    }
+
+    /// Sets the color of disabled text.
+    pub const fn with_disabled_color(mut self, disabled_color: C) -> Self {
+        self.disabled_color = disabled_color;
+        self
+    }
+
+    /// Returns the font family.
+    pub fn family(&self) -> &'a FontFamily<'a> {
+        self.family
+    }
+
+    /// Returns the text color.
+    pub fn text_color(&self) -> C {
+        self.text_color
+    }
+
+    /// Returns the background color.
+    pub fn background_color(&self) -> Option<C> {
+        self.background_color
+    }
+
+    /// Returns the accent color.
+    pub fn accent_color(&self) -> C {
+        self.accent_color
+    }
+
+    /// Returns the disabled color.
+    pub fn disabled_color(&self) -> C {
+        self.disabled_color
+    }
+
+    /// Returns the text style for a preset.
+    pub fn style(&self, preset: StylePreset) -> BdfTextStyle<'a, C> {
+        let (text_color, background_color) = match preset {
+            StylePreset::Normal => (self.text_color, self.background_color),
+            StylePreset::Emphasized => (self.accent_color, self.background_color),
+            StylePreset::Inverted => (
+                self.background_color.unwrap_or(self.accent_color),
+                Some(self.text_color),
+            ),
+            StylePreset::Disabled => (self.disabled_color, self.background_color),
+        };
+
+        let mut style =
+            BdfTextStyle::new(self.family.regular(), text_color).with_family(self.family);
+        style.set_background_color(background_color);
+
+        if preset == StylePreset::Emphasized {
+            style.bold()
+        } else {
+            style
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{family::FontStyle, include_bdf, BdfFont};
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    const REGULAR: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z');
+    const BOLD: BdfFont = include_bdf!("examples/10x20.bdf", 'A'..='Z');
+    const FAMILY: FontFamily = FontFamily::new(&REGULAR).with_bold(&BOLD);
+
+    #[test]
+    fn presets() {
+        let theme = Theme::new(&FAMILY, Rgb565::WHITE, Some(Rgb565::BLUE))
+            .with_accent_color(Rgb565::RED)
+            .with_disabled_color(Rgb565::CSS_GRAY);
+
+        let style = theme.style(StylePreset::default());
+        assert_eq!(style.font(), &REGULAR);
+        assert_eq!(style.text_color(), Rgb565::WHITE);
+        assert_eq!(style.background_color(), Some(Rgb565::BLUE));
+
+        let style = theme.style(StylePreset::Emphasized);
+        assert_eq!(style.font(), &BOLD);
+        assert_eq!(style.font_style(), FontStyle::BOLD);
+        assert_eq!(style.text_color(), Rgb565::RED);
+
+        let style = theme.style(StylePreset::Inverted);
+        assert_eq!(style.font(), &REGULAR);
+        assert_eq!(style.text_color(), Rgb565::BLUE);
+        assert_eq!(style.background_color(), Some(Rgb565::WHITE));
+
+        let style = theme.style(StylePreset::Disabled);
+        assert_eq!(style.text_color(), Rgb565::CSS_GRAY);
+        assert_eq!(style.background_color(), Some(Rgb565::BLUE));
+    }
+
+    #[test]
+    fn transparent() {
+        let theme = Theme::new(&FAMILY, Rgb565::WHITE, None).with_accent_color(Rgb565::BLACK);
+
+        let style = theme.style(StylePreset::Normal);
+        assert_eq!(style.text_color(), Rgb565::WHITE);
+        assert_eq!(style.background_color(), None);
+
+        let style = theme.style(StylePreset::Emphasized);
+        assert_eq!(style.text_color(), Rgb565::BLACK);
+        assert_eq!(style.background_color(), None);
+
+        let style = theme.style(StylePreset::Inverted);
+        assert_eq!(style.text_color(), Rgb565::BLACK);
+        assert_eq!(style.background_color(), Some(Rgb565::WHITE));
+    }
+}
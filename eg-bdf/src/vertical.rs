@@ -0,0 +1,206 @@
+//! Vertical text.
+
+use embedded_graphics::prelude::*;
+
+use crate::{text::BdfTextStyle, VerticalMetrics};
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Returns the vertical metrics of a character.
+    ///
+    /// The metrics are read from the font if it provides vertical metrics. Otherwise glyphs are
+    /// centered horizontally on the pen position, start at the top row of the font ascent and
+    /// advance by the height of the character cell.
+    pub fn char_vertical_metrics(&self, c: char) -> VerticalMetrics {
+        let font = self.font();
+
+        font.vertical_metrics(c).unwrap_or_else(|| VerticalMetrics {
+            origin: Point::new(
+                font.get_glyph(c).device_width / 2,
+                -(font.font_ascent as i32 - 1),
+            ),
+            advance: self.full_height() as i32,
+        })
+    }
+
+    /// Returns the sum of the vertical advances of all characters in `text`.
+    pub fn vertical_advance(&self, text: &str) -> i32 {
+        text.chars()
+            .map(|c| self.char_vertical_metrics(c).advance)
+            .sum()
+    }
+
+    /// Draws a string from top to bottom.
+    ///
+    /// `position` is the vertical origin of the first character, which is usually the
+    /// horizontal center of its top row. All characters are drawn in a single column, newlines
+    /// aren't treated specially. If the style has a background color, the bounding boxes of the
+    /// glyphs are filled with the background color. Returns the vertical origin of the next
+    /// character.
+    pub fn draw_vertical<D>(
+        &self,
+        text: &str,
+        mut position: Point,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let font = self.font();
+
+        for c in text.chars() {
+            let metrics = self.char_vertical_metrics(c);
+
+            font.get_glyph(c).draw_with_layout(
+                position - metrics.origin,
+                self.text_color(),
+                self.background_color(),
+                font.data,
+                font.bitmap_layout,
+                target,
+            )?;
+
+            position.y += metrics.advance;
+        }
+
+        Ok(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        pixelcolor::BinaryColor,
+        text::{renderer::TextRenderer, Baseline},
+    };
+
+    const VERTICAL: BdfFont = include_bdf!("examples/vertical.bdf");
+    const HORIZONTAL: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn font_metrics() {
+        assert_eq!(
+            VERTICAL.vertical_metrics('A'),
+            Some(VerticalMetrics {
+                origin: Point::new(2, -2),
+                advance: 5,
+            })
+        );
+        // Glyph specific vector and default advance.
+        assert_eq!(
+            VERTICAL.vertical_metrics('B'),
+            Some(VerticalMetrics {
+                origin: Point::new(1, -2),
+                advance: 4,
+            })
+        );
+        assert_eq!(HORIZONTAL.vertical_metrics('A'), None);
+    }
+
+    #[test]
+    fn draw_vertical() {
+        let style = BdfTextStyle::new(&VERTICAL, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_vertical("AB", Point::new(2, 0), &mut display)
+            .unwrap();
+        assert_eq!(next, Point::new(2, 9));
+        assert_eq!(style.vertical_advance("AB"), 9);
+
+        display.assert_pattern(&[
+            "### ", //
+            "# # ", //
+            "### ", //
+            "    ", //
+            "    ", //
+            "    ", //
+            " ###", //
+            " ###", //
+        ]);
+    }
+
+    #[test]
+    fn fallback_metrics() {
+        let style = BdfTextStyle::new(&HORIZONTAL, BinaryColor::On);
+
+        assert_eq!(
+            style.char_vertical_metrics('A'),
+            VerticalMetrics {
+                origin: Point::new(3, -7),
+                advance: 10,
+            }
+        );
+
+        let mut display = MockDisplay::new();
+        style
+            .draw_vertical("I", Point::new(3, 0), &mut display)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("I", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+        display.assert_eq(&expected);
+    }
+
+    /// Draws a string horizontally and rotates the pixels around the origin of the string.
+    fn rotated_pattern(
+        text: &str,
+        origin: Point,
+        rotate: fn(Point) -> Point,
+    ) -> MockDisplay<BinaryColor> {
+        let mut horizontal = MockDisplay::new();
+        BdfTextStyle::new(&HORIZONTAL, BinaryColor::On)
+            .draw_string(
+                text,
+                Point::new(0, 20),
+                Baseline::Alphabetic,
+                &mut horizontal,
+            )
+            .unwrap();
+
+        let mut display = MockDisplay::new();
+        let area = horizontal.affected_area();
+        for point in area.points() {
+            if let Some(color) = horizontal.get_pixel(point) {
+                let rotated = origin + rotate(point - Point::new(0, 20));
+                Pixel(rotated, color).draw(&mut display).unwrap();
+            }
+        }
+
+        display
+    }
+
+    #[test]
+    fn rotated_fonts() {
+        const CLOCKWISE: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z', rotate = 90);
+        const COUNTER_CLOCKWISE: BdfFont =
+            include_bdf!("examples/6x10.bdf", 'A'..='Z', rotate = 270);
+
+        let style = BdfTextStyle::new(&CLOCKWISE, BinaryColor::On);
+        assert_eq!(style.char_advance('A'), 10);
+        assert_eq!(CLOCKWISE.scalable_widths, &[] as &[i32]);
+
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_vertical("AB", Point::new(20, 2), &mut display)
+            .unwrap();
+        assert_eq!(next, Point::new(20, 14));
+        display.assert_eq(&rotated_pattern("AB", Point::new(20, 2), |p| {
+            Point::new(-p.y, p.x)
+        }));
+
+        let style = BdfTextStyle::new(&COUNTER_CLOCKWISE, BinaryColor::On);
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_vertical("AB", Point::new(20, 30), &mut display)
+            .unwrap();
+        assert_eq!(next, Point::new(20, 18));
+        display.assert_eq(&rotated_pattern("AB", Point::new(20, 30), |p| {
+            Point::new(p.y, -p.x)
+        }));
+    }
+}
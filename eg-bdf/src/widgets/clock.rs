@@ -0,0 +1,210 @@
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::text::BdfTextStyle;
+
+/// Fixed slot text display for clocks.
+///
+/// The clock displays time like strings, e.g. `"12:34:56"`, with every character drawn into a
+/// fixed slot. Slots are as wide as the [`digit_width`](crate::BdfFont::digit_width) of the font
+/// and as high as the sum of the font ascent and descent, which keeps all characters in place
+/// when the value changes. Characters are centered inside their slot and drawing is clipped to the
+/// slot.
+///
+/// Changing the text only marks the slots whose character has changed as dirty and
+/// [`draw_dirty`](Self::draw_dirty) only redraws the dirty slots. The style needs a background
+/// color to erase the previous characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Clock<'a, C, const SLOTS: usize> {
+    style: BdfTextStyle<'a, C>,
+    position: Point,
+    slots: [char; SLOTS],
+    dirty: [bool; SLOTS],
+}
+
+impl<'a, C: PixelColor, const SLOTS: usize> Clock<'a, C, SLOTS> {
+    /// Creates a new clock with all slots containing spaces.
+    ///
+    /// `position` is the top left corner of the first slot. All slots are initially dirty.
+    pub fn new(style: BdfTextStyle<'a, C>, position: Point) -> Self {
+        Self {
+            style,
+            position,
+            slots: [' '; SLOTS],
+            dirty: [true; SLOTS],
+        }
+    }
+
+    /// Sets the displayed text.
+    ///
+    /// Characters which exceed the number of slots are discarded and unused slots are filled
+    /// with spaces. Only slots whose character has changed are marked as dirty.
+    pub fn set_text(&mut self, text: &str) {
+        let mut chars = text.chars();
+
+        for (slot, dirty) in self.slots.iter_mut().zip(self.dirty.iter_mut()) {
+            let c = chars.next().unwrap_or(' ');
+            if *slot != c {
+                *slot = c;
+                *dirty = true;
+            }
+        }
+    }
+
+    /// Returns the character in a slot.
+    pub fn slot(&self, index: usize) -> Option<char> {
+        self.slots.get(index).copied()
+    }
+
+    /// Returns the size of a single slot.
+    pub fn slot_size(&self) -> Size {
+        Size::new(self.style.font().digit_width(), self.style.full_height())
+    }
+
+    /// Returns the area covered by a slot.
+    pub fn slot_bounding_box(&self, index: usize) -> Rectangle {
+        let slot_size = self.slot_size();
+        let offset = Point::new((index as u32 * slot_size.width) as i32, 0);
+
+        Rectangle::new(self.position + offset, slot_size)
+    }
+
+    /// Returns the area covered by the clock.
+    pub fn bounding_box(&self) -> Rectangle {
+        let slot_size = self.slot_size();
+
+        Rectangle::new(
+            self.position,
+            Size::new(slot_size.width * SLOTS as u32, slot_size.height),
+        )
+    }
+
+    /// Returns `true` if a slot needs to be redrawn.
+    pub fn is_dirty(&self, index: usize) -> bool {
+        self.dirty.get(index).copied().unwrap_or(false)
+    }
+
+    /// Marks all slots as dirty.
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty = [true; SLOTS];
+    }
+
+    /// Draws all dirty slots and marks them as clean.
+    pub fn draw_dirty<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        for index in 0..SLOTS {
+            if self.dirty[index] {
+                self.draw_slot(index, target)?;
+                self.dirty[index] = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw_slot<D>(&self, index: usize, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let slot = self.slot_bounding_box(index);
+        let mut target = target.clipped(&slot);
+
+        if let Some(color) = self.style.background_color() {
+            target.fill_solid(&slot, color)?;
+        }
+
+        let c = self.slots[index];
+        let offset = (slot.size.width as i32 - self.style.char_advance(c)) / 2;
+        let baseline = slot.top_left + Point::new(offset, self.style.font().font_ascent as i32 - 1);
+
+        self.style
+            .draw_string(
+                c.encode_utf8(&mut [0; 4]),
+                baseline,
+                Baseline::Alphabetic,
+                &mut target,
+            )
+            .map(drop)
+    }
+}
+
+impl<C: PixelColor, const SLOTS: usize> Drawable for Clock<'_, C, SLOTS> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        for index in 0..SLOTS {
+            self.draw_slot(index, target)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", '0'..='9' | ':' | ' ');
+
+    fn clock() -> Clock<'static, BinaryColor, 5> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+        Clock::new(style, Point::new(1, 2))
+    }
+
+    #[test]
+    fn slots() {
+        let mut clock = clock();
+        assert_eq!(clock.slot_size(), Size::new(6, 10));
+        assert_eq!(
+            clock.bounding_box(),
+            Rectangle::new(Point::new(1, 2), Size::new(30, 10))
+        );
+        assert_eq!(
+            clock.slot_bounding_box(2),
+            Rectangle::new(Point::new(13, 2), Size::new(6, 10))
+        );
+
+        clock.set_text("12:345");
+        assert_eq!(clock.slot(3), Some('3'));
+        assert_eq!(clock.slot(4), Some('4'));
+        assert_eq!(clock.slot(5), None);
+
+        clock.set_text("1");
+        assert_eq!(clock.slot(1), Some(' '));
+    }
+
+    #[test]
+    fn redraw_changed_slots() {
+        let mut clock = clock();
+        clock.set_text("12:34");
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        clock.draw_dirty(&mut display).unwrap();
+        assert!((0..5).all(|index| !clock.is_dirty(index)));
+
+        clock.set_text("12:35");
+        assert!(!clock.is_dirty(3));
+        assert!(clock.is_dirty(4));
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        clock.draw_dirty(&mut display).unwrap();
+        assert_eq!(display.affected_area(), clock.slot_bounding_box(4));
+        assert!(!clock.is_dirty(4));
+
+        clock.mark_all_dirty();
+        assert!(clock.is_dirty(0));
+    }
+}
@@ -0,0 +1,444 @@
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        Baseline, DecorationColor,
+    },
+};
+
+use crate::{text::BdfTextStyle, widgets::Attributes, CapacityError};
+
+/// Text item in a [`Compositor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextItem<'a, 'b, C> {
+    /// Text.
+    pub text: &'b str,
+    /// Text style.
+    pub style: BdfTextStyle<'a, C>,
+    /// Position.
+    pub position: Point,
+    /// Baseline.
+    pub baseline: Baseline,
+    /// Attributes.
+    pub attributes: Attributes,
+    /// Hidden items aren't drawn.
+    pub visible: bool,
+}
+
+impl<'a, 'b, C: PixelColor> TextItem<'a, 'b, C> {
+    /// Creates a new visible text item without attributes.
+    pub fn new(
+        text: &'b str,
+        style: BdfTextStyle<'a, C>,
+        position: Point,
+        baseline: Baseline,
+    ) -> Self {
+        Self {
+            text,
+            style,
+            position,
+            baseline,
+            attributes: Attributes::NONE,
+            visible: true,
+        }
+    }
+
+    /// Sets the attributes.
+    pub fn with_attributes(self, attributes: Attributes) -> Self {
+        Self { attributes, ..self }
+    }
+
+    /// Returns the style which is used to draw the item.
+    ///
+    /// Returns `None` if the item is inverted, but its style has no background color.
+    fn draw_style(&self) -> Option<BdfTextStyle<'a, C>> {
+        let mut style = self.style;
+
+        if self.attributes.contains(Attributes::INVERSE) {
+            let text_color = self.style.background_color()?;
+            style.set_text_color(Some(text_color));
+            style.set_background_color(Some(self.style.text_color()));
+        }
+
+        let decoration = |attribute| {
+            if self.attributes.contains(attribute) {
+                DecorationColor::TextColor
+            } else {
+                DecorationColor::None
+            }
+        };
+        style.set_underline_color(decoration(Attributes::UNDERLINE));
+        style.set_strikethrough_color(decoration(Attributes::STRIKETHROUGH));
+
+        Some(style)
+    }
+}
+
+/// Compositor for independent text items with blinking and inverse attributes.
+///
+/// The compositor holds up to `N` text items, e.g. the fields of a status bar. Changing an item
+/// marks it as dirty and [`tick`](Self::tick) marks all visible items with the
+/// [`BLINK`](Attributes::BLINK) attribute as dirty when the blink phase changes.
+/// [`draw_dirty`](Self::draw_dirty) only redraws the dirty items.
+///
+/// The area covered by an item is cleared using the background color of its style before the
+/// item is redrawn, hidden or moved. Items need a background color to be erased.
+/// [`INVERSE`](Attributes::INVERSE) items swap their text and background color and only draw
+/// their text if the style has a background color. The `UNDERLINE` and `STRIKETHROUGH` attributes
+/// enable the corresponding decorations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Compositor<'a, 'b, C, const N: usize> {
+    items: [Option<TextItem<'a, 'b, C>>; N],
+    /// Area of each item on the display.
+    drawn: [Option<Rectangle>; N],
+    dirty: [bool; N],
+    blink_period: u32,
+    ticks: u32,
+    blink_visible: bool,
+}
+
+impl<'a, 'b, C: PixelColor, const N: usize> Compositor<'a, 'b, C, N> {
+    /// Creates an empty compositor.
+    ///
+    /// The blink phase changes every 30 ticks.
+    pub fn new() -> Self {
+        Self {
+            items: [None; N],
+            drawn: [None; N],
+            dirty: [false; N],
+            blink_period: 30,
+            ticks: 0,
+            blink_visible: true,
+        }
+    }
+
+    /// Sets the number of ticks between blink phase changes.
+    ///
+    /// A blink period of zero disables blinking.
+    pub fn with_blink_period(self, blink_period: u32) -> Self {
+        Self {
+            blink_period,
+            ..self
+        }
+    }
+
+    /// Adds an item and returns its index.
+    pub fn add(&mut self, item: TextItem<'a, 'b, C>) -> Result<usize, CapacityError> {
+        let index = self
+            .items
+            .iter()
+            .position(Option::is_none)
+            .ok_or(CapacityError)?;
+
+        self.items[index] = Some(item);
+        self.dirty[index] = true;
+
+        Ok(index)
+    }
+
+    /// Removes an item.
+    ///
+    /// The area covered by the item is cleared by the next call to
+    /// [`draw_dirty`](Self::draw_dirty).
+    pub fn remove(&mut self, index: usize) -> Option<TextItem<'a, 'b, C>> {
+        let item = self.items.get_mut(index)?.take();
+        if item.is_some() {
+            self.dirty[index] = true;
+        }
+
+        item
+    }
+
+    /// Returns an item.
+    pub fn item(&self, index: usize) -> Option<&TextItem<'a, 'b, C>> {
+        self.items.get(index).and_then(Option::as_ref)
+    }
+
+    /// Replaces an item.
+    ///
+    /// The item is only marked as dirty if it has changed. Indices without an item are ignored.
+    pub fn set_item(&mut self, index: usize, item: TextItem<'a, 'b, C>) {
+        if let Some(current) = self.items.get_mut(index).and_then(Option::as_mut) {
+            if *current != item {
+                *current = item;
+                self.dirty[index] = true;
+            }
+        }
+    }
+
+    /// Changes the text of an item.
+    pub fn set_text(&mut self, index: usize, text: &'b str) {
+        if let Some(item) = self.item(index) {
+            self.set_item(index, TextItem { text, ..*item });
+        }
+    }
+
+    /// Changes the attributes of an item.
+    pub fn set_attributes(&mut self, index: usize, attributes: Attributes) {
+        if let Some(item) = self.item(index) {
+            self.set_item(
+                index,
+                TextItem {
+                    attributes,
+                    ..*item
+                },
+            );
+        }
+    }
+
+    /// Shows or hides an item.
+    pub fn set_visible(&mut self, index: usize, visible: bool) {
+        if let Some(item) = self.item(index) {
+            self.set_item(index, TextItem { visible, ..*item });
+        }
+    }
+
+    /// Returns `true` if an item needs to be redrawn.
+    pub fn is_dirty(&self, index: usize) -> bool {
+        self.dirty.get(index).copied().unwrap_or(false)
+    }
+
+    /// Marks all items as dirty.
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty = [true; N];
+    }
+
+    /// Advances the blink phase by one tick.
+    ///
+    /// Returns `true` if any item needs to be redrawn.
+    pub fn tick(&mut self) -> bool {
+        if self.blink_period > 0 {
+            self.ticks += 1;
+            if self.ticks >= self.blink_period {
+                self.ticks = 0;
+                self.blink_visible = !self.blink_visible;
+
+                for (item, dirty) in self.items.iter().zip(self.dirty.iter_mut()) {
+                    if let Some(item) = item {
+                        if item.visible && item.attributes.contains(Attributes::BLINK) {
+                            *dirty = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.dirty.iter().any(|dirty| *dirty)
+    }
+
+    /// Returns the item at `index` if it is shown in the current blink phase.
+    fn shown_item(&self, index: usize) -> Option<&TextItem<'a, 'b, C>> {
+        self.item(index).filter(|item| {
+            item.visible && (self.blink_visible || !item.attributes.contains(Attributes::BLINK))
+        })
+    }
+
+    /// Draws an item and returns the covered area.
+    fn draw_item<D>(
+        item: &TextItem<'a, 'b, C>,
+        target: &mut D,
+    ) -> Result<Option<Rectangle>, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        match item.draw_style() {
+            Some(style) => {
+                let area = style
+                    .measure_string(item.text, item.position, item.baseline)
+                    .bounding_box;
+                style.draw_string_opaque(item.text, item.position, item.baseline, target)?;
+
+                Ok(Some(area))
+            }
+            None => {
+                // Inverted items without background color are drawn as a block.
+                let area = item
+                    .style
+                    .measure_string(item.text, item.position, item.baseline)
+                    .bounding_box;
+                target.fill_solid(&area, item.style.text_color())?;
+
+                Ok(Some(area))
+            }
+        }
+    }
+
+    /// Draws all dirty items and marks them as clean.
+    pub fn draw_dirty<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        for index in 0..N {
+            if !self.dirty[index] {
+                continue;
+            }
+
+            let shown = self.shown_item(index).copied();
+            let area = shown.map(|item| {
+                item.draw_style()
+                    .unwrap_or(item.style)
+                    .measure_string(item.text, item.position, item.baseline)
+                    .bounding_box
+            });
+
+            // Clear the previous area if it isn't covered by the redrawn item.
+            if let Some(previous) = self.drawn[index] {
+                let background_color = self.items[index]
+                    .or(shown)
+                    .and_then(|item| item.style.background_color());
+
+                if let (Some(color), true) = (background_color, area != Some(previous)) {
+                    target.fill_solid(&previous, color)?;
+                }
+            }
+
+            self.drawn[index] = match shown {
+                Some(item) => Self::draw_item(&item, target)?,
+                None => None,
+            };
+            self.dirty[index] = false;
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: PixelColor, const N: usize> Default for Compositor<'_, '_, C, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: PixelColor, const N: usize> Drawable for Compositor<'_, '_, C, N> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        for index in 0..N {
+            if let Some(item) = self.shown_item(index) {
+                Self::draw_item(item, target)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    fn style() -> BdfTextStyle<'static, BinaryColor> {
+        BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off)
+    }
+
+    fn item(text: &str, x: i32) -> TextItem<'static, '_, BinaryColor> {
+        TextItem::new(text, style(), Point::new(x, 0), Baseline::Top)
+    }
+
+    fn overdraw_display() -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        display
+    }
+
+    fn expected(items: &[(&str, i32, BdfTextStyle<BinaryColor>)]) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        for (text, x, style) in items {
+            style
+                .draw_string(text, Point::new(*x, 0), Baseline::Top, &mut display)
+                .unwrap();
+        }
+
+        display
+    }
+
+    #[test]
+    fn capacity() {
+        let mut compositor = Compositor::<_, 2>::new();
+        assert_eq!(compositor.add(item("A", 0)), Ok(0));
+        assert_eq!(compositor.add(item("B", 6)), Ok(1));
+        assert_eq!(compositor.add(item("C", 12)), Err(CapacityError));
+
+        assert_eq!(compositor.remove(0), Some(item("A", 0)));
+        assert_eq!(compositor.add(item("C", 12)), Ok(0));
+        assert_eq!(compositor.item(0), Some(&item("C", 12)));
+    }
+
+    #[test]
+    fn blink() {
+        let mut compositor = Compositor::<_, 2>::new().with_blink_period(2);
+        compositor.add(item("OK", 0)).unwrap();
+        let alarm = compositor
+            .add(item("AL", 18).with_attributes(Attributes::BLINK))
+            .unwrap();
+
+        let mut display = overdraw_display();
+        compositor.draw_dirty(&mut display).unwrap();
+        display.assert_eq(&expected(&[("OK", 0, style()), ("AL", 18, style())]));
+
+        // Only the blinking item is redrawn when the blink phase changes.
+        assert!(!compositor.tick());
+        assert!(compositor.tick());
+        assert!(!compositor.is_dirty(0));
+        assert!(compositor.is_dirty(alarm));
+
+        let mut display = overdraw_display();
+        compositor.draw_dirty(&mut display).unwrap();
+        display.assert_eq(&expected(&[(
+            "AL",
+            18,
+            BdfTextStyle::new(&FONT, BinaryColor::Off).with_bg_color(BinaryColor::Off),
+        )]));
+
+        assert!(!compositor.tick());
+        assert!(compositor.tick());
+        let mut display = overdraw_display();
+        compositor.draw_dirty(&mut display).unwrap();
+        display.assert_eq(&expected(&[("AL", 18, style())]));
+    }
+
+    #[test]
+    fn attributes_and_visibility() {
+        let mut compositor = Compositor::<_, 1>::new();
+        let index = compositor.add(item("A", 0)).unwrap();
+
+        let mut display = overdraw_display();
+        compositor.draw_dirty(&mut display).unwrap();
+
+        // Unchanged attributes don't mark the item as dirty.
+        compositor.set_attributes(index, Attributes::NONE);
+        assert!(!compositor.is_dirty(index));
+
+        compositor.set_attributes(index, Attributes::INVERSE);
+        let mut display = overdraw_display();
+        compositor.draw_dirty(&mut display).unwrap();
+        display.assert_eq(&expected(&[(
+            "A",
+            0,
+            BdfTextStyle::new(&FONT, BinaryColor::Off).with_bg_color(BinaryColor::On),
+        )]));
+
+        // Hidden items are cleared with the background color.
+        compositor.set_visible(index, false);
+        let mut display = overdraw_display();
+        compositor.draw_dirty(&mut display).unwrap();
+        display.assert_pattern(&[
+            "......", "......", "......", "......", "......", "......", "......", "......",
+            "......", "......",
+        ]);
+
+        let mut display = overdraw_display();
+        compositor.draw(&mut display).unwrap();
+        assert_eq!(display, MockDisplay::new());
+    }
+}
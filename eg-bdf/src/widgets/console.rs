@@ -0,0 +1,635 @@
+use core::fmt;
+
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+use crate::{
+    ansi::{Action, EscapeParser, Rendition},
+    text::BdfTextStyle,
+    theme::{StylePreset, Theme},
+    widgets::grid::{cell_size, char_width, draw_cell, write_cell, Attributes, Cell},
+    BdfFont,
+};
+
+/// Text console with scrollback.
+///
+/// The console is a grid of `rows` by `COLS` cells, which is drawn using a monospaced font. The
+/// width of a cell is the advance of the space character and the height is the sum of the font
+/// ascent and descent. Text is written using [`write_str`](Self::write_str) or the
+/// [`core::fmt::Write`] implementation. Lines which are longer than `COLS` characters are wrapped
+/// and the console scrolls up once the cursor moves past the last row. Double width characters,
+/// like CJK ideographs, occupy two cells and are moved to the next line if they don't fit into
+/// the current line.
+///
+/// The console stores up to `LINES` lines in a ring buffer. Lines which aren't part of the visible
+/// rows are kept as scrollback and can be displayed by using [`scroll_back`](Self::scroll_back).
+///
+/// # ANSI escape sequences
+///
+/// Written text can contain ANSI control sequences, which makes it possible to display existing
+/// log output unchanged. The following sequences are supported, all other escape sequences are
+/// ignored:
+///
+/// | Sequence                | Effect                                                         |
+/// |-------------------------|----------------------------------------------------------------|
+/// | `ESC [ n m`             | Select graphic rendition, see below                            |
+/// | `ESC [ n K`             | Clear to the end (`0`), start (`1`) or all (`2`) of the line   |
+/// | `ESC [ n J`             | Clear to the end (`0`), start (`1`) or all (`2`) of the screen |
+/// | `ESC [ row ; col H`     | Move the cursor to a 1-based position, also `f`                |
+/// | `ESC [ n A`/`B`/`C`/`D` | Move the cursor up, down, forward or back by `n` cells         |
+/// | `ESC [ n G`             | Move the cursor to a 1-based column                            |
+///
+/// Select graphic rendition supports the parameters `0` (reset), `1`/`22` (bold on/off),
+/// `4`/`24` (underline), `7`/`27` (inverse), `9`/`29` (strikethrough), `30`-`37` and `90`-`97`
+/// (text color), `39` (default text color), `40`-`47` and `100`-`107` (background color) and
+/// `49` (default background color). Colors are only changed if a palette is set using
+/// [`with_palette`](Self::with_palette). Bold characters are drawn using the font set by
+/// [`with_bold_font`](Self::with_bold_font), which should have the same metrics as the regular
+/// font.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Console<'a, C, const COLS: usize, const LINES: usize> {
+    style: BdfTextStyle<'a, C>,
+    position: Point,
+    rows: usize,
+    lines: [[Cell<C>; COLS]; LINES],
+    /// Ring buffer index of the top row.
+    top: usize,
+    /// Number of lines in the scrollback.
+    history: usize,
+    /// Number of lines the view is scrolled back.
+    view_offset: usize,
+    cursor: (usize, usize),
+    rendition: Rendition<C>,
+    parser: EscapeParser,
+    palette: Option<&'a [C; 8]>,
+    bold_font: Option<&'a BdfFont<'a>>,
+}
+
+impl<'a, C: PixelColor, const COLS: usize, const LINES: usize> Console<'a, C, COLS, LINES> {
+    /// Creates a new console.
+    ///
+    /// The text and background color of the style are used as the default cell colors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows` is zero or larger than `LINES`.
+    pub fn new(style: BdfTextStyle<'a, C>, position: Point, rows: usize) -> Self {
+        assert!(rows > 0 && rows <= LINES, "invalid number of rows");
+
+        let empty = Cell::empty(style.text_color(), style.background_color());
+
+        Self {
+            style,
+            position,
+            rows,
+            lines: [[empty; COLS]; LINES],
+            top: 0,
+            history: 0,
+            view_offset: 0,
+            cursor: (0, 0),
+            rendition: Rendition {
+                text_color: empty.text_color,
+                background_color: empty.background_color,
+                attributes: Attributes::NONE,
+            },
+            parser: EscapeParser::new(),
+            palette: None,
+            bold_font: None,
+        }
+    }
+
+    /// Sets the palette used for the eight standard colors in ANSI escape sequences.
+    ///
+    /// The bright color codes use the same palette.
+    pub fn with_palette(self, palette: &'a [C; 8]) -> Self {
+        Self {
+            palette: Some(palette),
+            ..self
+        }
+    }
+
+    /// Sets the font which is used to draw bold characters.
+    pub fn with_bold_font(self, bold_font: &'a BdfFont<'a>) -> Self {
+        Self {
+            bold_font: Some(bold_font),
+            ..self
+        }
+    }
+
+    /// Sets the text color for subsequently written characters.
+    pub fn set_text_color(&mut self, text_color: C) {
+        self.rendition.text_color = text_color;
+    }
+
+    /// Sets the background color for subsequently written characters.
+    pub fn set_background_color(&mut self, background_color: Option<C>) {
+        self.rendition.background_color = background_color;
+    }
+
+    /// Sets the attributes for subsequently written characters.
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.rendition.attributes = attributes;
+    }
+
+    /// Sets the colors and attributes for subsequently written characters from a theme preset.
+    ///
+    /// The colors are taken from the style returned by [`Theme::style`]. Emphasized text is
+    /// drawn using the font set by [`with_bold_font`](Self::with_bold_font), the font of the theme
+    /// isn't used.
+    pub fn set_preset(&mut self, theme: &Theme<'_, C>, preset: StylePreset) {
+        let style = theme.style(preset);
+
+        self.rendition = Rendition {
+            text_color: style.text_color(),
+            background_color: style.background_color(),
+            attributes: if preset == StylePreset::Emphasized {
+                Attributes::BOLD
+            } else {
+                Attributes::NONE
+            },
+        };
+    }
+
+    /// Returns the number of visible rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the cursor position as `(row, column)`.
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Moves the cursor.
+    ///
+    /// The position is clamped to the size of the console.
+    pub fn set_cursor(&mut self, row: usize, column: usize) {
+        self.cursor = (row.min(self.rows - 1), column.min(COLS));
+    }
+
+    /// Returns the size of a single cell.
+    pub fn cell_size(&self) -> Size {
+        cell_size(&self.style)
+    }
+
+    /// Returns the area covered by the visible rows.
+    pub fn bounding_box(&self) -> Rectangle {
+        let cell_size = self.cell_size();
+
+        Rectangle::new(
+            self.position,
+            Size::new(
+                cell_size.width * COLS as u32,
+                cell_size.height * self.rows as u32,
+            ),
+        )
+    }
+
+    /// Returns the cell at a visible position.
+    ///
+    /// The position is relative to the current view, which can be scrolled back.
+    pub fn cell(&self, row: usize, column: usize) -> Option<&Cell<C>> {
+        if row >= self.rows {
+            return None;
+        }
+
+        self.lines[self.view_index(row)].get(column)
+    }
+
+    fn line_index(&self, row: usize) -> usize {
+        (self.top + row) % LINES
+    }
+
+    fn view_index(&self, row: usize) -> usize {
+        (self.top + LINES - self.view_offset + row) % LINES
+    }
+
+    fn empty_cell(&self) -> Cell<C> {
+        Cell::empty(self.rendition.text_color, self.rendition.background_color)
+    }
+
+    fn default_rendition(&self) -> Rendition<C> {
+        Rendition {
+            text_color: self.style.text_color(),
+            background_color: self.style.background_color(),
+            attributes: Attributes::NONE,
+        }
+    }
+
+    /// Clears the visible rows and moves the cursor to the top left corner.
+    ///
+    /// The scrollback isn't affected.
+    pub fn clear(&mut self) {
+        for row in 0..self.rows {
+            self.clear_row(row);
+        }
+        self.cursor = (0, 0);
+        self.view_offset = 0;
+    }
+
+    fn clear_row(&mut self, row: usize) {
+        self.clear_columns(row, 0, COLS);
+    }
+
+    fn clear_columns(&mut self, row: usize, start: usize, end: usize) {
+        let empty = self.empty_cell();
+        let index = self.line_index(row);
+        for cell in self.lines[index][start.min(COLS)..end.min(COLS)].iter_mut() {
+            *cell = empty;
+        }
+    }
+
+    /// Moves the cursor to the start of the next line and scrolls up if necessary.
+    pub fn newline(&mut self) {
+        self.cursor.1 = 0;
+
+        if self.cursor.0 + 1 < self.rows {
+            self.cursor.0 += 1;
+        } else {
+            self.scroll_up();
+        }
+    }
+
+    /// Scrolls the visible rows up by one line.
+    ///
+    /// The top row is moved into the scrollback and the bottom row is cleared.
+    pub fn scroll_up(&mut self) {
+        self.top = (self.top + 1) % LINES;
+        self.history = (self.history + 1).min(LINES - self.rows);
+        self.view_offset = self.view_offset.min(self.history);
+        self.clear_row(self.rows - 1);
+    }
+
+    /// Writes a single character.
+    ///
+    /// `\n` moves the cursor to the start of the next line, `\r` moves the cursor to the start of
+    /// the current line, `\t` moves the cursor to the next multiple of 8 columns and `\x08` moves
+    /// the cursor one column to the left. Escape sequences are processed as described in the
+    /// [type level documentation](Self#ansi-escape-sequences).
+    pub fn write_char(&mut self, c: char) {
+        let mut parser = self.parser;
+
+        match parser.advance(c) {
+            Action::Print(c) => self.print_char(c),
+            Action::ControlSequence(parameters, c) => self.control_sequence(parameters, c),
+            Action::None => {}
+        }
+
+        self.parser = parser;
+    }
+
+    fn print_char(&mut self, c: char) {
+        match c {
+            '\n' => self.newline(),
+            '\r' => self.cursor.1 = 0,
+            '\t' => self.cursor.1 = ((self.cursor.1 / 8 + 1) * 8).min(COLS),
+            '\x08' => self.cursor.1 = self.cursor.1.saturating_sub(1),
+            c => {
+                let width = char_width(c);
+                if width > COLS {
+                    return;
+                }
+
+                if self.cursor.1 + width > COLS {
+                    self.newline();
+                }
+
+                let cell = Cell {
+                    character: c,
+                    text_color: self.rendition.text_color,
+                    background_color: self.rendition.background_color,
+                    attributes: self.rendition.attributes,
+                };
+                let index = self.line_index(self.cursor.0);
+                write_cell(&mut self.lines[index], self.cursor.1, cell);
+                self.cursor.1 += width;
+            }
+        }
+    }
+
+    fn control_sequence(&mut self, parameters: &[u16], c: char) {
+        // Returns a parameter, omitted and zero parameters are replaced by `default`.
+        let parameter = |index: usize, default: usize| match parameters.get(index) {
+            Some(&value) if value > 0 => usize::from(value),
+            _ => default,
+        };
+        let (row, column) = self.cursor;
+
+        match c {
+            'm' => {
+                let default = self.default_rendition();
+                self.rendition.apply(parameters, &default, self.palette);
+            }
+            'K' => match parameters.first().copied().unwrap_or(0) {
+                0 => self.clear_columns(row, column, COLS),
+                1 => self.clear_columns(row, 0, column + 1),
+                2 => self.clear_row(row),
+                _ => {}
+            },
+            'J' => match parameters.first().copied().unwrap_or(0) {
+                0 => {
+                    self.clear_columns(row, column, COLS);
+                    for row in row + 1..self.rows {
+                        self.clear_row(row);
+                    }
+                }
+                1 => {
+                    for row in 0..row {
+                        self.clear_row(row);
+                    }
+                    self.clear_columns(row, 0, column + 1);
+                }
+                2 => {
+                    for row in 0..self.rows {
+                        self.clear_row(row);
+                    }
+                }
+                _ => {}
+            },
+            'H' | 'f' => self.set_cursor(parameter(0, 1) - 1, parameter(1, 1) - 1),
+            'A' => self.set_cursor(row.saturating_sub(parameter(0, 1)), column),
+            'B' => self.set_cursor(row.saturating_add(parameter(0, 1)), column),
+            'C' => self.set_cursor(row, column.saturating_add(parameter(0, 1)).min(COLS - 1)),
+            'D' => self.set_cursor(row, column.saturating_sub(parameter(0, 1))),
+            'G' => self.set_cursor(row, (parameter(0, 1) - 1).min(COLS - 1)),
+            _ => {}
+        }
+    }
+
+    /// Writes a string.
+    pub fn write_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.write_char(c);
+        }
+    }
+
+    /// Returns the number of lines in the scrollback.
+    pub fn history(&self) -> usize {
+        self.history
+    }
+
+    /// Returns the number of lines the view is scrolled back.
+    pub fn view_offset(&self) -> usize {
+        self.view_offset
+    }
+
+    /// Scrolls the view back by `lines` lines.
+    pub fn scroll_back(&mut self, lines: usize) {
+        self.view_offset = (self.view_offset + lines).min(self.history);
+    }
+
+    /// Scrolls the view forward by `lines` lines.
+    pub fn scroll_forward(&mut self, lines: usize) {
+        self.view_offset = self.view_offset.saturating_sub(lines);
+    }
+
+    /// Returns the view to the live rows.
+    pub fn scroll_to_bottom(&mut self) {
+        self.view_offset = 0;
+    }
+}
+
+impl<C: PixelColor, const COLS: usize, const LINES: usize> fmt::Write
+    for Console<'_, C, COLS, LINES>
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        Console::write_str(self, s);
+        Ok(())
+    }
+}
+
+impl<C: PixelColor, const COLS: usize, const LINES: usize> Drawable
+    for Console<'_, C, COLS, LINES>
+{
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let cell_size = self.cell_size();
+
+        for row in 0..self.rows {
+            let line = &self.lines[self.view_index(row)];
+
+            for (column, cell) in line.iter().enumerate() {
+                let position = self.position
+                    + Point::new(
+                        (column as u32 * cell_size.width) as i32,
+                        (row as u32 * cell_size.height) as i32,
+                    );
+
+                match self.bold_font {
+                    Some(font) if cell.attributes.contains(Attributes::BOLD) => {
+                        draw_cell(&self.style.with_font(font), cell, position, target)?;
+                    }
+                    _ => draw_cell(&self.style, cell, position, target)?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use alloc::string::String;
+    use core::fmt::Write;
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | '0'..='9' | ' ');
+
+    fn console<const LINES: usize>(rows: usize) -> Console<'static, BinaryColor, 4, LINES> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        Console::new(style, Point::zero(), rows)
+    }
+
+    fn row_text<const LINES: usize>(
+        console: &Console<'_, BinaryColor, 4, LINES>,
+        row: usize,
+    ) -> String {
+        (0..4)
+            .map(|column| console.cell(row, column).unwrap().character)
+            .collect()
+    }
+
+    #[test]
+    fn write_and_wrap() {
+        let mut console = console::<2>(2);
+
+        console.write_str("AB\nCDEFG");
+        assert_eq!(row_text(&console, 0), "CDEF");
+        assert_eq!(row_text(&console, 1), "G   ");
+        assert_eq!(console.cursor(), (1, 1));
+    }
+
+    #[test]
+    fn control_characters() {
+        let mut console = console::<2>(2);
+
+        write!(console, "AB\rC\x08\x08D\tE").unwrap();
+        assert_eq!(row_text(&console, 0), "DB  ");
+        assert_eq!(row_text(&console, 1), "E   ");
+    }
+
+    #[test]
+    fn double_width() {
+        let mut console = console::<2>(2);
+
+        console.write_str("A漢字");
+        assert_eq!(row_text(&console, 0), "A漢\0 ");
+        assert_eq!(row_text(&console, 1), "字\0  ");
+        assert_eq!(console.cursor(), (1, 2));
+
+        // Overwriting half of a double width character clears the other half.
+        console.write_str("\x1b[1;3HB");
+        assert_eq!(row_text(&console, 0), "A B ");
+    }
+
+    #[test]
+    fn scrollback() {
+        let mut console = console::<4>(2);
+
+        console.write_str("1\n2\n3\n4\n5");
+        assert_eq!(console.history(), 2);
+        assert_eq!(row_text(&console, 0), "4   ");
+
+        console.scroll_back(5);
+        assert_eq!(console.view_offset(), 2);
+        assert_eq!(row_text(&console, 0), "2   ");
+        assert_eq!(row_text(&console, 1), "3   ");
+
+        console.scroll_forward(1);
+        assert_eq!(row_text(&console, 0), "3   ");
+
+        console.scroll_to_bottom();
+        assert_eq!(row_text(&console, 1), "5   ");
+    }
+
+    #[test]
+    fn escape_sequences() {
+        let mut console = console::<3>(3);
+
+        console.write_str("ABCD\n1234\nWXYZ");
+        console.write_str("\x1b[2;3H\x1b[K\x1b[1;2H\x1b[1K\x1b[3;4H\x1b[2K");
+        assert_eq!(row_text(&console, 0), "  CD");
+        assert_eq!(row_text(&console, 1), "12  ");
+        assert_eq!(row_text(&console, 2), "    ");
+        assert_eq!(console.cursor(), (2, 3));
+
+        console.write_str("\x1b[2A\x1b[DE\x1b[GF\x1b[9CG\x1b[9B\x1b[DH");
+        assert_eq!(row_text(&console, 0), "F EG");
+        assert_eq!(row_text(&console, 2), "   H");
+
+        console.write_str("\x1b[2;2H\x1b[J");
+        assert_eq!(row_text(&console, 0), "F EG");
+        assert_eq!(row_text(&console, 1), "1   ");
+        assert_eq!(row_text(&console, 2), "    ");
+
+        console.write_str("\x1b[2J");
+        assert_eq!(row_text(&console, 0), "    ");
+        assert_eq!(console.cursor(), (1, 1));
+    }
+
+    #[test]
+    fn select_graphic_rendition() {
+        let palette = [BinaryColor::Off; 8];
+        let mut console = console::<1>(1).with_palette(&palette);
+
+        console.write_str("\x1b[1;7;31mA\x1b[22mB\x1b[0mC\x1b[41;39mD");
+        let cell = |column| *console.cell(0, column).unwrap();
+
+        assert_eq!(cell(0).attributes, Attributes::BOLD | Attributes::INVERSE);
+        assert_eq!(cell(0).text_color, BinaryColor::Off);
+        assert_eq!(cell(1).attributes, Attributes::INVERSE);
+        assert_eq!(
+            cell(2),
+            Cell {
+                character: 'C',
+                ..Cell::empty(BinaryColor::On, None)
+            }
+        );
+        assert_eq!(cell(3).text_color, BinaryColor::On);
+        assert_eq!(cell(3).background_color, Some(BinaryColor::Off));
+    }
+
+    #[test]
+    fn theme_presets() {
+        use crate::family::FontFamily;
+
+        const FAMILY: FontFamily = FontFamily::new(&FONT);
+        let theme = Theme::new(&FAMILY, BinaryColor::On, None).with_accent_color(BinaryColor::Off);
+
+        let mut console = console::<1>(1);
+        console.set_preset(&theme, StylePreset::Emphasized);
+        console.write_char('A');
+        console.set_preset(&theme, StylePreset::Inverted);
+        console.write_char('B');
+        console.set_preset(&theme, StylePreset::Normal);
+        console.write_char('C');
+        let cell = |column| *console.cell(0, column).unwrap();
+
+        assert_eq!(cell(0).attributes, Attributes::BOLD);
+        assert_eq!(cell(0).text_color, BinaryColor::Off);
+        assert_eq!(cell(0).background_color, None);
+        assert_eq!(cell(1).attributes, Attributes::NONE);
+        assert_eq!(cell(1).text_color, BinaryColor::Off);
+        assert_eq!(cell(1).background_color, Some(BinaryColor::On));
+        assert_eq!(
+            cell(2),
+            Cell {
+                character: 'C',
+                ..Cell::empty(BinaryColor::On, None)
+            }
+        );
+    }
+
+    #[test]
+    fn bold_font() {
+        const BOLD: BdfFont = include_bdf!("examples/10x20.bdf", 'A'..='Z' | ' ');
+
+        let mut console = console::<1>(1).with_bold_font(&BOLD);
+        console.write_str("\x1b[1mI");
+
+        let mut display = MockDisplay::new();
+        console.draw(&mut display).unwrap();
+
+        let mut expected = MockDisplay::new();
+        let style = BdfTextStyle::new(&BOLD, BinaryColor::On);
+        draw_cell(
+            &style,
+            console.cell(0, 0).unwrap(),
+            Point::zero(),
+            &mut expected,
+        )
+        .unwrap();
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn draw() {
+        let mut console = console::<1>(1);
+        console.set_background_color(Some(BinaryColor::Off));
+        console.write_str(" I");
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        console.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "............",
+            ".......###..",
+            "........#...",
+            "........#...",
+            "........#...",
+            "........#...",
+            "........#...",
+            ".......###..",
+            "............",
+            "............",
+        ]);
+    }
+}
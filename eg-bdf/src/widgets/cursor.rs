@@ -0,0 +1,274 @@
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        Baseline,
+    },
+};
+
+use crate::text::BdfTextStyle;
+
+/// Cursor shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub enum CursorShape {
+    /// Filled block covering the character under the cursor.
+    Block,
+    /// Horizontal line below the character under the cursor.
+    Underscore,
+    /// One pixel wide vertical bar before the character under the cursor.
+    Bar,
+}
+
+/// Blinking text cursor.
+///
+/// The cursor is positioned using the caret metrics of the text style and toggles its visibility
+/// every `blink_period` calls to [`tick`](Self::tick). When the cursor is hidden, the covered area
+/// is cleared with the background color of the style and the character under the cursor is
+/// redrawn. A block cursor draws the character under the cursor in the background color while
+/// it is visible.
+///
+/// If the style has no background color, nothing is drawn while the cursor is hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cursor<'a, C> {
+    style: BdfTextStyle<'a, C>,
+    shape: CursorShape,
+    blink_period: u32,
+    ticks: u32,
+    visible: bool,
+    position: Point,
+    character: Option<char>,
+    width: u32,
+}
+
+impl<'a, C: PixelColor> Cursor<'a, C> {
+    /// Creates a new cursor.
+    ///
+    /// The cursor is initially visible and blinks every 30 ticks.
+    pub fn new(style: BdfTextStyle<'a, C>, shape: CursorShape) -> Self {
+        Self {
+            style,
+            shape,
+            blink_period: 30,
+            ticks: 0,
+            visible: true,
+            position: Point::zero(),
+            character: None,
+            width: style.text_advance(" ").max(0) as u32,
+        }
+    }
+
+    /// Sets the number of ticks between visibility changes.
+    ///
+    /// A blink period of zero disables blinking.
+    pub fn with_blink_period(self, blink_period: u32) -> Self {
+        Self {
+            blink_period,
+            ..self
+        }
+    }
+
+    /// Places the cursor before the character at `index` in `text`.
+    ///
+    /// `origin` is the position the text is drawn at using [`Baseline::Alphabetic`] and `index`
+    /// is a character index.
+    pub fn set_position(&mut self, text: &str, origin: Point, index: usize) {
+        self.position = self.style.caret_position(text, origin, index);
+        self.character = text.chars().nth(index).filter(|c| *c != '\n');
+
+        // The width is the distance to the next caret position, which includes the fractional
+        // advances of the preceding characters.
+        let width = match self.character {
+            Some(_) => self.style.caret_position(text, origin, index + 1).x - self.position.x,
+            None => self.style.text_advance(" "),
+        };
+        self.width = width.max(0) as u32;
+    }
+
+    /// Advances the blink phase by one tick.
+    ///
+    /// Returns `true` if the visibility has changed and the cursor needs to be redrawn.
+    pub fn tick(&mut self) -> bool {
+        if self.blink_period == 0 {
+            return false;
+        }
+
+        self.ticks += 1;
+        if self.ticks >= self.blink_period {
+            self.ticks = 0;
+            self.visible = !self.visible;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Makes the cursor visible and restarts the blink phase.
+    ///
+    /// This should be called after user input to keep the cursor visible while typing.
+    pub fn reset_phase(&mut self) {
+        self.ticks = 0;
+        self.visible = true;
+    }
+
+    /// Returns `true` if the cursor is in the visible phase.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Returns the area covered by the cursor.
+    pub fn bounding_box(&self) -> Rectangle {
+        let width = self.width;
+        let height = self.style.caret_height();
+
+        match self.shape {
+            CursorShape::Block => Rectangle::new(self.position, Size::new(width, height)),
+            CursorShape::Underscore => {
+                Rectangle::new(self.baseline() + Point::new(0, 1), Size::new(width, 1))
+            }
+            CursorShape::Bar => Rectangle::new(self.position, Size::new(1, height)),
+        }
+    }
+
+    fn baseline(&self) -> Point {
+        self.position + Point::new(0, self.style.font().font_ascent as i32 - 1)
+    }
+
+    fn draw_character<D>(&self, color: C, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        if let Some(c) = self.character {
+            let mut style = self.style;
+            style.set_text_color(Some(color));
+            style.set_background_color(None);
+
+            style.draw_string(
+                c.encode_utf8(&mut [0; 4]),
+                self.baseline(),
+                Baseline::Alphabetic,
+                target,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: PixelColor> Drawable for Cursor<'_, C> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let bounding_box = self.bounding_box();
+        let mut target = target.clipped(&bounding_box);
+        let background_color = self.style.background_color();
+
+        if self.visible {
+            target.fill_solid(&bounding_box, self.style.text_color())?;
+
+            if let (CursorShape::Block, Some(color)) = (self.shape, background_color) {
+                self.draw_character(color, &mut target)?;
+            }
+        } else if let Some(color) = background_color {
+            target.fill_solid(&bounding_box, color)?;
+
+            if self.shape != CursorShape::Bar {
+                self.draw_character(self.style.text_color(), &mut target)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    fn cursor(shape: CursorShape) -> Cursor<'static, BinaryColor> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+        let mut cursor = Cursor::new(style, shape).with_blink_period(2);
+        cursor.set_position("AI", Point::new(0, 7), 1);
+        cursor
+    }
+
+    #[test]
+    fn blink() {
+        let mut cursor = cursor(CursorShape::Bar);
+
+        assert!(cursor.is_visible());
+        assert!(!cursor.tick());
+        assert!(cursor.tick());
+        assert!(!cursor.is_visible());
+
+        cursor.reset_phase();
+        assert!(cursor.is_visible());
+    }
+
+    #[test]
+    fn bounding_box() {
+        assert_eq!(
+            cursor(CursorShape::Block).bounding_box(),
+            Rectangle::new(Point::new(6, 0), Size::new(6, 10))
+        );
+        assert_eq!(
+            cursor(CursorShape::Underscore).bounding_box(),
+            Rectangle::new(Point::new(6, 8), Size::new(6, 1))
+        );
+        assert_eq!(
+            cursor(CursorShape::Bar).bounding_box(),
+            Rectangle::new(Point::new(6, 0), Size::new(1, 10))
+        );
+    }
+
+    #[test]
+    fn draw_block() {
+        let mut cursor = cursor(CursorShape::Block);
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        cursor.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "      ######",
+            "      #...##",
+            "      ##.###",
+            "      ##.###",
+            "      ##.###",
+            "      ##.###",
+            "      ##.###",
+            "      #...##",
+            "      ######",
+            "      ######",
+        ]);
+
+        cursor.tick();
+        cursor.tick();
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        cursor.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "      ......",
+            "      .###..",
+            "      ..#...",
+            "      ..#...",
+            "      ..#...",
+            "      ..#...",
+            "      ..#...",
+            "      .###..",
+            "      ......",
+            "      ......",
+        ]);
+    }
+}
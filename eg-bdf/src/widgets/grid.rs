@@ -0,0 +1,540 @@
+use core::ops::{BitOr, BitOrAssign, Range};
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        Baseline, DecorationColor,
+    },
+};
+
+use crate::text::BdfTextStyle;
+
+/// Cell attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct Attributes(u8);
+
+impl Attributes {
+    /// No attributes.
+    pub const NONE: Self = Self(0);
+
+    /// Swap text and background color.
+    pub const INVERSE: Self = Self(1 << 0);
+
+    /// Underline.
+    pub const UNDERLINE: Self = Self(1 << 1);
+
+    /// Strikethrough.
+    pub const STRIKETHROUGH: Self = Self(1 << 2);
+
+    /// Blink.
+    ///
+    /// Only supported by the [`Compositor`](super::Compositor), other widgets ignore this
+    /// attribute.
+    pub const BLINK: Self = Self(1 << 3);
+
+    /// Bold.
+    ///
+    /// Only supported by the [`Console`](super::Console), which draws bold cells using an
+    /// alternate font.
+    pub const BOLD: Self = Self(1 << 4);
+
+    /// Returns `true` if all attributes in `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Sets all attributes in `other`.
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Clears all attributes in `other`.
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for Attributes {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Attributes {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs)
+    }
+}
+
+/// Character cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct Cell<C> {
+    /// Character.
+    pub character: char,
+
+    /// Text color.
+    pub text_color: C,
+
+    /// Background color, if `None` the background isn't drawn.
+    pub background_color: Option<C>,
+
+    /// Attributes.
+    pub attributes: Attributes,
+}
+
+impl<C: PixelColor> Cell<C> {
+    /// Creates an empty cell, which contains a space.
+    pub fn empty(text_color: C, background_color: Option<C>) -> Self {
+        Self {
+            character: ' ',
+            text_color,
+            background_color,
+            attributes: Attributes::NONE,
+        }
+    }
+
+    /// Returns `true` if the cell contains a double width character.
+    pub fn is_wide(&self) -> bool {
+        char_width(self.character) == 2
+    }
+
+    /// Returns `true` if the cell is the right half of a double width character.
+    ///
+    /// The right half of a double width character is stored as a cell containing `'\0'`.
+    pub fn is_wide_spacer(&self) -> bool {
+        self.character == WIDE_SPACER
+    }
+}
+
+/// Character stored in the right half of a double width character.
+const WIDE_SPACER: char = '\0';
+
+/// Returns the number of cells used by a character.
+///
+/// Characters with the East Asian width property wide or fullwidth use two cells, all other
+/// characters use a single cell. Only the commonly used blocks are taken into account.
+pub(crate) fn char_width(c: char) -> usize {
+    match u32::from(c) {
+        0x1100..=0x115F // Hangul Jamo
+        | 0x2E80..=0x303E // CJK radicals, symbols and punctuation
+        | 0x3041..=0x33FF // Hiragana, Katakana and CJK compatibility
+        | 0x3400..=0x4DBF // CJK unified ideographs extension A
+        | 0x4E00..=0x9FFF // CJK unified ideographs
+        | 0xA000..=0xA4CF // Yi
+        | 0xAC00..=0xD7A3 // Hangul syllables
+        | 0xF900..=0xFAFF // CJK compatibility ideographs
+        | 0xFE30..=0xFE4F // CJK compatibility forms
+        | 0xFF00..=0xFF60 // Fullwidth forms
+        | 0xFFE0..=0xFFE6 // Fullwidth signs
+        | 0x1F300..=0x1F64F // Pictographs and emoticons
+        | 0x1F900..=0x1F9FF // Supplemental pictographs
+        | 0x20000..=0x2FFFD // CJK unified ideographs extension B to F
+        | 0x30000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// Writes a cell into a row and returns the range of changed columns.
+///
+/// Double width characters also occupy the following column, which must be part of the row.
+/// Double width characters which are partially overwritten are replaced by spaces.
+pub(crate) fn write_cell<C: PixelColor>(
+    cells: &mut [Cell<C>],
+    column: usize,
+    cell: Cell<C>,
+) -> Range<usize> {
+    let end = column + char_width(cell.character);
+    let mut changed = column..end;
+
+    let clear = |cell: &mut Cell<C>| cell.character = ' ';
+    if column > 0 && cells[column].is_wide_spacer() {
+        clear(&mut cells[column - 1]);
+        changed.start -= 1;
+    }
+    if let Some(next) = cells.get_mut(end) {
+        if next.is_wide_spacer() {
+            clear(next);
+            changed.end += 1;
+        }
+    }
+
+    cells[column] = cell;
+    if end > column + 1 {
+        cells[column + 1] = Cell {
+            character: WIDE_SPACER,
+            ..cell
+        };
+    }
+
+    changed
+}
+
+/// Returns the cell size for a monospaced style.
+pub(crate) fn cell_size<C: PixelColor>(style: &BdfTextStyle<'_, C>) -> Size {
+    Size::new(style.char_advance(' ').max(0) as u32, style.caret_height())
+}
+
+/// Draws a single cell with its top left corner at `position`.
+pub(crate) fn draw_cell<C, D>(
+    style: &BdfTextStyle<'_, C>,
+    cell: &Cell<C>,
+    position: Point,
+    target: &mut D,
+) -> Result<(), D::Error>
+where
+    C: PixelColor,
+    D: DrawTarget<Color = C>,
+{
+    let (text_color, background_color) = if cell.attributes.contains(Attributes::INVERSE) {
+        (
+            cell.background_color.or_else(|| style.background_color()),
+            Some(cell.text_color),
+        )
+    } else {
+        (Some(cell.text_color), cell.background_color)
+    };
+
+    // The right half of a double width character is drawn by the left half.
+    if cell.is_wide_spacer() {
+        return Ok(());
+    }
+
+    if let Some(color) = background_color {
+        let mut size = cell_size(style);
+        size.width *= char_width(cell.character) as u32;
+        target.fill_solid(&Rectangle::new(position, size), color)?;
+    }
+
+    // Inverted cells without a known background color can't draw their text.
+    let text_color = match text_color {
+        Some(color) => color,
+        None => return Ok(()),
+    };
+
+    let mut style = *style;
+    style.set_text_color(Some(text_color));
+    style.set_background_color(None);
+
+    let decoration = |attribute| {
+        if cell.attributes.contains(attribute) {
+            DecorationColor::TextColor
+        } else {
+            DecorationColor::None
+        }
+    };
+    style.set_underline_color(decoration(Attributes::UNDERLINE));
+    style.set_strikethrough_color(decoration(Attributes::STRIKETHROUGH));
+
+    if cell.character != ' ' || cell.attributes != Attributes::NONE {
+        let baseline = position + Point::new(0, style.font().font_ascent as i32 - 1);
+        style.draw_string(
+            cell.character.encode_utf8(&mut [0; 4]),
+            baseline,
+            Baseline::Alphabetic,
+            target,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Grid of character cells with dirty tracking.
+///
+/// Each cell stores a character, colors and [`Attributes`]. Changing a cell marks it as dirty
+/// and [`draw_dirty`](Self::draw_dirty) only redraws the dirty cells. Cells need a background
+/// color to erase their previous content when they are redrawn.
+///
+/// The grid is drawn using a monospaced font. The width of a cell is the advance of the space
+/// character and the height is the sum of the font ascent and descent. Double width characters,
+/// like CJK ideographs, occupy two cells, which keeps the columns aligned when ASCII and CJK text
+/// is mixed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextGrid<'a, C, const ROWS: usize, const COLS: usize> {
+    style: BdfTextStyle<'a, C>,
+    position: Point,
+    cells: [[Cell<C>; COLS]; ROWS],
+    dirty: [[bool; COLS]; ROWS],
+    text_color: C,
+    background_color: Option<C>,
+    attributes: Attributes,
+}
+
+impl<'a, C: PixelColor, const ROWS: usize, const COLS: usize> TextGrid<'a, C, ROWS, COLS> {
+    /// Creates a new grid.
+    ///
+    /// The text and background color of the style are used as the default cell colors. All cells
+    /// are initially dirty.
+    pub fn new(style: BdfTextStyle<'a, C>, position: Point) -> Self {
+        let empty = Cell::empty(style.text_color(), style.background_color());
+
+        Self {
+            style,
+            position,
+            cells: [[empty; COLS]; ROWS],
+            dirty: [[true; COLS]; ROWS],
+            text_color: empty.text_color,
+            background_color: empty.background_color,
+            attributes: Attributes::NONE,
+        }
+    }
+
+    /// Sets the text color for subsequently written characters.
+    pub fn set_text_color(&mut self, text_color: C) {
+        self.text_color = text_color;
+    }
+
+    /// Sets the background color for subsequently written characters.
+    pub fn set_background_color(&mut self, background_color: Option<C>) {
+        self.background_color = background_color;
+    }
+
+    /// Sets the attributes for subsequently written characters.
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.attributes = attributes;
+    }
+
+    /// Returns the size of a single cell.
+    pub fn cell_size(&self) -> Size {
+        cell_size(&self.style)
+    }
+
+    /// Returns the area covered by the grid.
+    pub fn bounding_box(&self) -> Rectangle {
+        let cell_size = self.cell_size();
+
+        Rectangle::new(
+            self.position,
+            Size::new(
+                cell_size.width * COLS as u32,
+                cell_size.height * ROWS as u32,
+            ),
+        )
+    }
+
+    /// Returns the area covered by a cell.
+    pub fn cell_bounding_box(&self, row: usize, column: usize) -> Rectangle {
+        let cell_size = self.cell_size();
+        let offset = Point::new(
+            (column as u32 * cell_size.width) as i32,
+            (row as u32 * cell_size.height) as i32,
+        );
+
+        Rectangle::new(self.position + offset, cell_size)
+    }
+
+    /// Returns a cell.
+    pub fn cell(&self, row: usize, column: usize) -> Option<&Cell<C>> {
+        self.cells.get(row).and_then(|cells| cells.get(column))
+    }
+
+    /// Replaces a cell.
+    ///
+    /// The cell is only marked as dirty if it has changed. Positions outside the grid are
+    /// ignored.
+    pub fn set_cell(&mut self, row: usize, column: usize, cell: Cell<C>) {
+        if let Some(current) = self
+            .cells
+            .get_mut(row)
+            .and_then(|cells| cells.get_mut(column))
+        {
+            if *current != cell {
+                *current = cell;
+                self.dirty[row][column] = true;
+            }
+        }
+    }
+
+    /// Writes a string starting at the given position using the current colors and attributes.
+    ///
+    /// Double width characters, like CJK ideographs, occupy two cells. Characters which exceed
+    /// the row are discarded.
+    pub fn write_str(&mut self, row: usize, mut column: usize, text: &str) {
+        if row >= ROWS {
+            return;
+        }
+
+        for c in text.chars() {
+            if column + char_width(c) > COLS {
+                break;
+            }
+
+            let cell = Cell {
+                character: c,
+                text_color: self.text_color,
+                background_color: self.background_color,
+                attributes: self.attributes,
+            };
+
+            // Changes are applied using `set_cell` to only mark changed cells as dirty.
+            let mut cells = self.cells[row];
+            let changed = write_cell(&mut cells, column, cell);
+            for column in changed {
+                self.set_cell(row, column, cells[column]);
+            }
+
+            column += char_width(c);
+        }
+    }
+
+    /// Fills all cells with spaces using the current colors.
+    pub fn clear(&mut self) {
+        let empty = Cell::empty(self.text_color, self.background_color);
+
+        for row in 0..ROWS {
+            for column in 0..COLS {
+                self.set_cell(row, column, empty);
+            }
+        }
+    }
+
+    /// Returns `true` if a cell needs to be redrawn.
+    pub fn is_dirty(&self, row: usize, column: usize) -> bool {
+        self.dirty
+            .get(row)
+            .and_then(|dirty| dirty.get(column))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Marks all cells as dirty.
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty = [[true; COLS]; ROWS];
+    }
+
+    /// Draws all dirty cells and marks them as clean.
+    pub fn draw_dirty<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        for row in 0..ROWS {
+            for column in 0..COLS {
+                if self.dirty[row][column] {
+                    let position = self.cell_bounding_box(row, column).top_left;
+                    draw_cell(&self.style, &self.cells[row][column], position, target)?;
+                    self.dirty[row][column] = false;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: PixelColor, const ROWS: usize, const COLS: usize> Drawable for TextGrid<'_, C, ROWS, COLS> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        for (row, cells) in self.cells.iter().enumerate() {
+            for (column, cell) in cells.iter().enumerate() {
+                let position = self.cell_bounding_box(row, column).top_left;
+                draw_cell(&self.style, cell, position, target)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    fn grid() -> TextGrid<'static, BinaryColor, 2, 3> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On).with_bg_color(BinaryColor::Off);
+        TextGrid::new(style, Point::zero())
+    }
+
+    #[test]
+    fn attributes() {
+        let mut attributes = Attributes::INVERSE | Attributes::UNDERLINE;
+
+        assert!(attributes.contains(Attributes::INVERSE));
+        assert!(!attributes.contains(Attributes::STRIKETHROUGH));
+
+        attributes.remove(Attributes::INVERSE);
+        assert_eq!(attributes, Attributes::UNDERLINE);
+    }
+
+    #[test]
+    fn dirty_tracking() {
+        let mut grid = grid();
+        grid.draw_dirty(&mut MockDisplay::new()).unwrap();
+        assert!(!grid.is_dirty(0, 0));
+
+        grid.write_str(1, 1, "ABCD");
+        assert!(grid.is_dirty(1, 1));
+        assert!(grid.is_dirty(1, 2));
+        assert!(!grid.is_dirty(1, 0));
+        assert_eq!(grid.cell(1, 2).unwrap().character, 'B');
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        grid.draw_dirty(&mut display).unwrap();
+        grid.write_str(1, 1, "A");
+        assert!(!grid.is_dirty(1, 1));
+    }
+
+    #[test]
+    fn double_width() {
+        let mut grid = grid();
+        grid.write_str(0, 0, "漢A");
+        assert!(grid.cell(0, 0).unwrap().is_wide());
+        assert!(grid.cell(0, 1).unwrap().is_wide_spacer());
+        assert_eq!(grid.cell(0, 2).unwrap().character, 'A');
+
+        // Double width characters which don't fit are discarded.
+        grid.write_str(1, 2, "字");
+        assert_eq!(grid.cell(1, 2).unwrap().character, ' ');
+
+        // The background of a double width character covers both cells.
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        grid.draw_dirty(&mut display).unwrap();
+        assert_eq!(display.get_pixel(Point::new(11, 9)), Some(BinaryColor::Off));
+
+        grid.write_str(0, 0, "B");
+        assert_eq!(grid.cell(0, 1).unwrap().character, ' ');
+        assert!(grid.is_dirty(0, 1));
+        assert!(!grid.is_dirty(0, 2));
+    }
+
+    #[test]
+    fn draw_only_dirty_cells() {
+        let mut grid = grid();
+        grid.draw_dirty(&mut MockDisplay::new()).unwrap();
+
+        grid.set_attributes(Attributes::INVERSE);
+        grid.write_str(0, 1, "I");
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        grid.draw_dirty(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "      ######",
+            "      #...##",
+            "      ##.###",
+            "      ##.###",
+            "      ##.###",
+            "      ##.###",
+            "      ##.###",
+            "      #...##",
+            "      ######",
+            "      ######",
+        ]);
+    }
+}
@@ -0,0 +1,347 @@
+use core::{convert::TryFrom, ops::Range};
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::{layout::VerticalAlignment, text::BdfTextStyle, CapacityError};
+
+/// Editable single line text input.
+///
+/// The text is stored in an internal buffer with a capacity of `N` bytes. If the text is wider
+/// than the bounding box the input is scrolled horizontally to keep the cursor visible. The text
+/// is vertically centered inside the bounding box and the cursor is drawn as a one pixel wide bar
+/// in the text color. If the style has a background color the bounding box is cleared before the
+/// text is drawn.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputLine<'a, C, const N: usize> {
+    style: BdfTextStyle<'a, C>,
+    bounds: Rectangle,
+    buffer: [u8; N],
+    len: usize,
+    /// Byte index of the cursor.
+    cursor: usize,
+    scroll: i32,
+}
+
+impl<'a, C: PixelColor, const N: usize> InputLine<'a, C, N> {
+    /// Creates a new empty input line.
+    pub fn new(style: BdfTextStyle<'a, C>, bounds: Rectangle) -> Self {
+        Self {
+            style,
+            bounds,
+            buffer: [0; N],
+            len: 0,
+            cursor: 0,
+            scroll: 0,
+        }
+    }
+
+    /// Returns the text.
+    pub fn text(&self) -> &str {
+        // The buffer only ever contains complete UTF-8 sequences.
+        core::str::from_utf8(&self.buffer[..self.len]).unwrap()
+    }
+
+    /// Replaces the text and moves the cursor to the end.
+    pub fn set_text(&mut self, text: &str) -> Result<(), CapacityError> {
+        if text.len() > N {
+            return Err(CapacityError);
+        }
+
+        self.buffer[..text.len()].copy_from_slice(text.as_bytes());
+        self.len = text.len();
+        self.cursor = self.len;
+        self.update_scroll();
+
+        Ok(())
+    }
+
+    /// Removes all text.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    /// Inserts a character at the cursor position.
+    pub fn insert(&mut self, c: char) -> Result<(), CapacityError> {
+        self.insert_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Inserts a string at the cursor position.
+    pub fn insert_str(&mut self, text: &str) -> Result<(), CapacityError> {
+        let len = text.len();
+        if self.len + len > N {
+            return Err(CapacityError);
+        }
+
+        self.buffer
+            .copy_within(self.cursor..self.len, self.cursor + len);
+        self.buffer[self.cursor..self.cursor + len].copy_from_slice(text.as_bytes());
+        self.len += len;
+        self.cursor += len;
+        self.update_scroll();
+
+        Ok(())
+    }
+
+    /// Deletes the character before the cursor.
+    ///
+    /// Returns `false` if the cursor is at the start of the text.
+    pub fn delete_backward(&mut self) -> bool {
+        match self.text()[..self.cursor].chars().next_back() {
+            Some(c) => {
+                let start = self.cursor - c.len_utf8();
+                self.remove(start..self.cursor);
+                self.cursor = start;
+                self.update_scroll();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Deletes the character after the cursor.
+    ///
+    /// Returns `false` if the cursor is at the end of the text.
+    pub fn delete_forward(&mut self) -> bool {
+        match self.text()[self.cursor..].chars().next() {
+            Some(c) => {
+                self.remove(self.cursor..self.cursor + c.len_utf8());
+                self.update_scroll();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove(&mut self, range: Range<usize>) {
+        let len = range.len();
+        self.buffer.copy_within(range.end..self.len, range.start);
+        self.len -= len;
+    }
+
+    /// Returns the cursor position as a character index.
+    pub fn cursor(&self) -> usize {
+        self.text()[..self.cursor].chars().count()
+    }
+
+    /// Sets the cursor position to a character index.
+    ///
+    /// Indices past the end of the text move the cursor to the end.
+    pub fn set_cursor(&mut self, index: usize) {
+        self.cursor = self
+            .text()
+            .char_indices()
+            .nth(index)
+            .map_or(self.len, |(byte_index, _)| byte_index);
+        self.update_scroll();
+    }
+
+    /// Moves the cursor one character to the left.
+    pub fn move_left(&mut self) {
+        if let Some(c) = self.text()[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.update_scroll();
+        }
+    }
+
+    /// Moves the cursor one character to the right.
+    pub fn move_right(&mut self) {
+        if let Some(c) = self.text()[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+            self.update_scroll();
+        }
+    }
+
+    /// Moves the cursor to the start of the text.
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+        self.update_scroll();
+    }
+
+    /// Moves the cursor to the end of the text.
+    pub fn move_end(&mut self) {
+        self.cursor = self.len;
+        self.update_scroll();
+    }
+
+    /// Returns the horizontal scroll offset in pixels.
+    pub fn scroll_offset(&self) -> u32 {
+        u32::try_from(self.scroll).unwrap_or(0)
+    }
+
+    /// Returns the style.
+    pub fn style(&self) -> &BdfTextStyle<'a, C> {
+        &self.style
+    }
+
+    /// Returns the bounding box.
+    pub fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    fn update_scroll(&mut self) {
+        let width = self.bounds.size.width as i32;
+        let text = self.text();
+        // The caret and the text width are measured like the drawn text, which takes fractional
+        // advances into account.
+        let caret = self
+            .style
+            .caret_position(text, Point::zero(), self.cursor())
+            .x;
+        let text_width = self.style.text_advance(text);
+
+        // Don't leave empty space on the right if the text was shortened.
+        self.scroll = self.scroll.min(text_width + 1 - width);
+
+        if caret < self.scroll {
+            self.scroll = caret;
+        } else if caret >= self.scroll + width {
+            self.scroll = caret - width + 1;
+        }
+
+        // Text with negative advances can move the caret left of the start of the text, which
+        // isn't scrolled into view.
+        self.scroll = self.scroll.max(0);
+    }
+}
+
+impl<C: PixelColor, const N: usize> Drawable for InputLine<'_, C, N> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut target = target.clipped(&self.bounds);
+
+        if let Some(color) = self.style.background_color() {
+            target.fill_solid(&self.bounds, color)?;
+        }
+
+        let origin = self
+            .style
+            .aligned_lines_position(1, &self.bounds, VerticalAlignment::Middle)
+            - Point::new(self.scroll, 0);
+
+        let text = self.text();
+        self.style
+            .draw_string(text, origin, Baseline::Alphabetic, &mut target)?;
+
+        let caret = self.style.caret_position(text, origin, self.cursor());
+        let caret = Rectangle::new(caret, Size::new(1, self.style.caret_height()));
+        target.fill_solid(&caret, self.style.text_color())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{__private, include_bdf, tests::FRACTIONAL_FONT, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | 'a'..='z' | ' ');
+
+    fn input<const N: usize>(width: u32) -> InputLine<'static, BinaryColor, N> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        InputLine::new(style, Rectangle::new(Point::zero(), Size::new(width, 10)))
+    }
+
+    #[test]
+    fn edit() {
+        let mut input = input::<8>(64);
+
+        input.insert_str("ac").unwrap();
+        input.move_left();
+        input.insert('b').unwrap();
+        assert_eq!(input.text(), "abc");
+        assert_eq!(input.cursor(), 2);
+
+        assert!(input.delete_forward());
+        assert!(!input.delete_forward());
+        assert!(input.delete_backward());
+        assert_eq!(input.text(), "a");
+        assert_eq!(input.cursor(), 1);
+
+        input.move_home();
+        assert!(!input.delete_backward());
+        input.set_cursor(10);
+        assert_eq!(input.cursor(), 1);
+    }
+
+    #[test]
+    fn capacity() {
+        let mut input = input::<4>(64);
+
+        assert_eq!(input.set_text("abcde"), Err(CapacityError));
+        input.set_text("abc").unwrap();
+        assert_eq!(input.insert('ä'), Err(CapacityError));
+        assert_eq!(input.insert('d'), Ok(()));
+        assert_eq!(input.text(), "abcd");
+    }
+
+    #[test]
+    fn scrolling() {
+        let mut input = input::<16>(20);
+
+        input.set_text("abcdef").unwrap();
+        assert_eq!(input.scroll_offset(), 17);
+
+        input.move_home();
+        assert_eq!(input.scroll_offset(), 0);
+
+        input.set_cursor(4);
+        assert_eq!(input.scroll_offset(), 5);
+
+        input.move_end();
+        input.delete_backward();
+        input.delete_backward();
+        input.delete_backward();
+        assert_eq!(input.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn scrolling_with_fractional_and_negative_advances() {
+        const NEGATIVE: BdfFont = BdfFont {
+            glyphs: &[
+                __private::glyph(' ', 0, 0, 0, 0, 4, 0),
+                __private::glyph('<', 0, -1, 2, 2, -4, 0),
+            ],
+            scalable_widths: &[],
+            ..FRACTIONAL_FONT
+        };
+
+        let style =
+            BdfTextStyle::new(&FRACTIONAL_FONT, BinaryColor::On).with_fractional_advances(true);
+        let mut input: InputLine<_, 8> =
+            InputLine::new(style, Rectangle::new(Point::zero(), Size::new(10, 4)));
+        input.set_text("AAAA").unwrap();
+        assert_eq!(input.scroll_offset(), 8);
+
+        let style = BdfTextStyle::new(&NEGATIVE, BinaryColor::On);
+        let mut input: InputLine<_, 8> =
+            InputLine::new(style, Rectangle::new(Point::zero(), Size::new(10, 4)));
+        input.set_text("<<").unwrap();
+        assert_eq!(input.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn draw() {
+        let mut input = input::<8>(8);
+        input.set_text("I").unwrap();
+        input.move_home();
+
+        let mut display = MockDisplay::new();
+        input.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "#       ", "####    ", "# #     ", "# #     ", "# #     ", "# #     ", "# #     ",
+            "####    ", "#       ", "#       ",
+        ]);
+    }
+}
@@ -0,0 +1,255 @@
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::{layout::VerticalAlignment, text::BdfTextStyle};
+
+/// Horizontally scrolling text.
+///
+/// Text which is wider than the bounding box is scrolled to the left by `speed` pixels on every
+/// call to [`tick`](Self::tick). After the end of the text a gap is inserted before the text
+/// repeats and the marquee pauses for a configurable number of ticks each time the start of the
+/// text is aligned with the left edge of the bounding box. Text which fits into the bounding box
+/// isn't scrolled.
+///
+/// Only the characters which are visible inside the bounding box are drawn. If the style has a
+/// background color the bounding box is cleared before the text is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Marquee<'a, C> {
+    text: &'a str,
+    style: BdfTextStyle<'a, C>,
+    bounds: Rectangle,
+    text_width: u32,
+    speed: u32,
+    gap: u32,
+    pause: u32,
+    offset: u32,
+    pause_ticks: u32,
+}
+
+impl<'a, C: PixelColor> Marquee<'a, C> {
+    /// Creates a new marquee.
+    ///
+    /// The marquee scrolls by one pixel per tick, uses a gap of 4 space widths and doesn't pause.
+    pub fn new(text: &'a str, style: BdfTextStyle<'a, C>, bounds: Rectangle) -> Self {
+        Self {
+            text,
+            style,
+            bounds,
+            text_width: style.text_advance(text).max(0) as u32,
+            speed: 1,
+            gap: (style.char_advance(' ') * 4).max(0) as u32,
+            pause: 0,
+            offset: 0,
+            pause_ticks: 0,
+        }
+    }
+
+    /// Sets the scroll speed in pixels per tick.
+    pub fn with_speed(self, speed: u32) -> Self {
+        Self { speed, ..self }
+    }
+
+    /// Sets the gap between the end and the repeated start of the text in pixels.
+    pub fn with_gap(self, gap: u32) -> Self {
+        Self { gap, ..self }
+    }
+
+    /// Sets the number of ticks the marquee pauses when the start of the text is visible.
+    pub fn with_pause(self, pause: u32) -> Self {
+        Self {
+            pause,
+            pause_ticks: pause,
+            ..self
+        }
+    }
+
+    /// Returns `true` if the text is wider than the bounding box and needs to be scrolled.
+    pub fn is_scrolling(&self) -> bool {
+        self.text_width > self.bounds.size.width
+    }
+
+    /// Returns the current scroll offset in pixels.
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Restarts scrolling from the start of the text.
+    pub fn reset(&mut self) {
+        self.offset = 0;
+        self.pause_ticks = self.pause;
+    }
+
+    /// Advances the marquee by one tick.
+    ///
+    /// Returns `true` if the scroll offset has changed and the marquee needs to be redrawn.
+    pub fn tick(&mut self) -> bool {
+        if !self.is_scrolling() || self.speed == 0 {
+            return false;
+        }
+
+        if self.pause_ticks > 0 {
+            self.pause_ticks -= 1;
+            return false;
+        }
+
+        let period = self.text_width + self.gap;
+
+        self.offset += self.speed;
+        if self.offset >= period {
+            // Align the start of the text with the left edge if the marquee pauses, otherwise
+            // keep scrolling smoothly.
+            self.offset = if self.pause > 0 {
+                0
+            } else {
+                self.offset % period
+            };
+            self.pause_ticks = self.pause;
+        }
+
+        true
+    }
+
+    /// Draws the characters which intersect the bounding box for text starting at `x`.
+    fn draw_visible<D>(&self, x: i32, baseline: i32, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let left = self.bounds.top_left.x - x;
+        let right = left + self.bounds.size.width as i32;
+
+        let mut start = None;
+        let mut end = self.text.len();
+
+        for ((index, _), (_, advance, offset)) in
+            self.text.char_indices().zip(self.style.advances(self.text))
+        {
+            if offset >= right {
+                end = index;
+                break;
+            }
+
+            if start.is_none() && offset + advance > left {
+                start = Some((index, offset));
+            }
+        }
+
+        if let Some((start, offset)) = start {
+            let position = Point::new(x + offset, baseline);
+            self.style.draw_string(
+                &self.text[start..end],
+                position,
+                Baseline::Alphabetic,
+                target,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: PixelColor> Drawable for Marquee<'_, C> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut target = target.clipped(&self.bounds);
+
+        if let Some(color) = self.style.background_color() {
+            target.fill_solid(&self.bounds, color)?;
+        }
+
+        let baseline = self
+            .style
+            .aligned_lines_position(1, &self.bounds, VerticalAlignment::Middle)
+            .y;
+
+        let x = self.bounds.top_left.x - self.offset as i32;
+        self.draw_visible(x, baseline, &mut target)?;
+
+        if self.is_scrolling() {
+            let x = x + (self.text_width + self.gap) as i32;
+            if x < self.bounds.top_left.x + self.bounds.size.width as i32 {
+                self.draw_visible(x, baseline, &mut target)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    fn marquee(text: &'static str) -> Marquee<'static, BinaryColor> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let bounds = Rectangle::new(Point::zero(), Size::new(12, 10));
+
+        Marquee::new(text, style, bounds).with_gap(3)
+    }
+
+    #[test]
+    fn short_text_isnt_scrolled() {
+        let mut marquee = marquee("AB");
+
+        assert!(!marquee.is_scrolling());
+        assert!(!marquee.tick());
+        assert_eq!(marquee.offset(), 0);
+    }
+
+    #[test]
+    fn scroll_and_wrap_around() {
+        let mut marquee = marquee("ABC").with_speed(10).with_pause(1);
+
+        assert!(!marquee.tick());
+        assert!(marquee.tick());
+        assert_eq!(marquee.offset(), 10);
+        assert!(marquee.tick());
+        assert_eq!(marquee.offset(), 20);
+        assert!(marquee.tick());
+        assert_eq!(marquee.offset(), 0);
+        assert!(!marquee.tick());
+        assert!(marquee.tick());
+        assert_eq!(marquee.offset(), 10);
+    }
+
+    #[test]
+    fn wrap_around_without_pause() {
+        let mut marquee = marquee("ABC").with_speed(10);
+
+        marquee.tick();
+        marquee.tick();
+        marquee.tick();
+        assert_eq!(marquee.offset(), 9);
+    }
+
+    #[test]
+    fn draw_repeated_text() {
+        let mut marquee = marquee("III").with_speed(14);
+        marquee.tick();
+
+        let mut display = MockDisplay::new();
+        marquee.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "            ",
+            "##      ### ",
+            "#        #  ",
+            "#        #  ",
+            "#        #  ",
+            "#        #  ",
+            "#        #  ",
+            "##      ### ",
+        ]);
+    }
+}
@@ -0,0 +1,26 @@
+//! Widgets.
+
+mod clock;
+mod compositor;
+mod console;
+mod cursor;
+mod grid;
+mod input_line;
+mod marquee;
+mod odometer;
+mod pager;
+mod scroll_view;
+
+pub use clock::Clock;
+pub use compositor::{Compositor, TextItem};
+pub use console::Console;
+pub use cursor::{Cursor, CursorShape};
+pub use grid::{Attributes, Cell, TextGrid};
+pub use input_line::InputLine;
+pub use marquee::Marquee;
+pub use odometer::Odometer;
+pub use pager::Pager;
+pub use scroll_view::ScrollView;
+
+// Re-exported, because most widgets return this error.
+pub use crate::CapacityError;
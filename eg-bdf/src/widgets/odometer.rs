@@ -0,0 +1,213 @@
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::text::BdfTextStyle;
+
+/// Rolling odometer style number display.
+///
+/// The odometer displays a number with `DIGITS` zero padded digits. When the value changes, each
+/// changed digit is animated by sliding the old digit up and out of its cell while the new digit
+/// slides in from below. The animation takes `frames` calls to [`tick`](Self::tick).
+///
+/// Every digit is drawn into a fixed cell, which is as wide as the widest digit and as high as the
+/// sum of the font ascent and descent. Drawing is clipped to the cells. If the style has a
+/// background color the cells are cleared before the digits are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Odometer<'a, C, const DIGITS: usize> {
+    style: BdfTextStyle<'a, C>,
+    position: Point,
+    previous: [char; DIGITS],
+    current: [char; DIGITS],
+    frames: u32,
+    frame: u32,
+}
+
+impl<'a, C: PixelColor, const DIGITS: usize> Odometer<'a, C, DIGITS> {
+    /// Creates a new odometer displaying zero.
+    ///
+    /// `position` is the top left corner of the first digit cell.
+    pub fn new(style: BdfTextStyle<'a, C>, position: Point, frames: u32) -> Self {
+        Self {
+            style,
+            position,
+            previous: ['0'; DIGITS],
+            current: ['0'; DIGITS],
+            frames,
+            frame: frames,
+        }
+    }
+
+    /// Sets the displayed value and starts the animation.
+    ///
+    /// Only the lowest `DIGITS` digits of the value are displayed. A running animation is
+    /// finished immediately.
+    pub fn set_value(&mut self, value: u32) {
+        self.previous = self.current;
+
+        let mut value = value;
+        for digit in self.current.iter_mut().rev() {
+            *digit = char::from(b'0' + (value % 10) as u8);
+            value /= 10;
+        }
+
+        self.frame = if self.previous == self.current {
+            self.frames
+        } else {
+            0
+        };
+    }
+
+    /// Returns `true` while the animation is running.
+    pub fn is_animating(&self) -> bool {
+        self.frame < self.frames
+    }
+
+    /// Advances the animation by one frame.
+    ///
+    /// Returns `true` if the odometer needs to be redrawn.
+    pub fn tick(&mut self) -> bool {
+        if self.is_animating() {
+            self.frame += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the size of a digit cell.
+    pub fn cell_size(&self) -> Size {
+        // Each digit is drawn separately, which rounds its advance to whole pixels.
+        let width = ('0'..='9')
+            .map(|c| self.style.text_advance(c.encode_utf8(&mut [0; 4])))
+            .max()
+            .unwrap_or_default();
+
+        Size::new(width.max(0) as u32, self.style.caret_height())
+    }
+
+    /// Returns the area covered by the odometer.
+    pub fn bounding_box(&self) -> Rectangle {
+        let cell_size = self.cell_size();
+
+        Rectangle::new(
+            self.position,
+            Size::new(cell_size.width * DIGITS as u32, cell_size.height),
+        )
+    }
+
+    fn draw_digit<D>(&self, c: char, top_left: Point, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let baseline = top_left + Point::new(0, self.style.font().font_ascent as i32 - 1);
+
+        self.style
+            .draw_string(
+                c.encode_utf8(&mut [0; 4]),
+                baseline,
+                Baseline::Alphabetic,
+                target,
+            )
+            .map(drop)
+    }
+}
+
+impl<C: PixelColor, const DIGITS: usize> Drawable for Odometer<'_, C, DIGITS> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let cell_size = self.cell_size();
+        let height = cell_size.height as i32;
+        let shift = if self.frames > 0 {
+            (height * self.frame as i32) / self.frames as i32
+        } else {
+            height
+        };
+
+        for (index, (previous, current)) in
+            self.previous.iter().zip(self.current.iter()).enumerate()
+        {
+            let cell = Rectangle::new(
+                self.position + Point::new((index as u32 * cell_size.width) as i32, 0),
+                cell_size,
+            );
+            let mut target = target.clipped(&cell);
+
+            if let Some(color) = self.style.background_color() {
+                target.fill_solid(&cell, color)?;
+            }
+
+            if previous != current && self.is_animating() {
+                self.draw_digit(*previous, cell.top_left - Point::new(0, shift), &mut target)?;
+                self.draw_digit(
+                    *current,
+                    cell.top_left + Point::new(0, height - shift),
+                    &mut target,
+                )?;
+            } else {
+                self.draw_digit(*current, cell.top_left, &mut target)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", '0'..='9' | ' ');
+
+    fn odometer() -> Odometer<'static, BinaryColor, 2> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        Odometer::new(style, Point::zero(), 2)
+    }
+
+    #[test]
+    fn animation() {
+        let mut odometer = odometer();
+        assert!(!odometer.is_animating());
+
+        odometer.set_value(123);
+        assert!(odometer.is_animating());
+        assert!(odometer.tick());
+        assert!(odometer.tick());
+        assert!(!odometer.tick());
+
+        odometer.set_value(23);
+        assert!(!odometer.is_animating());
+    }
+
+    #[test]
+    fn draw_halfway() {
+        let mut odometer = odometer();
+        odometer.set_value(1);
+        odometer.tick();
+
+        let mut display = MockDisplay::new();
+        odometer.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "      #   # ",
+            "  #    # #  ",
+            " # #    #   ",
+            "#   #       ",
+            "#   #       ",
+            "#   #       ",
+            " # #    #   ",
+            "  #    ##   ",
+            "      # #   ",
+            "        #   ",
+        ]);
+    }
+}
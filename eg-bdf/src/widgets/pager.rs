@@ -0,0 +1,220 @@
+use core::ops::Range;
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::{
+    text::BdfTextStyle,
+    wrap::{draw_wrapped_line, wrap_words},
+    CapacityError,
+};
+
+/// Paginated text.
+///
+/// The text is wrapped to the width of the bounding box using [`wrap_words`] and split into
+/// pages which contain as many lines as fit into the height of the bounding box. The layout is
+/// calculated once when the pager is created and the start of up to `PAGES` pages is stored, which
+/// makes drawing a page independent of the length of the text before it.
+///
+/// Lines which are broken at a soft hyphen are drawn with a hyphen. If the style has a background
+/// color the bounding box is cleared before a page is drawn.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pager<'a, C, const PAGES: usize> {
+    text: &'a str,
+    style: BdfTextStyle<'a, C>,
+    bounds: Rectangle,
+    lines_per_page: usize,
+    pages: [usize; PAGES],
+    page_count: usize,
+}
+
+impl<'a, C: PixelColor, const PAGES: usize> Pager<'a, C, PAGES> {
+    /// Creates a new pager.
+    ///
+    /// Every page contains at least one line, even if the bounding box is lower than the line
+    /// height. Returns an error if the text needs more than `PAGES` pages.
+    pub fn new(
+        text: &'a str,
+        style: BdfTextStyle<'a, C>,
+        bounds: Rectangle,
+    ) -> Result<Self, CapacityError> {
+        let lines_per_page = (bounds.size.height / style.line_height().max(1)).max(1) as usize;
+
+        let mut pages = [0; PAGES];
+        let mut page_count = 0;
+
+        for (line, range) in wrap_words(text, &style, bounds.size.width)
+            .ranges()
+            .enumerate()
+        {
+            if line % lines_per_page == 0 {
+                *pages.get_mut(page_count).ok_or(CapacityError)? = range.start;
+                page_count += 1;
+            }
+        }
+
+        Ok(Self {
+            text,
+            style,
+            bounds,
+            lines_per_page,
+            pages,
+            page_count,
+        })
+    }
+
+    /// Returns the number of pages.
+    ///
+    /// Empty text results in a single empty page.
+    pub fn page_count(&self) -> usize {
+        self.page_count
+    }
+
+    /// Returns the number of lines per page.
+    pub fn lines_per_page(&self) -> usize {
+        self.lines_per_page
+    }
+
+    /// Returns the byte range of a page inside the text.
+    ///
+    /// Returns `None` if `page` is out of range.
+    pub fn page_range(&self, page: usize) -> Option<Range<usize>> {
+        if page >= self.page_count {
+            return None;
+        }
+
+        let end = if page + 1 < self.page_count {
+            self.pages[page + 1]
+        } else {
+            self.text.len()
+        };
+
+        Some(self.pages[page]..end)
+    }
+
+    /// Draws a page.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page` isn't less than the [page count](Self::page_count).
+    pub fn draw_page<D>(&self, page: usize, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        assert!(page < self.page_count, "invalid page");
+
+        let mut target = target.clipped(&self.bounds);
+
+        if let Some(color) = self.style.background_color() {
+            target.fill_solid(&self.bounds, color)?;
+        }
+
+        let text = &self.text[self.pages[page]..];
+        let mut lines = wrap_words(text, &self.style, self.bounds.size.width);
+        let mut position = self
+            .style
+            .alphabetic_position(self.bounds.top_left, Baseline::Top);
+
+        for _ in 0..self.lines_per_page {
+            let line = match lines.next() {
+                Some(line) => line,
+                None => break,
+            };
+
+            draw_wrapped_line(
+                &self.style,
+                line,
+                lines.needs_hyphen(),
+                position,
+                &mut target,
+            )?;
+            position.y += self.style.line_height() as i32;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ' | '-');
+
+    fn new_pager(text: &'static str) -> Result<Pager<'static, BinaryColor, 3>, CapacityError> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let bounds = Rectangle::new(Point::new(1, 2), Size::new(18, 25));
+
+        Pager::new(text, style, bounds)
+    }
+
+    #[test]
+    fn pages() {
+        let pager = new_pager("AB CD EF GH IJ").unwrap();
+
+        assert_eq!(pager.lines_per_page(), 2);
+        assert_eq!(pager.page_count(), 3);
+        assert_eq!(pager.page_range(0), Some(0..6));
+        assert_eq!(pager.page_range(1), Some(6..12));
+        assert_eq!(pager.page_range(2), Some(12..14));
+        assert_eq!(pager.page_range(3), None);
+
+        assert_eq!(new_pager("").unwrap().page_count(), 1);
+        assert_eq!(
+            new_pager("AB CD EF GH IJ KL MN").unwrap_err(),
+            CapacityError
+        );
+    }
+
+    #[test]
+    fn draw_page() {
+        let pager = new_pager("AB CD EF GH IJ").unwrap();
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        pager.draw_page(1, &mut display).unwrap();
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("EF", Point::new(1, 2), Baseline::Top, &mut expected)
+            .unwrap();
+        style
+            .draw_string("GH", Point::new(1, 12), Baseline::Top, &mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn soft_hyphen() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let bounds = Rectangle::new(Point::new(1, 2), Size::new(24, 25));
+        let pager = Pager::<_, 1>::new("ABC\u{AD}DEF", style, bounds).unwrap();
+
+        let mut display = MockDisplay::new();
+        pager.draw_page(0, &mut display).unwrap();
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("ABC-", Point::new(1, 2), Baseline::Top, &mut expected)
+            .unwrap();
+        style
+            .draw_string("DEF", Point::new(1, 12), Baseline::Top, &mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid page")]
+    fn invalid_page() {
+        let pager = new_pager("AB").unwrap();
+
+        pager.draw_page(1, &mut MockDisplay::new()).unwrap();
+    }
+}
@@ -0,0 +1,367 @@
+use core::ops::Range;
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        Baseline,
+    },
+};
+
+use crate::{
+    text::BdfTextStyle,
+    wrap::{draw_wrapped_line, wrap_words},
+};
+
+/// Vertically scrolling text.
+///
+/// The text is wrapped to the width of the bounding box using [`wrap_words`] and can be scrolled
+/// by pixels or lines. The scroll offset is the number of pixel rows of the wrapped text which
+/// are hidden above the bounding box.
+///
+/// Redrawing the whole view after every scroll step is slow on most displays. The view therefore
+/// remembers the offset at which it was last drawn and can redraw only the rows which were
+/// exposed by scrolling:
+///
+/// * [`draw_exposed`](Self::draw_exposed) expects that the caller has already moved the
+///   previously drawn content by [`pending_scroll`](Self::pending_scroll) pixels, e.g. by copying
+///   rows inside a framebuffer.
+/// * [`draw_hardware_scrolled`](Self::draw_hardware_scrolled) is intended for displays which
+///   support hardware scrolling. The rows of the bounding box are used as a ring buffer and the
+///   returned value is the scroll offset which needs to be set in the display controller.
+///
+/// The same mode should be used for all draw calls, unless [`invalidate`](Self::invalidate) is
+/// called in between. Both modes fall back to redrawing the whole view if it was scrolled by more
+/// than its height. If the style has a background color the redrawn rows are cleared before the
+/// text is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScrollView<'a, C> {
+    text: &'a str,
+    style: BdfTextStyle<'a, C>,
+    bounds: Rectangle,
+    content_height: u32,
+    offset: u32,
+    /// Offset at which the view was last drawn.
+    drawn_offset: Option<u32>,
+}
+
+impl<'a, C: PixelColor> ScrollView<'a, C> {
+    /// Creates a new scroll view.
+    pub fn new(text: &'a str, style: BdfTextStyle<'a, C>, bounds: Rectangle) -> Self {
+        let lines = wrap_words(text, &style, bounds.size.width).count() as u32;
+
+        Self {
+            text,
+            style,
+            bounds,
+            content_height: lines * style.line_height(),
+            offset: 0,
+            drawn_offset: None,
+        }
+    }
+
+    /// Returns the height of the wrapped text in pixels.
+    pub fn content_height(&self) -> u32 {
+        self.content_height
+    }
+
+    /// Returns the current scroll offset in pixels.
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Returns the largest scroll offset.
+    ///
+    /// At the largest offset the end of the text is aligned with the bottom edge of the bounding
+    /// box. Text which fits into the bounding box can't be scrolled.
+    pub fn max_offset(&self) -> u32 {
+        self.content_height.saturating_sub(self.bounds.size.height)
+    }
+
+    /// Scrolls to an offset in pixels.
+    ///
+    /// The offset is limited to the [largest offset](Self::max_offset). Returns `true` if the
+    /// offset has changed.
+    pub fn scroll_to(&mut self, offset: u32) -> bool {
+        let offset = offset.min(self.max_offset());
+        let changed = offset != self.offset;
+        self.offset = offset;
+
+        changed
+    }
+
+    /// Scrolls by a number of pixels.
+    ///
+    /// Positive values scroll towards the end of the text. Returns `true` if the offset has
+    /// changed.
+    pub fn scroll_by(&mut self, pixels: i32) -> bool {
+        let offset = if pixels < 0 {
+            self.offset.saturating_sub(pixels.unsigned_abs())
+        } else {
+            self.offset.saturating_add(pixels as u32)
+        };
+
+        self.scroll_to(offset)
+    }
+
+    /// Scrolls by a number of lines.
+    ///
+    /// Positive values scroll towards the end of the text. Returns `true` if the offset has
+    /// changed.
+    pub fn scroll_lines(&mut self, lines: i32) -> bool {
+        self.scroll_by(lines.saturating_mul(self.style.line_height() as i32))
+    }
+
+    /// Returns the number of pixels the content has been scrolled since it was last drawn.
+    ///
+    /// Before calling [`draw_exposed`](Self::draw_exposed) the previously drawn content needs to
+    /// be moved up by this number of pixels, or down for negative values. Returns `None` if the
+    /// whole view will be redrawn.
+    pub fn pending_scroll(&self) -> Option<i32> {
+        self.drawn_offset
+            .filter(|drawn| drawn.abs_diff(self.offset) < self.bounds.size.height)
+            .map(|drawn| self.offset as i32 - drawn as i32)
+    }
+
+    /// Forces the next partial redraw to redraw the whole view.
+    pub fn invalidate(&mut self) {
+        self.drawn_offset = None;
+    }
+
+    /// Returns the rows of the wrapped text which need to be redrawn.
+    fn exposed_rows(&self) -> Range<u32> {
+        let height = self.bounds.size.height;
+
+        match self.pending_scroll() {
+            Some(delta) if delta > 0 => self.offset + height - delta as u32..self.offset + height,
+            Some(delta) => self.offset..self.offset + delta.unsigned_abs(),
+            None => self.offset..self.offset + height,
+        }
+    }
+
+    /// Draws the rows which were exposed since the view was last drawn.
+    ///
+    /// The whole view is drawn if it wasn't drawn before.
+    pub fn draw_exposed<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let rows = self.exposed_rows();
+        let top = self.bounds.top_left.y + (rows.start - self.offset) as i32;
+        self.draw_rows(rows, top, target)?;
+
+        self.drawn_offset = Some(self.offset);
+
+        Ok(())
+    }
+
+    /// Draws the rows which were exposed since the view was last drawn using hardware scrolling.
+    ///
+    /// Row `y` of the wrapped text is drawn to row `y % height` of the bounding box. Returns the
+    /// number of rows, relative to the top of the bounding box, by which the display controller
+    /// needs to scroll the area of the bounding box.
+    pub fn draw_hardware_scrolled<D>(&mut self, target: &mut D) -> Result<u32, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let height = self.bounds.size.height;
+        let mut rows = self.exposed_rows();
+
+        // Split the rows at the end of the ring buffer.
+        while !rows.is_empty() {
+            let end = rows.end.min((rows.start / height + 1) * height);
+            let top = self.bounds.top_left.y + (rows.start % height) as i32;
+            self.draw_rows(rows.start..end, top, target)?;
+
+            rows.start = end;
+        }
+
+        self.drawn_offset = Some(self.offset);
+
+        Ok(if height > 0 { self.offset % height } else { 0 })
+    }
+
+    /// Draws rows of the wrapped text with the first row at `top`.
+    fn draw_rows<D>(&self, rows: Range<u32>, top: i32, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let area = Rectangle::new(
+            Point::new(self.bounds.top_left.x, top),
+            Size::new(self.bounds.size.width, rows.end - rows.start),
+        );
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+
+        let mut target = target.clipped(&area);
+
+        // The glyphs are drawn without background after the area has been cleared.
+        let mut style = self.style;
+        if let Some(color) = style.background_color() {
+            target.fill_solid(&area, color)?;
+            style.set_background_color(None);
+        }
+
+        let line_height = self.style.line_height();
+        let mut lines = wrap_words(self.text, &self.style, self.bounds.size.width);
+        let mut line_top = 0;
+
+        while let Some(line) = lines.next() {
+            if line_top >= rows.end {
+                break;
+            }
+
+            if line_top + line_height > rows.start {
+                let position = Point::new(
+                    self.bounds.top_left.x,
+                    top + line_top as i32 - rows.start as i32,
+                );
+
+                draw_wrapped_line(
+                    &style,
+                    line,
+                    lines.needs_hyphen(),
+                    style.alphabetic_position(position, Baseline::Top),
+                    &mut target,
+                )?;
+            }
+
+            line_top += line_height;
+        }
+
+        Ok(())
+    }
+}
+
+impl<C: PixelColor> Drawable for ScrollView<'_, C> {
+    type Color = C;
+    type Output = ();
+
+    /// Draws the whole view.
+    ///
+    /// This doesn't affect the rows which are redrawn by the partial redraw methods.
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.draw_rows(
+            self.offset..self.offset + self.bounds.size.height,
+            self.bounds.top_left.y,
+            target,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, text::BdfTextStyleBuilder, BdfFont};
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    fn scroll_view() -> ScrollView<'static, BinaryColor> {
+        let style = BdfTextStyleBuilder::new(&FONT, BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+        let bounds = Rectangle::new(Point::new(0, 5), Size::new(12, 15));
+
+        ScrollView::new("AB CD EF GH", style, bounds)
+    }
+
+    /// Draws the expected content of the view at an offset.
+    fn expected(offset: u32) -> MockDisplay<BinaryColor> {
+        let mut view = scroll_view();
+        view.scroll_to(offset);
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        view.draw(&mut display).unwrap();
+
+        display
+    }
+
+    #[test]
+    fn scrolling() {
+        let mut view = scroll_view();
+
+        assert_eq!(view.content_height(), 40);
+        assert_eq!(view.max_offset(), 25);
+
+        assert!(!view.scroll_by(-1));
+        assert!(view.scroll_lines(1));
+        assert_eq!(view.offset(), 10);
+        assert!(view.scroll_by(30));
+        assert_eq!(view.offset(), 25);
+        assert!(view.scroll_to(3));
+        assert_eq!(view.offset(), 3);
+    }
+
+    #[test]
+    fn draw_exposed() {
+        let mut view = scroll_view();
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        assert_eq!(view.pending_scroll(), None);
+        view.draw_exposed(&mut display).unwrap();
+        display.assert_eq(&expected(0));
+
+        view.scroll_by(4);
+        assert_eq!(view.pending_scroll(), Some(4));
+
+        // Move the content up like a display would do.
+        let mut moved = MockDisplay::new();
+        moved.set_allow_overdraw(true);
+        for y in 5..16 {
+            for x in 0..12 {
+                if let Some(color) = display.get_pixel(Point::new(x, y + 4)) {
+                    moved.set_pixel(Point::new(x, y), Some(color));
+                }
+            }
+        }
+        view.draw_exposed(&mut moved).unwrap();
+        moved.assert_eq(&expected(4));
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        view.scroll_by(-2);
+        assert_eq!(view.pending_scroll(), Some(-2));
+        view.draw_exposed(&mut display).unwrap();
+        assert_eq!(
+            display.affected_area(),
+            Rectangle::new(Point::new(0, 5), Size::new(12, 2))
+        );
+
+        view.scroll_to(25);
+        assert_eq!(view.pending_scroll(), None);
+    }
+
+    #[test]
+    fn draw_hardware_scrolled() {
+        let mut view = scroll_view();
+
+        // Display memory of the bounding box.
+        let mut memory = MockDisplay::new();
+        memory.set_allow_overdraw(true);
+        assert_eq!(view.draw_hardware_scrolled(&mut memory).unwrap(), 0);
+
+        for offset in [7, 14, 25] {
+            view.scroll_to(offset);
+            let scroll = view.draw_hardware_scrolled(&mut memory).unwrap();
+            assert_eq!(scroll, offset % 15);
+
+            // Apply the hardware scroll offset to the memory contents.
+            let mut visible = MockDisplay::new();
+            for y in 0..15 {
+                for x in 0..12 {
+                    let source = Point::new(x, 5 + (y + scroll as i32) % 15);
+                    visible.set_pixel(Point::new(x, 5 + y), memory.get_pixel(source));
+                }
+            }
+            visible.assert_eq(&expected(offset));
+        }
+    }
+}
@@ -0,0 +1,541 @@
+//! Line breaking.
+
+use core::{convert::TryFrom, ops::Range};
+
+use embedded_graphics::{
+    prelude::*,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::text::{pen_to_pixels, BdfTextStyle};
+
+/// Iterator over the lines of a wrapped text.
+///
+/// Each item is the byte range of a line inside the original text. Lines are broken at
+/// newlines and, if a line would exceed the maximum width, at the last space before the
+/// overflowing character. Words which are wider than the maximum width are broken at the
+/// character boundary. Spaces at a soft line break are not part of either line.
+///
+/// This iterator is created by [`BdfTextStyle::line_breaks`] and [`WrapWords::ranges`].
+#[derive(Debug, Clone)]
+pub struct LineBreaks<'a, 'b, C, H = NoHyphenation> {
+    style: &'b BdfTextStyle<'b, C>,
+    text: &'a str,
+    max_width: i32,
+    position: usize,
+    finished: bool,
+    hyphens: bool,
+    hyphenator: H,
+    needs_hyphen: bool,
+}
+
+impl<'a, 'b, C: PixelColor> LineBreaks<'a, 'b, C> {
+    pub(crate) fn new(style: &'b BdfTextStyle<'b, C>, text: &'a str, max_width: u32) -> Self {
+        Self {
+            style,
+            text,
+            max_width: i32::try_from(max_width).unwrap_or(i32::MAX),
+            position: 0,
+            finished: false,
+            hyphens: false,
+            hyphenator: NoHyphenation,
+            needs_hyphen: false,
+        }
+    }
+
+    /// Returns the line slices instead of byte ranges.
+    pub fn lines(self) -> impl Iterator<Item = &'a str> + 'b
+    where
+        'a: 'b,
+    {
+        let text = self.text;
+        self.map(move |range| &text[range])
+    }
+}
+
+impl<C: PixelColor, H: Hyphenator> LineBreaks<'_, '_, C, H> {
+    /// Returns `true` if the last line was broken inside a word.
+    ///
+    /// Lines which are broken at a hyphenation point or a soft hyphen need a visible hyphen at
+    /// their end. The soft hyphen at the end of a line which was broken at a soft hyphen should
+    /// be replaced by the hyphen.
+    pub fn needs_hyphen(&self) -> bool {
+        self.needs_hyphen
+    }
+
+    /// Returns the width of a line in thousandths of a pixel.
+    fn line_width(&self, line: &str) -> i64 {
+        line.chars()
+            .filter(|&c| !(self.hyphens && c == SOFT_HYPHEN))
+            .map(|c| self.style.pen_advance(c))
+            .sum()
+    }
+
+    /// Returns `true` if a line with the given width in thousandths of a pixel fits.
+    fn fits(&self, width: i64) -> bool {
+        pen_to_pixels(width) <= self.max_width
+    }
+
+    /// Returns the end of the line at the last hyphenation point which fits into the line.
+    ///
+    /// `word_start` is the start of the word which contains the overflowing character at
+    /// `index`.
+    fn hyphenation_point(&self, rest: &str, word_start: usize, index: usize) -> Option<usize> {
+        let word_end = rest[index..]
+            .find(|c| matches!(c, ' ' | '\n' | '-' | SOFT_HYPHEN))
+            .map_or(rest.len(), |offset| index + offset);
+        let word = &rest[word_start..word_end];
+        let hyphen = self.style.pen_advance('-');
+
+        self.hyphenator
+            .hyphenation_points(word)
+            .filter(|&point| point > 0 && point < word.len() && word.is_char_boundary(point))
+            .map(|point| word_start + point)
+            .filter(|&end| end <= index && self.fits(self.line_width(&rest[..end]) + hyphen))
+            .max()
+    }
+}
+
+impl<C: PixelColor, H: Hyphenator> Iterator for LineBreaks<'_, '_, C, H> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.needs_hyphen = false;
+
+        if self.finished {
+            return None;
+        }
+
+        let start = self.position;
+        let rest = &self.text[start..];
+
+        // Width of the line in thousandths of a pixel, like the pen position in `draw_string`.
+        let mut width = 0;
+        // End of the line for the last break opportunity.
+        let mut soft_break = None;
+        let mut previous_space = false;
+        // End of the last character which isn't a soft hyphen.
+        let mut line_end = 0;
+        // Start of the current word.
+        let mut word_start = 0;
+
+        for (index, c) in rest.char_indices() {
+            if c == '\n' {
+                self.position = start + index + 1;
+                return Some(start..start + index);
+            }
+
+            if c == SOFT_HYPHEN && self.hyphens {
+                // Soft hyphens are invisible unless the line is broken at the soft hyphen.
+                let fits = self.fits(width + self.style.pen_advance('-'));
+                if index > 0 && !previous_space && fits {
+                    soft_break = Some(index + c.len_utf8());
+                }
+                word_start = index + c.len_utf8();
+                continue;
+            }
+
+            let advance = self.style.pen_advance(c);
+
+            if c == ' ' {
+                if !previous_space {
+                    soft_break = Some(index);
+                }
+                previous_space = true;
+                word_start = index + c.len_utf8();
+            } else {
+                let after_word = index > 0 && !previous_space;
+                previous_space = false;
+
+                if !self.fits(width + advance) {
+                    if let Some(end) = self.hyphenation_point(rest, word_start, index) {
+                        self.position = start + end;
+                        self.needs_hyphen = true;
+
+                        return Some(start..start + end);
+                    }
+
+                    let end = match soft_break {
+                        Some(end) => end,
+                        // Always include at least one character to guarantee progress.
+                        None if index == 0 => c.len_utf8(),
+                        None => line_end,
+                    };
+
+                    self.position = start + skip_spaces(rest, end);
+                    self.finished = self.position == self.text.len();
+                    self.needs_hyphen = self.hyphens && rest[..end].ends_with(SOFT_HYPHEN);
+
+                    return Some(start..start + end);
+                }
+
+                if c == '-' && self.hyphens {
+                    if after_word {
+                        soft_break = Some(index + c.len_utf8());
+                    }
+                    word_start = index + c.len_utf8();
+                }
+            }
+
+            width += advance;
+            line_end = index + c.len_utf8();
+        }
+
+        self.finished = true;
+        Some(start..self.text.len())
+    }
+}
+
+/// Soft hyphen.
+pub(crate) const SOFT_HYPHEN: char = '\u{AD}';
+
+/// Iterator over the lines of a word wrapped text.
+///
+/// Works like [`LineBreaks`], but lines are also broken after hyphens and at soft hyphens
+/// (`U+00AD`). Soft hyphens don't contribute to the width of a line, except at the end of a line
+/// where room is reserved for a visible hyphen. A line which is broken at a soft hyphen ends with
+/// the soft hyphen and it is up to the caller to draw it as a hyphen.
+///
+/// Words which don't fit into a line can additionally be broken at the points returned by a
+/// [`Hyphenator`], see [`with_hyphenator`](Self::with_hyphenator).
+///
+/// This iterator is created by [`wrap_words`].
+#[derive(Debug, Clone)]
+pub struct WrapWords<'a, 'b, C, H = NoHyphenation> {
+    line_breaks: LineBreaks<'a, 'b, C, H>,
+}
+
+impl<'a, 'b, C: PixelColor> WrapWords<'a, 'b, C> {
+    /// Sets the hyphenator which is used to break words that don't fit into a line.
+    ///
+    /// If the overflowing word contains a hyphenation point at which the line, including a
+    /// hyphen, fits into the maximum width, the line is broken at the last such point. This
+    /// takes precedence over breaking the line before the word. Lines which are broken at a
+    /// hyphenation point don't include a hyphen, use [`needs_hyphen`](Self::needs_hyphen) to
+    /// check if a hyphen needs to be drawn.
+    pub fn with_hyphenator<H: Hyphenator>(self, hyphenator: H) -> WrapWords<'a, 'b, C, H> {
+        let LineBreaks {
+            style,
+            text,
+            max_width,
+            position,
+            finished,
+            hyphens,
+            hyphenator: _,
+            needs_hyphen,
+        } = self.line_breaks;
+
+        WrapWords {
+            line_breaks: LineBreaks {
+                style,
+                text,
+                max_width,
+                position,
+                finished,
+                hyphens,
+                hyphenator,
+                needs_hyphen,
+            },
+        }
+    }
+}
+
+impl<'a, 'b, C: PixelColor, H: Hyphenator> WrapWords<'a, 'b, C, H> {
+    /// Returns `true` if the last line was broken inside a word.
+    ///
+    /// See [`LineBreaks::needs_hyphen`].
+    pub fn needs_hyphen(&self) -> bool {
+        self.line_breaks.needs_hyphen()
+    }
+
+    /// Returns the byte ranges of the lines instead of the line slices.
+    pub fn ranges(self) -> LineBreaks<'a, 'b, C, H> {
+        self.line_breaks
+    }
+}
+
+impl<'a, C: PixelColor, H: Hyphenator> Iterator for WrapWords<'a, '_, C, H> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let text = self.line_breaks.text;
+        self.line_breaks.next().map(|range| &text[range])
+    }
+}
+
+/// Wraps `text` to lines which are at most `max_width` pixels wide.
+///
+/// Returns an iterator over the lines, which can be used to paginate or scroll text without
+/// drawing it. See [`WrapWords`] for the supported break opportunities.
+///
+/// ```
+/// use eg_bdf::{include_bdf, text::BdfTextStyle, wrap::wrap_words, BdfFont};
+/// use embedded_graphics::pixelcolor::BinaryColor;
+///
+/// const FONT: BdfFont = include_bdf!("examples/6x10.bdf");
+///
+/// let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+/// let lines: Vec<_> = wrap_words("well-known words", &style, 48).collect();
+/// assert_eq!(lines, ["well-", "known", "words"]);
+/// ```
+pub fn wrap_words<'a, 'b, C: PixelColor>(
+    text: &'a str,
+    style: &'b BdfTextStyle<'b, C>,
+    max_width: u32,
+) -> WrapWords<'a, 'b, C> {
+    WrapWords {
+        line_breaks: LineBreaks {
+            hyphens: true,
+            ..LineBreaks::new(style, text, max_width)
+        },
+    }
+}
+
+/// Hyphenation.
+///
+/// A hyphenator returns the points at which a word can be broken with a hyphen. This trait is
+/// implemented for closures, which makes it possible to use external hyphenation libraries or
+/// simple lookup tables:
+///
+/// ```
+/// use eg_bdf::{include_bdf, text::BdfTextStyle, wrap::wrap_words, BdfFont};
+/// use embedded_graphics::pixelcolor::BinaryColor;
+///
+/// const FONT: BdfFont = include_bdf!("examples/6x10.bdf");
+///
+/// let hyphenate = |word: &str| match word {
+///     "hyphenation" => [2, 6].iter().copied(),
+///     _ => [].iter().copied(),
+/// };
+///
+/// let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+/// let mut lines = wrap_words("automatic hyphenation", &style, 60).with_hyphenator(hyphenate);
+///
+/// assert_eq!(lines.next(), Some("automatic"));
+/// assert_eq!(lines.next(), Some("hyphen"));
+/// assert!(lines.needs_hyphen());
+/// assert_eq!(lines.next(), Some("ation"));
+/// assert!(!lines.needs_hyphen());
+/// ```
+pub trait Hyphenator {
+    /// Iterator over the hyphenation points of a word.
+    type Points: Iterator<Item = usize>;
+
+    /// Returns the hyphenation points of a word.
+    ///
+    /// The points are byte offsets into `word`, a hyphen is inserted before the character at
+    /// the offset. Points which aren't inside the word or aren't on a character boundary are
+    /// ignored. The points don't need to be sorted.
+    fn hyphenation_points(&self, word: &str) -> Self::Points;
+}
+
+impl<F, I> Hyphenator for F
+where
+    F: Fn(&str) -> I,
+    I: Iterator<Item = usize>,
+{
+    type Points = I;
+
+    fn hyphenation_points(&self, word: &str) -> Self::Points {
+        self(word)
+    }
+}
+
+/// Hyphenator which doesn't hyphenate words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(::defmt::Format))]
+pub struct NoHyphenation;
+
+impl Hyphenator for NoHyphenation {
+    type Points = core::iter::Empty<usize>;
+
+    fn hyphenation_points(&self, _word: &str) -> Self::Points {
+        core::iter::empty()
+    }
+}
+
+/// Draws a line returned by [`WrapWords`].
+///
+/// Soft hyphens are skipped and a hyphen is appended if `needs_hyphen` is `true`. `position` is
+/// the start of the line on the alphabetic baseline.
+pub(crate) fn draw_wrapped_line<C, D>(
+    style: &BdfTextStyle<'_, C>,
+    line: &str,
+    needs_hyphen: bool,
+    mut position: Point,
+    target: &mut D,
+) -> Result<(), D::Error>
+where
+    C: PixelColor,
+    D: DrawTarget<Color = C>,
+{
+    for segment in line.split(SOFT_HYPHEN) {
+        position = style.draw_string(segment, position, Baseline::Alphabetic, target)?;
+    }
+
+    if needs_hyphen {
+        style.draw_string("-", position, Baseline::Alphabetic, target)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the index of the first non space character at or after `index`.
+fn skip_spaces(text: &str, index: usize) -> usize {
+    text[index..]
+        .find(|c| c != ' ')
+        .map_or(text.len(), |offset| index + offset)
+}
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Returns an iterator over the byte ranges of the lines of `text` wrapped to `max_width`.
+    pub fn line_breaks<'a, 'b>(&'b self, text: &'a str, max_width: u32) -> LineBreaks<'a, 'b, C> {
+        LineBreaks::new(self, text, max_width)
+    }
+
+    /// Returns an iterator over the lines of `text` wrapped to `max_width`.
+    pub fn wrap_lines<'a, 'b>(
+        &'b self,
+        text: &'a str,
+        max_width: u32,
+    ) -> impl Iterator<Item = &'a str> + 'b
+    where
+        'a: 'b,
+    {
+        self.line_breaks(text, max_width).lines()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, BdfFont};
+    use alloc::vec::Vec;
+    use embedded_graphics::pixelcolor::BinaryColor;
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | 'a'..='z' | ' ');
+
+    fn wrap(text: &str, max_width: u32) -> Vec<&str> {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        style.wrap_lines(text, max_width).collect()
+    }
+
+    #[test]
+    fn no_wrapping() {
+        assert_eq!(wrap("abc def", 100), ["abc def"]);
+        assert_eq!(wrap("", 100), [""]);
+    }
+
+    #[test]
+    fn newlines() {
+        assert_eq!(wrap("abc\ndef", 100), ["abc", "def"]);
+        assert_eq!(wrap("abc\n", 100), ["abc", ""]);
+    }
+
+    #[test]
+    fn break_at_spaces() {
+        assert_eq!(wrap("abc def ghi", 42), ["abc def", "ghi"]);
+        assert_eq!(wrap("abc   def", 30), ["abc", "def"]);
+        assert_eq!(wrap("abc def", 18), ["abc", "def"]);
+    }
+
+    #[test]
+    fn break_long_words() {
+        assert_eq!(wrap("abcdefgh", 18), ["abc", "def", "gh"]);
+        assert_eq!(wrap("abc", 0), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn hyphens() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let wrap = |text, max_width| wrap_words(text, &style, max_width).collect::<Vec<_>>();
+
+        assert_eq!(wrap("abc-def", 42), ["abc-def"]);
+        assert_eq!(wrap("abc-def", 36), ["abc-", "def"]);
+        assert_eq!(wrap("ab cd-ef", 36), ["ab cd-", "ef"]);
+        assert_eq!(wrap("ab -cdef", 30), ["ab", "-cdef"]);
+        // The hyphen doesn't fit, break at the space instead.
+        assert_eq!(wrap("ab cde-f", 30), ["ab", "cde-f"]);
+        // Hyphens aren't treated specially by `line_breaks`.
+        assert_eq!(
+            style.wrap_lines("abc-def", 36).collect::<Vec<_>>(),
+            ["abc-de", "f"]
+        );
+    }
+
+    #[test]
+    fn soft_hyphens() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let wrap = |text, max_width| wrap_words(text, &style, max_width).collect::<Vec<_>>();
+
+        assert_eq!(wrap("abc\u{AD}def", 36), ["abc\u{AD}def"]);
+        assert_eq!(wrap("abc\u{AD}def", 30), ["abc\u{AD}", "def"]);
+        // Room for the visible hyphen is required.
+        assert_eq!(wrap("abcd\u{AD}ef", 24), ["abcd", "\u{AD}ef"]);
+
+        assert_eq!(
+            wrap_words("ab\u{AD}cd ef", &style, 18)
+                .ranges()
+                .collect::<Vec<_>>(),
+            [0..4, 4..6, 7..9]
+        );
+    }
+
+    #[test]
+    fn hyphenation() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let hyphenate = |word: &str| {
+            let points: &[usize] = match word {
+                "abcdef" => &[4, 2, 0, 6, 9],
+                "ghijkl" => &[3],
+                _ => &[],
+            };
+            points.iter().copied()
+        };
+        let wrap = |text, max_width| {
+            let mut lines = wrap_words(text, &style, max_width).with_hyphenator(hyphenate);
+            let mut result = Vec::new();
+            while let Some(line) = lines.next() {
+                result.push((line, lines.needs_hyphen()));
+            }
+            result
+        };
+
+        assert_eq!(wrap("abcdef", 36), [("abcdef", false)]);
+        assert_eq!(
+            wrap("abcdef", 30),
+            [("abcd", true), ("ef", false)],
+            "last fitting point"
+        );
+        assert_eq!(
+            wrap("abcdef", 24),
+            [("ab", true), ("cdef", false)],
+            "hyphen doesn't fit"
+        );
+        assert_eq!(
+            wrap("x abcdef", 36),
+            [("x ab", true), ("cdef", false)],
+            "hyphenation before space"
+        );
+        assert_eq!(
+            wrap("x-ghijkl", 36),
+            [("x-ghi", true), ("jkl", false)],
+            "word after hyphen"
+        );
+        assert_eq!(
+            wrap("ab\u{AD}cd ef", 18),
+            [("ab\u{AD}", true), ("cd", false), ("ef", false)],
+            "soft hyphen"
+        );
+    }
+
+    #[test]
+    fn byte_ranges() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        assert_eq!(
+            style.line_breaks("ab cd\nef", 12).collect::<Vec<_>>(),
+            [0..2, 3..5, 6..8]
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Formatted text output.
+
+use core::fmt;
+
+use embedded_graphics::{
+    prelude::*,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+use crate::text::{pen_to_pixels, BdfTextStyle};
+
+/// Text writer which draws text as it is written.
+///
+/// The writer implements [`fmt::Write`], which makes it possible to use the `write!` macro to draw
+/// formatted text directly to a draw target. A newline moves the position to the start of the next
+/// line and a carriage return to the start of the current line.
+///
+/// If a wrap width is set, characters which would exceed the width are moved to the next line and
+/// spaces at the start of a wrapped line are skipped. Because the text is drawn while it is
+/// written, wrapping happens at character boundaries and not at word boundaries.
+///
+/// `fmt::Write` can only return [`fmt::Error`]. The draw target error which caused the writer to
+/// fail can be retrieved using [`take_error`](Self::take_error).
+#[derive(Debug)]
+pub struct TextWriter<'a, 'b, C, D>
+where
+    D: DrawTarget<Color = C>,
+{
+    style: BdfTextStyle<'a, C>,
+    target: &'b mut D,
+    line_start: i32,
+    position: Point,
+    /// Position relative to `line_start` in thousandths of a pixel.
+    pen: i64,
+    wrap_width: Option<u32>,
+    wrapped: bool,
+    error: Option<D::Error>,
+}
+
+impl<'a, 'b, C, D> TextWriter<'a, 'b, C, D>
+where
+    C: PixelColor,
+    D: DrawTarget<Color = C>,
+{
+    /// Creates a new text writer.
+    pub fn new(
+        style: BdfTextStyle<'a, C>,
+        target: &'b mut D,
+        position: Point,
+        baseline: Baseline,
+    ) -> Self {
+        Self {
+            style,
+            target,
+            line_start: position.x,
+            position: style.alphabetic_position(position, baseline),
+            pen: 0,
+            wrap_width: None,
+            wrapped: false,
+            error: None,
+        }
+    }
+
+    /// Sets the wrap width.
+    pub fn with_wrap_width(self, wrap_width: u32) -> Self {
+        Self {
+            wrap_width: Some(wrap_width),
+            ..self
+        }
+    }
+
+    /// Returns the current position on the alphabetic baseline.
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    /// Returns the draw target error which caused the last write to fail.
+    pub fn take_error(&mut self) -> Option<D::Error> {
+        self.error.take()
+    }
+
+    fn newline(&mut self) {
+        self.position = Point::new(
+            self.line_start,
+            self.position.y + self.style.line_height() as i32,
+        );
+        self.pen = 0;
+    }
+
+    fn write_char_inner(&mut self, c: char) -> Result<(), D::Error> {
+        match c {
+            '\n' => {
+                self.newline();
+                self.wrapped = false;
+            }
+            '\r' => {
+                self.position.x = self.line_start;
+                self.pen = 0;
+            }
+            _ => {
+                // The pen is tracked across characters to keep fractional advances.
+                let x = self.position.x - self.line_start;
+                let advance = self.style.pen_advance(c);
+
+                if let Some(wrap_width) = self.wrap_width {
+                    if x > 0 && pen_to_pixels(self.pen + advance) > wrap_width as i32 {
+                        self.newline();
+                        self.wrapped = true;
+                    }
+                }
+
+                if c == ' ' && self.wrapped {
+                    return Ok(());
+                }
+                self.wrapped = false;
+
+                self.style.draw_string(
+                    c.encode_utf8(&mut [0; 4]),
+                    self.position,
+                    Baseline::Alphabetic,
+                    self.target,
+                )?;
+                self.pen += advance;
+                self.position.x = self.line_start + pen_to_pixels(self.pen);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<C, D> fmt::Write for TextWriter<'_, '_, C, D>
+where
+    C: PixelColor,
+    D: DrawTarget<Color = C>,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.write_char(c)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.write_char_inner(c).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+impl<C: PixelColor> BdfTextStyle<'_, C> {
+    /// Draws formatted text.
+    ///
+    /// The text is drawn while it is formatted, without an intermediate string buffer. Newlines
+    /// are handled the same way as in [`TextWriter`]. Returns the position after the last
+    /// character, using the same baseline as `position`.
+    pub fn draw_fmt<D>(
+        &self,
+        args: fmt::Arguments<'_>,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let start = self.alphabetic_position(position, baseline);
+        let mut writer = TextWriter::new(*self, target, position, baseline);
+
+        let result = fmt::write(&mut writer, args);
+        let end = writer.position();
+        let next = Point::new(end.x, position.y + end.y - start.y);
+
+        match result {
+            Ok(()) => Ok(next),
+            Err(_) => match writer.take_error() {
+                Some(error) => Err(error),
+                // The formatting error was caused by a `Display` implementation and not by the
+                // draw target.
+                None => Ok(next),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{include_bdf, tests::FRACTIONAL_FONT, BdfFont};
+    use core::fmt::Write;
+    use embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor};
+
+    const FONT: BdfFont = include_bdf!("examples/6x10.bdf", 'A'..='Z' | ' ');
+
+    #[test]
+    fn newlines() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        let mut writer = TextWriter::new(style, &mut display, Point::new(1, 0), Baseline::Top);
+        let (first, second) = ('A', 'I');
+        write!(writer, "{first}\n{second}").unwrap();
+        assert_eq!(writer.position(), Point::new(7, 17));
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("A", Point::new(1, 0), Baseline::Top, &mut expected)
+            .unwrap();
+        style
+            .draw_string("I", Point::new(1, 10), Baseline::Top, &mut expected)
+            .unwrap();
+
+        assert_eq!(display, expected);
+    }
+
+    #[test]
+    fn wrap() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+
+        let mut display = MockDisplay::new();
+        let mut writer =
+            TextWriter::new(style, &mut display, Point::zero(), Baseline::Top).with_wrap_width(12);
+        write!(writer, "AI  A").unwrap();
+        assert_eq!(writer.position(), Point::new(6, 17));
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("AI", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+        style
+            .draw_string("A", Point::new(0, 10), Baseline::Top, &mut expected)
+            .unwrap();
+
+        assert_eq!(display, expected);
+    }
+
+    #[test]
+    fn fractional_advances() {
+        let style =
+            BdfTextStyle::new(&FRACTIONAL_FONT, BinaryColor::On).with_fractional_advances(true);
+
+        let mut display = MockDisplay::new();
+        let mut writer =
+            TextWriter::new(style, &mut display, Point::zero(), Baseline::Top).with_wrap_width(24);
+        write!(writer, "AAAAAA").unwrap();
+        assert_eq!(writer.position(), Point::new(4, 3));
+
+        let mut expected = MockDisplay::new();
+        style
+            .draw_string("AAAAA", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+        style
+            .draw_string("A", Point::new(0, 2), Baseline::Top, &mut expected)
+            .unwrap();
+
+        assert_eq!(display, expected);
+    }
+
+    #[test]
+    fn draw_fmt() {
+        let style = BdfTextStyle::new(&FONT, BinaryColor::On);
+        let value = 'I';
+
+        let mut display = MockDisplay::new();
+        let next = style
+            .draw_fmt(
+                format_args!("A{value}"),
+                Point::zero(),
+                Baseline::Top,
+                &mut display,
+            )
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        let expected_next = style
+            .draw_string("AI", Point::zero(), Baseline::Top, &mut expected)
+            .unwrap();
+
+        assert_eq!(display, expected);
+        assert_eq!(next, expected_next);
+    }
+}